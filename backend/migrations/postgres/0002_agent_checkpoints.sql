@@ -0,0 +1,7 @@
+-- Latest checkpoint of each agent, resumed from after a restart
+
+CREATE TABLE IF NOT EXISTS agent_checkpoints (
+    agent_id TEXT PRIMARY KEY,
+    recorded_at_ms BIGINT NOT NULL,
+    checkpoint TEXT NOT NULL
+);
@@ -0,0 +1,62 @@
+-- Agents, their state history and decisions, and vault activity
+
+CREATE TABLE IF NOT EXISTS agents (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    vault_id TEXT,
+    spec TEXT NOT NULL,
+    created_at_ms BIGINT NOT NULL,
+    updated_at_ms BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS agent_states (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL,
+    capital BIGINT NOT NULL,
+    risk_tolerance REAL NOT NULL,
+    state TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS agent_states_agent ON agent_states (agent_id, id);
+
+CREATE TABLE IF NOT EXISTS agent_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    executed BOOLEAN NOT NULL,
+    error TEXT
+);
+CREATE INDEX IF NOT EXISTS agent_actions_agent ON agent_actions (agent_id, id);
+
+CREATE TABLE IF NOT EXISTS ml_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL,
+    confidence REAL NOT NULL,
+    risk_score REAL NOT NULL,
+    decision TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ml_decisions_agent ON ml_decisions (agent_id, id);
+
+CREATE TABLE IF NOT EXISTS vault_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vault_id TEXT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL,
+    balance BIGINT NOT NULL,
+    total_value BIGINT NOT NULL,
+    total_shares BIGINT NOT NULL,
+    transaction_digest TEXT
+);
+CREATE INDEX IF NOT EXISTS vault_snapshots_vault ON vault_snapshots (vault_id, id);
+
+CREATE TABLE IF NOT EXISTS vault_transactions (
+    digest TEXT PRIMARY KEY,
+    vault_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    shares BIGINT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS vault_transactions_vault ON vault_transactions (vault_id, recorded_at_ms);
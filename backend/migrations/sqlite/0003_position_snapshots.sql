@@ -0,0 +1,10 @@
+-- Holdings of vault agents around each execution, for performance attribution
+
+CREATE TABLE IF NOT EXISTS position_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vault_id TEXT NOT NULL,
+    recorded_at_ms BIGINT NOT NULL,
+    snapshot TEXT NOT NULL,
+    trade TEXT
+);
+CREATE INDEX IF NOT EXISTS position_snapshots_vault ON position_snapshots (vault_id, recorded_at_ms, id);
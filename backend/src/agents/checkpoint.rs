@@ -0,0 +1,477 @@
+//! Agent checkpoints and crash recovery
+//!
+//! After every executed action an agent's [`AgentState`] and its model and
+//! strategy state are checkpointed, along with the on-chain balances of the
+//! vault it manages. On startup agents resume from their latest checkpoint.
+//! If the vault's balances have moved since, the checkpoint no longer
+//! describes what is on chain: the agent is still restored, but held to
+//! risk-reducing actions until an operator clears its checkpoint or
+//! re-baselines it against the balances on chain.
+
+use crate::agents::registry::AgentRegistry;
+use crate::agents::{Agent, AgentState};
+use crate::error::{ManusError, Result};
+use crate::storage::{Repository, VaultSnapshot};
+use crate::sui::{SuiClient, VaultInfo};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Default relative change in a vault balance tolerated between a checkpoint and the chain
+pub const DEFAULT_DIVERGENCE_TOLERANCE: f64 = 0.001;
+
+/// Everything an agent needs to resume where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Agent state
+    pub state: AgentState,
+
+    /// Model and strategy state, for agents that keep any
+    #[serde(default)]
+    pub strategy: Option<Value>,
+
+    /// Balances of the vault the agent manages, as of the checkpoint
+    #[serde(default)]
+    pub vault: Option<VaultSnapshot>,
+}
+
+impl Checkpoint {
+    /// Checkpoint `agent`
+    pub fn capture(agent: &dyn Agent) -> Self {
+        Self {
+            state: agent.state().clone(),
+            strategy: agent.strategy_state(),
+            vault: None,
+        }
+    }
+
+    /// Record the balances of the vault the agent manages
+    pub fn with_vault(mut self, vault: VaultSnapshot) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// ID of the checkpointed agent
+    pub fn agent_id(&self) -> &str {
+        &self.state.id
+    }
+
+    /// Overwrite `state` with the checkpointed state
+    pub fn restore_state(&self, state: &mut AgentState) -> Result<()> {
+        if state.id != self.state.id {
+            return Err(ManusError::Agent(format!(
+                "Checkpoint of agent {} cannot restore agent {}",
+                self.state.id, state.id
+            )));
+        }
+        *state = self.state.clone();
+        Ok(())
+    }
+
+    /// Decode the checkpointed strategy state
+    pub fn strategy<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        self.strategy
+            .clone()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                ManusError::Agent(format!(
+                    "Invalid strategy state in checkpoint of {}: {}",
+                    self.state.id, e
+                ))
+            })
+    }
+
+    /// How `vault` has moved since the checkpoint by more than `tolerance`, if it has
+    pub fn divergence(&self, vault: &VaultInfo, tolerance: f64) -> Option<Divergence> {
+        let on_chain = VaultSnapshot::new(vault, None);
+        let diverged = match &self.vault {
+            Some(checkpointed) => {
+                checkpointed.vault_id != on_chain.vault_id
+                    || moved(checkpointed.balance, on_chain.balance, tolerance)
+                    || moved(checkpointed.total_value, on_chain.total_value, tolerance)
+                    || moved(checkpointed.total_shares, on_chain.total_shares, tolerance)
+            }
+            None => true,
+        };
+
+        diverged.then(|| Divergence {
+            agent_id: self.state.id.clone(),
+            checkpointed: self.vault.clone(),
+            on_chain,
+        })
+    }
+}
+
+/// Whether `current` differs from `previous` by more than `tolerance` of `previous`
+fn moved(previous: u64, current: u64, tolerance: f64) -> bool {
+    let change = previous.abs_diff(current) as f64;
+    change > previous as f64 * tolerance
+}
+
+/// Vault balances that moved since an agent's checkpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Divergence {
+    /// Agent ID
+    pub agent_id: String,
+
+    /// Balances as of the checkpoint, if they were recorded
+    pub checkpointed: Option<VaultSnapshot>,
+
+    /// Balances on chain now
+    pub on_chain: VaultSnapshot,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_chain = &self.on_chain;
+        match &self.checkpointed {
+            Some(checkpointed) => write!(
+                f,
+                "agent {} checkpointed vault {} at balance {}, value {}, shares {}; \
+                 vault {} is at balance {}, value {}, shares {}",
+                self.agent_id,
+                checkpointed.vault_id,
+                checkpointed.balance,
+                checkpointed.total_value,
+                checkpointed.total_shares,
+                on_chain.vault_id,
+                on_chain.balance,
+                on_chain.total_value,
+                on_chain.total_shares
+            ),
+            None => write!(
+                f,
+                "agent {} has no checkpointed balances for vault {}",
+                self.agent_id, on_chain.vault_id
+            ),
+        }
+    }
+}
+
+/// Outcome of resuming one agent from its checkpoint
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// Restored from a checkpoint
+    Resumed,
+
+    /// No checkpoint, starting from the initial state
+    Fresh,
+
+    /// Restored from a checkpoint its vault has since diverged from
+    Diverged(Divergence),
+}
+
+/// Outcome of resuming agents from their checkpoints
+#[derive(Debug, Clone, Default)]
+pub struct RecoveryReport {
+    /// Agents restored from a checkpoint
+    pub resumed: Vec<String>,
+
+    /// Agents without a checkpoint, starting from their initial state
+    pub fresh: Vec<String>,
+
+    /// Agents restored from a checkpoint their vault has since diverged from
+    pub diverged: Vec<Divergence>,
+}
+
+/// Saves agent checkpoints and resumes agents from them
+pub struct Checkpointer {
+    repository: Arc<Repository>,
+    sui: Option<Arc<SuiClient>>,
+    tolerance: f64,
+}
+
+impl Checkpointer {
+    /// Checkpoint to `repository`
+    ///
+    /// Vault balances are neither recorded nor checked without a Sui client.
+    pub fn new(repository: Arc<Repository>) -> Self {
+        Self {
+            repository,
+            sui: None,
+            tolerance: DEFAULT_DIVERGENCE_TOLERANCE,
+        }
+    }
+
+    /// Record and check the balances of managed vaults through `sui`
+    pub fn with_sui_client(mut self, sui: Arc<SuiClient>) -> Self {
+        self.sui = Some(sui);
+        self
+    }
+
+    /// Set the relative change in a vault balance tolerated on recovery
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Checkpoint agent `id` in `registry`
+    pub async fn checkpoint(&self, registry: &AgentRegistry, id: &str) -> Result<()> {
+        let info = registry.get(id).await.ok_or_else(|| not_registered(id))?;
+        let mut checkpoint = registry
+            .with_agent(id, |agent| Checkpoint::capture(agent))
+            .await
+            .ok_or_else(|| not_registered(id))?;
+        if let (Some(vault_id), Some(sui)) = (&info.vault_id, &self.sui) {
+            let vault = sui.get_vault(vault_id).await?;
+            checkpoint = checkpoint.with_vault(VaultSnapshot::new(&vault, None));
+        }
+
+        self.repository.save_checkpoint(&checkpoint).await
+    }
+
+    /// Restore every agent in `registry` from its latest checkpoint
+    ///
+    /// Agents managing a vault whose balances no longer match the checkpoint
+    /// are restored but reported as diverged.
+    pub async fn resume(&self, registry: &AgentRegistry) -> Result<RecoveryReport> {
+        let mut report = RecoveryReport::default();
+        for id in registry.ids().await {
+            match self.resume_agent(registry, &id).await? {
+                Recovery::Resumed => report.resumed.push(id),
+                Recovery::Fresh => report.fresh.push(id),
+                Recovery::Diverged(divergence) => report.diverged.push(divergence),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Restore agent `id` in `registry` from its latest checkpoint
+    ///
+    /// The agent is restored even if the vault it manages has diverged from
+    /// the checkpoint.
+    pub async fn resume_agent(&self, registry: &AgentRegistry, id: &str) -> Result<Recovery> {
+        let info = registry.get(id).await.ok_or_else(|| not_registered(id))?;
+        let Some(checkpoint) = self.repository.latest_checkpoint(id).await? else {
+            return Ok(Recovery::Fresh);
+        };
+
+        let divergence = match (&info.vault_id, &self.sui) {
+            (Some(vault_id), Some(sui)) => {
+                let vault = sui.get_vault(vault_id).await?;
+                checkpoint.value.divergence(&vault, self.tolerance)
+            }
+            _ => None,
+        };
+        registry
+            .with_agent(id, |agent| agent.restore(&checkpoint.value))
+            .await
+            .ok_or_else(|| not_registered(id))??;
+
+        Ok(divergence.map_or(Recovery::Resumed, Recovery::Diverged))
+    }
+
+    /// How the vault agent `id` in `registry` manages moved since its latest checkpoint
+    ///
+    /// `None` if the agent has no checkpoint or its vault still matches it.
+    pub async fn divergence(
+        &self,
+        registry: &AgentRegistry,
+        id: &str,
+    ) -> Result<Option<Divergence>> {
+        let info = registry.get(id).await.ok_or_else(|| not_registered(id))?;
+        let Some(checkpoint) = self.repository.latest_checkpoint(id).await? else {
+            return Ok(None);
+        };
+        match (&info.vault_id, &self.sui) {
+            (Some(vault_id), Some(sui)) => {
+                let vault = sui.get_vault(vault_id).await?;
+                Ok(checkpoint.value.divergence(&vault, self.tolerance))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether agent `id` has a checkpoint
+    pub async fn has_checkpoint(&self, id: &str) -> Result<bool> {
+        Ok(self.repository.latest_checkpoint(id).await?.is_some())
+    }
+
+    /// Delete the checkpoint of agent `id`, returning whether it had one
+    ///
+    /// A held agent is released and checkpointed afresh after its next execution.
+    pub async fn clear(&self, id: &str) -> Result<bool> {
+        self.repository.clear_checkpoint(id).await
+    }
+
+    /// Replace the vault balances in the checkpoint of agent `id` with those on chain now
+    ///
+    /// The balances of `vault_id` are recorded, or of the vault already in the
+    /// checkpoint when not given. The agent state is kept, so a held agent is
+    /// released without being reset. Returns `None` if the agent has no checkpoint.
+    pub async fn rebaseline(&self, id: &str, vault_id: Option<&str>) -> Result<Option<Checkpoint>> {
+        let Some(checkpoint) = self.repository.latest_checkpoint(id).await? else {
+            return Ok(None);
+        };
+        let checkpoint = checkpoint.value;
+        let vault_id = vault_id
+            .or(checkpoint
+                .vault
+                .as_ref()
+                .map(|vault| vault.vault_id.as_str()))
+            .ok_or_else(|| ManusError::Agent(format!("Agent {} manages no vault", id)))?
+            .to_string();
+        let sui = self.sui.as_ref().ok_or_else(|| {
+            ManusError::Config("No Sui client to read vault balances from".to_string())
+        })?;
+
+        let vault = sui.get_vault(&vault_id).await?;
+        let checkpoint = checkpoint.with_vault(VaultSnapshot::new(&vault, None));
+        self.repository.save_checkpoint(&checkpoint).await?;
+        Ok(Some(checkpoint))
+    }
+}
+
+fn not_registered(id: &str) -> ManusError {
+    ManusError::Agent(format!("Agent {} not registered", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::concentrated::RangeProviderAgent;
+    use crate::agents::orchestrator::MarketWindow;
+    use crate::agents::registry::{AgentKind, AgentSpec};
+    use crate::agents::valuation::InvariantSet;
+    use crate::sui::{MockSuiNode, SuiKeypair};
+
+    const SUI: &str = "0x2::sui::SUI";
+
+    fn spec(vault_id: Option<&str>) -> AgentSpec {
+        AgentSpec {
+            id: "lp".to_string(),
+            kind: AgentKind::RangeProvider,
+            initial_capital: 1_000_000,
+            risk_tolerance: 0.5,
+            max_drawdown: None,
+            model: None,
+            min_confidence: None,
+            volatility_threshold: None,
+            invariants: InvariantSet::default(),
+            vault_id: vault_id.map(str::to_string),
+        }
+    }
+
+    /// Provide liquidity so the agent holds LP tokens and idle balances
+    async fn trade(registry: &AgentRegistry) {
+        let mut window = MarketWindow::new(8);
+        for price in [1.0, 1.02] {
+            let market_data = window.push(price, 10_000.0, 1_000_000.0);
+            registry
+                .with_agent("lp", |agent| {
+                    agent.observe(&market_data);
+                    let action = agent.decide().unwrap();
+                    agent.execute(action).unwrap();
+                })
+                .await;
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_other_agents() {
+        let agent = RangeProviderAgent::new("lp".to_string(), 1_000);
+        let checkpoint = Checkpoint::capture(&agent);
+        let mut other = RangeProviderAgent::new("other".to_string(), 1_000);
+        assert!(other.restore(&checkpoint).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_checkpoint() {
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let checkpointer = Checkpointer::new(repository);
+
+        let registry = AgentRegistry::new();
+        registry.create(&spec(None)).await.unwrap();
+        trade(&registry).await;
+        checkpointer.checkpoint(&registry, "lp").await.unwrap();
+        let before = registry.get("lp").await.unwrap().state;
+        let fees = registry.with_agent("lp", |agent| agent.fees_earned()).await;
+        assert!(!before.positions.is_empty());
+
+        // A restarted runner builds the agent from its spec again
+        let restarted = AgentRegistry::new();
+        restarted.create(&spec(None)).await.unwrap();
+        let report = checkpointer.resume(&restarted).await.unwrap();
+        assert_eq!(report.resumed, vec!["lp".to_string()]);
+        assert!(report.diverged.is_empty());
+
+        let after = restarted.get("lp").await.unwrap().state;
+        assert_eq!(after.capital, before.capital);
+        assert_eq!(after.positions.len(), before.positions.len());
+        assert_eq!(
+            restarted
+                .with_agent("lp", |agent| agent.fees_earned())
+                .await,
+            fees
+        );
+
+        let unknown = AgentRegistry::new();
+        unknown
+            .create(&AgentSpec {
+                id: "new".to_string(),
+                ..spec(None)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            checkpointer.resume(&unknown).await.unwrap().fresh,
+            vec!["new".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vault_divergence_detected() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[5u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 1_000_000, "lp");
+        node.mint_coin(&keypair.address(), SUI, 1_000_000_000);
+        let sui = Arc::new(SuiClient::with_transport(node).with_keypair(keypair));
+        sui.deposit(&vault_id, 10_000).await.unwrap();
+
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let checkpointer = Checkpointer::new(repository).with_sui_client(sui.clone());
+        let registry = AgentRegistry::new();
+        registry.create(&spec(Some(&vault_id))).await.unwrap();
+        checkpointer.checkpoint(&registry, "lp").await.unwrap();
+
+        let report = checkpointer.resume(&registry).await.unwrap();
+        assert_eq!(report.resumed, vec!["lp".to_string()]);
+
+        // Funds moved while the agent was down
+        sui.deposit(&vault_id, 5_000).await.unwrap();
+        let report = checkpointer.resume(&registry).await.unwrap();
+        assert!(report.resumed.is_empty());
+        let divergence = &report.diverged[0];
+        assert_eq!(divergence.checkpointed.as_ref().unwrap().balance, 10_000);
+        assert_eq!(divergence.on_chain.balance, 15_000);
+
+        // Re-baselining accepts the balances on chain without touching the agent state
+        let capital = registry.get("lp").await.unwrap().state.capital;
+        let rebaselined = checkpointer.rebaseline("lp", None).await.unwrap().unwrap();
+        assert_eq!(rebaselined.vault.unwrap().balance, 15_000);
+        assert_eq!(rebaselined.state.capital, capital);
+        assert!(checkpointer
+            .divergence(&registry, "lp")
+            .await
+            .unwrap()
+            .is_none());
+
+        sui.deposit(&vault_id, 5_000).await.unwrap();
+        assert!(checkpointer
+            .divergence(&registry, "lp")
+            .await
+            .unwrap()
+            .is_some());
+        assert!(checkpointer.clear("lp").await.unwrap());
+        assert!(checkpointer
+            .divergence(&registry, "lp")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(checkpointer.rebaseline("lp", None).await.unwrap().is_none());
+    }
+}
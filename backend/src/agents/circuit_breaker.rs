@@ -0,0 +1,320 @@
+//! Mirror of the on-chain `RiskControl` circuit breaker
+//!
+//! A [`RiskControlSync`] service polls a
+//! `deepbook_lp_risk_controls::risk_control::RiskControl` object and publishes
+//! its state to [`CircuitBreaker`] handles. While the breaker is active the
+//! orchestrator only lets agents hold or withdraw, and it fails closed: until
+//! the state is first read, once it goes stale and after the service stops,
+//! the breaker counts as active. Handles can also ask the
+//! service to activate the breaker on chain, which the orchestrator does when
+//! a risk manager's limits trip.
+
+use crate::error::Result;
+use crate::sui::objects::RiskControl;
+use crate::sui::{SuiClient, VaultReceipt};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+
+/// Default delay between reads of the `RiskControl` object
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Poll intervals without a successful read after which the mirrored state is stale
+pub const STALE_AFTER_POLLS: u32 = 3;
+
+/// Backend view of a `RiskControl` object
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BreakerState {
+    /// Whether the circuit breaker is active
+    pub active: bool,
+    /// Drawdown limit (0.0 - 1.0)
+    pub drawdown_limit: f64,
+    /// Whether the state was read from chain recently enough to be trusted
+    pub synced: bool,
+}
+
+impl From<&RiskControl> for BreakerState {
+    fn from(risk_control: &RiskControl) -> Self {
+        Self {
+            active: risk_control.circuit_breaker_active,
+            drawdown_limit: risk_control.drawdown_limit as f64 / 10_000.0,
+            synced: true,
+        }
+    }
+}
+
+/// Cheap handle on the mirrored circuit breaker
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    state: watch::Receiver<BreakerState>,
+    trips: mpsc::UnboundedSender<String>,
+}
+
+impl CircuitBreaker {
+    /// Latest mirrored state
+    pub fn state(&self) -> BreakerState {
+        self.state.borrow().clone()
+    }
+
+    /// Whether trading is halted
+    ///
+    /// True while the breaker was active at the last sync, and whenever its
+    /// state is unknown: before the first sync, once the last sync is stale
+    /// and after the sync service stopped.
+    pub fn is_active(&self) -> bool {
+        let state = self.state.borrow();
+        state.active || !state.synced || self.trips.is_closed()
+    }
+
+    /// Ask the sync service to activate the breaker on chain
+    ///
+    /// Returns false if the service has stopped.
+    pub fn trip(&self, reason: impl Into<String>) -> bool {
+        self.trips.send(reason.into()).is_ok()
+    }
+
+    /// Subscribe to state changes
+    pub fn subscribe(&self) -> watch::Receiver<BreakerState> {
+        self.state.clone()
+    }
+}
+
+/// Keeps a [`CircuitBreaker`] in sync with a `RiskControl` object
+pub struct RiskControlSync {
+    client: Arc<SuiClient>,
+    risk_control_id: String,
+    poll_interval: Duration,
+    state: watch::Sender<BreakerState>,
+    trips: mpsc::UnboundedReceiver<String>,
+}
+
+impl RiskControlSync {
+    /// Create a service mirroring `risk_control_id`, and a handle on it
+    ///
+    /// The handle reports an inactive, unsynced breaker until the first read.
+    pub fn new(client: Arc<SuiClient>, risk_control_id: &str) -> (Self, CircuitBreaker) {
+        let (state, state_rx) = watch::channel(BreakerState::default());
+        let (trips_tx, trips) = mpsc::unbounded_channel();
+        let sync = Self {
+            client,
+            risk_control_id: risk_control_id.to_string(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            state,
+            trips,
+        };
+
+        (
+            sync,
+            CircuitBreaker {
+                state: state_rx,
+                trips: trips_tx,
+            },
+        )
+    }
+
+    /// Set the delay between reads of the `RiskControl` object
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Read the `RiskControl` object and publish its state
+    pub async fn refresh(&self) -> Result<BreakerState> {
+        let risk_control = self.client.get_risk_control(&self.risk_control_id).await?;
+        let state = BreakerState::from(&risk_control);
+        self.state.send_if_modified(|current| {
+            let changed = *current != state;
+            *current = state.clone();
+            changed
+        });
+        Ok(state)
+    }
+
+    /// Activate the circuit breaker on chain unless it is already active
+    ///
+    /// Returns the receipt of the activation transaction, if one was sent.
+    pub async fn activate(&self, reason: &str) -> Result<Option<VaultReceipt>> {
+        if self.refresh().await?.active {
+            return Ok(None);
+        }
+
+        tracing::warn!(
+            "Activating circuit breaker {}: {}",
+            self.risk_control_id,
+            reason
+        );
+        let receipt = self
+            .client
+            .set_circuit_breaker(&self.risk_control_id, true)
+            .await?;
+        self.refresh().await?;
+        Ok(Some(receipt))
+    }
+
+    /// Poll the object and serve activation requests until every handle is dropped
+    ///
+    /// The state is marked unsynced once no read succeeded for
+    /// [`STALE_AFTER_POLLS`] poll intervals.
+    pub async fn run(mut self) {
+        let mut poll = tokio::time::interval(self.poll_interval);
+        let stale_after = self.poll_interval * STALE_AFTER_POLLS;
+        let mut last_synced = Instant::now();
+        loop {
+            tokio::select! {
+                _ = poll.tick() => match self.refresh().await {
+                    Ok(_) => last_synced = Instant::now(),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to sync risk control {}: {}",
+                            self.risk_control_id,
+                            e
+                        );
+                        if last_synced.elapsed() >= stale_after {
+                            self.mark_stale();
+                        }
+                    }
+                },
+                trip = self.trips.recv() => match trip {
+                    Some(reason) => {
+                        if let Err(e) = self.activate(&reason).await {
+                            tracing::error!(
+                                "Failed to activate circuit breaker {}: {}",
+                                self.risk_control_id,
+                                e
+                            );
+                        }
+                    }
+                    None => return,
+                },
+            }
+        }
+    }
+
+    /// Stop trusting the mirrored state until the next successful read
+    fn mark_stale(&self) {
+        self.state.send_if_modified(|current| {
+            let changed = current.synced;
+            if changed {
+                tracing::error!(
+                    "Risk control {} is stale, halting trading until it syncs",
+                    self.risk_control_id
+                );
+            }
+            current.synced = false;
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ManusError;
+    use crate::sui::{MockSuiNode, SuiKeypair, SuiTransport};
+    use async_trait::async_trait;
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Node that can be taken offline
+    struct Outage {
+        node: Arc<MockSuiNode>,
+        down: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SuiTransport for Outage {
+        async fn request(&self, method: &str, params: Value) -> Result<Value> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(ManusError::Sui("node unreachable".to_string()));
+            }
+            self.node.request(method, params).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_and_trip() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[12u8; 32]);
+        let id = node.create_risk_control(&admin.address(), 1_500, 0);
+        node.mint_coin(&admin.address(), "0x2::sui::SUI", 1_000_000_000);
+        let client = Arc::new(SuiClient::with_transport(node.clone()).with_keypair(admin));
+
+        let (sync, breaker) = RiskControlSync::new(client, &id);
+        // Unknown until read
+        assert!(!breaker.state().synced);
+        assert!(breaker.is_active());
+        let state = sync.refresh().await.unwrap();
+        assert!(state.synced && !state.active);
+        assert!(!breaker.is_active());
+        assert!((breaker.state().drawdown_limit - 0.15).abs() < 1e-12);
+
+        // Activated elsewhere
+        node.set_circuit_breaker(&id, true);
+        sync.refresh().await.unwrap();
+        assert!(breaker.is_active());
+        assert!(sync.activate("again").await.unwrap().is_none());
+        node.set_circuit_breaker(&id, false);
+        sync.refresh().await.unwrap();
+        assert!(!breaker.is_active());
+
+        // Tripped through a handle
+        let mut changes = breaker.subscribe();
+        let service = tokio::spawn(sync.with_poll_interval(Duration::from_secs(3600)).run());
+        assert!(breaker.trip("drawdown"));
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            changes.wait_for(|state| state.active),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        drop(changes);
+        drop(breaker);
+        service.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_state_halts() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[15u8; 32]);
+        let id = node.create_risk_control(&admin.address(), 1_500, 0);
+        let outage = Arc::new(Outage {
+            node,
+            down: AtomicBool::new(false),
+        });
+        let client = Arc::new(SuiClient::with_transport(outage.clone()));
+        let (sync, breaker) = RiskControlSync::new(client, &id);
+        let sync = sync.with_poll_interval(Duration::from_millis(10));
+        sync.refresh().await.unwrap();
+        let mut changes = breaker.subscribe();
+        let service = tokio::spawn(sync.run());
+        assert!(!breaker.is_active());
+
+        outage.down.store(true, Ordering::SeqCst);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            changes.wait_for(|state| !state.synced),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(breaker.is_active());
+
+        outage.down.store(false, Ordering::SeqCst);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            changes.wait_for(|state| state.synced),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!breaker.is_active());
+
+        // A stopped service leaves the state unknown
+        service.abort();
+        let _ = service.await;
+        assert!(breaker.is_active());
+    }
+}
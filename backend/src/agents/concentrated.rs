@@ -0,0 +1,891 @@
+//! Concentrated liquidity positions and range selection
+//!
+//! A [`ConcentratedPosition`] provides liquidity in one or more price ranges,
+//! the way the strategies in `contracts/deepbook_lp_strategies` are meant to.
+//! Within a range its tokens follow the constant-product curve, so it holds
+//! only quote below the range and only base above it. Fees accrue to the
+//! ranges the price trades through, in proportion to their share of the
+//! pool's liquidity, and impermanent loss is measured against holding the
+//! deposited tokens. A [`RangeSelector`] sizes ranges from volatility and
+//! decides when to re-centre them, and [`RangeProviderAgent`] runs it the same
+//! way in a [`Backtest`](crate::backtest::Backtest) and under the orchestrator.
+
+use crate::agents::checkpoint::Checkpoint;
+use crate::agents::ml_agent::MarketData;
+use crate::agents::rebalancer::{ExecutionEngine, BASE_ASSET};
+use crate::agents::regime::RegimeTransition;
+use crate::agents::valuation::InvariantReport;
+use crate::agents::{Agent, AgentAction, AgentState, Position};
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+
+/// Default share of traded volume paid to liquidity providers
+pub const DEFAULT_FEE_RATE: f64 = 0.003;
+
+/// Prices between `lower` and `upper`, in quote units per base unit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceRange {
+    /// Lowest price of the range
+    pub lower: f64,
+    /// Highest price of the range
+    pub upper: f64,
+}
+
+impl PriceRange {
+    /// Range from `lower` to `upper`, which must satisfy `0 < lower < upper`
+    pub fn new(lower: f64, upper: f64) -> Result<Self> {
+        let range = Self { lower, upper };
+        range.validate()?;
+        Ok(range)
+    }
+
+    /// Range reaching `half_width` either side of `centre` in log price
+    pub fn around(centre: f64, half_width: f64) -> Self {
+        Self {
+            lower: centre * (-half_width).exp(),
+            upper: centre * half_width.exp(),
+        }
+    }
+
+    /// Geometric mean of the bounds
+    pub fn centre(&self) -> f64 {
+        (self.lower * self.upper).sqrt()
+    }
+
+    /// Half the distance between the bounds in log price
+    pub fn half_width(&self) -> f64 {
+        (self.upper / self.lower).ln() / 2.0
+    }
+
+    /// Whether `price` lies within the range
+    pub fn contains(&self, price: f64) -> bool {
+        (self.lower..=self.upper).contains(&price)
+    }
+
+    /// Base and quote units backing one unit of liquidity at `price`
+    pub fn amounts(&self, price: f64) -> (f64, f64) {
+        let (sqrt_lower, sqrt_upper) = (self.lower.sqrt(), self.upper.sqrt());
+        let sqrt_price = price.sqrt().clamp(sqrt_lower, sqrt_upper);
+        (1.0 / sqrt_price - 1.0 / sqrt_upper, sqrt_price - sqrt_lower)
+    }
+
+    /// Fraction of a move from `from` to `to` spent inside the range
+    ///
+    /// The price is assumed to move evenly in log terms; without a move the
+    /// fraction is 1 if `to` is in the range and 0 otherwise.
+    pub fn time_in_range(&self, from: f64, to: f64) -> f64 {
+        let (start, end) = (from.ln().min(to.ln()), from.ln().max(to.ln()));
+        if end - start < 1e-12 {
+            return if self.contains(to) { 1.0 } else { 0.0 };
+        }
+        let overlap = end.min(self.upper.ln()) - start.max(self.lower.ln());
+        overlap.max(0.0) / (end - start)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.lower > 0.0 && self.lower < self.upper && self.upper.is_finite()) {
+            return Err(ManusError::Agent(format!(
+                "Invalid price range {}..{}",
+                self.lower, self.upper
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Share of a provider's value to place in a price range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityRange {
+    /// Price range
+    #[serde(flatten)]
+    pub range: PriceRange,
+    /// Share of value (0.0 - 1.0)
+    pub weight: f64,
+}
+
+/// Reject invalid ranges and weights summing to more than 1
+pub fn validate_ranges(ranges: &[LiquidityRange]) -> Result<()> {
+    for range in ranges {
+        range.range.validate()?;
+        if !(0.0..=1.0).contains(&range.weight) {
+            return Err(ManusError::Agent(format!(
+                "Range weight must be between 0 and 1, got {}",
+                range.weight
+            )));
+        }
+    }
+    if ranges.iter().map(|range| range.weight).sum::<f64>() > 1.0 + 1e-9 {
+        return Err(ManusError::Agent(
+            "Range weights sum to more than 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Liquidity provided in one price range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RangePosition {
+    /// Price range
+    pub range: PriceRange,
+    /// Liquidity, in units of the square root of base times quote
+    pub liquidity: f64,
+    /// Fees earned and not yet collected, in quote units
+    pub fees: f64,
+}
+
+impl RangePosition {
+    /// Base and quote units held at `price`, fees excluded
+    pub fn amounts(&self, price: f64) -> (f64, f64) {
+        let (base, quote) = self.range.amounts(price);
+        (base * self.liquidity, quote * self.liquidity)
+    }
+}
+
+/// Liquidity provided across price ranges of one pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcentratedPosition {
+    fee_rate: f64,
+    ranges: Vec<RangePosition>,
+    deposited: (f64, f64),
+    fees_earned: f64,
+}
+
+impl Default for ConcentratedPosition {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_RATE)
+    }
+}
+
+impl ConcentratedPosition {
+    /// Empty position in a pool charging `fee_rate` of traded volume
+    pub fn new(fee_rate: f64) -> Self {
+        Self {
+            fee_rate,
+            ranges: vec![],
+            deposited: (0.0, 0.0),
+            fees_earned: 0.0,
+        }
+    }
+
+    /// Share of traded volume paid to liquidity providers
+    pub fn fee_rate(&self) -> f64 {
+        self.fee_rate
+    }
+
+    /// Liquidity in each range, in the order ranges were first deposited into
+    pub fn ranges(&self) -> &[RangePosition] {
+        &self.ranges
+    }
+
+    /// Whether no liquidity is provided
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Add `liquidity` to `range`, returning the base and quote units it takes at `price`
+    pub fn add_liquidity(
+        &mut self,
+        range: PriceRange,
+        liquidity: f64,
+        price: f64,
+    ) -> Result<(f64, f64)> {
+        range.validate()?;
+        if !(liquidity > 0.0 && liquidity.is_finite()) {
+            return Err(ManusError::Agent(format!(
+                "Liquidity must be positive, got {}",
+                liquidity
+            )));
+        }
+
+        match self
+            .ranges
+            .iter_mut()
+            .find(|position| position.range == range)
+        {
+            Some(position) => position.liquidity += liquidity,
+            None => self.ranges.push(RangePosition {
+                range,
+                liquidity,
+                fees: 0.0,
+            }),
+        }
+        let (base, quote) = range.amounts(price);
+        let used = (base * liquidity, quote * liquidity);
+        self.deposited.0 += used.0;
+        self.deposited.1 += used.1;
+        Ok(used)
+    }
+
+    /// Deposit as much of `base` and `quote` into `range` as its ratio at `price` allows
+    ///
+    /// Returns the base and quote units deposited.
+    pub fn deposit(
+        &mut self,
+        range: PriceRange,
+        base: f64,
+        quote: f64,
+        price: f64,
+    ) -> Result<(f64, f64)> {
+        range.validate()?;
+        let (unit_base, unit_quote) = range.amounts(price);
+        let liquidity = match (unit_base > 0.0, unit_quote > 0.0) {
+            (true, true) => (base / unit_base).min(quote / unit_quote),
+            (true, false) => base / unit_base,
+            _ => quote / unit_quote,
+        };
+        self.add_liquidity(range, liquidity, price)
+    }
+
+    /// Remove all liquidity and collect fees, returning base and quote units
+    pub fn withdraw(&mut self, price: f64) -> (f64, f64) {
+        let (base, quote) = self.amounts(price);
+        let fees = self.fees();
+        self.ranges.clear();
+        self.deposited = (0.0, 0.0);
+        (base, quote + fees)
+    }
+
+    /// Base and quote units held at `price`, fees excluded
+    pub fn amounts(&self, price: f64) -> (f64, f64) {
+        self.ranges
+            .iter()
+            .map(|position| position.amounts(price))
+            .fold((0.0, 0.0), |(base, quote), (b, q)| (base + b, quote + q))
+    }
+
+    /// Fees earned and not yet collected, in quote units
+    pub fn fees(&self) -> f64 {
+        self.ranges.iter().map(|position| position.fees).sum()
+    }
+
+    /// Fees earned over the position's lifetime, collected or not
+    pub fn fees_earned(&self) -> f64 {
+        self.fees_earned
+    }
+
+    /// Value at `price` in quote units, uncollected fees included
+    pub fn value(&self, price: f64) -> f64 {
+        let (base, quote) = self.amounts(price);
+        base * price + quote + self.fees()
+    }
+
+    /// Value at `price` of the tokens deposited since the last withdrawal
+    pub fn hold_value(&self, price: f64) -> f64 {
+        self.deposited.0 * price + self.deposited.1
+    }
+
+    /// Shortfall of the liquidity against holding the deposited tokens (0.0 - 1.0)
+    ///
+    /// Fees are excluded; the loss is zero at the price of deposit.
+    pub fn impermanent_loss(&self, price: f64) -> f64 {
+        let hold = self.hold_value(price);
+        if hold <= 0.0 {
+            return 0.0;
+        }
+        let (base, quote) = self.amounts(price);
+        (1.0 - (base * price + quote) / hold).max(0.0)
+    }
+
+    /// Accrue fees for a move from `from` to `to` trading `volume` quote units
+    ///
+    /// `pool_liquidity` is the value in quote units of the other liquidity in
+    /// the pool, treated as spread over all prices. Each range earns the
+    /// volume traded while the price was inside it, in proportion to its
+    /// share of the liquidity active there. Returns the fees accrued.
+    pub fn accrue(&mut self, from: f64, to: f64, volume: f64, pool_liquidity: f64) -> f64 {
+        if self.ranges.is_empty() || volume <= 0.0 || from <= 0.0 || to <= 0.0 {
+            return 0.0;
+        }
+        // A full-range position worth V at price p has liquidity V / (2 sqrt p)
+        let pool = pool_liquidity.max(0.0) / (2.0 * to.sqrt());
+        let in_range: Vec<f64> = self
+            .ranges
+            .iter()
+            .map(|position| position.range.time_in_range(from, to))
+            .collect();
+        let active: f64 = self
+            .ranges
+            .iter()
+            .zip(&in_range)
+            .map(|(position, time)| position.liquidity * time)
+            .sum();
+        if pool + active <= 0.0 {
+            return 0.0;
+        }
+
+        let mut accrued = 0.0;
+        for (position, time) in self.ranges.iter_mut().zip(in_range) {
+            let fees = volume * self.fee_rate * time * position.liquidity / (pool + active);
+            position.fees += fees;
+            accrued += fees;
+        }
+        self.fees_earned += accrued;
+        accrued
+    }
+}
+
+/// Range laid around the core range, sized relative to it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RangeBand {
+    /// Half-width as a multiple of the core range's
+    pub width: f64,
+    /// Share of value placed in the range (0.0 - 1.0)
+    pub weight: f64,
+}
+
+/// How ranges are sized and when they are re-centred
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RangeConfig {
+    /// Half-width of the core range, in standard deviations of the price over the horizon
+    pub width: f64,
+    /// Snapshots the core range should hold the price for
+    pub horizon: f64,
+    /// Narrowest core half-width, in log price
+    pub min_width: f64,
+    /// Widest core half-width, in log price
+    pub max_width: f64,
+    /// Ranges provided, the first being the core range
+    pub bands: Vec<RangeBand>,
+    /// Distance of the price from the centre, in core half-widths, that triggers a re-centre
+    pub recenter_threshold: f64,
+    /// Relative change of the core half-width that triggers a re-centre
+    pub width_tolerance: f64,
+}
+
+impl Default for RangeConfig {
+    fn default() -> Self {
+        Self {
+            width: 2.0,
+            horizon: 24.0,
+            min_width: 0.005,
+            max_width: 1.0,
+            bands: vec![
+                RangeBand {
+                    width: 1.0,
+                    weight: 0.8,
+                },
+                RangeBand {
+                    width: 3.0,
+                    weight: 0.2,
+                },
+            ],
+            recenter_threshold: 0.5,
+            width_tolerance: 0.5,
+        }
+    }
+}
+
+/// Chooses price ranges from volatility
+#[derive(Debug, Clone, Default)]
+pub struct RangeSelector {
+    config: RangeConfig,
+}
+
+impl RangeSelector {
+    /// Create a selector sizing ranges as `config` describes
+    pub fn new(config: RangeConfig) -> Self {
+        Self { config }
+    }
+
+    /// Range sizing
+    pub fn config(&self) -> &RangeConfig {
+        &self.config
+    }
+
+    /// Core half-width in log price for per-snapshot `volatility`
+    ///
+    /// At a risk tolerance of 0.5 the range spans the configured number of
+    /// standard deviations; at 0.0 it is 1.5 times wider and at 1.0 half as
+    /// wide, concentrating liquidity to earn more fees at more risk.
+    pub fn half_width(&self, volatility: f64, risk_tolerance: f64) -> f64 {
+        let config = &self.config;
+        let tolerance = risk_tolerance.clamp(0.0, 1.0);
+        let width = config.width * volatility.max(0.0) * config.horizon.max(0.0).sqrt();
+        (width * (1.5 - tolerance)).clamp(config.min_width, config.max_width.max(config.min_width))
+    }
+
+    /// Ranges centred on `price`, one per band
+    pub fn ranges(&self, price: f64, volatility: f64, risk_tolerance: f64) -> Vec<LiquidityRange> {
+        let half_width = self.half_width(volatility, risk_tolerance);
+        self.config
+            .bands
+            .iter()
+            .map(|band| LiquidityRange {
+                range: PriceRange::around(price, half_width * band.width),
+                weight: band.weight,
+            })
+            .collect()
+    }
+
+    /// Whether `core` should be re-centred on `price`
+    ///
+    /// Ranges move when the price leaves the core range or drifts too far
+    /// from its centre, or when volatility calls for a much wider or
+    /// narrower range.
+    pub fn should_recenter(
+        &self,
+        core: &PriceRange,
+        price: f64,
+        volatility: f64,
+        risk_tolerance: f64,
+    ) -> bool {
+        let current = core.half_width();
+        if !core.contains(price)
+            || (price / core.centre()).ln().abs() > self.config.recenter_threshold * current
+        {
+            return true;
+        }
+        let ratio = self.half_width(volatility, risk_tolerance) / current;
+        let tolerance = 1.0 + self.config.width_tolerance;
+        ratio > tolerance || ratio < 1.0 / tolerance
+    }
+}
+
+/// Range Provider Agent - Provides concentrated liquidity around the price
+///
+/// Liquidity is provided from both the capital and the base asset held, and
+/// the agent's state mirrors the tokens its ranges hold at the latest price:
+/// capital includes quote units and uncollected fees, the base position the
+/// base units. Moving liquidity withdraws every range, trades through the
+/// execution engine to the ratio the new ranges need, and deposits again.
+pub struct RangeProviderAgent {
+    state: AgentState,
+    engine: ExecutionEngine,
+    selector: RangeSelector,
+    position: ConcentratedPosition,
+    idle_base: f64,
+    idle_quote: f64,
+    price: Option<f64>,
+    volatility: f64,
+    stale: bool,
+}
+
+impl RangeProviderAgent {
+    /// Create a range provider with default range sizing and pool fee
+    pub fn new(id: String, initial_capital: u64) -> Self {
+        Self {
+            state: AgentState {
+                id,
+                capital: initial_capital,
+                initial_capital,
+                positions: vec![],
+                risk_tolerance: 0.5,
+            },
+            engine: ExecutionEngine::new(),
+            selector: RangeSelector::default(),
+            position: ConcentratedPosition::default(),
+            idle_base: 0.0,
+            idle_quote: initial_capital as f64,
+            price: None,
+            volatility: 0.0,
+            stale: false,
+        }
+    }
+
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Set how ranges are sized and re-centred
+    pub fn with_range_config(mut self, config: RangeConfig) -> Self {
+        self.selector = RangeSelector::new(config);
+        self
+    }
+
+    /// Provide liquidity in a pool charging `fee_rate` of traded volume
+    pub fn with_fee_rate(mut self, fee_rate: f64) -> Self {
+        self.position = ConcentratedPosition::new(fee_rate);
+        self
+    }
+
+    /// Liquidity currently provided
+    pub fn position(&self) -> &ConcentratedPosition {
+        &self.position
+    }
+
+    /// Base and quote units held outside the ranges
+    pub fn idle(&self) -> (f64, f64) {
+        (self.idle_base, self.idle_quote)
+    }
+
+    /// Move liquidity into `ranges` at the latest price
+    fn provide(&mut self, ranges: &[LiquidityRange]) -> Result<()> {
+        let price = self
+            .price
+            .ok_or_else(|| ManusError::Agent("No price observed to provide at".to_string()))?;
+        self.withdraw(price);
+
+        let base_weight = ranges
+            .iter()
+            .map(|target| {
+                let (base, quote) = target.range.amounts(price);
+                target.weight * base * price / (base * price + quote)
+            })
+            .sum::<f64>()
+            .min(1.0);
+        self.engine.execute(
+            &mut self.state,
+            &AgentAction::Rebalance {
+                targets: vec![(BASE_ASSET.to_string(), base_weight)],
+            },
+        )?;
+        self.sync_idle();
+
+        // Size every range from the value, then scale down to what fees and
+        // rounding left of each token
+        let value = self.idle_base * price + self.idle_quote;
+        let liquidity: Vec<f64> = ranges
+            .iter()
+            .map(|target| {
+                let (base, quote) = target.range.amounts(price);
+                target.weight * value / (base * price + quote)
+            })
+            .collect();
+        let (base_needed, quote_needed) =
+            ranges
+                .iter()
+                .zip(&liquidity)
+                .fold((0.0, 0.0), |(base, quote), (target, liquidity)| {
+                    let (b, q) = target.range.amounts(price);
+                    (base + b * liquidity, quote + q * liquidity)
+                });
+        let mut scale: f64 = 1.0;
+        if base_needed > 0.0 {
+            scale = scale.min(self.idle_base / base_needed);
+        }
+        if quote_needed > 0.0 {
+            scale = scale.min(self.idle_quote / quote_needed);
+        }
+
+        for (target, liquidity) in ranges.iter().zip(liquidity) {
+            if liquidity * scale > 0.0 {
+                let (base, quote) =
+                    self.position
+                        .add_liquidity(target.range, liquidity * scale, price)?;
+                self.idle_base = (self.idle_base - base).max(0.0);
+                self.idle_quote = (self.idle_quote - quote).max(0.0);
+            }
+        }
+        self.stale = false;
+        self.sync_state();
+        Ok(())
+    }
+
+    /// Move every range's tokens and fees out of the position
+    fn withdraw(&mut self, price: f64) {
+        let (base, quote) = self.position.withdraw(price);
+        self.idle_base += base;
+        self.idle_quote += quote;
+        self.sync_state();
+    }
+
+    /// Run `f`, restoring the position and state if it fails
+    fn staged(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        let position = self.position.clone();
+        let (idle_base, idle_quote) = (self.idle_base, self.idle_quote);
+        let state = self.state.clone();
+        let result = f(self);
+        if result.is_err() {
+            self.position = position;
+            self.idle_base = idle_base;
+            self.idle_quote = idle_quote;
+            self.state = state;
+        }
+        result
+    }
+
+    /// Mirror the tokens held, in and out of ranges, in the agent state
+    fn sync_state(&mut self) {
+        let (base, quote) = match self.price {
+            Some(price) => self.position.amounts(price),
+            None => (0.0, 0.0),
+        };
+        self.state.capital = (self.idle_quote + quote + self.position.fees()).round() as u64;
+
+        let amount = (self.idle_base + base).round() as u64;
+        let positions = &mut self.state.positions;
+        let index = positions
+            .iter()
+            .position(|position| position.asset == BASE_ASSET);
+        match index {
+            Some(index) if amount == 0 => {
+                positions.remove(index);
+            }
+            Some(index) => positions[index].amount = amount,
+            None if amount > 0 => positions.push(Position {
+                asset: BASE_ASSET.to_string(),
+                amount,
+                entry_price: self.price.unwrap_or(0.0),
+            }),
+            None => {}
+        }
+    }
+
+    /// Take idle tokens from the state once every range is withdrawn
+    fn sync_idle(&mut self) {
+        self.idle_quote = self.state.capital as f64;
+        self.idle_base = self
+            .state
+            .positions
+            .iter()
+            .filter(|position| position.asset == BASE_ASSET)
+            .map(|position| position.amount as f64)
+            .sum();
+    }
+}
+
+impl Agent for RangeProviderAgent {
+    fn id(&self) -> &str {
+        &self.state.id
+    }
+
+    fn state(&self) -> &AgentState {
+        &self.state
+    }
+
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+        let Some(&price) = market_data.prices.last() else {
+            return;
+        };
+        if let Some(previous) = self.price {
+            let volume = market_data.volumes.last().copied().unwrap_or(0.0);
+            self.position
+                .accrue(previous, price, volume, market_data.liquidity);
+        }
+        self.price = Some(price);
+        self.volatility = market_data.volatility;
+        self.sync_state();
+    }
+
+    fn on_regime_change(&mut self, transition: &RegimeTransition) {
+        tracing::info!(
+            "{} re-sizing ranges after regime change to {}",
+            self.state.id,
+            transition.to
+        );
+        self.stale = true;
+    }
+
+    fn decide(&mut self) -> Result<AgentAction> {
+        let Some(price) = self.price else {
+            return Ok(AgentAction::Hold);
+        };
+        let tolerance = self.state.risk_tolerance;
+        let recenter = match self.position.ranges().first() {
+            Some(core) => {
+                self.stale
+                    || self
+                        .selector
+                        .should_recenter(&core.range, price, self.volatility, tolerance)
+            }
+            None => true,
+        };
+
+        if recenter {
+            Ok(AgentAction::Provide {
+                ranges: self.selector.ranges(price, self.volatility, tolerance),
+            })
+        } else {
+            Ok(AgentAction::Hold)
+        }
+    }
+
+    fn execute(&mut self, action: AgentAction) -> Result<()> {
+        match &action {
+            AgentAction::Provide { ranges } => self.staged(|agent| {
+                agent.engine.execute(&mut agent.state, &action)?;
+                agent.provide(ranges)?;
+                tracing::info!(
+                    "{} provided liquidity in {} ranges",
+                    agent.state.id,
+                    agent.position.ranges().len()
+                );
+                Ok(())
+            }),
+            // Trades need the tokens out of the ranges first
+            AgentAction::Rebalance { .. } | AgentAction::EmergencyWithdraw => {
+                self.staged(|agent| {
+                    if let Some(price) = agent.price {
+                        agent.withdraw(price);
+                    }
+                    agent.engine.execute(&mut agent.state, &action)?;
+                    agent.sync_idle();
+                    Ok(())
+                })
+            }
+            _ => {
+                self.engine.execute(&mut self.state, &action)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+
+    fn fees_earned(&self) -> f64 {
+        self.position.fees_earned()
+    }
+
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(RangeProviderStrategy {
+            position: self.position.clone(),
+            idle_base: self.idle_base,
+            idle_quote: self.idle_quote,
+        })
+        .ok()
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let strategy = checkpoint.strategy::<RangeProviderStrategy>()?;
+        checkpoint.restore_state(&mut self.state)?;
+        if let Some(strategy) = strategy {
+            self.position = strategy.position;
+            self.idle_base = strategy.idle_base;
+            self.idle_quote = strategy.idle_quote;
+        }
+        Ok(())
+    }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
+}
+
+/// Liquidity a range provider holds, in and out of its ranges
+#[derive(Serialize, Deserialize)]
+struct RangeProviderStrategy {
+    position: ConcentratedPosition,
+    idle_base: f64,
+    idle_quote: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::orchestrator::MarketWindow;
+    use crate::backtest::Backtest;
+
+    #[test]
+    fn test_position_amounts_fees_and_impermanent_loss() {
+        let range = PriceRange::new(0.8, 1.25).unwrap();
+        assert!((range.centre() - 1.0).abs() < 1e-12);
+        assert!(PriceRange::new(1.0, 1.0).is_err());
+
+        let mut position = ConcentratedPosition::new(0.003);
+        let (base, quote) = position.deposit(range, 100.0, 1_000.0, 1.0).unwrap();
+        // Symmetric in log price: equal value of each token at the centre
+        assert!((base - 100.0).abs() < 1e-9);
+        assert!((quote - 100.0).abs() < 1e-9);
+        assert_eq!(position.impermanent_loss(1.0), 0.0);
+
+        // All quote below the range, all base above it
+        let (base, quote) = position.amounts(0.5);
+        assert!(base > 0.0 && quote == 0.0);
+        let (base, quote) = position.amounts(2.0);
+        assert!(base == 0.0 && quote > 0.0);
+
+        // Concentrated liquidity loses more than a full-range position would
+        let loss = position.impermanent_loss(1.2);
+        let full_range = 1.0 - 2.0 * 1.2_f64.sqrt() / 2.2;
+        assert!(loss > full_range && loss < 0.05);
+
+        // Fees only accrue while the price is inside the range
+        assert_eq!(position.accrue(1.3, 1.4, 1_000.0, 0.0), 0.0);
+        let fees = position.accrue(1.0, 1.0, 1_000.0, 0.0);
+        assert!((fees - 3.0).abs() < 1e-9);
+        let half = position.accrue(1.0, 1.5625, 1_000.0, 0.0);
+        assert!((half - 3.0).abs() < 1e-9);
+        let shared = position.accrue(1.0, 1.0, 1_000.0, 1_000_000.0);
+        assert!(shared > 0.0 && shared < 0.1);
+        assert!((position.fees_earned() - 6.0 - shared).abs() < 1e-9);
+
+        let (_, quote) = position.amounts(1.0);
+        let (base, collected) = position.withdraw(1.0);
+        assert!((base - 100.0).abs() < 1e-9);
+        assert!((collected - quote - 6.0 - shared).abs() < 1e-9);
+        assert!(position.is_empty());
+        assert_eq!(position.fees(), 0.0);
+    }
+
+    #[test]
+    fn test_selector_widens_with_volatility_and_recenters() {
+        let selector = RangeSelector::default();
+        let calm = selector.half_width(0.001, 0.5);
+        let volatile = selector.half_width(0.02, 0.5);
+        assert_eq!(selector.half_width(0.0001, 0.5), 0.005);
+        assert!(volatile > calm);
+        assert!(selector.half_width(0.02, 0.0) > volatile);
+        assert_eq!(selector.half_width(10.0, 0.5), 1.0);
+
+        let ranges = selector.ranges(2.0, 0.02, 0.5);
+        assert_eq!(ranges.len(), 2);
+        assert!(validate_ranges(&ranges).is_ok());
+        assert!((ranges[0].range.centre() - 2.0).abs() < 1e-9);
+        assert!(ranges[1].range.lower < ranges[0].range.lower);
+
+        let core = ranges[0].range;
+        assert!(!selector.should_recenter(&core, 2.0, 0.02, 0.5));
+        assert!(selector.should_recenter(&core, core.upper * 1.01, 0.02, 0.5));
+        assert!(selector.should_recenter(&core, 2.0 * (0.6 * core.half_width()).exp(), 0.02, 0.5));
+        assert!(selector.should_recenter(&core, 2.0, 0.05, 0.5));
+
+        let overweight = [ranges[0], ranges[0]].map(|range| LiquidityRange {
+            weight: 0.6,
+            ..range
+        });
+        assert!(validate_ranges(&overweight).is_err());
+    }
+
+    #[test]
+    fn test_range_provider_backtest() {
+        let mut window = MarketWindow::new(8);
+        let series: Vec<MarketData> = (0..40)
+            .map(|step| {
+                let price = 1.0 + 0.01 * ((step as f64) / 3.0).sin();
+                window.push(price, 50_000.0, 1_000_000.0)
+            })
+            .collect();
+
+        let mut agent = RangeProviderAgent::new("range".to_string(), 1_000_000)
+            .with_engine(ExecutionEngine::new().with_fee_bps(0).with_slippage_bps(0));
+        assert!(matches!(agent.decide().unwrap(), AgentAction::Hold));
+
+        let report = Backtest::new().run(&mut agent, &series).unwrap();
+        assert!(matches!(
+            report.decisions[0].action,
+            AgentAction::Provide { .. }
+        ));
+        assert!(report.decisions.iter().all(|step| step.executed));
+        assert!(agent.position().ranges().len() == 2);
+        assert!(agent.position().fees_earned() > 0.0);
+        // Oscillating around the entry price, fees outweigh impermanent loss
+        assert!(report.pnl > 0.0);
+
+        // Tokens in and out of the ranges add up to the mirrored state
+        let price = *series.last().unwrap().prices.last().unwrap();
+        let (base, quote) = agent.position().amounts(price);
+        let (idle_base, idle_quote) = agent.idle();
+        let held = agent.state().positions[0].amount as f64;
+        assert!((idle_base + base - held).abs() <= 0.5);
+        let capital = agent.state().capital as f64;
+        assert!((idle_quote + quote + agent.position().fees() - capital).abs() <= 0.5);
+
+        // Withdrawing sells everything back to capital
+        agent.execute(AgentAction::EmergencyWithdraw).unwrap();
+        assert!(agent.position().is_empty());
+        assert!(agent.state().positions.is_empty());
+        assert!(agent.state().capital > 1_000_000);
+    }
+}
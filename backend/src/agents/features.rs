@@ -0,0 +1,587 @@
+//! Feature engineering over a sliding market window
+//!
+//! A [`FeatureEngine`] folds ticks into running statistics so every feature
+//! is updated in constant time per tick. Agents ask for a named
+//! [`FeatureSet`] and get the same vector whether the engine was fed live by
+//! the orchestrator, replayed by a backtest, or rebuilt while training.
+//!
+//! The first snapshot an engine observes seeds it with the snapshot's whole
+//! price and volume history; later snapshots only contribute their latest
+//! tick, as consecutive snapshots from a
+//! [`MarketWindow`](crate::agents::orchestrator::MarketWindow) overlap.
+
+use crate::agents::ml_agent::{MarketData, OrderBookTop};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Named features an engine can compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Price change over the momentum period
+    Momentum,
+    /// Volume change from the oldest to the latest tick in the window
+    VolumeChange,
+    /// Volatility reported with the latest snapshot
+    Volatility,
+    /// Liquidity depth reported with the latest snapshot
+    Liquidity,
+    /// Return of the latest tick
+    Return,
+    /// Return from the oldest to the latest tick in the window
+    RollingReturn,
+    /// Exponentially weighted volatility of tick returns
+    EwmaVolatility,
+    /// Wilder's relative strength index (0 - 100)
+    Rsi,
+    /// Bollinger band width relative to the moving average
+    BollingerWidth,
+    /// Resting bid depth minus ask depth over total depth (-1 to 1)
+    OrderBookImbalance,
+    /// Mean distance between each trade and the following mid, as a fraction of the mid
+    RealizedSpread,
+    /// Standard score of the latest volume within the window
+    VolumeZScore,
+}
+
+impl Feature {
+    /// Every feature, in a stable order
+    pub const ALL: [Feature; 12] = [
+        Feature::Momentum,
+        Feature::VolumeChange,
+        Feature::Volatility,
+        Feature::Liquidity,
+        Feature::Return,
+        Feature::RollingReturn,
+        Feature::EwmaVolatility,
+        Feature::Rsi,
+        Feature::BollingerWidth,
+        Feature::OrderBookImbalance,
+        Feature::RealizedSpread,
+        Feature::VolumeZScore,
+    ];
+}
+
+/// Ordered list of features making up a model input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FeatureSet {
+    features: Vec<Feature>,
+}
+
+impl Default for FeatureSet {
+    fn default() -> Self {
+        Self::baseline()
+    }
+}
+
+impl FeatureSet {
+    /// Names accepted by [`FeatureSet::named`]
+    pub const NAMES: [&'static str; 4] = ["baseline", "technical", "microstructure", "full"];
+
+    /// Features in the given order
+    pub fn new(features: Vec<Feature>) -> Self {
+        Self { features }
+    }
+
+    /// Momentum, volume change, volatility and liquidity
+    ///
+    /// The inputs the rebalancer's heuristic weights were written for.
+    pub fn baseline() -> Self {
+        Self::new(vec![
+            Feature::Momentum,
+            Feature::VolumeChange,
+            Feature::Volatility,
+            Feature::Liquidity,
+        ])
+    }
+
+    /// Price and volume indicators
+    pub fn technical() -> Self {
+        Self::new(vec![
+            Feature::Return,
+            Feature::RollingReturn,
+            Feature::EwmaVolatility,
+            Feature::Rsi,
+            Feature::BollingerWidth,
+            Feature::VolumeZScore,
+        ])
+    }
+
+    /// Order book indicators
+    pub fn microstructure() -> Self {
+        Self::new(vec![
+            Feature::OrderBookImbalance,
+            Feature::RealizedSpread,
+            Feature::Liquidity,
+        ])
+    }
+
+    /// Every feature
+    pub fn full() -> Self {
+        Self::new(Feature::ALL.to_vec())
+    }
+
+    /// Predefined set called `name`
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "baseline" => Some(Self::baseline()),
+            "technical" => Some(Self::technical()),
+            "microstructure" => Some(Self::microstructure()),
+            "full" => Some(Self::full()),
+            _ => None,
+        }
+    }
+
+    /// Features in order
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    /// Number of features
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Whether the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+}
+
+/// Lookback parameters for a [`FeatureEngine`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureConfig {
+    /// Ticks kept for rolling returns, volume and spread statistics
+    pub window: usize,
+    /// Ticks spanned by momentum
+    pub momentum_period: usize,
+    /// Decay of the EWMA variance (RiskMetrics uses 0.94)
+    pub ewma_lambda: f64,
+    /// Smoothing period of the RSI
+    pub rsi_period: usize,
+    /// Ticks in the Bollinger moving average
+    pub bollinger_period: usize,
+    /// Standard deviations between the Bollinger middle and outer bands
+    pub bollinger_k: f64,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            window: 32,
+            momentum_period: 5,
+            ewma_lambda: 0.94,
+            rsi_period: 14,
+            bollinger_period: 20,
+            bollinger_k: 2.0,
+        }
+    }
+}
+
+/// Values in a fixed-size window with running sums
+#[derive(Debug, Clone)]
+struct RollingStats {
+    capacity: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl RollingStats {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            values: VecDeque::new(),
+            sum: 0.0,
+            sum_squares: 0.0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.values.len() == self.capacity {
+            if let Some(old) = self.values.pop_front() {
+                self.sum -= old;
+                self.sum_squares -= old * old;
+            }
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_squares += value * value;
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn first(&self) -> Option<f64> {
+        self.values.front().copied()
+    }
+
+    fn last(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+
+    fn mean(&self) -> f64 {
+        if self.values.is_empty() {
+            0.0
+        } else {
+            self.sum / self.values.len() as f64
+        }
+    }
+
+    /// Population standard deviation
+    fn std(&self) -> f64 {
+        if self.values.is_empty() {
+            return 0.0;
+        }
+        let mean = self.mean();
+        // Running sums can drift slightly negative
+        (self.sum_squares / self.values.len() as f64 - mean * mean)
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+/// Incrementally computed market features
+#[derive(Debug, Clone)]
+pub struct FeatureEngine {
+    config: FeatureConfig,
+    prices: VecDeque<f64>,
+    price_capacity: usize,
+    band: RollingStats,
+    volumes: RollingStats,
+    spreads: RollingStats,
+    ewma_variance: Option<f64>,
+    average_gain: f64,
+    average_loss: f64,
+    price_changes: usize,
+    volatility: f64,
+    liquidity: f64,
+    book: Option<OrderBookTop>,
+}
+
+impl Default for FeatureEngine {
+    fn default() -> Self {
+        Self::new(FeatureConfig::default())
+    }
+}
+
+impl FeatureEngine {
+    /// Create an empty engine
+    pub fn new(config: FeatureConfig) -> Self {
+        Self {
+            config,
+            prices: VecDeque::new(),
+            // Momentum reaches back `momentum_period - 1` ticks
+            price_capacity: config.window.max(config.momentum_period).max(2),
+            band: RollingStats::new(config.bollinger_period),
+            volumes: RollingStats::new(config.window),
+            spreads: RollingStats::new(config.window),
+            ewma_variance: None,
+            average_gain: 0.0,
+            average_loss: 0.0,
+            price_changes: 0,
+            volatility: 0.0,
+            liquidity: 0.0,
+            book: None,
+        }
+    }
+
+    /// Engine seeded with every tick of `market_data`
+    pub fn from_snapshot(market_data: &MarketData) -> Self {
+        let mut engine = Self::default();
+        engine.observe(market_data);
+        engine
+    }
+
+    /// Lookback parameters
+    pub fn config(&self) -> &FeatureConfig {
+        &self.config
+    }
+
+    /// Whether no prices have been observed
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Update from a market snapshot
+    ///
+    /// An empty engine replays the snapshot's full history; otherwise only
+    /// the latest price and volume are new.
+    pub fn observe(&mut self, market_data: &MarketData) {
+        if self.is_empty() {
+            for &price in &market_data.prices {
+                self.push_price(price);
+            }
+            for &volume in &market_data.volumes {
+                self.volumes.push(volume);
+            }
+        } else {
+            if let Some(&price) = market_data.prices.last() {
+                self.push_price(price);
+            }
+            if let Some(&volume) = market_data.volumes.last() {
+                self.volumes.push(volume);
+            }
+        }
+        self.volatility = market_data.volatility;
+        self.liquidity = market_data.liquidity;
+        self.set_book(market_data.book);
+    }
+
+    /// Record a traded price
+    pub fn push_price(&mut self, price: f64) {
+        if let Some(&previous) = self.prices.back() {
+            let change = price - previous;
+            self.update_rsi(change.max(0.0), (-change).max(0.0));
+            if previous != 0.0 {
+                let r = change / previous;
+                let lambda = self.config.ewma_lambda;
+                self.ewma_variance = Some(match self.ewma_variance {
+                    Some(variance) => lambda * variance + (1.0 - lambda) * r * r,
+                    None => r * r,
+                });
+            }
+        }
+
+        if self.prices.len() == self.price_capacity {
+            self.prices.pop_front();
+        }
+        self.prices.push_back(price);
+        self.band.push(price);
+    }
+
+    /// Record the top of the order book following the latest trade
+    pub fn set_book(&mut self, book: Option<OrderBookTop>) {
+        if let (Some(book), Some(&price)) = (book, self.prices.back()) {
+            let mid = book.mid();
+            if mid > 0.0 {
+                self.spreads.push(2.0 * (price - mid).abs() / mid);
+            }
+        }
+        self.book = book;
+    }
+
+    fn update_rsi(&mut self, gain: f64, loss: f64) {
+        let period = self.config.rsi_period.max(1) as f64;
+        if self.price_changes < self.config.rsi_period {
+            // Simple average until the first full period
+            let n = self.price_changes as f64;
+            self.average_gain = (self.average_gain * n + gain) / (n + 1.0);
+            self.average_loss = (self.average_loss * n + loss) / (n + 1.0);
+        } else {
+            self.average_gain = (self.average_gain * (period - 1.0) + gain) / period;
+            self.average_loss = (self.average_loss * (period - 1.0) + loss) / period;
+        }
+        self.price_changes += 1;
+    }
+
+    /// Current value of `feature`
+    ///
+    /// Features lacking the data they need, such as order book features
+    /// without a book, are neutral: 0, or 50 for the RSI.
+    pub fn value(&self, feature: Feature) -> f64 {
+        let latest = self.prices.back().copied();
+        match feature {
+            Feature::Momentum => {
+                let period = self.config.momentum_period.max(2);
+                if self.prices.len() < period {
+                    return 0.0;
+                }
+                change(self.prices[self.prices.len() - period], latest)
+            }
+            Feature::VolumeChange => {
+                if self.volumes.len() < 2 {
+                    return 0.0;
+                }
+                change(self.volumes.first().unwrap_or(0.0), self.volumes.last())
+            }
+            Feature::Volatility => self.volatility,
+            Feature::Liquidity => self.liquidity,
+            Feature::Return => {
+                if self.prices.len() < 2 {
+                    return 0.0;
+                }
+                change(self.prices[self.prices.len() - 2], latest)
+            }
+            Feature::RollingReturn => {
+                let window = self.config.window.max(2);
+                if self.prices.len() < 2 {
+                    return 0.0;
+                }
+                let start = self.prices.len().saturating_sub(window);
+                change(self.prices[start], latest)
+            }
+            Feature::EwmaVolatility => self.ewma_variance.unwrap_or(0.0).sqrt(),
+            Feature::Rsi => {
+                if self.price_changes == 0 {
+                    50.0
+                } else if self.average_loss == 0.0 {
+                    if self.average_gain == 0.0 {
+                        50.0
+                    } else {
+                        100.0
+                    }
+                } else {
+                    100.0 - 100.0 / (1.0 + self.average_gain / self.average_loss)
+                }
+            }
+            Feature::BollingerWidth => {
+                let mean = self.band.mean();
+                if self.band.len() < 2 || mean == 0.0 {
+                    return 0.0;
+                }
+                2.0 * self.config.bollinger_k * self.band.std() / mean
+            }
+            Feature::OrderBookImbalance => self.book.map_or(0.0, |book| book.imbalance()),
+            Feature::RealizedSpread => self.spreads.mean(),
+            Feature::VolumeZScore => {
+                let std = self.volumes.std();
+                if std == 0.0 {
+                    return 0.0;
+                }
+                (self.volumes.last().unwrap_or(0.0) - self.volumes.mean()) / std
+            }
+        }
+    }
+
+    /// Values of `set`, in order
+    pub fn vector(&self, set: &FeatureSet) -> Vec<f64> {
+        set.features()
+            .iter()
+            .map(|&feature| self.value(feature))
+            .collect()
+    }
+}
+
+/// Relative change from `from` to `to`, zero when undefined
+fn change(from: f64, to: Option<f64>) -> f64 {
+    match to {
+        Some(to) if from != 0.0 => (to - from) / from,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::orchestrator::MarketWindow;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_baseline_matches_snapshot_features() {
+        let market_data = MarketData {
+            prices: vec![1.0, 1.05, 1.03, 1.07, 1.10],
+            volumes: vec![1000.0, 1200.0, 1100.0, 1300.0, 1400.0],
+            volatility: 0.15,
+            liquidity: 5000.0,
+            book: None,
+        };
+
+        let features = FeatureEngine::from_snapshot(&market_data).vector(&FeatureSet::baseline());
+        assert!(approx(features[0], 0.1));
+        assert!(approx(features[1], 0.4));
+        assert_eq!(features[2], 0.15);
+        assert_eq!(features[3], 5000.0);
+    }
+
+    #[test]
+    fn test_indicators() {
+        let mut engine = FeatureEngine::new(FeatureConfig {
+            rsi_period: 3,
+            bollinger_period: 4,
+            ..FeatureConfig::default()
+        });
+        assert_eq!(engine.value(Feature::Rsi), 50.0);
+
+        for price in [1.0, 1.1, 1.2, 1.3] {
+            engine.push_price(price);
+        }
+        // Only gains
+        assert_eq!(engine.value(Feature::Rsi), 100.0);
+        assert!(approx(engine.value(Feature::Return), 0.1 / 1.2));
+        assert!(approx(engine.value(Feature::RollingReturn), 0.3));
+        assert!(engine.value(Feature::EwmaVolatility) > 0.0);
+
+        let mean: f64 = 1.15;
+        let std = ([1.0f64, 1.1, 1.2, 1.3]
+            .iter()
+            .map(|p| (p - mean).powi(2))
+            .sum::<f64>()
+            / 4.0)
+            .sqrt();
+        assert!(approx(
+            engine.value(Feature::BollingerWidth),
+            4.0 * std / mean
+        ));
+
+        engine.push_price(1.0);
+        let rsi = engine.value(Feature::Rsi);
+        assert!(rsi > 0.0 && rsi < 100.0);
+    }
+
+    #[test]
+    fn test_order_book_features() {
+        let mut engine = FeatureEngine::default();
+        assert_eq!(engine.value(Feature::OrderBookImbalance), 0.0);
+        assert_eq!(engine.value(Feature::RealizedSpread), 0.0);
+
+        engine.push_price(1.01);
+        engine.set_book(Some(OrderBookTop {
+            bid: 0.99,
+            ask: 1.01,
+            bid_depth: 300.0,
+            ask_depth: 100.0,
+        }));
+        assert!(approx(engine.value(Feature::OrderBookImbalance), 0.5));
+        assert!(approx(engine.value(Feature::RealizedSpread), 0.02));
+    }
+
+    #[test]
+    fn test_incremental_matches_replay() {
+        // Live: snapshots arrive one tick at a time
+        let mut window = MarketWindow::new(8);
+        let mut live = FeatureEngine::default();
+        let mut replay = FeatureEngine::default();
+        let mut snapshot = None;
+        for step in 0..40 {
+            let price = 1.0 + 0.05 * ((step as f64) * 0.7).sin();
+            let data = window.push(price, 1000.0 + (step % 5) as f64 * 40.0, 5000.0);
+            live.observe(&data);
+            replay.push_price(price);
+            replay.volumes.push(data.volumes[data.volumes.len() - 1]);
+            snapshot = Some(data);
+        }
+        let snapshot = snapshot.unwrap();
+        replay.observe(&MarketData {
+            prices: vec![],
+            volumes: vec![],
+            ..snapshot.clone()
+        });
+
+        assert_eq!(
+            live.vector(&FeatureSet::full()),
+            replay.vector(&FeatureSet::full())
+        );
+        assert!(live.value(Feature::VolumeZScore).is_finite());
+    }
+
+    #[test]
+    fn test_named_sets() {
+        for name in FeatureSet::NAMES {
+            assert!(!FeatureSet::named(name).unwrap().is_empty());
+        }
+        assert!(FeatureSet::named("unknown").is_none());
+        assert_eq!(FeatureSet::full().len(), Feature::ALL.len());
+
+        let json = serde_json::to_string(&FeatureSet::microstructure()).unwrap();
+        assert_eq!(
+            json,
+            r#"["order_book_imbalance","realized_spread","liquidity"]"#
+        );
+    }
+}
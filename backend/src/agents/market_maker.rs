@@ -0,0 +1,886 @@
+//! Market-making quote engine
+//!
+//! A [`QuoteEngine`] lays a ladder of post-only bids and asks around the
+//! microprice of an [`OrderBook`]. The spread widens and sizes shrink as risk
+//! tolerance falls, and the ladder leans away from inventory the maker
+//! already holds. [`MarketMakerAgent`] diffs each new ladder against the
+//! orders it has resting and proposes the difference as [`OrderIntent`]s,
+//! which [`order_transaction`] turns into a DeepBook transaction. When orders
+//! are tracked on chain by an [`OrderManager`](crate::agents::order_manager::OrderManager),
+//! the [`Orchestrator`](crate::agents::orchestrator::Orchestrator) places the
+//! agent's quotes through it and feeds its view of resting orders and its
+//! fills back into the agent.
+
+use crate::agents::checkpoint::Checkpoint;
+use crate::agents::ml_agent::MarketData;
+use crate::agents::order_book::OrderBook;
+use crate::agents::order_manager::OrderFill;
+use crate::agents::rebalancer::{ExecutionEngine, Side, BASE_ASSET};
+use crate::agents::valuation::InvariantReport;
+use crate::agents::{Agent, AgentAction, AgentState, Position};
+use crate::error::{ManusError, Result};
+use crate::sui::deepbook::{PoolAccount, MAX_TIMESTAMP};
+use crate::sui::ptb::ProgrammableTransaction;
+use serde::{Deserialize, Serialize};
+
+/// Shape of the quote ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuoteConfig {
+    /// Spread between the innermost bid and ask, as a fraction of the reference price
+    pub spread: f64,
+    /// Orders on each side
+    pub levels: usize,
+    /// Distance between consecutive levels, as a fraction of the reference price
+    pub level_spacing: f64,
+    /// Quantity of the innermost orders, in base units
+    pub order_size: f64,
+    /// Growth of the quantity from one level to the next
+    pub size_multiplier: f64,
+    /// Inventory the maker aims to hold, in base units
+    pub target_inventory: f64,
+    /// Distance from the target inventory at which one side stops quoting
+    pub max_inventory: f64,
+    /// Shift of the ladder at the maximum inventory, in half spreads
+    pub inventory_skew: f64,
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        Self {
+            spread: 0.002,
+            levels: 3,
+            level_spacing: 0.001,
+            order_size: 10.0,
+            size_multiplier: 1.5,
+            target_inventory: 0.0,
+            max_inventory: 100.0,
+            inventory_skew: 1.0,
+        }
+    }
+}
+
+/// Order the maker wants resting on the book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    /// Buy for bids, sell for asks
+    pub side: Side,
+    /// Limit price in quote units per base unit
+    pub price: f64,
+    /// Quantity in base units
+    pub quantity: f64,
+}
+
+/// Order the maker has resting on the book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RestingOrder {
+    /// ID the maker assigned when placing the order
+    pub client_order_id: u64,
+    /// Side, price and remaining quantity
+    #[serde(flatten)]
+    pub quote: Quote,
+}
+
+/// Change to the maker's resting orders
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderIntent {
+    /// Place a post-only limit order
+    Place {
+        /// ID identifying the order until it is cancelled or filled
+        client_order_id: u64,
+        /// Buy for bids, sell for asks
+        side: Side,
+        /// Limit price
+        price: f64,
+        /// Quantity
+        quantity: f64,
+    },
+    /// Reduce the remaining quantity of a resting order
+    Modify {
+        /// Order to modify
+        client_order_id: u64,
+        /// New remaining quantity
+        quantity: f64,
+    },
+    /// Cancel a resting order
+    Cancel {
+        /// Order to cancel
+        client_order_id: u64,
+    },
+}
+
+impl OrderIntent {
+    /// Order the intent applies to
+    pub fn client_order_id(&self) -> u64 {
+        match *self {
+            OrderIntent::Place {
+                client_order_id, ..
+            }
+            | OrderIntent::Modify {
+                client_order_id, ..
+            }
+            | OrderIntent::Cancel { client_order_id } => client_order_id,
+        }
+    }
+
+    /// Whether the intent only takes liquidity off the book
+    pub fn is_cancel(&self) -> bool {
+        matches!(self, OrderIntent::Cancel { .. })
+    }
+}
+
+/// Computes quote ladders from an order book
+#[derive(Debug, Clone, Default)]
+pub struct QuoteEngine {
+    config: QuoteConfig,
+}
+
+impl QuoteEngine {
+    /// Create an engine laying ladders shaped by `config`
+    pub fn new(config: QuoteConfig) -> Self {
+        Self { config }
+    }
+
+    /// Ladder shape
+    pub fn config(&self) -> &QuoteConfig {
+        &self.config
+    }
+
+    /// Ladder for `book` holding `inventory` base units and `capital` quote units
+    ///
+    /// The ladder is centred on the microprice, shifted down when long and up
+    /// when short. At a risk tolerance of 0.5 it quotes the configured spread
+    /// and sizes; at 0.0 the spread is 1.5 times wider and sizes halved, at
+    /// 1.0 the spread is halved and sizes 1.5 times larger. Bids never spend
+    /// more than `capital` and asks never sell more than `inventory`. Prices
+    /// are rounded away from the mid onto the tick grid without crossing the
+    /// book, and quantities rounded down onto the lot grid.
+    pub fn quote(
+        &self,
+        book: &OrderBook,
+        inventory: f64,
+        capital: f64,
+        risk_tolerance: f64,
+    ) -> Vec<Quote> {
+        let (Some(reference), Some(best_bid), Some(best_ask)) =
+            (book.microprice(), book.best_bid(), book.best_ask())
+        else {
+            return vec![];
+        };
+        let config = &self.config;
+        let tolerance = risk_tolerance.clamp(0.0, 1.0);
+        let tick = book.tick_size();
+
+        let half_spread = (reference * config.spread * (1.5 - tolerance) / 2.0).max(tick);
+        let deviation = if config.max_inventory > 0.0 {
+            ((inventory - config.target_inventory) / config.max_inventory).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let centre = reference - deviation * config.inventory_skew * half_spread;
+
+        let mut bid_budget = capital.max(0.0);
+        let mut ask_budget = inventory.max(0.0);
+        let mut quotes: Vec<Quote> = Vec::new();
+        for level in 0..config.levels {
+            let offset = half_spread + level as f64 * reference * config.level_spacing;
+            let size =
+                config.order_size * config.size_multiplier.powi(level as i32) * (0.5 + tolerance);
+
+            let bid_price = book
+                .floor_price(centre - offset)
+                .min(book.floor_price(best_ask.price - tick));
+            if bid_price > 0.0 {
+                let quantity =
+                    book.floor_quantity((size * (1.0 - deviation)).min(bid_budget / bid_price));
+                if quantity > 0.0 {
+                    bid_budget -= quantity * bid_price;
+                    add_quote(&mut quotes, Side::Buy, bid_price, quantity, tick);
+                }
+            }
+
+            let ask_price = book
+                .ceil_price(centre + offset)
+                .max(book.ceil_price(best_bid.price + tick));
+            let quantity = book.floor_quantity((size * (1.0 + deviation)).min(ask_budget));
+            if quantity > 0.0 {
+                ask_budget -= quantity;
+                add_quote(&mut quotes, Side::Sell, ask_price, quantity, tick);
+            }
+        }
+
+        quotes
+    }
+}
+
+/// Add a quote, merging it into an existing quote at the same side and price
+fn add_quote(quotes: &mut Vec<Quote>, side: Side, price: f64, quantity: f64, tick: f64) {
+    match quotes
+        .iter_mut()
+        .find(|quote| quote.side == side && (quote.price - price).abs() < tick / 2.0)
+    {
+        Some(quote) => quote.quantity += quantity,
+        None => quotes.push(Quote {
+            side,
+            price,
+            quantity,
+        }),
+    }
+}
+
+/// Intents turning `resting` orders into the `desired` ladder
+///
+/// Orders at a desired price are kept when their quantity matches, reduced
+/// when it is too large and replaced when it is too small; all other resting
+/// orders are cancelled. Cancels come first so their balances are released
+/// before new orders are placed, which take IDs from `next_client_order_id`.
+pub fn order_intents(
+    resting: &[RestingOrder],
+    desired: &[Quote],
+    tick_size: f64,
+    next_client_order_id: &mut u64,
+) -> Vec<OrderIntent> {
+    let mut unmatched = desired.to_vec();
+    let mut cancels = Vec::new();
+    let mut modifies = Vec::new();
+    for order in resting {
+        let client_order_id = order.client_order_id;
+        let matching = unmatched.iter().position(|quote| {
+            quote.side == order.quote.side
+                && (quote.price - order.quote.price).abs() < tick_size / 2.0
+        });
+        let Some(index) = matching else {
+            cancels.push(OrderIntent::Cancel { client_order_id });
+            continue;
+        };
+
+        let tolerance = 1e-9 * order.quote.quantity.max(1.0);
+        let quantity = unmatched[index].quantity;
+        if quantity < order.quote.quantity - tolerance {
+            modifies.push(OrderIntent::Modify {
+                client_order_id,
+                quantity,
+            });
+        } else if quantity > order.quote.quantity + tolerance {
+            cancels.push(OrderIntent::Cancel { client_order_id });
+            continue;
+        }
+        unmatched.remove(index);
+    }
+
+    let places = unmatched.into_iter().map(|quote| {
+        let client_order_id = *next_client_order_id;
+        *next_client_order_id += 1;
+        OrderIntent::Place {
+            client_order_id,
+            side: quote.side,
+            price: quote.price,
+            quantity: quote.quantity,
+        }
+    });
+    cancels.into_iter().chain(modifies).chain(places).collect()
+}
+
+/// DeepBook transaction applying `intents` to `account`
+///
+/// `order` resolves a client order ID to the on-chain order ID and filled
+/// quantity that modifies and cancels need; DeepBook modifies set the total
+/// quantity, so the filled quantity is added to the new remaining quantity.
+pub fn order_transaction(
+    account: &PoolAccount,
+    intents: &[OrderIntent],
+    order: impl Fn(u64) -> Option<(u128, f64)>,
+) -> Result<ProgrammableTransaction> {
+    if intents.is_empty() {
+        return Err(ManusError::Agent("No order intents to submit".to_string()));
+    }
+    let resolve = |client_order_id: u64| {
+        order(client_order_id).ok_or_else(|| {
+            ManusError::Agent(format!(
+                "No on-chain order for client order {}",
+                client_order_id
+            ))
+        })
+    };
+
+    let mut builder = account.orders();
+    for intent in intents {
+        match *intent {
+            OrderIntent::Place {
+                client_order_id,
+                side,
+                price,
+                quantity,
+            } => builder.place_limit_order(
+                client_order_id,
+                account.native_price(price),
+                account.native_quantity(quantity),
+                side == Side::Buy,
+                MAX_TIMESTAMP,
+            )?,
+            OrderIntent::Modify {
+                client_order_id,
+                quantity,
+            } => {
+                let (order_id, filled) = resolve(client_order_id)?;
+                builder.modify_order(order_id, account.native_quantity(filled + quantity))?
+            }
+            OrderIntent::Cancel { client_order_id } => {
+                builder.cancel_order(resolve(client_order_id)?.0)?
+            }
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// Market Maker Agent - Provides liquidity with a ladder of resting orders
+///
+/// Quotes against the latest book passed to [`Agent::observe_book`] and pulls
+/// every order while it has none. Resting orders are tracked as intents are
+/// executed, and fills only change capital and inventory when passed to
+/// [`Agent::record_fill`].
+pub struct MarketMakerAgent {
+    state: AgentState,
+    engine: ExecutionEngine,
+    quotes: QuoteEngine,
+    book: Option<OrderBook>,
+    resting: Vec<RestingOrder>,
+    next_client_order_id: u64,
+}
+
+impl MarketMakerAgent {
+    /// Create a market maker with default ladder shape
+    pub fn new(id: String, initial_capital: u64) -> Self {
+        Self {
+            state: AgentState {
+                id,
+                capital: initial_capital,
+                initial_capital,
+                positions: vec![],
+                risk_tolerance: 0.5,
+            },
+            engine: ExecutionEngine::new(),
+            quotes: QuoteEngine::default(),
+            book: None,
+            resting: vec![],
+            next_client_order_id: 1,
+        }
+    }
+
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Set the shape of the quote ladder
+    pub fn with_quote_config(mut self, config: QuoteConfig) -> Self {
+        self.quotes = QuoteEngine::new(config);
+        self
+    }
+
+    /// Latest order book
+    pub fn book(&self) -> Option<&OrderBook> {
+        self.book.as_ref()
+    }
+
+    /// Orders resting on the book
+    pub fn resting_orders(&self) -> &[RestingOrder] {
+        &self.resting
+    }
+
+    /// Base units held
+    pub fn inventory(&self) -> f64 {
+        self.state
+            .positions
+            .iter()
+            .filter(|position| position.asset == BASE_ASSET)
+            .map(|position| position.amount as f64)
+            .sum()
+    }
+
+    fn apply(&mut self, intent: &OrderIntent) {
+        match *intent {
+            OrderIntent::Place {
+                client_order_id,
+                side,
+                price,
+                quantity,
+            } => self.resting.push(RestingOrder {
+                client_order_id,
+                quote: Quote {
+                    side,
+                    price,
+                    quantity,
+                },
+            }),
+            OrderIntent::Modify {
+                client_order_id,
+                quantity,
+            } => {
+                if let Some(order) = self
+                    .resting
+                    .iter_mut()
+                    .find(|order| order.client_order_id == client_order_id)
+                {
+                    order.quote.quantity = quantity;
+                }
+            }
+            OrderIntent::Cancel { client_order_id } => self
+                .resting
+                .retain(|order| order.client_order_id != client_order_id),
+        }
+    }
+}
+
+impl Agent for MarketMakerAgent {
+    fn id(&self) -> &str {
+        &self.state.id
+    }
+
+    fn state(&self) -> &AgentState {
+        &self.state
+    }
+
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+    }
+
+    fn observe_book(&mut self, book: &OrderBook) {
+        self.book = Some(book.clone());
+    }
+
+    // New client order IDs continue above the highest ID seen.
+    fn sync_orders(&mut self, resting: Vec<RestingOrder>) {
+        if let Some(highest) = resting.iter().map(|order| order.client_order_id).max() {
+            self.next_client_order_id = self.next_client_order_id.max(highest + 1);
+        }
+        self.resting = resting;
+    }
+
+    // Maker fees are paid in DEEP and are not charged against capital.
+    // Inventory is held in whole base units, so quantities are rounded.
+    fn record_fill(&mut self, fill: &OrderFill) {
+        let amount = fill.quantity.round() as u64;
+        let notional = (fill.quantity * fill.price).round() as u64;
+        let positions = &mut self.state.positions;
+        let index = positions
+            .iter()
+            .position(|position| position.asset == BASE_ASSET);
+        match (fill.side, index) {
+            (Side::Buy, Some(index)) => {
+                let position = &mut positions[index];
+                let basis = position.amount as f64 * position.entry_price + notional as f64;
+                position.amount += amount;
+                position.entry_price = basis / position.amount.max(1) as f64;
+                self.state.capital = self.state.capital.saturating_sub(notional);
+            }
+            (Side::Buy, None) => {
+                positions.push(Position {
+                    asset: BASE_ASSET.to_string(),
+                    amount,
+                    entry_price: fill.price,
+                });
+                self.state.capital = self.state.capital.saturating_sub(notional);
+            }
+            (Side::Sell, index) => {
+                if let Some(index) = index {
+                    positions[index].amount = positions[index].amount.saturating_sub(amount);
+                    if positions[index].amount == 0 {
+                        positions.remove(index);
+                    }
+                }
+                self.state.capital += notional;
+            }
+        }
+
+        if let Some(index) = self
+            .resting
+            .iter()
+            .position(|order| order.client_order_id == fill.client_order_id)
+        {
+            let order = &mut self.resting[index];
+            order.quote.quantity -= fill.quantity;
+            if order.quote.quantity <= 1e-9 {
+                self.resting.remove(index);
+            }
+        }
+    }
+
+    fn decide(&mut self) -> Result<AgentAction> {
+        let (desired, tick_size) = match &self.book {
+            Some(book) => (
+                self.quotes.quote(
+                    book,
+                    self.inventory(),
+                    self.state.capital as f64,
+                    self.state.risk_tolerance,
+                ),
+                book.tick_size(),
+            ),
+            None => (vec![], 0.0),
+        };
+        let intents = order_intents(
+            &self.resting,
+            &desired,
+            tick_size,
+            &mut self.next_client_order_id,
+        );
+
+        if intents.is_empty() {
+            Ok(AgentAction::Hold)
+        } else {
+            Ok(AgentAction::Quote { intents })
+        }
+    }
+
+    fn execute(&mut self, action: AgentAction) -> Result<()> {
+        self.engine.execute(&mut self.state, &action)?;
+        if let AgentAction::Quote { intents } = &action {
+            for intent in intents {
+                self.apply(intent);
+            }
+            tracing::info!(
+                "{} now has {} resting orders",
+                self.state.id,
+                self.resting.len()
+            );
+        }
+        Ok(())
+    }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(MarketMakerStrategy {
+            resting: self.resting.clone(),
+            next_client_order_id: self.next_client_order_id,
+        })
+        .ok()
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let strategy = checkpoint.strategy::<MarketMakerStrategy>()?;
+        checkpoint.restore_state(&mut self.state)?;
+        if let Some(strategy) = strategy {
+            self.resting = strategy.resting;
+            self.next_client_order_id = strategy.next_client_order_id;
+        }
+        Ok(())
+    }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
+}
+
+/// Orders a market maker has resting, and the client order ID it assigns next
+#[derive(Serialize, Deserialize)]
+struct MarketMakerStrategy {
+    resting: Vec<RestingOrder>,
+    next_client_order_id: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::order_book::Level;
+    use crate::sui::objects::Address;
+    use crate::sui::ptb::{Command, ObjectArg};
+
+    fn book() -> OrderBook {
+        OrderBook::new(
+            vec![
+                Level {
+                    price: 0.999,
+                    quantity: 500.0,
+                },
+                Level {
+                    price: 0.995,
+                    quantity: 1_000.0,
+                },
+            ],
+            vec![
+                Level {
+                    price: 1.001,
+                    quantity: 500.0,
+                },
+                Level {
+                    price: 1.005,
+                    quantity: 1_000.0,
+                },
+            ],
+            0.001,
+            0.1,
+        )
+        .unwrap()
+    }
+
+    fn side(quotes: &[Quote], side: Side) -> Vec<Quote> {
+        quotes
+            .iter()
+            .filter(|quote| quote.side == side)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_ladder_spread_sizes_and_budgets() {
+        let engine = QuoteEngine::default();
+        let quotes = engine.quote(&book(), 50.0, 1_000.0, 0.5);
+        let (bids, asks) = (side(&quotes, Side::Buy), side(&quotes, Side::Sell));
+        assert_eq!(bids.len(), 3);
+        assert_eq!(asks.len(), 3);
+
+        // Long half the maximum inventory: the ladder leans down
+        assert!(bids[0].price < 0.999 && asks[0].price > 0.999);
+        assert!(bids.windows(2).all(|pair| pair[0].price > pair[1].price));
+        assert!(asks.windows(2).all(|pair| pair[0].price < pair[1].price));
+        assert!((bids[0].quantity - 5.0).abs() < 1e-9);
+        assert!((asks[0].quantity - 15.0).abs() < 1e-9);
+        for quote in &quotes {
+            let ticks = quote.price / 0.001;
+            assert!((ticks - ticks.round()).abs() < 1e-6);
+        }
+
+        // Asks never sell more than is held; bids never spend more than capital
+        let asks = side(&engine.quote(&book(), 20.0, 1_000.0, 0.5), Side::Sell);
+        assert!(asks.iter().map(|quote| quote.quantity).sum::<f64>() <= 20.0 + 1e-9);
+        let bids = side(&engine.quote(&book(), 0.0, 12.0, 0.5), Side::Buy);
+        let spent: f64 = bids.iter().map(|quote| quote.price * quote.quantity).sum();
+        assert!(spent <= 12.0 && spent > 11.0);
+
+        // At maximum inventory only asks are quoted
+        assert!(side(&engine.quote(&book(), 100.0, 1_000.0, 0.5), Side::Buy).is_empty());
+
+        // Lower tolerance quotes wider and smaller
+        let cautious = engine.quote(&book(), 50.0, 1_000.0, 0.0);
+        let cautious_bid = side(&cautious, Side::Buy)[0];
+        assert!(cautious_bid.price <= bids[0].price);
+        assert!(cautious_bid.quantity < 5.0);
+    }
+
+    #[test]
+    fn test_order_intents_diff() {
+        let resting = [
+            RestingOrder {
+                client_order_id: 1,
+                quote: Quote {
+                    side: Side::Buy,
+                    price: 0.998,
+                    quantity: 10.0,
+                },
+            },
+            RestingOrder {
+                client_order_id: 2,
+                quote: Quote {
+                    side: Side::Sell,
+                    price: 1.002,
+                    quantity: 10.0,
+                },
+            },
+            RestingOrder {
+                client_order_id: 3,
+                quote: Quote {
+                    side: Side::Sell,
+                    price: 1.004,
+                    quantity: 10.0,
+                },
+            },
+        ];
+        let desired = [
+            Quote {
+                side: Side::Buy,
+                price: 0.998,
+                quantity: 10.0,
+            },
+            Quote {
+                side: Side::Sell,
+                price: 1.002,
+                quantity: 4.0,
+            },
+            Quote {
+                side: Side::Sell,
+                price: 1.004,
+                quantity: 12.0,
+            },
+            Quote {
+                side: Side::Buy,
+                price: 0.996,
+                quantity: 10.0,
+            },
+        ];
+
+        let mut next = 10;
+        let intents = order_intents(&resting, &desired, 0.001, &mut next);
+        assert_eq!(
+            intents,
+            vec![
+                OrderIntent::Cancel { client_order_id: 3 },
+                OrderIntent::Modify {
+                    client_order_id: 2,
+                    quantity: 4.0
+                },
+                OrderIntent::Place {
+                    client_order_id: 10,
+                    side: Side::Sell,
+                    price: 1.004,
+                    quantity: 12.0
+                },
+                OrderIntent::Place {
+                    client_order_id: 11,
+                    side: Side::Buy,
+                    price: 0.996,
+                    quantity: 10.0
+                },
+            ]
+        );
+        assert_eq!(next, 12);
+        assert!(order_intents(&resting, &[], 0.001, &mut next)
+            .iter()
+            .all(OrderIntent::is_cancel));
+    }
+
+    #[test]
+    fn test_order_transaction() {
+        let account = PoolAccount {
+            package: Address([7; 32]),
+            base: "0x2::sui::SUI".parse().unwrap(),
+            quote: "0xdba3::usdc::USDC".parse().unwrap(),
+            pool: ObjectArg::SharedObject {
+                id: Address([1; 32]),
+                initial_shared_version: 5,
+                mutable: true,
+            },
+            balance_manager: ObjectArg::SharedObject {
+                id: Address([2; 32]),
+                initial_shared_version: 6,
+                mutable: true,
+            },
+            base_decimals: 9,
+            quote_decimals: 6,
+        };
+        let intents = [
+            OrderIntent::Cancel { client_order_id: 1 },
+            OrderIntent::Place {
+                client_order_id: 2,
+                side: Side::Sell,
+                price: 1.002,
+                quantity: 4.0,
+            },
+        ];
+
+        let ptb =
+            order_transaction(&account, &intents, |id| (id == 1).then_some((99, 0.0))).unwrap();
+        let functions: Vec<&str> = ptb
+            .commands
+            .iter()
+            .map(|command| match command {
+                Command::MoveCall(call) => call.function.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                "generate_proof_as_owner",
+                "cancel_order",
+                "place_limit_order"
+            ]
+        );
+
+        assert!(order_transaction(&account, &intents, |_| None).is_err());
+        assert!(order_transaction(&account, &[], |_| None).is_err());
+    }
+
+    #[test]
+    fn test_market_maker_agent_requotes() {
+        let mut agent = MarketMakerAgent::new("maker".to_string(), 10_000);
+        assert!(matches!(agent.decide().unwrap(), AgentAction::Hold));
+
+        agent.observe_book(&book());
+        let action = agent.decide().unwrap();
+        let AgentAction::Quote { intents } = &action else {
+            panic!("expected quotes, got {:?}", action);
+        };
+        // No inventory: bids only
+        assert_eq!(intents.len(), 3);
+        agent.execute(action).unwrap();
+        assert_eq!(agent.resting_orders().len(), 3);
+        assert!(matches!(agent.decide().unwrap(), AgentAction::Hold));
+
+        // Inventory arrives: asks are added, bids shrink and move down
+        agent.state.positions.push(Position {
+            asset: BASE_ASSET.to_string(),
+            amount: 40,
+            entry_price: 1.0,
+        });
+        let action = agent.decide().unwrap();
+        let AgentAction::Quote { intents } = &action else {
+            panic!("expected quotes, got {:?}", action);
+        };
+        assert!(intents.iter().any(|intent| matches!(
+            intent,
+            OrderIntent::Place {
+                side: Side::Sell,
+                ..
+            }
+        )));
+        agent.execute(action).unwrap();
+        assert!(agent
+            .resting_orders()
+            .iter()
+            .any(|order| order.quote.side == Side::Sell));
+        assert!(matches!(agent.decide().unwrap(), AgentAction::Hold));
+    }
+
+    #[test]
+    fn test_sync_orders_and_fills() {
+        let mut agent = MarketMakerAgent::new("maker".to_string(), 10_000);
+        agent.sync_orders(vec![RestingOrder {
+            client_order_id: 41,
+            quote: Quote {
+                side: Side::Buy,
+                price: 0.998,
+                quantity: 10.0,
+            },
+        }]);
+        assert_eq!(agent.next_client_order_id, 42);
+
+        let mut fill = OrderFill {
+            client_order_id: 41,
+            order_id: 7,
+            side: Side::Buy,
+            price: 0.998,
+            quantity: 4.0,
+            timestamp_ms: 0,
+        };
+        agent.record_fill(&fill);
+        assert_eq!(agent.inventory(), 4.0);
+        assert_eq!(agent.state().capital, 10_000 - 4);
+        assert!((agent.resting_orders()[0].quote.quantity - 6.0).abs() < 1e-9);
+
+        fill.quantity = 6.0;
+        agent.record_fill(&fill);
+        assert_eq!(agent.inventory(), 10.0);
+        assert!(agent.resting_orders().is_empty());
+
+        agent.record_fill(&OrderFill {
+            side: Side::Sell,
+            price: 1.002,
+            quantity: 10.0,
+            ..fill
+        });
+        assert_eq!(agent.inventory(), 0.0);
+        assert_eq!(agent.state().capital, 10_000 - 4 - 6 + 10);
+    }
+}
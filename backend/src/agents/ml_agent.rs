@@ -5,9 +5,22 @@
 
 use crate::error::{ManusError, Result};
 use crate::agents::{Agent, AgentState, AgentAction, Position};
+use crate::agents::checkpoint::Checkpoint;
+use crate::agents::features::{Feature, FeatureEngine, FeatureSet};
+use crate::agents::market_maker::OrderIntent;
+use crate::agents::model::ReturnModel;
+use crate::agents::rebalancer::{ExecutionEngine, Side, QUOTE_ASSET};
+use crate::agents::regime::{
+    Regime, RegimeClassifier, RegimeConfig, RegimeProbabilities, RegimeTransition,
+};
+use crate::agents::risk::{
+    EquityTracker, RiskAssessment, RiskLimits, RiskResponse, DEFAULT_RISK_WINDOW,
+};
+use crate::agents::strategy::{
+    BacktestObjective, Evaluation, Metric, Optimizer, ParameterSpace, Parameters, SearchMethod,
+};
+use crate::agents::valuation::InvariantReport;
 use ndarray::{Array1, Array2};
-use smartcore::linear::linear_regression::LinearRegression;
-use smartcore::linalg::basic::matrix::DenseMatrix;
 use serde::{Deserialize, Serialize};
 
 /// Market data for ML analysis
@@ -21,6 +34,39 @@ pub struct MarketData {
     pub volatility: f64,
     /// Liquidity depth
     pub liquidity: f64,
+    /// Top of the order book, when a book feed is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book: Option<OrderBookTop>,
+}
+
+/// Best bid and ask with the depth resting at each
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderBookTop {
+    /// Best bid price
+    pub bid: f64,
+    /// Best ask price
+    pub ask: f64,
+    /// Quantity resting on the bid side
+    pub bid_depth: f64,
+    /// Quantity resting on the ask side
+    pub ask_depth: f64,
+}
+
+impl OrderBookTop {
+    /// Midpoint between the best bid and ask
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+
+    /// Bid depth minus ask depth over total depth (-1.0 - 1.0)
+    pub fn imbalance(&self) -> f64 {
+        let total = self.bid_depth + self.ask_depth;
+        if total > 0.0 {
+            (self.bid_depth - self.ask_depth) / total
+        } else {
+            0.0
+        }
+    }
 }
 
 /// ML-based decision output
@@ -39,8 +85,15 @@ pub struct MLDecision {
 /// Rebalancer Agent - Uses ML to optimize portfolio allocation
 pub struct RebalancerAgent {
     state: AgentState,
-    model: LinearRegression<f64, DenseMatrix<f64>>,
+    model: Option<ReturnModel>,
+    feature_set: FeatureSet,
+    features: FeatureEngine,
     min_confidence: f64,
+    volatility_threshold: f64,
+    regime: Regime,
+    last_decision: Option<MLDecision>,
+    market_data: Option<MarketData>,
+    engine: ExecutionEngine,
 }
 
 impl RebalancerAgent {
@@ -54,22 +107,77 @@ impl RebalancerAgent {
                 positions: vec![],
                 risk_tolerance: 0.5,
             },
-            model: LinearRegression::fit(
-                &DenseMatrix::zeros(1, 1),
-                &vec![0.0],
-                Default::default()
-            ).unwrap(),
+            // Heuristic weights until a trained model is loaded
+            model: None,
+            feature_set: FeatureSet::baseline(),
+            features: FeatureEngine::default(),
             min_confidence: 0.8,
+            volatility_threshold: 0.2,
+            regime: Regime::Unknown,
+            last_decision: None,
+            market_data: None,
+            engine: ExecutionEngine::new(),
         }
     }
 
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Predict returns with a trained model instead of the heuristic weights
+    pub fn with_model(mut self, model: ReturnModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Only rebalance when confidence exceeds `min_confidence` (0.0 - 1.0)
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Volatility above which the allocation shifts towards the quote asset
+    pub fn with_volatility_threshold(mut self, volatility_threshold: f64) -> Self {
+        self.volatility_threshold = volatility_threshold;
+        self
+    }
+
+    /// Feed models the features in `feature_set`
+    ///
+    /// Must match the set the model was trained on.
+    pub fn with_feature_set(mut self, feature_set: FeatureSet) -> Self {
+        self.feature_set = feature_set;
+        self
+    }
+
+    /// Features fed to the model
+    pub fn feature_set(&self) -> &FeatureSet {
+        &self.feature_set
+    }
+
     /// Analyze market data using ML
+    ///
+    /// Features are computed from the snapshot alone; observed agents use
+    /// the features accumulated across the feed instead.
     pub fn analyze(&self, market_data: &MarketData) -> Result<MLDecision> {
-        // Feature engineering
-        let features = self.extract_features(market_data)?;
-        
-        // Predict using linear regression (placeholder for more complex models)
-        let prediction = self.predict_return(&features)?;
+        self.analyze_with(&FeatureEngine::from_snapshot(market_data), market_data)
+    }
+
+    fn analyze_with(
+        &self,
+        features: &FeatureEngine,
+        market_data: &MarketData,
+    ) -> Result<MLDecision> {
+        // Predict using the trained model, if any
+        let prediction = self.predict_return(features)?;
         
         // Calculate confidence based on volatility and liquidity
         let confidence = self.calculate_confidence(market_data);
@@ -98,47 +206,24 @@ impl RebalancerAgent {
         })
     }
 
-    /// Extract features from market data
-    fn extract_features(&self, data: &MarketData) -> Result<Array1<f64>> {
-        let mut features = Vec::new();
-        
-        // Price momentum (last 5 periods)
-        if data.prices.len() >= 5 {
-            let recent_prices = &data.prices[data.prices.len()-5..];
-            let momentum = (recent_prices.last().unwrap() - recent_prices.first().unwrap()) 
-                / recent_prices.first().unwrap();
-            features.push(momentum);
-        } else {
-            features.push(0.0);
-        }
-        
-        // Volume trend
-        if data.volumes.len() >= 2 {
-            let volume_change = (data.volumes.last().unwrap() - data.volumes.first().unwrap())
-                / data.volumes.first().unwrap();
-            features.push(volume_change);
-        } else {
-            features.push(0.0);
-        }
-        
-        // Volatility
-        features.push(data.volatility);
-        
-        // Liquidity
-        features.push(data.liquidity);
-        
-        Ok(Array1::from_vec(features))
+    /// Extract the agent's feature set from a market snapshot
+    pub fn extract_features(&self, data: &MarketData) -> Result<Array1<f64>> {
+        Ok(Array1::from_vec(
+            FeatureEngine::from_snapshot(data).vector(&self.feature_set),
+        ))
     }
 
     /// Predict expected return
-    fn predict_return(&self, features: &Array1<f64>) -> Result<f64> {
-        // In a real implementation, this would use a trained model
-        // For now, we use a simple heuristic
+    fn predict_return(&self, features: &FeatureEngine) -> Result<f64> {
+        if let Some(model) = &self.model {
+            return model.predict(&features.vector(&self.feature_set));
+        }
         
-        let momentum = features[0];
-        let volume_change = features[1];
-        let volatility = features[2];
-        let liquidity = features[3];
+        // Simple heuristic until a model is trained
+        let momentum = features.value(Feature::Momentum);
+        let volume_change = features.value(Feature::VolumeChange);
+        let volatility = features.value(Feature::Volatility);
+        let liquidity = features.value(Feature::Liquidity);
         
         // Simple prediction formula (placeholder)
         let prediction = momentum * 0.4 + volume_change * 0.3 - volatility * 0.2 + liquidity * 0.1;
@@ -167,7 +252,10 @@ impl RebalancerAgent {
     /// Calculate optimal portfolio allocation
     fn calculate_optimal_allocation(&self, data: &MarketData) -> Result<Vec<(String, f64)>> {
         // Simple allocation strategy based on risk tolerance
-        let risk_adjusted_allocation = if data.volatility < 0.2 {
+        // Stay defensive for as long as the market analyzer reports a volatile regime
+        let risk_adjusted_allocation = if data.volatility < self.volatility_threshold
+            && self.regime != Regime::Volatile
+        {
             vec![
                 ("SUI".to_string(), 0.6),
                 ("USDC".to_string(), 0.4),
@@ -192,48 +280,115 @@ impl Agent for RebalancerAgent {
         &self.state
     }
 
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+        self.features.observe(market_data);
+        self.market_data = Some(market_data.clone());
+    }
+
+    fn on_regime_change(&mut self, transition: &RegimeTransition) {
+        self.regime = transition.to;
+    }
+
     fn decide(&mut self) -> Result<AgentAction> {
-        // In a real implementation, this would fetch current market data
+        // Sample data until a market feed has been observed
         let mock_market_data = MarketData {
             prices: vec![1.0, 1.05, 1.03, 1.07, 1.10],
             volumes: vec![1000.0, 1200.0, 1100.0, 1300.0, 1400.0],
             volatility: 0.15,
             liquidity: 5000.0,
+            book: None,
         };
         
-        let decision = self.analyze(&mock_market_data)?;
-        Ok(decision.action)
+        let decision = match &self.market_data {
+            Some(market_data) => self.analyze_with(&self.features, market_data)?,
+            None => self.analyze(&mock_market_data)?,
+        };
+        let action = decision.action.clone();
+        self.last_decision = Some(decision);
+        Ok(action)
     }
 
     fn execute(&mut self, action: AgentAction) -> Result<()> {
+        let fills = self.engine.execute(&mut self.state, &action)?;
         match action {
             AgentAction::Rebalance { targets } => {
-                // Execute rebalancing
-                tracing::info!("Rebalancing portfolio to targets: {:?}", targets);
-                // In a real implementation, this would execute trades
-                Ok(())
+                tracing::info!(
+                    "Rebalanced portfolio to targets {:?} with {} fills",
+                    targets,
+                    fills.len()
+                );
             }
             AgentAction::AdjustRisk { new_tolerance } => {
-                self.state.risk_tolerance = new_tolerance;
                 tracing::info!("Adjusted risk tolerance to {}", new_tolerance);
-                Ok(())
             }
             AgentAction::EmergencyWithdraw => {
-                tracing::warn!("Emergency withdraw triggered");
-                // In a real implementation, this would withdraw all funds
-                Ok(())
+                tracing::warn!("Emergency withdraw liquidated {} positions", fills.len());
+            }
+            AgentAction::Quote { intents } => {
+                tracing::info!("Submitted {} order intents", intents.len());
+            }
+            AgentAction::Provide { ranges } => {
+                tracing::info!("Provided liquidity in {} ranges", ranges.len());
             }
             AgentAction::Hold => {
                 tracing::info!("Holding current positions");
-                Ok(())
             }
         }
+        Ok(())
+    }
+
+    fn last_decision(&self) -> Option<&MLDecision> {
+        self.last_decision.as_ref()
+    }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
     }
+
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(RebalancerStrategy {
+            model: self.model.as_ref(),
+            feature_set: self.feature_set.clone(),
+            regime: self.regime,
+        })
+        .ok()
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let strategy = checkpoint.strategy::<RebalancerStrategy<ReturnModel>>()?;
+        checkpoint.restore_state(&mut self.state)?;
+        if let Some(strategy) = strategy {
+            self.model = strategy.model;
+            self.feature_set = strategy.feature_set;
+            self.regime = strategy.regime;
+        }
+        Ok(())
+    }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
+}
+
+/// Model the rebalancer trades with, and the regime it trades in
+#[derive(Serialize, Deserialize)]
+struct RebalancerStrategy<M> {
+    model: Option<M>,
+    feature_set: FeatureSet,
+    regime: Regime,
 }
 
+/// Market observations kept by the strategy optimizer
+const OPTIMIZER_HISTORY: usize = 256;
+
 /// Strategy Optimizer Agent - Optimizes strategy parameters
 pub struct StrategyOptimizerAgent {
     state: AgentState,
+    history: Vec<MarketData>,
+    search: SearchMethod,
+    metric: Metric,
+    engine: ExecutionEngine,
 }
 
 impl StrategyOptimizerAgent {
@@ -246,14 +401,59 @@ impl StrategyOptimizerAgent {
                 positions: vec![],
                 risk_tolerance: 0.5,
             },
+            history: Vec::new(),
+            search: SearchMethod::default(),
+            metric: Metric::default(),
+            engine: ExecutionEngine::new(),
         }
     }
 
-    /// Optimize strategy parameters using gradient descent
-    pub fn optimize_parameters(&self, historical_data: &[MarketData]) -> Result<Vec<f64>> {
-        // Placeholder for parameter optimization
-        // In a real implementation, this would use optimization algorithms
-        Ok(vec![0.5, 0.3, 0.2])
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Search parameters with `search`, maximizing `metric`
+    pub fn with_search(mut self, search: SearchMethod, metric: Metric) -> Self {
+        self.search = search;
+        self.metric = metric;
+        self
+    }
+
+    /// Market data observed so far, oldest first
+    pub fn history(&self) -> &[MarketData] {
+        &self.history
+    }
+
+    /// Rebalancer parameters searched by [`optimize_parameters`](Self::optimize_parameters)
+    pub fn rebalancer_space() -> ParameterSpace {
+        ParameterSpace::new()
+            .with("min_confidence", 0.5, 0.95)
+            .with("volatility_threshold", 0.01, 0.5)
+    }
+
+    /// Optimize rebalancer parameters by backtesting them over `historical_data`
+    ///
+    /// Returns every evaluated candidate with its metrics, best first.
+    pub fn optimize_parameters(&self, historical_data: &[MarketData]) -> Result<Vec<Evaluation>> {
+        let objective = BacktestObjective::new(historical_data, |parameters: &Parameters| {
+            let agent = RebalancerAgent::new(self.state.id.clone(), self.state.initial_capital)
+                .with_risk_tolerance(self.state.risk_tolerance)
+                .with_min_confidence(parameters["min_confidence"])
+                .with_volatility_threshold(parameters["volatility_threshold"])
+                .with_engine(self.engine.clone());
+            Ok(Box::new(agent) as Box<dyn Agent>)
+        })
+        .with_metric(self.metric);
+
+        Optimizer::new(Self::rebalancer_space(), self.search).run(&objective)
     }
 }
 
@@ -266,20 +466,59 @@ impl Agent for StrategyOptimizerAgent {
         &self.state
     }
 
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+        if self.history.len() == OPTIMIZER_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(market_data.clone());
+    }
+
     fn decide(&mut self) -> Result<AgentAction> {
         // Analyze and optimize
         Ok(AgentAction::Hold)
     }
 
     fn execute(&mut self, action: AgentAction) -> Result<()> {
+        self.engine.execute(&mut self.state, &action)?;
+        Ok(())
+    }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.history).ok()
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let history = checkpoint.strategy::<Vec<MarketData>>()?;
+        checkpoint.restore_state(&mut self.state)?;
+        if let Some(history) = history {
+            self.history = history;
+        }
         Ok(())
     }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
 }
 
 /// Risk Manager Agent - Monitors and manages risk
+///
+/// Tracks its marked-to-market equity against a high-water mark and responds
+/// in steps: reducing exposure, pausing trading (vetoing other agents'
+/// trades) and finally withdrawing everything.
 pub struct RiskManagerAgent {
     state: AgentState,
-    max_drawdown: f64,
+    limits: RiskLimits,
+    configured_drawdown: f64,
+    equity: EquityTracker,
+    max_volatility: f64,
+    volatility: f64,
+    engine: ExecutionEngine,
 }
 
 impl RiskManagerAgent {
@@ -292,16 +531,64 @@ impl RiskManagerAgent {
                 positions: vec![],
                 risk_tolerance: 0.5,
             },
-            max_drawdown,
+            limits: RiskLimits::with_max_drawdown(max_drawdown),
+            configured_drawdown: max_drawdown,
+            equity: EquityTracker::new(initial_capital as f64, DEFAULT_RISK_WINDOW),
+            max_volatility: 0.5,
+            volatility: 0.0,
+            engine: ExecutionEngine::new(),
         }
     }
 
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Set the market volatility above which rebalances are vetoed
+    pub fn with_max_volatility(mut self, max_volatility: f64) -> Self {
+        self.max_volatility = max_volatility;
+        self
+    }
+
+    /// Replace the drawdown, VaR and CVaR limits
+    pub fn with_risk_limits(mut self, limits: RiskLimits) -> Self {
+        self.configured_drawdown = limits.max_drawdown;
+        self.limits = limits;
+        self
+    }
+
+    /// Track equity over the last `window` observations
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.equity = EquityTracker::new(self.equity.current(), window);
+        self
+    }
+
+    /// Drawdown, VaR and CVaR limits
+    pub fn risk_limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Recorded equity curve
+    pub fn equity(&self) -> &EquityTracker {
+        &self.equity
+    }
+
+    /// Measure the recorded equity curve against the limits
+    pub fn assess(&self) -> RiskAssessment {
+        self.limits.assess(&self.equity)
+    }
+
     /// Check if risk limits are exceeded
     pub fn check_risk_limits(&self) -> Result<bool> {
-        let current_value = self.state.capital;
-        let drawdown = 1.0 - (current_value as f64 / self.state.initial_capital as f64);
-        
-        Ok(drawdown < self.max_drawdown)
+        Ok(self.equity.drawdown() < self.limits.max_drawdown)
     }
 }
 
@@ -314,22 +601,136 @@ impl Agent for RiskManagerAgent {
         &self.state
     }
 
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+        self.volatility = market_data.volatility;
+        let equity = self.state.valuation(&self.engine).equity;
+        self.equity.record(equity);
+    }
+
     fn decide(&mut self) -> Result<AgentAction> {
-        if !self.check_risk_limits()? {
-            Ok(AgentAction::EmergencyWithdraw)
-        } else {
-            Ok(AgentAction::Hold)
+        let assessment = self.assess();
+        if assessment.response != RiskResponse::Normal {
+            tracing::warn!(
+                "{} risk response {:?}: {}",
+                self.state.id,
+                assessment.response,
+                assessment.reasons.join("; ")
+            );
         }
+        let exposure = self.state.valuation(&self.engine).leverage();
+        Ok(assessment
+            .response
+            .action(exposure, self.limits.reduced_exposure))
     }
 
     fn execute(&mut self, action: AgentAction) -> Result<()> {
+        self.engine.execute(&mut self.state, &action)?;
         Ok(())
     }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.equity).ok()
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let equity = checkpoint.strategy::<EquityTracker>()?;
+        checkpoint.restore_state(&mut self.state)?;
+        if let Some(equity) = equity {
+            self.equity = equity;
+        }
+        Ok(())
+    }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
+
+    fn on_drawdown_limit(&mut self, limit: f64) {
+        // The tighter of the configured and on-chain limits applies
+        self.limits.max_drawdown = if limit > 0.0 {
+            self.configured_drawdown.min(limit)
+        } else {
+            self.configured_drawdown
+        };
+    }
+
+    fn circuit_breaker_trip(&self) -> Option<String> {
+        let assessment = self.assess();
+        (assessment.response == RiskResponse::EmergencyWithdraw)
+            .then(|| assessment.reasons.join("; "))
+    }
+
+    fn review(&self, action: &AgentAction) -> Option<String> {
+        if action.reduces_risk() {
+            return None;
+        }
+        let assessment = self.assess();
+        match action {
+            _ if assessment.response >= RiskResponse::Pause => Some(format!(
+                "Trading paused: {}",
+                assessment.reasons.join("; ")
+            )),
+            AgentAction::Rebalance { targets }
+                if assessment.response == RiskResponse::ReduceExposure
+                    && risky_weight(targets) > self.limits.reduced_exposure =>
+            {
+                Some(format!(
+                    "Exposure {:.2} above reduced limit {:.2}: {}",
+                    risky_weight(targets),
+                    self.limits.reduced_exposure,
+                    assessment.reasons.join("; ")
+                ))
+            }
+            AgentAction::Quote { intents }
+                if assessment.response == RiskResponse::ReduceExposure
+                    && intents.iter().any(|intent| {
+                        matches!(intent, OrderIntent::Place { side: Side::Buy, .. })
+                    }) =>
+            {
+                Some(format!(
+                    "New bids while reducing exposure: {}",
+                    assessment.reasons.join("; ")
+                ))
+            }
+            AgentAction::Rebalance { .. } if self.volatility > self.max_volatility => {
+                Some(format!(
+                    "Volatility {:.2} above limit {:.2}",
+                    self.volatility, self.max_volatility
+                ))
+            }
+            AgentAction::AdjustRisk { new_tolerance }
+                if *new_tolerance > self.state.risk_tolerance =>
+            {
+                Some(format!(
+                    "Risk tolerance {:.2} above limit {:.2}",
+                    new_tolerance, self.state.risk_tolerance
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Total target weight outside the quote asset
+fn risky_weight(targets: &[(String, f64)]) -> f64 {
+    targets
+        .iter()
+        .filter(|(asset, _)| asset != QUOTE_ASSET)
+        .map(|(_, weight)| weight)
+        .sum()
 }
 
 /// Market Analyzer Agent - Analyzes market conditions
 pub struct MarketAnalyzerAgent {
     state: AgentState,
+    classifier: RegimeClassifier,
+    transition: Option<RegimeTransition>,
+    engine: ExecutionEngine,
 }
 
 impl MarketAnalyzerAgent {
@@ -342,23 +743,43 @@ impl MarketAnalyzerAgent {
                 positions: vec![],
                 risk_tolerance: 0.5,
             },
+            classifier: RegimeClassifier::default(),
+            transition: None,
+            engine: ExecutionEngine::new(),
         }
     }
 
-    /// Analyze market regime (trending, ranging, volatile)
-    pub fn analyze_regime(&self, data: &MarketData) -> String {
-        if data.volatility > 0.3 {
-            "volatile".to_string()
-        } else if data.prices.len() >= 2 {
-            let trend = data.prices.last().unwrap() - data.prices.first().unwrap();
-            if trend.abs() < 0.01 {
-                "ranging".to_string()
-            } else {
-                "trending".to_string()
-            }
-        } else {
-            "unknown".to_string()
-        }
+    /// Set the risk tolerance (0.0 - 1.0)
+    pub fn with_risk_tolerance(mut self, risk_tolerance: f64) -> Self {
+        self.state.risk_tolerance = risk_tolerance;
+        self
+    }
+
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Classify regimes with `config`
+    pub fn with_regime_config(mut self, config: RegimeConfig) -> Self {
+        self.classifier = RegimeClassifier::new(config);
+        self
+    }
+
+    /// Regime confirmed across the observed market data
+    pub fn regime(&self) -> Regime {
+        self.classifier.regime()
+    }
+
+    /// Filtered regime probabilities, once market data has been observed
+    pub fn regime_probabilities(&self) -> Option<&RegimeProbabilities> {
+        self.classifier.probabilities()
+    }
+
+    /// Analyze market regime (trending, ranging, volatile) of a single snapshot
+    pub fn analyze_regime(&self, data: &MarketData) -> Regime {
+        self.classifier.classify(data)
     }
 }
 
@@ -371,13 +792,42 @@ impl Agent for MarketAnalyzerAgent {
         &self.state
     }
 
+    fn observe(&mut self, market_data: &MarketData) {
+        self.engine.observe(market_data);
+        self.transition = self.classifier.update(market_data);
+        if let Some(transition) = &self.transition {
+            tracing::info!(
+                "Market regime changed from {} to {}",
+                transition.from,
+                transition.to
+            );
+        }
+    }
+
+    fn regime_transition(&self) -> Option<&RegimeTransition> {
+        self.transition.as_ref()
+    }
+
     fn decide(&mut self) -> Result<AgentAction> {
         Ok(AgentAction::Hold)
     }
 
     fn execute(&mut self, action: AgentAction) -> Result<()> {
+        self.engine.execute(&mut self.state, &action)?;
         Ok(())
     }
+
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        checkpoint.restore_state(&mut self.state)
+    }
+
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
 }
 
 #[cfg(test)]
@@ -393,6 +843,7 @@ mod tests {
             volumes: vec![1000.0, 1200.0, 1100.0, 1300.0, 1400.0],
             volatility: 0.15,
             liquidity: 5000.0,
+            book: None,
         };
         
         let decision = agent.analyze(&market_data).unwrap();
@@ -406,6 +857,54 @@ mod tests {
         assert!(agent.check_risk_limits().unwrap());
     }
 
+    #[test]
+    fn test_risk_manager_graduated_responses() {
+        let mut agent = RiskManagerAgent::new("risk_001".to_string(), 1000, 0.2)
+            .with_engine(ExecutionEngine::new().with_price("SUI", 1.0));
+        agent
+            .execute(AgentAction::Rebalance {
+                targets: vec![("SUI".to_string(), 0.8), ("USDC".to_string(), 0.2)],
+            })
+            .unwrap();
+        let market = |price: f64| MarketData {
+            prices: vec![price],
+            volumes: vec![1000.0],
+            volatility: 0.1,
+            liquidity: 5000.0,
+            book: None,
+        };
+        let rebalance = AgentAction::Rebalance {
+            targets: vec![("SUI".to_string(), 0.6), ("USDC".to_string(), 0.4)],
+        };
+
+        // A 16% fall in SUI costs about 13% of equity: reduce exposure
+        agent.observe(&market(1.0));
+        agent.observe(&market(0.84));
+        assert_eq!(agent.assess().response, RiskResponse::ReduceExposure);
+        match agent.decide().unwrap() {
+            AgentAction::Rebalance { targets } => assert_eq!(targets[0].1, 0.3),
+            other => panic!("expected a reduction, got {:?}", other),
+        }
+        assert!(agent.review(&rebalance).unwrap().contains("reduced limit"));
+
+        // Another leg down within the window pauses trading
+        agent.observe(&market(0.8));
+        assert_eq!(agent.assess().response, RiskResponse::Pause);
+        assert!(matches!(agent.decide().unwrap(), AgentAction::Hold));
+        assert!(agent.review(&rebalance).unwrap().starts_with("Trading paused"));
+        assert!(agent.review(&AgentAction::EmergencyWithdraw).is_none());
+
+        // Past the drawdown limit everything is withdrawn
+        agent.observe(&market(0.7));
+        assert!(!agent.check_risk_limits().unwrap());
+        assert!(matches!(
+            agent.decide().unwrap(),
+            AgentAction::EmergencyWithdraw
+        ));
+        assert!(agent.circuit_breaker_trip().unwrap().contains("high-water mark"));
+        assert!(agent.equity().high_water_mark() >= 1000.0 * 0.99);
+    }
+
     #[test]
     fn test_market_analyzer() {
         let agent = MarketAnalyzerAgent::new("analyzer_001".to_string(), 1000000);
@@ -415,10 +914,32 @@ mod tests {
             volumes: vec![1000.0, 1200.0],
             volatility: 0.4,
             liquidity: 5000.0,
+            book: None,
         };
         
         let regime = agent.analyze_regime(&volatile_data);
-        assert_eq!(regime, "volatile");
+        assert_eq!(regime, Regime::Volatile);
+    }
+
+    #[test]
+    fn test_optimize_parameters() {
+        let mut window = crate::agents::orchestrator::MarketWindow::new(8);
+        let series: Vec<MarketData> = (0..40)
+            .map(|step| {
+                let price = 1.0 + 0.1 * (step as f64 * 0.3).sin() + step as f64 * 0.002;
+                window.push(price, 1000.0 + (step % 3) as f64 * 100.0, 5000.0)
+            })
+            .collect();
+        let agent = StrategyOptimizerAgent::new("optimizer_001".to_string(), 1_000_000)
+            .with_search(SearchMethod::Grid { points: 3 }, Metric::Return);
+
+        let ranked = agent.optimize_parameters(&series).unwrap();
+        assert_eq!(ranked.len(), 9);
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].metrics.score >= pair[1].metrics.score));
+        assert!(ranked[0].parameters.contains_key("volatility_threshold"));
+        assert!(agent.optimize_parameters(&[]).is_err());
     }
 }
 
@@ -2,12 +2,27 @@
 //!
 //! MCP-style agents with formally verified invariants
 
+use crate::agents::rebalancer::ExecutionEngine;
+use crate::agents::valuation::{CostBasis, InvariantReport, InvariantSet, PriceOracle, Valuation};
 use crate::error::{ManusError, Result};
 use serde::{Deserialize, Serialize};
 
 pub mod strategy;
 pub mod rebalancer;
+pub mod checkpoint;
+pub mod circuit_breaker;
+pub mod concentrated;
+pub mod features;
+pub mod market_maker;
 pub mod ml_agent;
+pub mod model;
+pub mod orchestrator;
+pub mod order_book;
+pub mod order_manager;
+pub mod regime;
+pub mod registry;
+pub mod risk;
+pub mod valuation;
 
 /// Agent state
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +43,34 @@ pub struct AgentState {
     pub risk_tolerance: f64,
 }
 
+impl AgentState {
+    /// Capital plus positions valued at their cost basis
+    pub fn total_value(&self) -> u64 {
+        self.capital + self.positions.iter()
+            .map(|p| (p.amount as f64 * p.entry_price).round() as u64)
+            .sum::<u64>()
+    }
+    
+    /// Capital and positions marked at `oracle` prices
+    pub fn valuation(&self, oracle: &dyn PriceOracle) -> Valuation {
+        Valuation::new(self, oracle)
+    }
+    
+    /// Check `invariants` with positions marked at `oracle` prices
+    pub fn check_invariants(
+        &self,
+        invariants: &InvariantSet,
+        oracle: &dyn PriceOracle,
+    ) -> InvariantReport {
+        invariants.check(self, oracle)
+    }
+    
+    /// Verify the default invariants, valuing positions at their cost basis
+    pub fn verify_invariants(&self) -> Result<()> {
+        self.check_invariants(&InvariantSet::default(), &CostBasis).into_result()
+    }
+}
+
 /// Trading position
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
@@ -59,10 +102,35 @@ pub enum AgentAction {
     /// Emergency withdraw
     EmergencyWithdraw,
     
+    /// Place, modify or cancel resting orders
+    Quote {
+        /// Changes to the resting orders
+        intents: Vec<market_maker::OrderIntent>,
+    },
+    
+    /// Move concentrated liquidity into new price ranges
+    Provide {
+        /// Ranges to provide in, replacing the current ones
+        ranges: Vec<concentrated::LiquidityRange>,
+    },
+    
     /// No action
     Hold,
 }
 
+impl AgentAction {
+    /// Whether the action can only reduce risk: holding, withdrawing, cancelling orders or
+    /// pulling liquidity
+    pub fn reduces_risk(&self) -> bool {
+        match self {
+            AgentAction::Hold | AgentAction::EmergencyWithdraw => true,
+            AgentAction::Quote { intents } => intents.iter().all(|intent| intent.is_cancel()),
+            AgentAction::Provide { ranges } => ranges.is_empty(),
+            _ => false,
+        }
+    }
+}
+
 /// Agent trait
 pub trait Agent: Send + Sync {
     /// Get agent ID
@@ -77,29 +145,84 @@ pub trait Agent: Send + Sync {
     /// Execute action
     fn execute(&mut self, action: AgentAction) -> Result<()>;
     
+    /// Observe the latest market data before deciding
+    fn observe(&mut self, _market_data: &ml_agent::MarketData) {}
+    
+    /// Reason for vetoing another agent's proposed action, if any
+    fn review(&self, _action: &AgentAction) -> Option<String> {
+        None
+    }
+    
+    /// Scores behind the most recent decision, for agents driven by an ML model
+    fn last_decision(&self) -> Option<&ml_agent::MLDecision> {
+        None
+    }
+    
+    /// Regime change detected while observing the latest market data
+    fn regime_transition(&self) -> Option<&regime::RegimeTransition> {
+        None
+    }
+    
+    /// React to a regime change detected by another agent
+    fn on_regime_change(&mut self, _transition: &regime::RegimeTransition) {}
+    
+    /// Apply the drawdown limit (0.0 - 1.0) set on chain, for agents enforcing one
+    fn on_drawdown_limit(&mut self, _limit: f64) {}
+    
+    /// Observe the latest level 2 book, for agents that place orders on it
+    fn observe_book(&mut self, _book: &order_book::OrderBook) {}
+    
+    /// Replace the orders the agent tracks with those actually resting on the book
+    fn sync_orders(&mut self, _resting: Vec<market_maker::RestingOrder>) {}
+    
+    /// Apply a fill of one of the agent's orders to its capital and inventory
+    fn record_fill(&mut self, _fill: &order_manager::OrderFill) {}
+    
+    /// Price `asset` is marked at, for agents that observe a market feed
+    fn mark_price(&self, _asset: &str) -> Option<f64> {
+        None
+    }
+    
+    /// Fees earned providing liquidity over the agent's lifetime, in quote units
+    fn fees_earned(&self) -> f64 {
+        0.0
+    }
+    
+    /// Reason to activate the on-chain circuit breaker, if this agent's limits have tripped
+    fn circuit_breaker_trip(&self) -> Option<String> {
+        None
+    }
+    
+    /// Model and strategy state to checkpoint alongside the agent's state, if any
+    fn strategy_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    
+    /// Resume from `checkpoint`
+    fn restore(&mut self, _checkpoint: &checkpoint::Checkpoint) -> Result<()> {
+        Err(ManusError::Agent(format!(
+            "Agent {} cannot be restored from a checkpoint",
+            self.id()
+        )))
+    }
+    
+    /// Check invariants against current valuations
+    ///
+    /// Agents without a price source value positions at their cost basis.
+    fn invariant_report(&self) -> InvariantReport {
+        self.state().check_invariants(&InvariantSet::default(), &CostBasis)
+    }
+    
     /// Verify invariants
     fn verify_invariants(&self) -> Result<()> {
-        let state = self.state();
-        
-        // Invariant: Agent never loses capital
-        let total_value = state.capital + state.positions.iter()
-            .map(|p| (p.amount as f64 * p.entry_price) as u64)
-            .sum::<u64>();
-        
-        if total_value < state.initial_capital {
-            return Err(ManusError::Agent(
-                format!("Invariant violation: capital loss detected. Initial: {}, Current: {}", 
-                    state.initial_capital, total_value)
-            ));
-        }
-        
-        Ok(())
+        self.invariant_report().into_result()
     }
 }
 
 /// Basic autonomous agent implementation
 pub struct AutonomousAgent {
     state: AgentState,
+    engine: ExecutionEngine,
 }
 
 impl AutonomousAgent {
@@ -113,8 +236,15 @@ impl AutonomousAgent {
                 positions: vec![],
                 risk_tolerance,
             },
+            engine: ExecutionEngine::new(),
         }
     }
+    
+    /// Execute actions through `engine`
+    pub fn with_engine(mut self, engine: ExecutionEngine) -> Self {
+        self.engine = engine;
+        self
+    }
 }
 
 impl Agent for AutonomousAgent {
@@ -132,12 +262,27 @@ impl Agent for AutonomousAgent {
         Ok(AgentAction::Hold)
     }
     
+    fn observe(&mut self, market_data: &ml_agent::MarketData) {
+        self.engine.observe(market_data);
+    }
+    
     fn execute(&mut self, action: AgentAction) -> Result<()> {
-        // TODO: Implement action execution
-        // Verify invariants after execution
-        self.verify_invariants()?;
+        // Invariants are verified before the execution is committed
+        self.engine.execute(&mut self.state, &action)?;
         Ok(())
     }
+    
+    fn mark_price(&self, asset: &str) -> Option<f64> {
+        self.engine.price(asset)
+    }
+    
+    fn restore(&mut self, checkpoint: &checkpoint::Checkpoint) -> Result<()> {
+        checkpoint.restore_state(&mut self.state)
+    }
+    
+    fn invariant_report(&self) -> InvariantReport {
+        self.engine.check(&self.state)
+    }
 }
 
 #[cfg(test)]
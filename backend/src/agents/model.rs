@@ -0,0 +1,520 @@
+//! Trainable return models for the rebalancer
+//!
+//! Feature matrices are built by feeding historical [`MarketData`] through a
+//! [`FeatureEngine`] exactly as a live agent observes it, so training and
+//! inference see the same features. Fitted models are persisted as versioned
+//! JSON artifacts in a [`ModelStore`], together with the [`FeatureSet`] they
+//! expect, and evaluated with walk-forward validation.
+
+use crate::agents::features::{FeatureEngine, FeatureSet};
+use crate::agents::ml_agent::{MarketData, RebalancerAgent};
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+use smartcore::ensemble::random_forest_regressor::{
+    RandomForestRegressor, RandomForestRegressorParameters,
+};
+use smartcore::linalg::basic::matrix::DenseMatrix;
+use smartcore::linear::linear_regression::{
+    LinearRegression, LinearRegressionParameters, LinearRegressionSolverName,
+};
+use smartcore::linear::ridge_regression::{RidgeRegression, RidgeRegressionParameters};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Regularization strength for ridge models
+pub const RIDGE_ALPHA: f64 = 1.0;
+
+/// Trees grown by random forest models
+pub const FOREST_TREES: usize = 50;
+
+/// Seed for random forests, so retraining on the same data is reproducible
+pub const FOREST_SEED: u64 = 42;
+
+type Matrix = DenseMatrix<f64>;
+
+/// Supported model families
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    /// Ordinary least squares
+    Linear,
+    /// L2-regularized least squares
+    Ridge,
+    /// Random forest regressor
+    RandomForest,
+}
+
+/// Fitted model predicting the forward return from a feature vector
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "model", rename_all = "snake_case")]
+pub enum ReturnModel {
+    /// Ordinary least squares
+    Linear(LinearRegression<f64, f64, Matrix, Vec<f64>>),
+    /// L2-regularized least squares
+    Ridge(RidgeRegression<f64, f64, Matrix, Vec<f64>>),
+    /// Random forest regressor
+    RandomForest(RandomForestRegressor<f64, f64, Matrix, Vec<f64>>),
+}
+
+impl ReturnModel {
+    /// Fit a model of `kind` on `data`
+    pub fn fit(kind: ModelKind, data: &TrainingSet) -> Result<Self> {
+        if data.is_empty() {
+            return Err(ManusError::Agent("No training samples".to_string()));
+        }
+        let x = data.matrix()?;
+        let y = data.targets.clone();
+
+        let model = match kind {
+            ModelKind::Linear => {
+                // SVD copes with constant features such as a fixed liquidity
+                let parameters = LinearRegressionParameters::default()
+                    .with_solver(LinearRegressionSolverName::SVD);
+                LinearRegression::fit(&x, &y, parameters).map(ReturnModel::Linear)
+            }
+            ModelKind::Ridge => {
+                let parameters = RidgeRegressionParameters::default()
+                    .with_alpha(RIDGE_ALPHA)
+                    .with_normalize(false);
+                RidgeRegression::fit(&x, &y, parameters).map(ReturnModel::Ridge)
+            }
+            ModelKind::RandomForest => {
+                let parameters = RandomForestRegressorParameters::default()
+                    .with_n_trees(FOREST_TREES)
+                    .with_seed(FOREST_SEED);
+                RandomForestRegressor::fit(&x, &y, parameters).map(ReturnModel::RandomForest)
+            }
+        };
+
+        model.map_err(|e| ManusError::Agent(format!("Failed to fit {:?} model: {}", kind, e)))
+    }
+
+    /// Model family
+    pub fn kind(&self) -> ModelKind {
+        match self {
+            ReturnModel::Linear(_) => ModelKind::Linear,
+            ReturnModel::Ridge(_) => ModelKind::Ridge,
+            ReturnModel::RandomForest(_) => ModelKind::RandomForest,
+        }
+    }
+
+    /// Predict forward returns for each row of `features`
+    pub fn predict_batch(&self, features: &[Vec<f64>]) -> Result<Vec<f64>> {
+        let x = to_matrix(features)?;
+        let predictions = match self {
+            ReturnModel::Linear(model) => model.predict(&x),
+            ReturnModel::Ridge(model) => model.predict(&x),
+            ReturnModel::RandomForest(model) => model.predict(&x),
+        };
+        predictions.map_err(|e| ManusError::Agent(format!("Prediction failed: {}", e)))
+    }
+
+    /// Predict the forward return for one feature vector
+    pub fn predict(&self, features: &[f64]) -> Result<f64> {
+        Ok(self.predict_batch(&[features.to_vec()])?[0])
+    }
+}
+
+/// Feature rows paired with the forward returns they should predict
+#[derive(Debug, Clone, Default)]
+pub struct TrainingSet {
+    /// Feature vectors, one per snapshot
+    pub features: Vec<Vec<f64>>,
+    /// Forward return following each snapshot
+    pub targets: Vec<f64>,
+}
+
+impl TrainingSet {
+    /// Build samples from a series, targeting the return `horizon` snapshots ahead
+    ///
+    /// Features are `agent`'s feature set, accumulated over the series as the
+    /// agent would observe it. Snapshots whose features or target are not
+    /// finite are skipped.
+    pub fn from_series(
+        agent: &RebalancerAgent,
+        series: &[MarketData],
+        horizon: usize,
+    ) -> Result<Self> {
+        let horizon = horizon.max(1);
+        let mut engine = FeatureEngine::default();
+        let mut data = Self::default();
+        for (index, snapshot) in series.iter().enumerate() {
+            engine.observe(snapshot);
+            let Some(future) = series.get(index + horizon) else {
+                break;
+            };
+            let (Some(&now), Some(&later)) = (snapshot.prices.last(), future.prices.last()) else {
+                continue;
+            };
+
+            let features = engine.vector(agent.feature_set());
+            let target = later / now - 1.0;
+            if target.is_finite() && features.iter().all(|value| value.is_finite()) {
+                data.features.push(features);
+                data.targets.push(target);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Whether there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Samples in `start..end`
+    pub fn slice(&self, start: usize, end: usize) -> Self {
+        Self {
+            features: self.features[start..end].to_vec(),
+            targets: self.targets[start..end].to_vec(),
+        }
+    }
+
+    fn matrix(&self) -> Result<Matrix> {
+        to_matrix(&self.features)
+    }
+}
+
+fn to_matrix(rows: &[Vec<f64>]) -> Result<Matrix> {
+    DenseMatrix::from_2d_vec(&rows.to_vec())
+        .map_err(|e| ManusError::Agent(format!("Invalid feature matrix: {}", e)))
+}
+
+/// Out-of-sample metrics for one walk-forward fold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FoldMetrics {
+    /// Samples the fold was trained on
+    pub train_size: usize,
+    /// Samples the fold was evaluated on
+    pub test_size: usize,
+    /// Mean squared error
+    pub mse: f64,
+    /// Mean absolute error
+    pub mae: f64,
+    /// Share of test samples whose return sign was predicted correctly
+    pub directional_accuracy: f64,
+}
+
+/// Walk-forward validation results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Per-fold metrics, oldest first
+    pub folds: Vec<FoldMetrics>,
+    /// Mean squared error averaged over folds
+    pub mse: f64,
+    /// Mean absolute error averaged over folds
+    pub mae: f64,
+    /// Directional accuracy averaged over folds
+    pub directional_accuracy: f64,
+}
+
+/// Validate `kind` with an expanding training window
+///
+/// The samples are split into `folds + 1` consecutive blocks; fold `k` trains
+/// on the first `k` blocks and tests on block `k + 1`, so every evaluation is
+/// out of sample and in the future of its training data.
+pub fn walk_forward(kind: ModelKind, data: &TrainingSet, folds: usize) -> Result<ValidationReport> {
+    let folds = folds.max(1);
+    let block = data.len() / (folds + 1);
+    if block < 2 {
+        return Err(ManusError::Agent(format!(
+            "{} samples are too few for {} walk-forward folds",
+            data.len(),
+            folds
+        )));
+    }
+
+    let mut results = Vec::with_capacity(folds);
+    for fold in 1..=folds {
+        let train = data.slice(0, fold * block);
+        let end = if fold == folds {
+            data.len()
+        } else {
+            (fold + 1) * block
+        };
+        let test = data.slice(fold * block, end);
+
+        let predictions = ReturnModel::fit(kind, &train)?.predict_batch(&test.features)?;
+        let errors: Vec<f64> = predictions
+            .iter()
+            .zip(&test.targets)
+            .map(|(predicted, actual)| predicted - actual)
+            .collect();
+        let hits = predictions
+            .iter()
+            .zip(&test.targets)
+            .filter(|(predicted, actual)| predicted.signum() == actual.signum())
+            .count();
+
+        results.push(FoldMetrics {
+            train_size: train.len(),
+            test_size: test.len(),
+            mse: errors.iter().map(|e| e * e).sum::<f64>() / test.len() as f64,
+            mae: errors.iter().map(|e| e.abs()).sum::<f64>() / test.len() as f64,
+            directional_accuracy: hits as f64 / test.len() as f64,
+        });
+    }
+
+    let mean = |metric: fn(&FoldMetrics) -> f64| {
+        results.iter().map(metric).sum::<f64>() / results.len() as f64
+    };
+    Ok(ValidationReport {
+        mse: mean(|fold| fold.mse),
+        mae: mean(|fold| fold.mae),
+        directional_accuracy: mean(|fold| fold.directional_accuracy),
+        folds: results,
+    })
+}
+
+/// Description of a persisted model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// Model name
+    pub name: String,
+    /// Version, starting at 1
+    pub version: u32,
+    /// Model family
+    pub kind: ModelKind,
+    /// Unix timestamp the model was saved at
+    pub created_at: u64,
+    /// Samples the model was trained on
+    pub samples: usize,
+    /// Features the model expects, in order
+    #[serde(default)]
+    pub features: FeatureSet,
+    /// Walk-forward metrics, if validated
+    pub validation: Option<ValidationReport>,
+}
+
+/// Persisted model with its metadata
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelArtifact {
+    /// Metadata
+    pub metadata: ModelMetadata,
+    /// Fitted model
+    pub model: ReturnModel,
+}
+
+impl ModelArtifact {
+    /// Read an artifact written by [`ModelStore::save`]
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ManusError::Agent(format!("Failed to read model {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&contents).map_err(|e| {
+            ManusError::Agent(format!("Invalid model artifact {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Directory of versioned model artifacts, laid out as `<name>/v<version>.json`
+#[derive(Debug, Clone)]
+pub struct ModelStore {
+    root: PathBuf,
+}
+
+impl ModelStore {
+    /// Store rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Path of `name` at `version`
+    pub fn path(&self, name: &str, version: u32) -> PathBuf {
+        self.root.join(name).join(format!("v{}.json", version))
+    }
+
+    /// Saved versions of `name`, in ascending order
+    pub fn versions(&self, name: &str) -> Result<Vec<u32>> {
+        let entries = match std::fs::read_dir(self.root.join(name)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(ManusError::Agent(format!("Failed to list models: {}", e))),
+        };
+
+        let mut versions: Vec<u32> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                file_name
+                    .to_str()?
+                    .strip_prefix('v')?
+                    .strip_suffix(".json")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    /// Save `model`, trained on `features`, as the next version of `name`
+    pub fn save(
+        &self,
+        name: &str,
+        model: ReturnModel,
+        features: FeatureSet,
+        samples: usize,
+        validation: Option<ValidationReport>,
+    ) -> Result<ModelArtifact> {
+        let version = self.versions(name)?.last().map_or(1, |latest| latest + 1);
+        let artifact = ModelArtifact {
+            metadata: ModelMetadata {
+                name: name.to_string(),
+                version,
+                kind: model.kind(),
+                created_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs())
+                    .unwrap_or(0),
+                samples,
+                features,
+                validation,
+            },
+            model,
+        };
+
+        let path = self.path(name, version);
+        let json = serde_json::to_string(&artifact)
+            .map_err(|e| ManusError::Agent(format!("Failed to serialize model: {}", e)))?;
+        std::fs::create_dir_all(self.root.join(name))
+            .and_then(|_| std::fs::write(&path, json))
+            .map_err(|e| {
+                ManusError::Agent(format!("Failed to write model {}: {}", path.display(), e))
+            })?;
+
+        Ok(artifact)
+    }
+
+    /// Load `name` at `version`, or its latest version
+    pub fn load(&self, name: &str, version: Option<u32>) -> Result<ModelArtifact> {
+        let version = match version {
+            Some(version) => version,
+            None => *self
+                .versions(name)?
+                .last()
+                .ok_or_else(|| ManusError::Agent(format!("No saved versions of model {}", name)))?,
+        };
+        ModelArtifact::load(&self.path(name, version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::orchestrator::MarketWindow;
+    use crate::agents::Agent;
+
+    /// Prices whose momentum carries into the next step
+    fn trending_series(len: usize) -> Vec<MarketData> {
+        let mut window = MarketWindow::new(5);
+        let mut price = 1.0;
+        let mut drift: f64 = 0.01;
+        (0..len)
+            .map(|step| {
+                if step % 10 == 0 {
+                    drift = -drift;
+                }
+                price *= 1.0 + drift + 0.002 * ((step * 7 % 5) as f64 - 2.0);
+                window.push(price, 1000.0 + (step % 4) as f64 * 50.0, 5000.0)
+            })
+            .collect()
+    }
+
+    fn training_set() -> TrainingSet {
+        let agent = RebalancerAgent::new("trainer".to_string(), 1_000);
+        TrainingSet::from_series(&agent, &trending_series(120), 1).unwrap()
+    }
+
+    #[test]
+    fn test_fit_each_kind() {
+        let data = training_set();
+        assert_eq!(data.len(), 119);
+
+        for kind in [ModelKind::Linear, ModelKind::Ridge, ModelKind::RandomForest] {
+            let model = ReturnModel::fit(kind, &data).unwrap();
+            assert_eq!(model.kind(), kind);
+            assert!(model.predict(&data.features[10]).unwrap().is_finite());
+        }
+        assert!(ReturnModel::fit(ModelKind::Linear, &TrainingSet::default()).is_err());
+    }
+
+    #[test]
+    fn test_training_uses_agent_feature_set() {
+        let agent = RebalancerAgent::new("trainer".to_string(), 1_000)
+            .with_feature_set(FeatureSet::technical());
+        let data = TrainingSet::from_series(&agent, &trending_series(60), 1).unwrap();
+        assert_eq!(data.features[0].len(), FeatureSet::technical().len());
+
+        let model = ReturnModel::fit(ModelKind::Ridge, &data).unwrap();
+        let mut agent = agent.with_model(model);
+        let mut snapshot = None;
+        for market_data in trending_series(20) {
+            agent.observe(&market_data);
+            snapshot = Some(market_data);
+        }
+        assert!(agent.decide().is_ok());
+        assert!(agent.analyze(&snapshot.unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_walk_forward() {
+        let data = training_set();
+        let report = walk_forward(ModelKind::Linear, &data, 3).unwrap();
+        assert_eq!(report.folds.len(), 3);
+        assert_eq!(report.folds[0].train_size, 29);
+        assert_eq!(
+            report
+                .folds
+                .iter()
+                .map(|fold| fold.test_size)
+                .sum::<usize>(),
+            90
+        );
+        assert!(report.directional_accuracy > 0.5);
+
+        assert!(walk_forward(ModelKind::Linear, &data.slice(0, 4), 3).is_err());
+    }
+
+    #[test]
+    fn test_store_versions_models() {
+        let root = std::env::temp_dir().join(format!("manus-models-{}", std::process::id()));
+        let store = ModelStore::new(&root);
+        let data = training_set();
+
+        for kind in [ModelKind::Ridge, ModelKind::RandomForest] {
+            let model = ReturnModel::fit(kind, &data).unwrap();
+            store
+                .save(
+                    "rebalancer",
+                    model,
+                    FeatureSet::baseline(),
+                    data.len(),
+                    None,
+                )
+                .unwrap();
+        }
+        assert_eq!(store.versions("rebalancer").unwrap(), vec![1, 2]);
+
+        let latest = store.load("rebalancer", None).unwrap();
+        assert_eq!(latest.metadata.version, 2);
+        assert_eq!(latest.metadata.kind, ModelKind::RandomForest);
+        assert_eq!(latest.metadata.features, FeatureSet::baseline());
+
+        // The reloaded model predicts exactly as the original
+        let original = ReturnModel::fit(ModelKind::Ridge, &data).unwrap();
+        let reloaded = ModelArtifact::load(&store.path("rebalancer", 1)).unwrap();
+        assert_eq!(
+            reloaded.model.predict(&data.features[3]).unwrap(),
+            original.predict(&data.features[3]).unwrap()
+        );
+        assert!(store.load("missing", None).is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
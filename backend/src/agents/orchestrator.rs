@@ -0,0 +1,1200 @@
+//! Multi-agent orchestrator
+//!
+//! Runs every configured agent concurrently against a shared market feed.
+//! Each market update is first observed by all agents; regime changes one
+//! agent detects are passed on to the others and to regime subscribers.
+//! Then every agent decides independently.
+//!
+//! Risk managers review the other agents' proposals and may veto them.
+//! Executions that introduce or deepen an invariant violation are rolled
+//! back and fail. Breaches that were already there are published to breach
+//! subscribers instead.
+//!
+//! A supervisor rebuilds agents that panic. While a mirrored on-chain
+//! circuit breaker is active, agents may only hold or withdraw; agents whose
+//! limits trip ask for it to be activated.
+//!
+//! Executions of agents managing a vault are recorded for performance
+//! attribution.
+//!
+//! With a checkpointer, agents resume from their latest checkpoint on
+//! startup and whenever they are restarted, and are checkpointed after every
+//! execution. Agents whose vault diverged from their checkpoint, or that
+//! could not be restored, are held like a halted agent until an operator
+//! clears or re-baselines their checkpoint.
+//!
+//! With an order manager, market makers quote against the latest level 2
+//! book and are fed the fills and resting orders it tracks. Their quotes are
+//! placed on chain through it.
+
+use crate::agents::checkpoint::{Checkpoint, Checkpointer, Recovery};
+use crate::agents::circuit_breaker::CircuitBreaker;
+use crate::agents::ml_agent::{MarketData, OrderBookTop};
+use crate::agents::order_book::OrderBook;
+use crate::agents::order_manager::OrderManager;
+use crate::agents::regime::RegimeTransition;
+use crate::agents::registry::{configured_specs, AgentKind, AgentRegistry, AgentSpec};
+use crate::agents::valuation::InvariantReport;
+use crate::agents::{Agent, AgentAction};
+use crate::attribution::{timestamp_ms, AttributionStore, PositionSnapshot};
+use crate::config::AgentConfig;
+use crate::error::{ManusError, Result};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, RwLock};
+use tokio::task::JoinSet;
+
+/// Default delay before restarting a crashed agent, multiplied by the restart count
+pub const DEFAULT_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Regime transitions buffered for slow subscribers
+const REGIME_BUFFER: usize = 16;
+
+/// Invariant breaches buffered for slow subscribers
+const BREACH_BUFFER: usize = 64;
+
+/// Rolling window turning price ticks into [`MarketData`]
+#[derive(Debug, Clone)]
+pub struct MarketWindow {
+    capacity: usize,
+    prices: VecDeque<f64>,
+    volumes: VecDeque<f64>,
+}
+
+impl MarketWindow {
+    /// Create a window holding the last `capacity` ticks
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(2),
+            prices: VecDeque::new(),
+            volumes: VecDeque::new(),
+        }
+    }
+
+    /// Record a tick and return the resulting market snapshot
+    ///
+    /// Volatility is the standard deviation of returns across the window.
+    pub fn push(&mut self, price: f64, volume: f64, liquidity: f64) -> MarketData {
+        self.push_with_book(price, volume, liquidity, None)
+    }
+
+    /// Record a tick along with the top of the order book
+    pub fn push_with_book(
+        &mut self,
+        price: f64,
+        volume: f64,
+        liquidity: f64,
+        book: Option<OrderBookTop>,
+    ) -> MarketData {
+        if self.prices.len() == self.capacity {
+            self.prices.pop_front();
+            self.volumes.pop_front();
+        }
+        self.prices.push_back(price);
+        self.volumes.push_back(volume);
+
+        MarketData {
+            prices: self.prices.iter().copied().collect(),
+            volumes: self.volumes.iter().copied().collect(),
+            volatility: self.volatility(),
+            liquidity,
+            book,
+        }
+    }
+
+    fn volatility(&self) -> f64 {
+        let returns: Vec<f64> = self
+            .prices
+            .iter()
+            .zip(self.prices.iter().skip(1))
+            .filter(|(previous, _)| **previous != 0.0)
+            .map(|(previous, current)| (current - previous) / previous)
+            .collect();
+        if returns.is_empty() {
+            return 0.0;
+        }
+
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        variance.sqrt()
+    }
+}
+
+/// Result of one decision cycle
+#[derive(Debug, Clone)]
+pub enum StepOutcome {
+    /// The action passed review and was executed
+    Executed(AgentAction),
+
+    /// A risk manager vetoed the action
+    Vetoed {
+        /// Proposed action
+        action: AgentAction,
+        /// ID of the vetoing risk manager
+        reviewer: String,
+        /// Reason given for the veto
+        reason: String,
+    },
+
+    /// The on-chain circuit breaker is active and the action was not run
+    Halted(AgentAction),
+}
+
+/// Execution that introduced no invariant violation
+#[derive(Debug)]
+pub struct Execution {
+    /// Holdings before and after, for agents marking a price
+    pub snapshots: Option<(PositionSnapshot, PositionSnapshot)>,
+    /// Invariants after executing; any breaches were already present
+    pub report: InvariantReport,
+    /// Agent as it was before executing
+    pub checkpoint: Checkpoint,
+}
+
+/// Why an execution was rolled back
+#[derive(Debug)]
+pub enum Rollback {
+    /// The agent failed to execute the action
+    Failed(ManusError),
+    /// The action introduced the violations in the report
+    Violation(InvariantReport),
+}
+
+impl From<Rollback> for ManusError {
+    fn from(rollback: Rollback) -> Self {
+        match rollback {
+            Rollback::Failed(error) => error,
+            Rollback::Violation(report) => ManusError::Agent(report.to_string()),
+        }
+    }
+}
+
+/// Execute `action` on `agent`, keeping the result only if it introduced no violation
+///
+/// The agent is restored from a checkpoint taken beforehand when the action
+/// fails or introduces an invariant violation, as judged by
+/// [`InvariantReport::introduced_by`].
+pub fn execute_checked(
+    agent: &mut dyn Agent,
+    action: &AgentAction,
+) -> std::result::Result<Execution, Rollback> {
+    let checkpoint = Checkpoint::capture(agent);
+    let checked = agent.invariant_report();
+    let before = PositionSnapshot::capture(agent, timestamp_ms());
+    let rollback = match agent.execute(action.clone()) {
+        Err(e) => Some(Rollback::Failed(e)),
+        Ok(()) => {
+            let report = agent.invariant_report();
+            let violations: Vec<_> = report
+                .introduced_by(action, &checked)
+                .into_iter()
+                .cloned()
+                .collect();
+            (!violations.is_empty()).then(|| {
+                Rollback::Violation(InvariantReport {
+                    violations,
+                    ..report
+                })
+            })
+        }
+    };
+    if let Some(rollback) = rollback {
+        roll_back(agent, &checkpoint);
+        return Err(rollback);
+    }
+
+    let after = PositionSnapshot::capture(agent, timestamp_ms());
+    Ok(Execution {
+        snapshots: before.zip(after),
+        report: agent.invariant_report(),
+        checkpoint,
+    })
+}
+
+/// Restore `agent` from `checkpoint`, taken before an execution that did not stand
+pub fn roll_back(agent: &mut dyn Agent, checkpoint: &Checkpoint) {
+    if let Err(e) = agent.restore(checkpoint) {
+        tracing::error!("Failed to roll back agent {}: {}", agent.id(), e);
+    }
+}
+
+/// Why an agent is held to risk-reducing actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hold {
+    /// Its vault diverged from its checkpoint
+    Diverged,
+    /// It could not be restored from its checkpoint
+    Unrestored,
+}
+
+/// Order manager placing market makers' quotes, and the books they quote against
+struct OrderFlow {
+    orders: Arc<OrderManager>,
+    books: watch::Receiver<OrderBook>,
+}
+
+/// Runs agents concurrently and supervises them
+pub struct Orchestrator {
+    registry: Arc<AgentRegistry>,
+    specs: Vec<AgentSpec>,
+    max_restarts: u32,
+    restart_backoff: Duration,
+    regimes: broadcast::Sender<RegimeTransition>,
+    breaches: broadcast::Sender<InvariantReport>,
+    circuit_breaker: Option<CircuitBreaker>,
+    attribution: Option<Arc<AttributionStore>>,
+    checkpointer: Option<Arc<Checkpointer>>,
+    order_flow: Option<OrderFlow>,
+    held: RwLock<HashMap<String, Hold>>,
+}
+
+impl Orchestrator {
+    /// Create an orchestrator running [`configured_specs`] in `registry`
+    pub fn new(config: &AgentConfig, registry: Arc<AgentRegistry>) -> Self {
+        Self {
+            registry,
+            specs: configured_specs(config),
+            max_restarts: config.max_restarts,
+            restart_backoff: DEFAULT_RESTART_BACKOFF,
+            regimes: broadcast::channel(REGIME_BUFFER).0,
+            breaches: broadcast::channel(BREACH_BUFFER).0,
+            circuit_breaker: None,
+            attribution: None,
+            checkpointer: None,
+            order_flow: None,
+            held: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Run `specs` instead of the default agents
+    pub fn with_specs(mut self, specs: Vec<AgentSpec>) -> Self {
+        self.specs = specs;
+        self
+    }
+
+    /// Set the base delay before restarting a crashed agent
+    pub fn with_restart_backoff(mut self, restart_backoff: Duration) -> Self {
+        self.restart_backoff = restart_backoff;
+        self
+    }
+
+    /// Halt trading while `circuit_breaker` is active, and trip it when an agent's limits do
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Record the executions of agents managing a vault in `attribution`
+    pub fn with_attribution(mut self, attribution: Arc<AttributionStore>) -> Self {
+        self.attribution = Some(attribution);
+        self
+    }
+
+    /// Resume agents from `checkpointer` when run, and checkpoint them after every execution
+    pub fn with_checkpointer(mut self, checkpointer: Arc<Checkpointer>) -> Self {
+        self.checkpointer = Some(checkpointer);
+        self
+    }
+
+    /// Place market makers' quotes through `orders`, quoting against the latest of `books`
+    ///
+    /// The order manager's account backs a single market maker, which takes
+    /// every fill and resting order it tracks.
+    pub fn with_order_manager(
+        mut self,
+        orders: Arc<OrderManager>,
+        books: watch::Receiver<OrderBook>,
+    ) -> Self {
+        self.order_flow = Some(OrderFlow { orders, books });
+        self
+    }
+
+    /// Registry holding the running agents
+    pub fn registry(&self) -> &Arc<AgentRegistry> {
+        &self.registry
+    }
+
+    /// Subscribe to regime transitions detected by any agent
+    pub fn subscribe_regimes(&self) -> broadcast::Receiver<RegimeTransition> {
+        self.regimes.subscribe()
+    }
+
+    /// Subscribe to invariant breaches that persisted through an execution
+    pub fn subscribe_breaches(&self) -> broadcast::Receiver<InvariantReport> {
+        self.breaches.subscribe()
+    }
+
+    /// Let every registered agent observe `market_data`
+    ///
+    /// Regime transitions detected while observing are delivered to every
+    /// other agent and published to regime subscribers. Agents first apply
+    /// the drawdown limit of the synced circuit breaker, and those whose
+    /// limits tripped ask for it to be activated.
+    pub async fn observe_all(&self, market_data: &MarketData) {
+        let ids = self.registry.ids().await;
+        let drawdown_limit = self
+            .circuit_breaker
+            .as_ref()
+            .map(CircuitBreaker::state)
+            .filter(|state| state.synced)
+            .map(|state| state.drawdown_limit);
+        let mut transitions = Vec::new();
+        for id in &ids {
+            let Some((transition, trip)) = self
+                .registry
+                .with_agent(id, |agent| {
+                    if let Some(limit) = drawdown_limit {
+                        agent.on_drawdown_limit(limit);
+                    }
+                    agent.observe(market_data);
+                    (
+                        agent.regime_transition().cloned(),
+                        agent.circuit_breaker_trip(),
+                    )
+                })
+                .await
+            else {
+                continue;
+            };
+            if let Some(transition) = transition {
+                transitions.push((id.clone(), transition));
+            }
+            if let (Some(reason), Some(breaker)) = (trip, &self.circuit_breaker) {
+                if !breaker.state().active && !breaker.trip(format!("{}: {}", id, reason)) {
+                    tracing::error!("Circuit breaker sync stopped, cannot trip for {}", id);
+                }
+            }
+        }
+
+        for (source, transition) in transitions {
+            for id in ids.iter().filter(|id| **id != source) {
+                self.registry
+                    .with_agent(id, |agent| agent.on_regime_change(&transition))
+                    .await;
+            }
+            // Nobody may be listening
+            let _ = self.regimes.send(transition);
+        }
+    }
+
+    /// Run one decision cycle for agent `id`
+    ///
+    /// The proposed action is reviewed by every other risk manager before it
+    /// is executed. Only holds, emergency withdrawals and order cancellations
+    /// run while the circuit breaker is active or the agent is held.
+    ///
+    /// The step fails, leaving the agent as it was, if the execution
+    /// introduced or deepened an invariant violation. Breaches already present
+    /// before it are published to breach subscribers instead.
+    ///
+    /// With an order manager, quotes are placed on chain once they pass the
+    /// invariant check. If they could not be, the agent is rolled back and the
+    /// step fails.
+    pub async fn step(&self, id: &str) -> Result<StepOutcome> {
+        if let Some(flow) = &self.order_flow {
+            self.sync_order_flow(id, flow).await?;
+        }
+
+        let action = self
+            .registry
+            .with_agent(id, |agent| agent.decide())
+            .await
+            .ok_or_else(|| not_registered(id))??;
+
+        let held = self.is_held(id).await;
+        if held && !action.reduces_risk() {
+            tracing::warn!(
+                "Agent {} is held on its checkpoint, not running {:?}",
+                id,
+                action
+            );
+            return Ok(StepOutcome::Halted(action));
+        }
+
+        let halted = self
+            .circuit_breaker
+            .as_ref()
+            .is_some_and(CircuitBreaker::is_active);
+        if halted && !action.reduces_risk() {
+            tracing::warn!(
+                "Circuit breaker active, not running {:?} from {}",
+                action,
+                id
+            );
+            return Ok(StepOutcome::Halted(action));
+        }
+
+        if let Some((reviewer, reason)) = self.registry.review(id, &action).await {
+            tracing::warn!("{} vetoed {:?} from {}: {}", reviewer, action, id, reason);
+            return Ok(StepOutcome::Vetoed {
+                action,
+                reviewer,
+                reason,
+            });
+        }
+
+        let Execution {
+            snapshots,
+            report,
+            checkpoint,
+        } = self
+            .registry
+            .with_agent(id, |agent| execute_checked(agent, &action))
+            .await
+            .ok_or_else(|| not_registered(id))??;
+
+        if let (Some(flow), AgentAction::Quote { intents }) = (&self.order_flow, &action) {
+            match flow.orders.submit(intents).await {
+                Ok(receipt) => tracing::info!(
+                    "Agent {} placed {} order intents in {}",
+                    id,
+                    intents.len(),
+                    receipt.digest
+                ),
+                Err(e) => {
+                    self.registry
+                        .with_agent(id, |agent| roll_back(agent, &checkpoint))
+                        .await;
+                    return Err(e);
+                }
+            }
+        }
+
+        if !report.is_ok() {
+            tracing::warn!("{}", report);
+            // Nobody may be listening
+            let _ = self.breaches.send(report);
+        }
+
+        if let (Some(attribution), Some((before, after))) = (&self.attribution, snapshots) {
+            if let Some(vault_id) = self.registry.get(id).await.and_then(|info| info.vault_id) {
+                if let Err(e) = attribution.record_execution(&vault_id, before, after).await {
+                    tracing::warn!("Failed to record execution on vault {}: {}", vault_id, e);
+                }
+            }
+        }
+        // Held agents keep their checkpoint so they stay held across restarts
+        if let (Some(checkpointer), false) = (&self.checkpointer, held) {
+            if let Err(e) = checkpointer.checkpoint(&self.registry, id).await {
+                tracing::warn!("Failed to checkpoint agent {}: {}", id, e);
+            }
+        }
+
+        Ok(StepOutcome::Executed(action))
+    }
+
+    /// Feed market maker `id` the latest book, its fills and its resting orders
+    async fn sync_order_flow(&self, id: &str, flow: &OrderFlow) -> Result<()> {
+        let info = self
+            .registry
+            .get(id)
+            .await
+            .ok_or_else(|| not_registered(id))?;
+        if info.kind != AgentKind::MarketMaker {
+            return Ok(());
+        }
+
+        let book = flow.books.borrow().clone();
+        let fills = flow.orders.take_fills().await;
+        let resting = flow.orders.resting_orders().await;
+        self.registry
+            .with_agent(id, |agent| {
+                agent.observe_book(&book);
+                for fill in &fills {
+                    agent.record_fill(fill);
+                }
+                agent.sync_orders(resting);
+            })
+            .await
+            .ok_or_else(|| not_registered(id))
+    }
+
+    /// Drive all agents from `market` until the feed closes
+    ///
+    /// Agents missing from the registry are created from their specs first,
+    /// then resumed from their checkpoints. The current market value is
+    /// processed immediately.
+    pub async fn run(self, mut market: watch::Receiver<MarketData>) -> Result<()> {
+        for spec in &self.specs {
+            if self.registry.get(&spec.id).await.is_none() {
+                self.registry.create(spec).await?;
+            }
+        }
+        for id in self.registry.ids().await {
+            self.resume(&id).await;
+        }
+
+        let (ticks, tick) = watch::channel(0u64);
+        let orchestrator = Arc::new(self);
+        let mut supervisors = JoinSet::new();
+        for spec in orchestrator.specs.clone() {
+            supervisors.spawn(orchestrator.clone().supervise(spec, tick.clone()));
+        }
+        drop(tick);
+
+        loop {
+            let market_data = market.borrow_and_update().clone();
+            orchestrator.observe_all(&market_data).await;
+            ticks.send_modify(|tick| *tick += 1);
+
+            if market.changed().await.is_err() {
+                break;
+            }
+        }
+
+        tracing::info!("Market feed closed, stopping agents");
+        drop(ticks);
+        while supervisors.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Restore agent `id` from its checkpoint, holding it if that fails or its vault diverged
+    async fn resume(&self, id: &str) {
+        let Some(checkpointer) = &self.checkpointer else {
+            return;
+        };
+        let hold = match checkpointer.resume_agent(&self.registry, id).await {
+            Ok(Recovery::Resumed) => {
+                tracing::info!("Resumed agent {} from its checkpoint", id);
+                None
+            }
+            Ok(Recovery::Fresh) => {
+                tracing::info!("No checkpoint for agent {}, starting afresh", id);
+                None
+            }
+            Ok(Recovery::Diverged(divergence)) => {
+                tracing::error!("Holding agent: {}", divergence);
+                Some(Hold::Diverged)
+            }
+            Err(e) => {
+                tracing::error!("Holding agent {}, failed to restore it: {}", id, e);
+                Some(Hold::Unrestored)
+            }
+        };
+
+        let mut held = self.held.write().await;
+        match hold {
+            Some(hold) => held.insert(id.to_string(), hold),
+            None => held.remove(id),
+        };
+    }
+
+    /// Whether agent `id` is still held
+    ///
+    /// A hold is released once the agent's checkpoint was cleared, or, for an
+    /// agent whose vault diverged, re-baselined against the balances on chain.
+    async fn is_held(&self, id: &str) -> bool {
+        let Some(hold) = self.held.read().await.get(id).copied() else {
+            return false;
+        };
+        let Some(checkpointer) = &self.checkpointer else {
+            return true;
+        };
+        let released = match hold {
+            Hold::Diverged => checkpointer
+                .divergence(&self.registry, id)
+                .await
+                .map(|divergence| divergence.is_none()),
+            Hold::Unrestored => checkpointer.has_checkpoint(id).await.map(|found| !found),
+        };
+
+        match released {
+            Ok(true) => {
+                tracing::info!(
+                    "Releasing agent {}, its checkpoint was cleared or re-baselined",
+                    id
+                );
+                self.held.write().await.remove(id);
+                false
+            }
+            Ok(false) => true,
+            Err(e) => {
+                tracing::warn!("Failed to check the hold on agent {}: {}", id, e);
+                true
+            }
+        }
+    }
+
+    /// Step the agent on every tick until the ticks stop
+    ///
+    /// Failed steps are skipped; the agent is only considered crashed once it
+    /// is no longer registered.
+    async fn drive(&self, id: &str, mut tick: watch::Receiver<u64>) -> Result<()> {
+        while tick.changed().await.is_ok() {
+            match self.step(id).await {
+                Ok(StepOutcome::Executed(action)) => {
+                    tracing::info!("Agent {} executed {:?}", id, action);
+                }
+                Ok(StepOutcome::Vetoed { .. } | StepOutcome::Halted(_)) => {}
+                Err(e) => {
+                    tracing::warn!("Agent {} step failed: {}", id, e);
+                    if self.registry.get(id).await.is_none() {
+                        return Err(not_registered(id));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drive `spec`'s agent, rebuilding it whenever it panics or goes missing
+    ///
+    /// Rebuilt agents are resumed from their checkpoint before being driven again.
+    async fn supervise(self: Arc<Self>, spec: AgentSpec, tick: watch::Receiver<u64>) {
+        let mut restarts = 0;
+        loop {
+            let orchestrator = self.clone();
+            let id = spec.id.clone();
+            let ticks = tick.clone();
+            let failure =
+                match tokio::spawn(async move { orchestrator.drive(&id, ticks).await }).await {
+                    Ok(Ok(())) => return,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) if e.is_panic() => "agent panicked".to_string(),
+                    Err(e) => e.to_string(),
+                };
+
+            if restarts == self.max_restarts {
+                tracing::error!(
+                    "Agent {} crashed ({}), giving up after {} restarts",
+                    spec.id,
+                    failure,
+                    restarts
+                );
+                self.registry.remove(&spec.id).await;
+                return;
+            }
+
+            restarts += 1;
+            tracing::warn!(
+                "Agent {} crashed ({}), restart {}/{}",
+                spec.id,
+                failure,
+                restarts,
+                self.max_restarts
+            );
+            tokio::time::sleep(self.restart_backoff * restarts).await;
+
+            self.registry.remove(&spec.id).await;
+            if let Err(e) = self.registry.create(&spec).await {
+                tracing::error!("Failed to restart agent {}: {}", spec.id, e);
+                return;
+            }
+            self.resume(&spec.id).await;
+        }
+    }
+}
+
+fn not_registered(id: &str) -> ManusError {
+    ManusError::Agent(format!("Agent {} not registered", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::checkpoint::Checkpoint;
+    use crate::agents::circuit_breaker::RiskControlSync;
+    use crate::agents::ml_agent::{MarketAnalyzerAgent, RebalancerAgent, RiskManagerAgent};
+    use crate::agents::rebalancer::{ExecutionEngine, Side};
+    use crate::agents::regime::Regime;
+    use crate::agents::valuation::InvariantSet;
+    use crate::agents::{Agent, AgentState, AutonomousAgent};
+    use crate::storage::Repository;
+    use crate::sui::{MockSuiNode, SuiClient, SuiKeypair};
+
+    fn config() -> AgentConfig {
+        AgentConfig {
+            enabled: true,
+            rebalance_interval: 1,
+            risk_tolerance: 0.5,
+            max_restarts: 3,
+            rebalancer_model: None,
+            invariants: InvariantSet::default(),
+            instances: Default::default(),
+        }
+    }
+
+    fn spec(id: &str, kind: AgentKind) -> AgentSpec {
+        AgentSpec {
+            id: id.to_string(),
+            kind,
+            initial_capital: 1_000,
+            risk_tolerance: 0.5,
+            max_drawdown: None,
+            model: None,
+            min_confidence: None,
+            volatility_threshold: None,
+            invariants: InvariantSet::default(),
+            vault_id: None,
+        }
+    }
+
+    fn market(volatility: f64) -> MarketData {
+        MarketData {
+            prices: vec![1.0, 1.05, 1.03, 1.07, 1.10],
+            volumes: vec![1000.0, 1200.0, 1100.0, 1300.0, 1400.0],
+            volatility,
+            liquidity: 5000.0,
+            book: None,
+        }
+    }
+
+    #[test]
+    fn test_market_window() {
+        let mut window = MarketWindow::new(3);
+        assert_eq!(window.push(1.0, 10.0, 500.0).volatility, 0.0);
+        window.push(1.1, 10.0, 500.0);
+        window.push(1.0, 10.0, 500.0);
+        let data = window.push(1.1, 10.0, 500.0);
+        assert_eq!(data.prices, vec![1.1, 1.0, 1.1]);
+        assert!(data.volatility > 0.09);
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_vetoes_rebalance() {
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Rebalancer,
+                Box::new(RebalancerAgent::new("rebalancer".to_string(), 1_000)),
+            )
+            .await
+            .unwrap();
+        registry
+            .register(
+                AgentKind::RiskManager,
+                Box::new(
+                    RiskManagerAgent::new("risk".to_string(), 1_000, 0.2).with_max_volatility(0.1),
+                ),
+            )
+            .await
+            .unwrap();
+        let orchestrator = Orchestrator::new(&config(), registry);
+
+        orchestrator.observe_all(&market(0.15)).await;
+        match orchestrator.step("rebalancer").await.unwrap() {
+            StepOutcome::Vetoed {
+                action, reviewer, ..
+            } => {
+                assert!(matches!(action, AgentAction::Rebalance { .. }));
+                assert_eq!(reviewer, "risk");
+            }
+            other => panic!("expected a veto, got {:?}", other),
+        }
+
+        // The risk manager's own decisions are not reviewed
+        assert!(matches!(
+            orchestrator.step("risk").await.unwrap(),
+            StepOutcome::Executed(AgentAction::Hold)
+        ));
+        assert!(orchestrator.step("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_halts_and_trips() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[13u8; 32]);
+        let risk_control_id = node.create_risk_control(&admin.address(), 2_000, 0);
+        node.mint_coin(&admin.address(), "0x2::sui::SUI", 1_000_000_000);
+        let client = Arc::new(SuiClient::with_transport(node.clone()).with_keypair(admin));
+        let (sync, breaker) = RiskControlSync::new(client, &risk_control_id);
+        sync.refresh().await.unwrap();
+
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Rebalancer,
+                Box::new(RebalancerAgent::new("rebalancer".to_string(), 1_000_000)),
+            )
+            .await
+            .unwrap();
+        let engine = ExecutionEngine::new().with_price("SUI", 1.0);
+        let risk = RiskManagerAgent::new("risk".to_string(), 1_000, 0.2).with_engine(engine);
+        registry
+            .register(AgentKind::RiskManager, Box::new(risk))
+            .await
+            .unwrap();
+        registry
+            .with_agent("risk", |agent| {
+                agent.execute(AgentAction::Rebalance {
+                    targets: vec![("SUI".to_string(), 0.8), ("USDC".to_string(), 0.2)],
+                })
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let orchestrator =
+            Orchestrator::new(&config(), registry).with_circuit_breaker(breaker.clone());
+        let mut breaches = orchestrator.subscribe_breaches();
+        let mut changes = breaker.subscribe();
+        let service = tokio::spawn(sync.run());
+
+        // A 30% fall in SUI breaches the risk manager's drawdown limit
+        let mut crash = market(0.05);
+        crash.prices = vec![1.0, 0.7];
+        orchestrator.observe_all(&crash).await;
+        tokio::time::timeout(Duration::from_secs(5), changes.wait_for(|s| s.active))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            orchestrator.step("rebalancer").await.unwrap(),
+            StepOutcome::Halted(AgentAction::Rebalance { .. })
+        ));
+        assert!(matches!(
+            orchestrator.step("risk").await.unwrap(),
+            StepOutcome::Executed(AgentAction::EmergencyWithdraw)
+        ));
+        // The drawdown was breached before the withdrawal, which realized it
+        let breach = breaches.try_recv().unwrap();
+        assert_eq!(breach.agent_id, "risk");
+        assert!(!breach.is_ok());
+
+        drop(changes);
+        drop(orchestrator);
+        drop(breaker);
+        service.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_risk_managers_apply_onchain_drawdown_limit() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[16u8; 32]);
+        let risk_control_id = node.create_risk_control(&admin.address(), 500, 0);
+        let client = Arc::new(SuiClient::with_transport(node).with_keypair(admin));
+        let (sync, breaker) = RiskControlSync::new(client, &risk_control_id);
+        sync.refresh().await.unwrap();
+
+        let registry = Arc::new(AgentRegistry::new());
+        let engine = ExecutionEngine::new().with_price("SUI", 1.0);
+        let risk = RiskManagerAgent::new("risk".to_string(), 1_000, 0.2).with_engine(engine);
+        registry
+            .register(AgentKind::RiskManager, Box::new(risk))
+            .await
+            .unwrap();
+        registry
+            .with_agent("risk", |agent| {
+                agent.execute(AgentAction::Rebalance {
+                    targets: vec![("SUI".to_string(), 0.8), ("USDC".to_string(), 0.2)],
+                })
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let orchestrator =
+            Orchestrator::new(&config(), registry.clone()).with_circuit_breaker(breaker);
+
+        // An 8% drawdown is within the configured 20%, but not the 5% set on chain
+        let mut fall = market(0.05);
+        fall.prices = vec![1.0, 0.9];
+        orchestrator.observe_all(&fall).await;
+        let trip = registry
+            .with_agent("risk", |agent| agent.circuit_breaker_trip())
+            .await
+            .unwrap();
+        assert!(trip.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_regime_transitions_reach_other_agents() {
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Rebalancer,
+                Box::new(RebalancerAgent::new("rebalancer".to_string(), 1_000_000)),
+            )
+            .await
+            .unwrap();
+        registry
+            .register(
+                AgentKind::MarketAnalyzer,
+                Box::new(MarketAnalyzerAgent::new("analyzer".to_string(), 0)),
+            )
+            .await
+            .unwrap();
+        let orchestrator = Orchestrator::new(&config(), registry);
+        let mut regimes = orchestrator.subscribe_regimes();
+
+        orchestrator.observe_all(&market(0.5)).await;
+        let transition = regimes.try_recv().unwrap();
+        assert_eq!(transition.to, Regime::Volatile);
+
+        // Calm again, but the regime has not switched back yet
+        orchestrator.observe_all(&market(0.05)).await;
+        assert!(regimes.try_recv().is_err());
+        match orchestrator.step("rebalancer").await.unwrap() {
+            StepOutcome::Executed(AgentAction::Rebalance { targets }) => {
+                assert_eq!(targets[0], ("SUI".to_string(), 0.3));
+            }
+            other => panic!("expected a defensive rebalance, got {:?}", other),
+        }
+    }
+
+    /// Agent that panics whenever it is asked to decide
+    struct PanickingAgent(AgentState);
+
+    impl Agent for PanickingAgent {
+        fn id(&self) -> &str {
+            &self.0.id
+        }
+
+        fn state(&self) -> &AgentState {
+            &self.0
+        }
+
+        fn decide(&mut self) -> Result<AgentAction> {
+            panic!("decision model crashed")
+        }
+
+        fn execute(&mut self, _action: AgentAction) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Agent whose capital changes by the next of its scripted amounts on every execution
+    struct ScriptedAgent {
+        state: AgentState,
+        changes: VecDeque<i64>,
+    }
+
+    impl Agent for ScriptedAgent {
+        fn id(&self) -> &str {
+            &self.state.id
+        }
+
+        fn state(&self) -> &AgentState {
+            &self.state
+        }
+
+        fn decide(&mut self) -> Result<AgentAction> {
+            Ok(AgentAction::AdjustRisk { new_tolerance: 0.5 })
+        }
+
+        fn execute(&mut self, _action: AgentAction) -> Result<()> {
+            let change = self.changes.pop_front().unwrap_or(0);
+            self.state.capital = self.state.capital.saturating_add_signed(change);
+            Ok(())
+        }
+
+        fn restore(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+            checkpoint.restore_state(&mut self.state)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fails_only_on_introduced_violations() {
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Autonomous,
+                Box::new(ScriptedAgent {
+                    // Already 30% down, past the 20% drawdown limit
+                    state: AgentState {
+                        id: "scripted".to_string(),
+                        capital: 700,
+                        initial_capital: 1_000,
+                        positions: vec![],
+                        risk_tolerance: 0.5,
+                    },
+                    changes: VecDeque::from([-300, 50]),
+                }),
+            )
+            .await
+            .unwrap();
+        let orchestrator = Orchestrator::new(&config(), registry.clone());
+        let mut breaches = orchestrator.subscribe_breaches();
+
+        // Deepening the drawdown to 60% fails and is rolled back
+        assert!(orchestrator.step("scripted").await.is_err());
+        assert_eq!(registry.get("scripted").await.unwrap().state.capital, 700);
+        assert!(breaches.try_recv().is_err());
+
+        // Reducing it runs, and the remaining breach is reported rather than failed
+        assert!(matches!(
+            orchestrator.step("scripted").await.unwrap(),
+            StepOutcome::Executed(AgentAction::AdjustRisk { .. })
+        ));
+        assert_eq!(registry.get("scripted").await.unwrap().state.capital, 750);
+        assert_eq!(breaches.try_recv().unwrap().agent_id, "scripted");
+    }
+
+    #[tokio::test]
+    async fn test_restarts_crashed_agents() {
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Autonomous,
+                Box::new(PanickingAgent(AgentState {
+                    id: "flaky".to_string(),
+                    capital: 7,
+                    initial_capital: 7,
+                    positions: vec![],
+                    risk_tolerance: 0.5,
+                })),
+            )
+            .await
+            .unwrap();
+
+        let spec = spec("flaky", AgentKind::Autonomous);
+        let orchestrator = Orchestrator::new(&config(), registry.clone())
+            .with_specs(vec![spec])
+            .with_restart_backoff(Duration::ZERO);
+
+        let (feed, market_rx) = watch::channel(market(0.1));
+        let run = tokio::spawn(orchestrator.run(market_rx));
+        drop(feed);
+        run.await.unwrap().unwrap();
+
+        let info = registry.get("flaky").await.unwrap();
+        assert_eq!(info.state.capital, 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_restarted_agents_resume_from_checkpoint() {
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let mut checkpoint =
+            Checkpoint::capture(&AutonomousAgent::new("flaky".to_string(), 1_000, 0.5));
+        checkpoint.state.capital = 1_200;
+        repository.save_checkpoint(&checkpoint).await.unwrap();
+
+        // The crashing agent cannot be restored, so it is held until rebuilt
+        let registry = Arc::new(AgentRegistry::new());
+        registry
+            .register(
+                AgentKind::Autonomous,
+                Box::new(PanickingAgent(AgentState {
+                    id: "flaky".to_string(),
+                    capital: 7,
+                    initial_capital: 7,
+                    positions: vec![],
+                    risk_tolerance: 0.5,
+                })),
+            )
+            .await
+            .unwrap();
+        let spec = spec("flaky", AgentKind::Autonomous);
+        let orchestrator = Orchestrator::new(&config(), registry.clone())
+            .with_specs(vec![spec])
+            .with_restart_backoff(Duration::ZERO)
+            .with_checkpointer(Arc::new(Checkpointer::new(repository)));
+
+        let (feed, market_rx) = watch::channel(market(0.1));
+        let run = tokio::spawn(orchestrator.run(market_rx));
+        drop(feed);
+        run.await.unwrap().unwrap();
+
+        assert_eq!(registry.get("flaky").await.unwrap().state.capital, 1_200);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let mut agent = AutonomousAgent::new("auto".to_string(), 1_000, 0.5);
+        let mut checkpoint = Checkpoint::capture(&agent);
+        checkpoint.state.capital = 1_200;
+        agent.restore(&checkpoint).unwrap();
+        repository.save_checkpoint(&checkpoint).await.unwrap();
+
+        let spec = spec("auto", AgentKind::Autonomous);
+        let registry = Arc::new(AgentRegistry::new());
+        let orchestrator = Orchestrator::new(&config(), registry.clone())
+            .with_specs(vec![spec])
+            .with_checkpointer(Arc::new(Checkpointer::new(repository.clone())));
+
+        let (feed, market_rx) = watch::channel(market(0.1));
+        let run = tokio::spawn(orchestrator.run(market_rx));
+        drop(feed);
+        run.await.unwrap().unwrap();
+
+        assert_eq!(registry.get("auto").await.unwrap().state.capital, 1_200);
+        let latest = repository.latest_checkpoint("auto").await.unwrap().unwrap();
+        assert_eq!(latest.value.state.capital, 1_200);
+    }
+
+    #[tokio::test]
+    async fn test_holds_diverged_agents_until_rebaselined() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[14u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), "0x2::sui::SUI", 1_000_000, "lp");
+        node.mint_coin(&keypair.address(), "0x2::sui::SUI", 1_000_000_000);
+        let sui = Arc::new(SuiClient::with_transport(node).with_keypair(keypair));
+        sui.deposit(&vault_id, 10_000).await.unwrap();
+
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let checkpointer = Arc::new(Checkpointer::new(repository).with_sui_client(sui.clone()));
+        let spec = AgentSpec {
+            initial_capital: 1_000_000,
+            vault_id: Some(vault_id.clone()),
+            ..spec("lp", AgentKind::RangeProvider)
+        };
+        let registry = Arc::new(AgentRegistry::new());
+        registry.create(&spec).await.unwrap();
+        checkpointer.checkpoint(&registry, "lp").await.unwrap();
+        let orchestrator =
+            Orchestrator::new(&config(), registry).with_checkpointer(checkpointer.clone());
+
+        // Funds moved while the agent was down
+        sui.deposit(&vault_id, 5_000).await.unwrap();
+        orchestrator.resume("lp").await;
+        assert!(orchestrator.is_held("lp").await);
+
+        checkpointer.rebaseline("lp", None).await.unwrap().unwrap();
+        assert!(!orchestrator.is_held("lp").await);
+    }
+
+    #[tokio::test]
+    async fn test_market_maker_trades_through_order_manager() {
+        let node = Arc::new(MockSuiNode::new());
+        let (base, quote) = ("0x2::sui::SUI", "0xdba3::usdc::USDC");
+        node.set_coin_decimals(quote, 6);
+        let owner = SuiKeypair::from_bytes(&[15u8; 32]);
+        let pool_id = node.create_pool(base, quote, 1_000, 100_000_000, 1_000_000_000);
+        node.set_pool_levels(
+            &pool_id,
+            vec![(1_990_000, 5_000_000_000)],
+            vec![(2_010_000, 5_000_000_000)],
+        );
+        let manager_id = node.create_balance_manager(&owner.address());
+        node.mint_coin(&owner.address(), base, 1_000_000_000);
+        let client = Arc::new(SuiClient::with_transport(node.clone()).with_keypair(owner));
+        let account = client.pool_account(&pool_id, &manager_id).await.unwrap();
+        let orders = Arc::new(OrderManager::new(client.clone(), account));
+        orders.start().await.unwrap();
+        let snapshot = client.get_level2_snapshot(&pool_id, 10).await.unwrap();
+        let (_books, books_rx) = watch::channel(OrderBook::from_snapshot(&snapshot).unwrap());
+
+        let registry = Arc::new(AgentRegistry::new());
+        let spec = AgentSpec {
+            initial_capital: 1_000_000,
+            ..spec("mm", AgentKind::MarketMaker)
+        };
+        registry.create(&spec).await.unwrap();
+        let orchestrator = Orchestrator::new(&config(), registry.clone())
+            .with_order_manager(orders.clone(), books_rx);
+
+        // Quotes against the book are placed on chain
+        let outcome = orchestrator.step("mm").await.unwrap();
+        assert!(matches!(
+            outcome,
+            StepOutcome::Executed(AgentAction::Quote { .. })
+        ));
+        let bid = orders
+            .open_orders()
+            .await
+            .into_iter()
+            .find(|order| order.side == Side::Buy)
+            .unwrap();
+
+        // Fills detected by the order manager reach the agent on its next step
+        node.fill_order(&pool_id, bid.order_id.unwrap(), 4_000_000_000);
+        orders.sync_events().await.unwrap();
+        orchestrator.step("mm").await.unwrap();
+        let state = registry.get("mm").await.unwrap().state;
+        assert!(state.capital < 1_000_000);
+        assert_eq!(state.positions[0].amount, 4);
+        assert!(orders.take_fills().await.is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+//! DeepBook order book model
+//!
+//! An [`OrderBook`] holds price levels in decimal units together with the
+//! pool's tick and lot sizes. Books are built from DeepBook [`L2Snapshot`]s,
+//! read from the Sui RPC or replayed from recorded JSON fixtures, and turned
+//! into [`MarketData`] for the agents through a [`MarketWindow`].
+
+use crate::agents::ml_agent::{MarketData, OrderBookTop};
+use crate::agents::orchestrator::MarketWindow;
+use crate::error::{ManusError, Result};
+use crate::sui::deepbook::L2Snapshot;
+use serde::{Deserialize, Serialize};
+
+/// Distance from the mid (0.02 = 2%) within which resting depth counts as liquidity
+pub const DEFAULT_DEPTH_DISTANCE: f64 = 0.02;
+
+/// Quantity resting at one price
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    /// Price in quote units per base unit
+    pub price: f64,
+    /// Quantity in base units
+    pub quantity: f64,
+}
+
+/// Bid and ask levels of a pool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBook {
+    bids: Vec<Level>,
+    asks: Vec<Level>,
+    tick_size: f64,
+    lot_size: f64,
+    timestamp_ms: u64,
+}
+
+impl OrderBook {
+    /// Build a book, sorting bids best (highest) first and asks best (lowest) first
+    ///
+    /// Fails on non-positive prices, quantities or sizes, and on crossed books.
+    pub fn new(
+        mut bids: Vec<Level>,
+        mut asks: Vec<Level>,
+        tick_size: f64,
+        lot_size: f64,
+    ) -> Result<Self> {
+        if !(tick_size > 0.0 && lot_size > 0.0) {
+            return Err(ManusError::Agent(format!(
+                "Tick size {} and lot size {} must be positive",
+                tick_size, lot_size
+            )));
+        }
+        if let Some(level) = bids
+            .iter()
+            .chain(&asks)
+            .find(|level| !(level.price > 0.0 && level.quantity > 0.0))
+        {
+            return Err(ManusError::Agent(format!(
+                "Invalid level {} @ {}",
+                level.quantity, level.price
+            )));
+        }
+
+        bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+        asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+        if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+            if bid.price >= ask.price {
+                return Err(ManusError::Agent(format!(
+                    "Crossed book: bid {} >= ask {}",
+                    bid.price, ask.price
+                )));
+            }
+        }
+
+        Ok(Self {
+            bids,
+            asks,
+            tick_size,
+            lot_size,
+            timestamp_ms: 0,
+        })
+    }
+
+    /// Build a book from a DeepBook snapshot in native units
+    ///
+    /// Fails if a price is off the tick grid or a quantity off the lot grid.
+    pub fn from_snapshot(snapshot: &L2Snapshot) -> Result<Self> {
+        let levels = |prices: &[u64], quantities: &[u64], side: &str| -> Result<Vec<Level>> {
+            if prices.len() != quantities.len() {
+                return Err(ManusError::Agent(format!(
+                    "Pool {} has {} {} prices but {} quantities",
+                    snapshot.pool_id,
+                    prices.len(),
+                    side,
+                    quantities.len()
+                )));
+            }
+            prices
+                .iter()
+                .zip(quantities)
+                .map(|(&price, &quantity)| {
+                    if price % snapshot.tick_size.max(1) != 0
+                        || quantity % snapshot.lot_size.max(1) != 0
+                    {
+                        return Err(ManusError::Agent(format!(
+                            "Pool {} {} level {} @ {} is off the tick or lot grid",
+                            snapshot.pool_id, side, quantity, price
+                        )));
+                    }
+                    Ok(Level {
+                        price: snapshot.price(price),
+                        quantity: snapshot.quantity(quantity),
+                    })
+                })
+                .collect()
+        };
+
+        let mut book = Self::new(
+            levels(&snapshot.bid_prices, &snapshot.bid_quantities, "bid")?,
+            levels(&snapshot.ask_prices, &snapshot.ask_quantities, "ask")?,
+            snapshot.price(snapshot.tick_size),
+            snapshot.quantity(snapshot.lot_size),
+        )?;
+        book.timestamp_ms = snapshot.timestamp_ms;
+        Ok(book)
+    }
+
+    /// Bids, best first
+    pub fn bids(&self) -> &[Level] {
+        &self.bids
+    }
+
+    /// Asks, best first
+    pub fn asks(&self) -> &[Level] {
+        &self.asks
+    }
+
+    /// Minimum price increment
+    pub fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// Minimum quantity increment
+    pub fn lot_size(&self) -> f64 {
+        self.lot_size
+    }
+
+    /// When the book was observed (ms since the Unix epoch, 0 if unknown)
+    pub fn timestamp_ms(&self) -> u64 {
+        self.timestamp_ms
+    }
+
+    /// Highest bid
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.first().copied()
+    }
+
+    /// Lowest ask
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.first().copied()
+    }
+
+    /// Midpoint between the best bid and ask
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / 2.0)
+    }
+
+    /// Best ask minus best bid
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Mid weighted towards the side with less quantity at the top
+    ///
+    /// A heavy bid pushes the microprice towards the ask, where the next
+    /// trade is more likely to happen.
+    pub fn microprice(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        let total = bid.quantity + ask.quantity;
+        Some((bid.price * ask.quantity + ask.price * bid.quantity) / total)
+    }
+
+    /// Bid and ask quantity resting within `distance` (0.01 = 1%) of the mid
+    pub fn depth_at_distance(&self, distance: f64) -> (f64, f64) {
+        let Some(mid) = self.mid() else {
+            return (0.0, 0.0);
+        };
+        let bid_floor = mid * (1.0 - distance);
+        let ask_ceiling = mid * (1.0 + distance);
+        let bid_depth = self
+            .bids
+            .iter()
+            .take_while(|level| level.price >= bid_floor)
+            .map(|level| level.quantity)
+            .sum();
+        let ask_depth = self
+            .asks
+            .iter()
+            .take_while(|level| level.price <= ask_ceiling)
+            .map(|level| level.quantity)
+            .sum();
+        (bid_depth, ask_depth)
+    }
+
+    /// Bid depth minus ask depth over total depth within `distance` of the mid (-1.0 - 1.0)
+    pub fn imbalance(&self, distance: f64) -> f64 {
+        let (bid_depth, ask_depth) = self.depth_at_distance(distance);
+        let total = bid_depth + ask_depth;
+        if total > 0.0 {
+            (bid_depth - ask_depth) / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Quote value resting within `distance` of the mid on both sides
+    pub fn liquidity(&self, distance: f64) -> f64 {
+        let Some(mid) = self.mid() else {
+            return 0.0;
+        };
+        let (lower, upper) = (mid * (1.0 - distance), mid * (1.0 + distance));
+        self.bids
+            .iter()
+            .chain(&self.asks)
+            .filter(|level| (lower..=upper).contains(&level.price))
+            .map(|level| level.price * level.quantity)
+            .sum()
+    }
+
+    /// Round a price down to the tick grid
+    pub fn floor_price(&self, price: f64) -> f64 {
+        (price / self.tick_size + 1e-9).floor() * self.tick_size
+    }
+
+    /// Round a price up to the tick grid
+    pub fn ceil_price(&self, price: f64) -> f64 {
+        (price / self.tick_size - 1e-9).ceil() * self.tick_size
+    }
+
+    /// Round a quantity down to the lot grid
+    pub fn floor_quantity(&self, quantity: f64) -> f64 {
+        (quantity / self.lot_size + 1e-9).floor() * self.lot_size
+    }
+
+    /// Best bid and ask with the quantity resting at each
+    pub fn top(&self) -> Option<OrderBookTop> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some(OrderBookTop {
+            bid: bid.price,
+            ask: ask.price,
+            bid_depth: bid.quantity,
+            ask_depth: ask.quantity,
+        })
+    }
+}
+
+impl MarketWindow {
+    /// Record the mid of `book` as a tick and return the resulting market snapshot
+    ///
+    /// Books carry no trades, so the tick's volume is the base quantity and
+    /// its liquidity the quote value resting within [`DEFAULT_DEPTH_DISTANCE`]
+    /// of the mid. Fails on a book missing either side.
+    pub fn push_book(&mut self, book: &OrderBook) -> Result<MarketData> {
+        let mid = book
+            .mid()
+            .ok_or_else(|| ManusError::Agent("Order book has no mid price".to_string()))?;
+        let (bid_depth, ask_depth) = book.depth_at_distance(DEFAULT_DEPTH_DISTANCE);
+        Ok(self.push_with_book(
+            mid,
+            bid_depth + ask_depth,
+            book.liquidity(DEFAULT_DEPTH_DISTANCE),
+            book.top(),
+        ))
+    }
+}
+
+/// Market snapshots of the last `window` books of a recorded snapshot series
+pub fn market_series(snapshots: &[L2Snapshot], window: usize) -> Result<Vec<MarketData>> {
+    let mut window = MarketWindow::new(window);
+    snapshots
+        .iter()
+        .map(|snapshot| window.push_book(&OrderBook::from_snapshot(snapshot)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/deepbook_sui_usdc_l2.json");
+
+    fn level(price: f64, quantity: f64) -> Level {
+        Level { price, quantity }
+    }
+
+    #[test]
+    fn test_book_metrics() {
+        let book = OrderBook::new(
+            vec![level(0.99, 100.0), level(1.00, 300.0), level(0.95, 50.0)],
+            vec![level(1.02, 100.0), level(1.10, 500.0)],
+            0.01,
+            1.0,
+        )
+        .unwrap();
+
+        assert_eq!(book.best_bid().unwrap().price, 1.00);
+        assert!((book.mid().unwrap() - 1.01).abs() < 1e-12);
+        assert!((book.spread().unwrap() - 0.02).abs() < 1e-12);
+        // Heavy bid: microprice leans towards the ask
+        assert!((book.microprice().unwrap() - 1.015).abs() < 1e-12);
+
+        let (bid_depth, ask_depth) = book.depth_at_distance(0.02);
+        assert_eq!((bid_depth, ask_depth), (400.0, 100.0));
+        assert!((book.imbalance(0.02) - 0.6).abs() < 1e-12);
+        assert!((book.liquidity(0.02) - (300.0 + 99.0 + 102.0)).abs() < 1e-9);
+
+        assert!((book.floor_price(1.0149) - 1.01).abs() < 1e-12);
+        assert!((book.ceil_price(1.0101) - 1.02).abs() < 1e-12);
+        assert!((book.ceil_price(1.01) - 1.01).abs() < 1e-12);
+        assert_eq!(book.floor_quantity(2.7), 2.0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_books() {
+        assert!(OrderBook::new(vec![level(1.0, 1.0)], vec![level(1.0, 1.0)], 0.01, 1.0).is_err());
+        assert!(OrderBook::new(vec![level(1.0, 0.0)], vec![], 0.01, 1.0).is_err());
+        assert!(OrderBook::new(vec![], vec![], 0.0, 1.0).is_err());
+
+        let one_sided = OrderBook::new(vec![level(1.0, 1.0)], vec![], 0.01, 1.0).unwrap();
+        assert!(one_sided.mid().is_none());
+        assert_eq!(one_sided.depth_at_distance(0.1), (0.0, 0.0));
+        assert!(MarketWindow::new(4).push_book(&one_sided).is_err());
+    }
+
+    #[test]
+    fn test_fixture_series() {
+        let snapshots = L2Snapshot::parse(FIXTURE).unwrap();
+        let book = OrderBook::from_snapshot(&snapshots[0]).unwrap();
+        assert!((book.tick_size() - 0.001).abs() < 1e-12);
+        assert!((book.lot_size() - 0.1).abs() < 1e-12);
+        assert!((book.best_bid().unwrap().price - 3.512).abs() < 1e-9);
+        assert!((book.best_ask().unwrap().price - 3.514).abs() < 1e-9);
+        assert_eq!(book.timestamp_ms(), snapshots[0].timestamp_ms);
+
+        let series = market_series(&snapshots, 8).unwrap();
+        assert_eq!(series.len(), snapshots.len());
+        let last = series.last().unwrap();
+        assert_eq!(last.prices.len(), snapshots.len());
+        assert!(last.liquidity > 0.0);
+        assert!(last.book.is_some());
+
+        let mut off_grid = snapshots[0].clone();
+        off_grid.bid_prices[0] += 1;
+        assert!(OrderBook::from_snapshot(&off_grid).is_err());
+    }
+}
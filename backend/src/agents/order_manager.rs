@@ -0,0 +1,755 @@
+//! Order lifecycle tracking
+//!
+//! An [`OrderManager`] submits [`OrderIntent`]s for one DeepBook
+//! [`PoolAccount`] and follows each order by its client order ID from pending
+//! through open and partially filled to filled or cancelled. Changes are
+//! taken from the order events DeepBook emits: those in transaction receipts,
+//! and those read by polling the `pool` module's event stream, which is where
+//! fills are detected. Events can be missed, so the manager also reconciles
+//! its state against the orders resting on chain at startup and periodically.
+//!
+//! Detected fills are kept for [`OrderManager::take_fills`] up to
+//! [`MAX_PENDING_FILLS`], beyond which the oldest are dropped.
+
+use crate::agents::market_maker::{order_transaction, OrderIntent, Quote, RestingOrder};
+use crate::agents::rebalancer::Side;
+use crate::error::{ManusError, Result};
+use crate::sui::deepbook::{EventCursor, Order, OrderEvent, OrderEventKind, PoolAccount};
+use crate::sui::{OrderReceipt, SuiClient};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+/// Default delay between polls of the order event stream
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default delay between reconciliations against on-chain orders
+pub const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Events requested per page of the event stream
+const EVENT_PAGE_SIZE: usize = 50;
+
+/// Fills kept until taken
+pub const MAX_PENDING_FILLS: usize = 1_024;
+
+/// Where an order is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    /// Submitted, not yet seen on chain
+    Pending,
+    /// Resting with nothing filled
+    Open,
+    /// Resting with part of its quantity filled
+    PartiallyFilled,
+    /// Completely filled
+    Filled,
+    /// Cancelled, or gone from the book without a fill being seen
+    Cancelled,
+}
+
+impl OrderStatus {
+    /// Whether the order is, or may soon be, resting on the book
+    pub fn is_open(self) -> bool {
+        !matches!(self, OrderStatus::Filled | OrderStatus::Cancelled)
+    }
+}
+
+/// Order tracked by an [`OrderManager`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManagedOrder {
+    /// ID assigned by the client placing the order
+    pub client_order_id: u64,
+    /// On-chain order ID, once the order has been placed
+    #[serde(with = "decimal::option")]
+    pub order_id: Option<u128>,
+    /// Buy for bids, sell for asks
+    pub side: Side,
+    /// Limit price
+    pub price: f64,
+    /// Total quantity, including anything filled
+    pub quantity: f64,
+    /// Quantity filled so far
+    pub filled_quantity: f64,
+    /// Lifecycle state
+    pub status: OrderStatus,
+    /// When the order last changed on chain (ms since the Unix epoch)
+    pub updated_at_ms: u64,
+}
+
+impl ManagedOrder {
+    /// Quantity still resting on the book
+    pub fn remaining(&self) -> f64 {
+        (self.quantity - self.filled_quantity).max(0.0)
+    }
+
+    fn from_chain(account: &PoolAccount, order: &Order) -> Self {
+        let mut managed = Self {
+            client_order_id: order.client_order_id,
+            order_id: Some(order.order_id),
+            side: side(order.is_bid()),
+            price: account.price(order.price()),
+            quantity: account.quantity(order.quantity),
+            filled_quantity: account.quantity(order.filled_quantity),
+            status: OrderStatus::Open,
+            updated_at_ms: 0,
+        };
+        managed.update_fill_status();
+        managed
+    }
+
+    /// Whether two views of an order agree up to float rounding
+    fn agrees_with(&self, other: &ManagedOrder) -> bool {
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0);
+        self.order_id == other.order_id
+            && self.status == other.status
+            && close(self.quantity, other.quantity)
+            && close(self.filled_quantity, other.filled_quantity)
+    }
+
+    fn update_fill_status(&mut self) {
+        self.status = if self.remaining() <= 1e-9 * self.quantity.max(1.0) {
+            OrderStatus::Filled
+        } else if self.filled_quantity > 0.0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Open
+        };
+    }
+}
+
+/// Fill of a managed order detected on chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFill {
+    /// Order filled
+    pub client_order_id: u64,
+    /// On-chain order ID
+    #[serde(with = "decimal")]
+    pub order_id: u128,
+    /// Side of the filled order
+    pub side: Side,
+    /// Execution price
+    pub price: f64,
+    /// Quantity filled
+    pub quantity: f64,
+    /// When the fill happened (ms since the Unix epoch)
+    pub timestamp_ms: u64,
+}
+
+/// Differences found and fixed by [`OrderManager::reconcile`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    /// Orders resting on chain that were not tracked
+    pub adopted: Vec<u64>,
+    /// Tracked open orders no longer resting on chain, now cancelled
+    pub closed: Vec<u64>,
+    /// Tracked orders whose on-chain ID, quantity or fills were out of date
+    pub corrected: Vec<u64>,
+}
+
+impl ReconcileReport {
+    /// Whether local state already matched the chain
+    pub fn is_clean(&self) -> bool {
+        self.adopted.is_empty() && self.closed.is_empty() && self.corrected.is_empty()
+    }
+}
+
+/// Orders keyed by client order ID, and the event stream position
+#[derive(Default)]
+struct Ledger {
+    orders: BTreeMap<u64, ManagedOrder>,
+    cursor: Option<EventCursor>,
+    fills: VecDeque<OrderFill>,
+}
+
+impl Ledger {
+    /// Keep a fill until taken, dropping the oldest beyond [`MAX_PENDING_FILLS`]
+    fn push_fill(fills: &mut VecDeque<OrderFill>, fill: OrderFill) {
+        if fills.len() == MAX_PENDING_FILLS {
+            if let Some(dropped) = fills.pop_front() {
+                tracing::warn!(
+                    "Dropping untaken fill of order {}, more than {} are pending",
+                    dropped.client_order_id,
+                    MAX_PENDING_FILLS
+                );
+            }
+        }
+        fills.push_back(fill);
+    }
+
+    /// Apply an event about one of the account's orders
+    ///
+    /// Re-applying placements, modifications and cancellations is harmless;
+    /// fills accumulate, so each fill event must be applied once.
+    fn apply(&mut self, account: &PoolAccount, event: &OrderEvent) {
+        let client_order_id = event.client_order_id;
+        let tracked = self
+            .orders
+            .get_mut(&client_order_id)
+            .filter(|order| order.order_id.is_none_or(|id| id == event.order_id));
+
+        let order = match (event.kind, tracked) {
+            (
+                OrderEventKind::Placed {
+                    is_bid,
+                    price,
+                    quantity,
+                },
+                None,
+            ) => self
+                .orders
+                .entry(client_order_id)
+                .insert_entry(ManagedOrder {
+                    client_order_id,
+                    order_id: Some(event.order_id),
+                    side: side(is_bid),
+                    price: account.price(price),
+                    quantity: account.quantity(quantity),
+                    filled_quantity: 0.0,
+                    status: OrderStatus::Open,
+                    updated_at_ms: event.timestamp_ms,
+                })
+                .into_mut(),
+            (OrderEventKind::Placed { .. }, Some(order)) => {
+                order.order_id = Some(event.order_id);
+                if order.status == OrderStatus::Pending {
+                    order.status = OrderStatus::Open;
+                }
+                order
+            }
+            (
+                OrderEventKind::Filled {
+                    price,
+                    base_quantity,
+                },
+                Some(order),
+            ) => {
+                let fill = OrderFill {
+                    client_order_id,
+                    order_id: event.order_id,
+                    side: order.side,
+                    price: account.price(price),
+                    quantity: account.quantity(base_quantity),
+                    timestamp_ms: event.timestamp_ms,
+                };
+                tracing::info!(
+                    "Order {} filled {} at {}",
+                    client_order_id,
+                    fill.quantity,
+                    fill.price
+                );
+                order.filled_quantity += fill.quantity;
+                order.update_fill_status();
+                Self::push_fill(&mut self.fills, fill);
+                order
+            }
+            (OrderEventKind::Modified { quantity }, Some(order)) => {
+                order.quantity = account.quantity(quantity);
+                if order.status.is_open() {
+                    order.update_fill_status();
+                }
+                order
+            }
+            (OrderEventKind::Canceled, Some(order)) => {
+                order.status = OrderStatus::Cancelled;
+                order
+            }
+            // Orders from before tracking started are adopted by reconciliation
+            (_, None) => return,
+        };
+        order.updated_at_ms = order.updated_at_ms.max(event.timestamp_ms);
+    }
+}
+
+/// Submits and tracks the orders of one [`PoolAccount`]
+pub struct OrderManager {
+    client: Arc<SuiClient>,
+    account: PoolAccount,
+    ledger: RwLock<Ledger>,
+    sync: Mutex<()>,
+}
+
+impl OrderManager {
+    /// Track orders placed through `account`, starting from an empty ledger
+    pub fn new(client: Arc<SuiClient>, account: PoolAccount) -> Self {
+        Self {
+            client,
+            account,
+            ledger: RwLock::new(Ledger::default()),
+            sync: Mutex::new(()),
+        }
+    }
+
+    /// Pool and balance manager orders are placed through
+    pub fn account(&self) -> &PoolAccount {
+        &self.account
+    }
+
+    /// Skip events emitted so far, then reconcile with the orders resting on chain
+    pub async fn start(&self) -> Result<ReconcileReport> {
+        let latest = self
+            .client
+            .latest_order_event_cursor(&self.account.package)
+            .await?;
+        let mut ledger = self.ledger.write().await;
+        if ledger.cursor.is_none() {
+            ledger.cursor = latest;
+        }
+        drop(ledger);
+        self.reconcile().await
+    }
+
+    /// Sign and execute `intents`, tracking placed orders from submission
+    ///
+    /// Placed orders are pending until their `OrderPlaced` event is applied
+    /// from the receipt. Fills are left for [`OrderManager::sync_events`].
+    pub async fn submit(&self, intents: &[OrderIntent]) -> Result<OrderReceipt> {
+        let placed: Vec<u64> = intents
+            .iter()
+            .filter(|intent| matches!(intent, OrderIntent::Place { .. }))
+            .map(OrderIntent::client_order_id)
+            .collect();
+
+        let transaction = {
+            let mut ledger = self.ledger.write().await;
+            if let Some(id) = placed.iter().find(|id| {
+                ledger
+                    .orders
+                    .get(id)
+                    .is_some_and(|order| order.status.is_open())
+            }) {
+                return Err(ManusError::Agent(format!(
+                    "Client order {} is already open",
+                    id
+                )));
+            }
+
+            let transaction = order_transaction(&self.account, intents, |id| {
+                let order = ledger.orders.get(&id).filter(|o| o.status.is_open())?;
+                Some((order.order_id?, order.filled_quantity))
+            })?;
+            for intent in intents {
+                if let OrderIntent::Place {
+                    client_order_id,
+                    side,
+                    price,
+                    quantity,
+                } = *intent
+                {
+                    ledger.orders.insert(
+                        client_order_id,
+                        ManagedOrder {
+                            client_order_id,
+                            order_id: None,
+                            side,
+                            price,
+                            quantity,
+                            filled_quantity: 0.0,
+                            status: OrderStatus::Pending,
+                            updated_at_ms: 0,
+                        },
+                    );
+                }
+            }
+            transaction
+        };
+
+        let result = self.client.execute_orders(transaction).await;
+        let mut ledger = self.ledger.write().await;
+        match result {
+            Ok(receipt) => {
+                for event in &receipt.events {
+                    let is_fill = matches!(event.kind, OrderEventKind::Filled { .. });
+                    if self.account.owns(event) && !is_fill {
+                        ledger.apply(&self.account, event);
+                    }
+                }
+                Ok(receipt)
+            }
+            Err(e) => {
+                ledger.orders.retain(|id, order| {
+                    !(placed.contains(id) && order.status == OrderStatus::Pending)
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Apply events emitted since the last sync, returning how many concerned the account
+    pub async fn sync_events(&self) -> Result<usize> {
+        let _sync = self.sync.lock().await;
+        let mut applied = 0;
+        loop {
+            let cursor = self.ledger.read().await.cursor.clone();
+            let page = self
+                .client
+                .query_order_events(&self.account.package, cursor.as_ref(), EVENT_PAGE_SIZE)
+                .await?;
+
+            let mut ledger = self.ledger.write().await;
+            for event in page.events.iter().filter(|event| self.account.owns(event)) {
+                ledger.apply(&self.account, event);
+                applied += 1;
+            }
+            if page.next_cursor.is_some() {
+                ledger.cursor = page.next_cursor;
+            }
+            if !page.has_next_page {
+                return Ok(applied);
+            }
+        }
+    }
+
+    /// Catch up on events, then align local state with the orders resting on chain
+    ///
+    /// Untracked resting orders are adopted, tracked orders are corrected to
+    /// their on-chain quantities, and open orders missing from the book are
+    /// closed as cancelled; fills on them that were never reported are lost.
+    pub async fn reconcile(&self) -> Result<ReconcileReport> {
+        self.sync_events().await?;
+
+        // Hold the ledger while reading so submissions cannot interleave
+        let mut ledger = self.ledger.write().await;
+        let on_chain = self.client.account_orders(&self.account).await?;
+        let mut report = ReconcileReport::default();
+
+        for order in &on_chain {
+            let actual = ManagedOrder::from_chain(&self.account, order);
+            match ledger.orders.get_mut(&order.client_order_id) {
+                Some(local)
+                    if local.status.is_open()
+                        && local.order_id.is_none_or(|id| id == order.order_id) =>
+                {
+                    if !local.agrees_with(&actual) {
+                        *local = ManagedOrder {
+                            updated_at_ms: local.updated_at_ms,
+                            ..actual
+                        };
+                        report.corrected.push(order.client_order_id);
+                    }
+                }
+                _ => {
+                    ledger.orders.insert(order.client_order_id, actual);
+                    report.adopted.push(order.client_order_id);
+                }
+            }
+        }
+
+        for local in ledger.orders.values_mut() {
+            let missing = local
+                .order_id
+                .is_some_and(|id| !on_chain.iter().any(|order| order.order_id == id));
+            if local.status.is_open() && missing {
+                local.status = OrderStatus::Cancelled;
+                report.closed.push(local.client_order_id);
+            }
+        }
+
+        if !report.is_clean() {
+            tracing::warn!(
+                "Reconciled orders on pool {}: {} adopted, {} closed, {} corrected",
+                self.account.pool.id(),
+                report.adopted.len(),
+                report.closed.len(),
+                report.corrected.len()
+            );
+        }
+        Ok(report)
+    }
+
+    /// Every tracked order, by client order ID
+    pub async fn orders(&self) -> Vec<ManagedOrder> {
+        self.ledger.read().await.orders.values().cloned().collect()
+    }
+
+    /// Tracked order with `client_order_id`
+    pub async fn get(&self, client_order_id: u64) -> Option<ManagedOrder> {
+        self.ledger
+            .read()
+            .await
+            .orders
+            .get(&client_order_id)
+            .cloned()
+    }
+
+    /// Pending, open and partially filled orders
+    pub async fn open_orders(&self) -> Vec<ManagedOrder> {
+        self.ledger
+            .read()
+            .await
+            .orders
+            .values()
+            .filter(|order| order.status.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Open orders with their remaining quantity, as a market maker tracks them
+    pub async fn resting_orders(&self) -> Vec<RestingOrder> {
+        self.open_orders()
+            .await
+            .into_iter()
+            .map(|order| RestingOrder {
+                client_order_id: order.client_order_id,
+                quote: Quote {
+                    side: order.side,
+                    price: order.price,
+                    quantity: order.remaining(),
+                },
+            })
+            .collect()
+    }
+
+    /// Fills detected since the last call, oldest first
+    ///
+    /// At most [`MAX_PENDING_FILLS`] are kept between calls.
+    pub async fn take_fills(&self) -> Vec<OrderFill> {
+        self.ledger.write().await.fills.drain(..).collect()
+    }
+
+    /// Poll events and reconcile on their intervals, forever
+    pub async fn run(self: Arc<Self>, poll_interval: Duration, reconcile_interval: Duration) {
+        let mut poll = tokio::time::interval(poll_interval);
+        let mut reconcile = tokio::time::interval(reconcile_interval);
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    if let Err(e) = self.sync_events().await {
+                        tracing::warn!("Failed to sync order events: {}", e);
+                    }
+                }
+                _ = reconcile.tick() => {
+                    if let Err(e) = self.reconcile().await {
+                        tracing::warn!("Failed to reconcile orders: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Order IDs as decimal strings, as Move renders `u128` in JSON
+mod decimal {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            id: &Option<u128>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match id {
+                Some(id) => super::serialize(id, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<u128>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|id| id.parse().map_err(D::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+fn side(is_bid: bool) -> Side {
+    if is_bid {
+        Side::Buy
+    } else {
+        Side::Sell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sui::{MockSuiNode, SuiKeypair};
+
+    const SUI: &str = "0x2::sui::SUI";
+    const USDC: &str = "0xdba3::usdc::USDC";
+
+    struct Setup {
+        node: Arc<MockSuiNode>,
+        client: Arc<SuiClient>,
+        pool_id: String,
+        manager_id: String,
+    }
+
+    impl Setup {
+        fn new() -> Self {
+            let node = Arc::new(MockSuiNode::new());
+            node.set_coin_decimals(USDC, 6);
+            let owner = SuiKeypair::from_bytes(&[15u8; 32]);
+            let pool_id = node.create_pool(SUI, USDC, 1_000, 100_000_000, 1_000_000_000);
+            node.set_pool_levels(
+                &pool_id,
+                vec![(1_990_000, 5_000_000_000)],
+                vec![(2_010_000, 5_000_000_000)],
+            );
+            let manager_id = node.create_balance_manager(&owner.address());
+            node.mint_coin(&owner.address(), SUI, 1_000_000_000);
+            let client = Arc::new(SuiClient::with_transport(node.clone()).with_keypair(owner));
+            Self {
+                node,
+                client,
+                pool_id,
+                manager_id,
+            }
+        }
+
+        async fn manager(&self) -> OrderManager {
+            let account = self
+                .client
+                .pool_account(&self.pool_id, &self.manager_id)
+                .await
+                .unwrap();
+            OrderManager::new(self.client.clone(), account)
+        }
+    }
+
+    fn place(client_order_id: u64, side: Side, price: f64) -> OrderIntent {
+        OrderIntent::Place {
+            client_order_id,
+            side,
+            price,
+            quantity: 10.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_from_events() {
+        let setup = Setup::new();
+        let manager = setup.manager().await;
+        assert!(manager.start().await.unwrap().is_clean());
+
+        manager
+            .submit(&[place(1, Side::Buy, 2.0), place(2, Side::Sell, 2.005)])
+            .await
+            .unwrap();
+        let bid = manager.get(1).await.unwrap();
+        assert_eq!(bid.status, OrderStatus::Open);
+        assert!(manager.submit(&[place(1, Side::Buy, 1.995)]).await.is_err());
+
+        setup
+            .node
+            .fill_order(&setup.pool_id, bid.order_id.unwrap(), 4_000_000_000);
+        // The stream was empty at startup, so the placements are replayed too
+        assert_eq!(manager.sync_events().await.unwrap(), 3);
+        let bid = manager.get(1).await.unwrap();
+        assert_eq!(bid.status, OrderStatus::PartiallyFilled);
+        assert!((bid.remaining() - 6.0).abs() < 1e-9);
+        let fills = manager.take_fills().await;
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].side, fills[0].price), (Side::Buy, 2.0));
+        assert!(manager.take_fills().await.is_empty());
+
+        // Modifies set the remaining quantity; DeepBook adds back what was filled
+        manager
+            .submit(&[OrderIntent::Modify {
+                client_order_id: 1,
+                quantity: 3.0,
+            }])
+            .await
+            .unwrap();
+        let bid = manager.get(1).await.unwrap();
+        assert!((bid.quantity - 7.0).abs() < 1e-9);
+        assert!((bid.remaining() - 3.0).abs() < 1e-9);
+
+        manager
+            .submit(&[OrderIntent::Cancel { client_order_id: 2 }])
+            .await
+            .unwrap();
+        assert_eq!(manager.get(2).await.unwrap().status, OrderStatus::Cancelled);
+
+        // Events already applied from receipts are harmless when polled
+        manager.sync_events().await.unwrap();
+        assert!(manager.take_fills().await.is_empty());
+        let resting = manager.resting_orders().await;
+        assert_eq!(resting.len(), 1);
+        assert!((resting[0].quote.quantity - 3.0).abs() < 1e-9);
+        assert!(manager.reconcile().await.unwrap().is_clean());
+
+        // A filled order cannot be modified
+        setup
+            .node
+            .fill_order(&setup.pool_id, bid.order_id.unwrap(), 3_000_000_000);
+        manager.sync_events().await.unwrap();
+        assert_eq!(manager.get(1).await.unwrap().status, OrderStatus::Filled);
+        let modify = OrderIntent::Modify {
+            client_order_id: 1,
+            quantity: 1.0,
+        };
+        assert!(manager.submit(&[modify]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_adopts_and_closes() {
+        let setup = Setup::new();
+        let first = setup.manager().await;
+        first.start().await.unwrap();
+        first
+            .submit(&[place(1, Side::Buy, 2.0), place(2, Side::Sell, 2.005)])
+            .await
+            .unwrap();
+
+        // A restarted manager adopts what is resting without replaying history
+        let restarted = setup.manager().await;
+        let report = restarted.start().await.unwrap();
+        assert_eq!(report.adopted, vec![1, 2]);
+        assert_eq!(restarted.open_orders().await.len(), 2);
+        assert!(restarted.take_fills().await.is_empty());
+
+        // An order leaves the book without any event being seen
+        let ask = restarted.get(2).await.unwrap();
+        setup.node.drop_order(&setup.pool_id, ask.order_id.unwrap());
+        let report = restarted.reconcile().await.unwrap();
+        assert_eq!(report.closed, vec![2]);
+        assert_eq!(
+            restarted.get(2).await.unwrap().status,
+            OrderStatus::Cancelled
+        );
+        assert_eq!(restarted.open_orders().await.len(), 1);
+
+        // Failed submissions leave nothing pending
+        assert!(restarted
+            .submit(&[place(3, Side::Buy, 2.01)])
+            .await
+            .is_err());
+        assert!(restarted.get(3).await.is_none());
+    }
+
+    #[test]
+    fn test_pending_fills_are_bounded() {
+        let mut ledger = Ledger::default();
+        for client_order_id in 0..=MAX_PENDING_FILLS as u64 {
+            Ledger::push_fill(
+                &mut ledger.fills,
+                OrderFill {
+                    client_order_id,
+                    order_id: client_order_id.into(),
+                    side: Side::Buy,
+                    price: 2.0,
+                    quantity: 1.0,
+                    timestamp_ms: client_order_id,
+                },
+            );
+        }
+        assert_eq!(ledger.fills.len(), MAX_PENDING_FILLS);
+        assert_eq!(ledger.fills[0].client_order_id, 1);
+    }
+}
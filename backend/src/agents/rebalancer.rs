@@ -1,4 +1,539 @@
 //! Portfolio rebalancing logic
+//!
+//! [`ExecutionEngine`] turns agent actions into fills against marked prices
+//! and applies them to an [`AgentState`]. Every execution is staged on a copy
+//! of the state and only committed if it does not breach any of the engine's
+//! invariants, checked with positions marked to market.
 
-// TODO: Implement rebalancing algorithms
+use crate::agents::concentrated::validate_ranges;
+use crate::agents::market_maker::OrderIntent;
+use crate::agents::ml_agent::MarketData;
+use crate::agents::valuation::{InvariantReport, InvariantSet, PriceOracle};
+use crate::agents::{AgentAction, AgentState, Position};
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
 
+/// Asset priced by the shared market feed
+pub const BASE_ASSET: &str = "SUI";
+
+/// Asset agent capital is denominated in
+pub const QUOTE_ASSET: &str = "USDC";
+
+/// Default trading fee (basis points of notional)
+pub const DEFAULT_FEE_BPS: u32 = 10;
+
+/// Default slippage against the marked price (basis points)
+pub const DEFAULT_SLIPPAGE_BPS: u32 = 5;
+
+const BPS: f64 = 10_000.0;
+
+/// Order side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    /// Buy the asset with capital
+    Buy,
+    /// Sell the asset for capital
+    Sell,
+}
+
+/// Executed order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fill {
+    /// Asset traded
+    pub asset: String,
+    /// Order side
+    pub side: Side,
+    /// Units traded
+    pub amount: u64,
+    /// Execution price after slippage
+    pub price: f64,
+    /// Fee paid (in capital units)
+    pub fee: u64,
+}
+
+/// Executes agent actions with fees and slippage
+#[derive(Debug, Clone)]
+pub struct ExecutionEngine {
+    fee_bps: u32,
+    slippage_bps: u32,
+    prices: HashMap<String, f64>,
+    invariants: InvariantSet,
+}
+
+impl Default for ExecutionEngine {
+    fn default() -> Self {
+        Self {
+            fee_bps: DEFAULT_FEE_BPS,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            prices: HashMap::new(),
+            invariants: InvariantSet::default(),
+        }
+    }
+}
+
+impl ExecutionEngine {
+    /// Create an engine with default fees and no marked prices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the trading fee in basis points
+    pub fn with_fee_bps(mut self, fee_bps: u32) -> Self {
+        self.fee_bps = fee_bps;
+        self
+    }
+
+    /// Set the slippage in basis points
+    pub fn with_slippage_bps(mut self, slippage_bps: u32) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+
+    /// Enforce `invariants` on every execution
+    pub fn with_invariants(mut self, invariants: InvariantSet) -> Self {
+        self.invariants = invariants;
+        self
+    }
+
+    /// Invariants enforced on every execution
+    pub fn invariants(&self) -> &InvariantSet {
+        &self.invariants
+    }
+
+    /// Check the engine's invariants with `state` marked at current prices
+    pub fn check(&self, state: &AgentState) -> InvariantReport {
+        self.invariants.check(state, self)
+    }
+
+    /// Mark `asset` at `price`
+    pub fn with_price(mut self, asset: &str, price: f64) -> Self {
+        self.set_price(asset, price);
+        self
+    }
+
+    /// Mark `asset` at `price` (capital units per unit of asset)
+    pub fn set_price(&mut self, asset: &str, price: f64) {
+        self.prices.insert(asset.to_string(), price);
+    }
+
+    /// Marked price of `asset`; the quote asset is always worth 1
+    pub fn price(&self, asset: &str) -> Option<f64> {
+        if asset == QUOTE_ASSET {
+            return Some(1.0);
+        }
+        self.prices.get(asset).copied()
+    }
+
+    /// Mark [`BASE_ASSET`] at the latest price in `market_data`
+    pub fn observe(&mut self, market_data: &MarketData) {
+        if let Some(&price) = market_data.prices.last() {
+            self.set_price(BASE_ASSET, price);
+        }
+    }
+
+    /// Apply `action` to `state`, returning the resulting fills
+    ///
+    /// `state` is left untouched if the action fails, would breach an
+    /// invariant that held before it, or would deepen a breach. Breaches
+    /// caused by the market, such as a drawdown after prices fall, do not
+    /// block actions like withdrawals.
+    pub fn execute(&self, state: &mut AgentState, action: &AgentAction) -> Result<Vec<Fill>> {
+        let before = self.check(state);
+        let mut next = state.clone();
+        let fills = match action {
+            AgentAction::Rebalance { targets } => self.rebalance(&mut next, targets)?,
+            AgentAction::EmergencyWithdraw => self.liquidate(&mut next)?,
+            AgentAction::AdjustRisk { new_tolerance } => {
+                if !(0.0..=1.0).contains(new_tolerance) {
+                    return Err(ManusError::Agent(format!(
+                        "Risk tolerance must be between 0 and 1, got {}",
+                        new_tolerance
+                    )));
+                }
+                next.risk_tolerance = *new_tolerance;
+                vec![]
+            }
+            // Orders rest on the book and trade nothing until they fill
+            AgentAction::Quote { intents } => {
+                validate_intents(intents)?;
+                vec![]
+            }
+            // Liquidity is moved by the agent holding the position
+            AgentAction::Provide { ranges } => {
+                validate_ranges(ranges)?;
+                vec![]
+            }
+            AgentAction::Hold => vec![],
+        };
+
+        let after = self.check(&next);
+        let introduced = after.introduced_by(action, &before);
+        if !introduced.is_empty() {
+            let violations: Vec<String> = introduced.iter().map(|v| v.to_string()).collect();
+            tracing::warn!(
+                "Rolling back {:?} for {}: {}",
+                action,
+                state.id,
+                violations.join("; ")
+            );
+            return Err(ManusError::Agent(format!(
+                "Invariant violation for {}: {}",
+                state.id,
+                violations.join("; ")
+            )));
+        }
+
+        *state = next;
+        Ok(fills)
+    }
+
+    /// Trade `state` towards the target weights of its total value
+    ///
+    /// Weights for the quote asset are left as capital, and held assets
+    /// missing from `targets` are sold.
+    fn rebalance(&self, state: &mut AgentState, targets: &[(String, f64)]) -> Result<Vec<Fill>> {
+        let mut weights = HashMap::new();
+        for (asset, weight) in targets {
+            if !(0.0..=1.0).contains(weight) {
+                return Err(ManusError::Agent(format!(
+                    "Target weight for {} must be between 0 and 1, got {}",
+                    asset, weight
+                )));
+            }
+            *weights.entry(asset.as_str()).or_insert(0.0) += weight;
+        }
+        if weights.values().sum::<f64>() > 1.0 + 1e-9 {
+            return Err(ManusError::Agent(
+                "Target weights sum to more than 1".to_string(),
+            ));
+        }
+
+        let assets: BTreeSet<String> = weights
+            .keys()
+            .map(|asset| asset.to_string())
+            .chain(state.positions.iter().map(|p| p.asset.clone()))
+            .filter(|asset| asset != QUOTE_ASSET)
+            .collect();
+        let prices = assets
+            .iter()
+            .map(|asset| Ok((asset.as_str(), self.require_price(asset)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let total_value = state.capital as f64
+            + state
+                .positions
+                .iter()
+                .map(|p| p.amount as f64 * prices[p.asset.as_str()])
+                .sum::<f64>();
+
+        let mut sells = Vec::new();
+        let mut buys = Vec::new();
+        for asset in &assets {
+            let price = prices[asset.as_str()];
+            let weight = weights.get(asset.as_str()).copied().unwrap_or(0.0);
+            let held = held_amount(state, asset);
+            let delta = weight * total_value - held as f64 * price;
+
+            if weight == 0.0 {
+                sells.push((asset, held));
+            } else if delta < 0.0 {
+                sells.push((asset, ((-delta / price).floor() as u64).min(held)));
+            } else {
+                buys.push((asset, delta));
+            }
+        }
+
+        // Sell first so the proceeds can fund the buys
+        let mut fills = Vec::new();
+        for (asset, amount) in sells {
+            fills.extend(self.sell(state, asset, amount, prices[asset.as_str()]));
+        }
+        for (asset, value) in buys {
+            fills.extend(self.buy(state, asset, value, prices[asset.as_str()]));
+        }
+
+        Ok(fills)
+    }
+
+    /// Sell every position for capital
+    fn liquidate(&self, state: &mut AgentState) -> Result<Vec<Fill>> {
+        let positions: Vec<(String, u64)> = state
+            .positions
+            .iter()
+            .map(|p| (p.asset.clone(), p.amount))
+            .collect();
+
+        let mut fills = Vec::new();
+        for (asset, amount) in positions {
+            let price = self.require_price(&asset)?;
+            fills.extend(self.sell(state, &asset, amount, price));
+        }
+        Ok(fills)
+    }
+
+    /// Spend up to `value` of capital on `asset`, fees included
+    ///
+    /// Fees are added to the position's cost basis.
+    fn buy(&self, state: &mut AgentState, asset: &str, value: f64, price: f64) -> Option<Fill> {
+        let execution_price = price * (1.0 + self.slippage_bps as f64 / BPS);
+        let fee_rate = self.fee_bps as f64 / BPS;
+        let budget = (value.floor() as u64).min(state.capital);
+        let mut amount = (budget as f64 / (execution_price * (1.0 + fee_rate))).floor() as u64;
+
+        let cost = |amount: u64| {
+            let notional = (amount as f64 * execution_price).ceil() as u64;
+            let fee = (notional as f64 * fee_rate).ceil() as u64;
+            (notional, fee)
+        };
+        // Rounding can push the total a few units over budget
+        while amount > 0 && cost(amount).0 + cost(amount).1 > budget {
+            amount -= 1;
+        }
+        if amount == 0 {
+            return None;
+        }
+
+        let (notional, fee) = cost(amount);
+        let paid = notional + fee;
+        state.capital -= paid;
+        match state.positions.iter_mut().find(|p| p.asset == asset) {
+            Some(position) => {
+                let basis = position.amount as f64 * position.entry_price + paid as f64;
+                position.amount += amount;
+                position.entry_price = basis / position.amount as f64;
+            }
+            None => state.positions.push(Position {
+                asset: asset.to_string(),
+                amount,
+                entry_price: paid as f64 / amount as f64,
+            }),
+        }
+
+        Some(Fill {
+            asset: asset.to_string(),
+            side: Side::Buy,
+            amount,
+            price: execution_price,
+            fee,
+        })
+    }
+
+    /// Sell `amount` of `asset` for capital, net of fees
+    fn sell(&self, state: &mut AgentState, asset: &str, amount: u64, price: f64) -> Option<Fill> {
+        if amount == 0 {
+            return None;
+        }
+
+        let execution_price = price * (1.0 - self.slippage_bps as f64 / BPS);
+        let proceeds = (amount as f64 * execution_price).floor() as u64;
+        let fee = (proceeds as f64 * self.fee_bps as f64 / BPS).ceil() as u64;
+        state.capital += proceeds.saturating_sub(fee);
+
+        if let Some(index) = state.positions.iter().position(|p| p.asset == asset) {
+            state.positions[index].amount -= amount;
+            if state.positions[index].amount == 0 {
+                state.positions.remove(index);
+            }
+        }
+
+        Some(Fill {
+            asset: asset.to_string(),
+            side: Side::Sell,
+            amount,
+            price: execution_price,
+            fee,
+        })
+    }
+
+    fn require_price(&self, asset: &str) -> Result<f64> {
+        match self.price(asset) {
+            Some(price) if price > 0.0 => Ok(price),
+            _ => Err(ManusError::Agent(format!("No price for {}", asset))),
+        }
+    }
+}
+
+impl PriceOracle for ExecutionEngine {
+    fn price(&self, asset: &str) -> Option<f64> {
+        ExecutionEngine::price(self, asset)
+    }
+}
+
+fn held_amount(state: &AgentState, asset: &str) -> u64 {
+    state
+        .positions
+        .iter()
+        .filter(|p| p.asset == asset)
+        .map(|p| p.amount)
+        .sum()
+}
+
+/// Reject orders with non-positive prices or quantities
+fn validate_intents(intents: &[OrderIntent]) -> Result<()> {
+    for intent in intents {
+        let valid = match *intent {
+            OrderIntent::Place {
+                price, quantity, ..
+            } => price > 0.0 && quantity > 0.0,
+            OrderIntent::Modify { quantity, .. } => quantity > 0.0,
+            OrderIntent::Cancel { .. } => true,
+        };
+        if !valid {
+            return Err(ManusError::Agent(format!(
+                "Invalid order intent {:?}",
+                intent
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> AgentState {
+        AgentState {
+            id: "agent".to_string(),
+            capital: 1_000_000,
+            initial_capital: 1_000_000,
+            positions: vec![],
+            risk_tolerance: 0.5,
+        }
+    }
+
+    fn rebalance(weight: f64) -> AgentAction {
+        AgentAction::Rebalance {
+            targets: vec![
+                (BASE_ASSET.to_string(), weight),
+                (QUOTE_ASSET.to_string(), 1.0 - weight),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_rebalance_without_costs() {
+        let engine = ExecutionEngine::new()
+            .with_fee_bps(0)
+            .with_slippage_bps(0)
+            .with_price(BASE_ASSET, 2.0);
+        let mut state = state();
+
+        let fills = engine.execute(&mut state, &rebalance(0.6)).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(state.capital, 400_000);
+        assert_eq!(state.positions[0].amount, 300_000);
+
+        // Dropping the target weight sells the difference
+        let fills = engine.execute(&mut state, &rebalance(0.2)).unwrap();
+        assert_eq!(fills[0].side, Side::Sell);
+        assert_eq!(state.capital, 800_000);
+        assert_eq!(state.positions[0].amount, 100_000);
+    }
+
+    #[test]
+    fn test_fees_and_slippage() {
+        let mut engine = ExecutionEngine::new()
+            .with_fee_bps(30)
+            .with_slippage_bps(50)
+            .with_price(BASE_ASSET, 1.0);
+        let mut state = state();
+
+        let buy = engine
+            .execute(&mut state, &rebalance(0.5))
+            .unwrap()
+            .remove(0);
+        assert!((buy.price - 1.005).abs() < 1e-12);
+        assert!(buy.fee > 0);
+        assert!(state.positions[0].amount < 500_000);
+        assert!(state.capital >= 500_000);
+        assert!(state.verify_invariants().is_ok());
+
+        engine.set_price(BASE_ASSET, 1.2);
+        let sell = engine
+            .execute(&mut state, &AgentAction::EmergencyWithdraw)
+            .unwrap()
+            .remove(0);
+        assert_eq!(sell.side, Side::Sell);
+        assert!(state.positions.is_empty());
+        assert!(state.capital > state.initial_capital);
+    }
+
+    #[test]
+    fn test_rollback_on_invariant_violation() {
+        let engine = ExecutionEngine::new()
+            .with_price(BASE_ASSET, 1.0)
+            .with_invariants(InvariantSet::default().with_max_exposure(0.5));
+        let mut state = state();
+        engine.execute(&mut state, &rebalance(0.4)).unwrap();
+        let before = state.clone();
+
+        // Going overweight would breach the exposure limit
+        assert!(engine.execute(&mut state, &rebalance(0.8)).is_err());
+        assert_eq!(state.capital, before.capital);
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions[0].amount, before.positions[0].amount);
+    }
+
+    #[test]
+    fn test_withdraw_after_market_loss() {
+        let mut engine = ExecutionEngine::new().with_price(BASE_ASSET, 1.0);
+        let mut state = state();
+        engine.execute(&mut state, &rebalance(1.0)).unwrap();
+        assert!(engine.check(&state).is_ok());
+
+        // The loss shows up as soon as the position is marked down
+        engine.set_price(BASE_ASSET, 0.7);
+        assert!(state.verify_invariants().is_ok());
+        assert!(!engine.check(&state).is_ok());
+
+        // and does not stop the agent from getting out
+        engine
+            .execute(&mut state, &AgentAction::EmergencyWithdraw)
+            .unwrap();
+        assert!(state.positions.is_empty());
+        assert!(state.capital < 700_000);
+    }
+
+    #[test]
+    fn test_rejects_deepening_breaches() {
+        let mut engine = ExecutionEngine::new()
+            .with_price(BASE_ASSET, 1.0)
+            .with_invariants(InvariantSet::default().with_max_exposure(0.5));
+        let mut state = state();
+        engine.execute(&mut state, &rebalance(0.4)).unwrap();
+
+        // A rally pushes the position past the exposure limit
+        engine.set_price(BASE_ASSET, 2.0);
+        assert!(!engine.check(&state).is_ok());
+        let before = state.clone();
+        assert!(engine.execute(&mut state, &rebalance(0.8)).is_err());
+        assert_eq!(state.positions[0].amount, before.positions[0].amount);
+
+        // Trimming the position still breaches the limit, but less
+        engine.execute(&mut state, &rebalance(0.55)).unwrap();
+        assert!(state.positions[0].amount < before.positions[0].amount);
+    }
+
+    #[test]
+    fn test_rejects_invalid_actions() {
+        let engine = ExecutionEngine::new();
+        let mut state = state();
+
+        // No price for the base asset yet
+        assert!(engine.execute(&mut state, &rebalance(0.5)).is_err());
+
+        let engine = engine.with_price(BASE_ASSET, 1.0);
+        let overweight = AgentAction::Rebalance {
+            targets: vec![(BASE_ASSET.to_string(), 0.8), ("ETH".to_string(), 0.4)],
+        };
+        assert!(engine.execute(&mut state, &overweight).is_err());
+        assert!(engine
+            .execute(&mut state, &AgentAction::AdjustRisk { new_tolerance: 2.0 })
+            .is_err());
+        assert_eq!(state.capital, 1_000_000);
+        assert!(state.positions.is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Market regime classification
+//!
+//! Each snapshot is scored against the trending, ranging and volatile
+//! regimes, and the scores are filtered through a sticky hidden Markov
+//! model so a single noisy snapshot barely moves the regime probabilities.
+//! On top of that, a [`RegimeClassifier`] only switches regime once a new
+//! regime has been the most likely one, with enough probability, for several
+//! consecutive snapshots. Switches are reported as [`RegimeTransition`]s.
+
+use crate::agents::ml_agent::MarketData;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Market regime
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    /// Prices moving steadily in one direction
+    Trending,
+    /// Prices oscillating around a level
+    Ranging,
+    /// Large price swings
+    Volatile,
+    /// Not enough data observed
+    #[default]
+    Unknown,
+}
+
+impl Regime {
+    /// Lowercase name, as reported by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Regime::Trending => "trending",
+            Regime::Ranging => "ranging",
+            Regime::Volatile => "volatile",
+            Regime::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Probability of each regime, summing to 1
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegimeProbabilities {
+    /// Probability the market is trending
+    pub trending: f64,
+    /// Probability the market is ranging
+    pub ranging: f64,
+    /// Probability the market is volatile
+    pub volatile: f64,
+}
+
+impl RegimeProbabilities {
+    const REGIMES: [Regime; 3] = [Regime::Trending, Regime::Ranging, Regime::Volatile];
+
+    /// Probability of `regime`, zero for [`Regime::Unknown`]
+    pub fn get(&self, regime: Regime) -> f64 {
+        match regime {
+            Regime::Trending => self.trending,
+            Regime::Ranging => self.ranging,
+            Regime::Volatile => self.volatile,
+            Regime::Unknown => 0.0,
+        }
+    }
+
+    /// Regime with the highest probability
+    pub fn most_likely(&self) -> Regime {
+        Self::REGIMES
+            .into_iter()
+            .max_by(|a, b| self.get(*a).total_cmp(&self.get(*b)))
+            .unwrap_or_default()
+    }
+
+    fn from_fn(f: impl Fn(Regime) -> f64) -> Self {
+        let (trending, ranging, volatile) =
+            (f(Regime::Trending), f(Regime::Ranging), f(Regime::Volatile));
+        let total = trending + ranging + volatile;
+        if total > 0.0 && total.is_finite() {
+            Self {
+                trending: trending / total,
+                ranging: ranging / total,
+                volatile: volatile / total,
+            }
+        } else {
+            Self {
+                trending: 1.0 / 3.0,
+                ranging: 1.0 / 3.0,
+                volatile: 1.0 / 3.0,
+            }
+        }
+    }
+}
+
+/// A confirmed change of regime
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegimeTransition {
+    /// Previous regime
+    pub from: Regime,
+    /// New regime
+    pub to: Regime,
+    /// Regime probabilities when the change was confirmed
+    pub probabilities: RegimeProbabilities,
+}
+
+/// Classifier thresholds and smoothing
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegimeConfig {
+    /// Volatility at which a snapshot is as likely volatile as not
+    pub volatility_threshold: f64,
+    /// Width of the volatility transition band
+    pub volatility_scale: f64,
+    /// Relative price change across a snapshot at which it is as likely
+    /// trending as ranging
+    pub trend_threshold: f64,
+    /// Width of the trend transition band
+    pub trend_scale: f64,
+    /// Probability of staying in the same regime between snapshots
+    pub persistence: f64,
+    /// Probability a new regime needs before it can replace the current one
+    pub switch_probability: f64,
+    /// Consecutive snapshots a new regime must lead before switching
+    pub confirmations: usize,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            volatility_threshold: 0.3,
+            volatility_scale: 0.05,
+            trend_threshold: 0.01,
+            trend_scale: 0.004,
+            persistence: 0.9,
+            switch_probability: 0.6,
+            confirmations: 2,
+        }
+    }
+}
+
+/// Stateful regime classifier
+#[derive(Debug, Clone, Default)]
+pub struct RegimeClassifier {
+    config: RegimeConfig,
+    probabilities: Option<RegimeProbabilities>,
+    regime: Regime,
+    /// Challenger regime and how many consecutive snapshots it has led
+    pending: Option<(Regime, usize)>,
+}
+
+impl RegimeClassifier {
+    /// Create a classifier that has not observed anything
+    pub fn new(config: RegimeConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Thresholds and smoothing
+    pub fn config(&self) -> &RegimeConfig {
+        &self.config
+    }
+
+    /// Current regime
+    pub fn regime(&self) -> Regime {
+        self.regime
+    }
+
+    /// Filtered regime probabilities, once a snapshot has been observed
+    pub fn probabilities(&self) -> Option<&RegimeProbabilities> {
+        self.probabilities.as_ref()
+    }
+
+    /// Regime probabilities of a single snapshot, ignoring history
+    pub fn likelihoods(&self, data: &MarketData) -> RegimeProbabilities {
+        let config = &self.config;
+        let volatile = logistic(
+            (data.volatility - config.volatility_threshold) / config.volatility_scale.max(1e-9),
+        );
+        let trending = match (data.prices.first(), data.prices.last()) {
+            (Some(&first), Some(&last)) if data.prices.len() >= 2 && first != 0.0 => logistic(
+                ((last / first - 1.0).abs() - config.trend_threshold)
+                    / config.trend_scale.max(1e-9),
+            ),
+            _ => 0.5,
+        };
+
+        RegimeProbabilities::from_fn(|regime| match regime {
+            Regime::Volatile => volatile,
+            Regime::Trending => (1.0 - volatile) * trending,
+            _ => (1.0 - volatile) * (1.0 - trending),
+        })
+    }
+
+    /// Most likely regime of a single snapshot, ignoring history
+    ///
+    /// Unknown when the snapshot is calm but has fewer than two prices.
+    pub fn classify(&self, data: &MarketData) -> Regime {
+        let likelihoods = self.likelihoods(data);
+        if data.prices.len() < 2 && likelihoods.volatile < 0.5 {
+            return Regime::Unknown;
+        }
+        likelihoods.most_likely()
+    }
+
+    /// Update with a snapshot, returning the transition if the regime changed
+    pub fn update(&mut self, data: &MarketData) -> Option<RegimeTransition> {
+        let likelihoods = self.likelihoods(data);
+        let persistence = self.config.persistence.clamp(0.0, 1.0);
+        let probabilities = match self.probabilities {
+            // Forward step of the hidden Markov model
+            Some(previous) => RegimeProbabilities::from_fn(|regime| {
+                let stay = previous.get(regime);
+                let prior = persistence * stay + (1.0 - persistence) / 2.0 * (1.0 - stay);
+                prior * likelihoods.get(regime)
+            }),
+            None => likelihoods,
+        };
+        self.probabilities = Some(probabilities);
+
+        let leader = probabilities.most_likely();
+        if self.regime == Regime::Unknown {
+            if self.classify(data) == Regime::Unknown {
+                return None;
+            }
+            return Some(self.switch(leader, probabilities));
+        }
+        if leader == self.regime || probabilities.get(leader) < self.config.switch_probability {
+            self.pending = None;
+            return None;
+        }
+
+        let count = match self.pending {
+            Some((regime, count)) if regime == leader => count + 1,
+            _ => 1,
+        };
+        if count >= self.config.confirmations {
+            Some(self.switch(leader, probabilities))
+        } else {
+            self.pending = Some((leader, count));
+            None
+        }
+    }
+
+    fn switch(&mut self, to: Regime, probabilities: RegimeProbabilities) -> RegimeTransition {
+        let transition = RegimeTransition {
+            from: self.regime,
+            to,
+            probabilities,
+        };
+        self.regime = to;
+        self.pending = None;
+        transition
+    }
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(prices: Vec<f64>, volatility: f64) -> MarketData {
+        MarketData {
+            volumes: vec![1000.0; prices.len()],
+            prices,
+            volatility,
+            liquidity: 5000.0,
+            book: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let classifier = RegimeClassifier::default();
+        assert_eq!(
+            classifier.classify(&market(vec![1.0, 1.5, 0.8, 1.3], 0.4)),
+            Regime::Volatile
+        );
+        assert_eq!(
+            classifier.classify(&market(vec![1.0, 1.02, 1.05], 0.05)),
+            Regime::Trending
+        );
+        assert_eq!(
+            classifier.classify(&market(vec![1.0, 1.01, 1.001], 0.05)),
+            Regime::Ranging
+        );
+        assert_eq!(
+            classifier.classify(&market(vec![1.0], 0.05)),
+            Regime::Unknown
+        );
+
+        // The trend threshold is relative, not an absolute price move
+        assert_eq!(
+            classifier.classify(&market(vec![100.0, 100.5], 0.05)),
+            Regime::Ranging
+        );
+
+        let p = classifier.likelihoods(&market(vec![1.0, 1.02], 0.2));
+        assert!((p.trending + p.ranging + p.volatile - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut classifier = RegimeClassifier::default();
+        let trending = market(vec![1.0, 1.05], 0.05);
+        let ranging = market(vec![1.0, 1.001], 0.05);
+
+        let transition = classifier.update(&trending).unwrap();
+        assert_eq!(
+            (transition.from, transition.to),
+            (Regime::Unknown, Regime::Trending)
+        );
+
+        // One ranging snapshot is not enough to switch
+        assert!(classifier.update(&ranging).is_none());
+        assert!(classifier.update(&trending).is_none());
+        assert_eq!(classifier.regime(), Regime::Trending);
+
+        // A sustained change is
+        let transitions: Vec<RegimeTransition> =
+            (0..5).filter_map(|_| classifier.update(&ranging)).collect();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to, Regime::Ranging);
+        assert!(transitions[0].probabilities.ranging >= 0.6);
+        assert_eq!(classifier.regime(), Regime::Ranging);
+    }
+
+    #[test]
+    fn test_serialization() {
+        assert_eq!(
+            serde_json::to_string(&Regime::Volatile).unwrap(),
+            r#""volatile""#
+        );
+        assert_eq!(Regime::Trending.to_string(), "trending");
+    }
+}
@@ -0,0 +1,468 @@
+//! Registry of running agents shared across the API and agent runner
+
+use crate::agents::concentrated::RangeProviderAgent;
+use crate::agents::market_maker::MarketMakerAgent;
+use crate::agents::ml_agent::{
+    MarketAnalyzerAgent, RebalancerAgent, RiskManagerAgent, StrategyOptimizerAgent,
+};
+use crate::agents::model::ModelArtifact;
+use crate::agents::rebalancer::ExecutionEngine;
+use crate::agents::valuation::InvariantSet;
+use crate::agents::{Agent, AgentAction, AgentState, AutonomousAgent};
+use crate::config::AgentConfig;
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::sync::RwLock;
+
+/// Initial capital given to the agents seeded from configuration
+pub const DEFAULT_AGENT_CAPITAL: u64 = 1_000_000;
+
+/// Default maximum drawdown for risk manager agents
+pub const DEFAULT_MAX_DRAWDOWN: f64 = 0.2;
+
+/// Kinds of agent the registry can instantiate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentKind {
+    /// [`AutonomousAgent`]
+    Autonomous,
+    /// [`RebalancerAgent`]
+    Rebalancer,
+    /// [`RiskManagerAgent`]
+    RiskManager,
+    /// [`StrategyOptimizerAgent`]
+    StrategyOptimizer,
+    /// [`MarketAnalyzerAgent`]
+    MarketAnalyzer,
+    /// [`RangeProviderAgent`]
+    RangeProvider,
+    /// [`MarketMakerAgent`]
+    MarketMaker,
+}
+
+/// Parameters for instantiating an agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSpec {
+    /// Agent ID
+    pub id: String,
+
+    /// Agent kind
+    #[serde(rename = "type")]
+    pub kind: AgentKind,
+
+    /// Initial capital (in base units)
+    pub initial_capital: u64,
+
+    /// Risk tolerance (0.0 - 1.0)
+    pub risk_tolerance: f64,
+
+    /// Maximum drawdown before a risk manager withdraws (0.0 - 1.0)
+    #[serde(default)]
+    pub max_drawdown: Option<f64>,
+
+    /// Path of a trained model artifact, for rebalancers
+    ///
+    /// Only trusted from configuration; the API rejects specs setting it.
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Confidence a rebalancer needs before rebalancing (0.0 - 1.0)
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+
+    /// Volatility above which a rebalancer shifts towards the quote asset
+    #[serde(default)]
+    pub volatility_threshold: Option<f64>,
+
+    /// Portfolio limits enforced on the agent's executions
+    #[serde(default)]
+    pub invariants: InvariantSet,
+
+    /// Vault whose PnL is attributed to the agent's trades and positions
+    #[serde(default)]
+    pub vault_id: Option<String>,
+}
+
+impl AgentSpec {
+    /// Instantiate the agent described by this spec
+    pub fn build(&self) -> Result<Box<dyn Agent>> {
+        self.build_with_engine(ExecutionEngine::new())
+    }
+
+    /// Instantiate the agent, executing its actions through `engine`
+    ///
+    /// The spec's invariants replace the engine's.
+    pub fn build_with_engine(&self, engine: ExecutionEngine) -> Result<Box<dyn Agent>> {
+        if !(0.0..=1.0).contains(&self.risk_tolerance) {
+            return Err(ManusError::Agent(format!(
+                "Risk tolerance must be between 0 and 1, got {}",
+                self.risk_tolerance
+            )));
+        }
+
+        if self.model.is_some() && self.kind != AgentKind::Rebalancer {
+            return Err(ManusError::Agent(format!(
+                "Only rebalancer agents load trained models, {} is a {:?} agent",
+                self.id, self.kind
+            )));
+        }
+
+        let thresholds = self.min_confidence.is_some() || self.volatility_threshold.is_some();
+        if thresholds && self.kind != AgentKind::Rebalancer {
+            return Err(ManusError::Agent(format!(
+                "Only rebalancer agents take confidence and volatility thresholds, {} is a {:?} agent",
+                self.id, self.kind
+            )));
+        }
+
+        let id = self.id.clone();
+        let engine = engine.with_invariants(self.invariants.clone());
+        let agent: Box<dyn Agent> = match self.kind {
+            AgentKind::Autonomous => Box::new(
+                AutonomousAgent::new(id, self.initial_capital, self.risk_tolerance)
+                    .with_engine(engine),
+            ),
+            AgentKind::Rebalancer => {
+                let mut agent = RebalancerAgent::new(id, self.initial_capital)
+                    .with_risk_tolerance(self.risk_tolerance)
+                    .with_engine(engine);
+                if let Some(path) = &self.model {
+                    let artifact = ModelArtifact::load(Path::new(path))?;
+                    agent = agent
+                        .with_feature_set(artifact.metadata.features)
+                        .with_model(artifact.model);
+                }
+                if let Some(min_confidence) = self.min_confidence {
+                    agent = agent.with_min_confidence(min_confidence);
+                }
+                if let Some(volatility_threshold) = self.volatility_threshold {
+                    agent = agent.with_volatility_threshold(volatility_threshold);
+                }
+                Box::new(agent)
+            }
+            AgentKind::RiskManager => Box::new(
+                RiskManagerAgent::new(
+                    id,
+                    self.initial_capital,
+                    self.max_drawdown.unwrap_or(DEFAULT_MAX_DRAWDOWN),
+                )
+                .with_risk_tolerance(self.risk_tolerance)
+                .with_engine(engine),
+            ),
+            AgentKind::StrategyOptimizer => Box::new(
+                StrategyOptimizerAgent::new(id, self.initial_capital)
+                    .with_risk_tolerance(self.risk_tolerance)
+                    .with_engine(engine),
+            ),
+            AgentKind::MarketAnalyzer => Box::new(
+                MarketAnalyzerAgent::new(id, self.initial_capital)
+                    .with_risk_tolerance(self.risk_tolerance)
+                    .with_engine(engine),
+            ),
+            AgentKind::RangeProvider => Box::new(
+                RangeProviderAgent::new(id, self.initial_capital)
+                    .with_risk_tolerance(self.risk_tolerance)
+                    .with_engine(engine),
+            ),
+            AgentKind::MarketMaker => Box::new(
+                MarketMakerAgent::new(id, self.initial_capital)
+                    .with_risk_tolerance(self.risk_tolerance)
+                    .with_engine(engine),
+            ),
+        };
+
+        Ok(agent)
+    }
+}
+
+/// One agent of each ML kind, configured from `config`
+pub fn default_specs(config: &AgentConfig) -> Vec<AgentSpec> {
+    [
+        ("rebalancer_001", AgentKind::Rebalancer),
+        ("risk_manager_001", AgentKind::RiskManager),
+        ("market_analyzer_001", AgentKind::MarketAnalyzer),
+        ("strategy_optimizer_001", AgentKind::StrategyOptimizer),
+    ]
+    .into_iter()
+    .map(|(id, kind)| AgentSpec {
+        id: id.to_string(),
+        kind,
+        initial_capital: DEFAULT_AGENT_CAPITAL,
+        risk_tolerance: config.risk_tolerance.clamp(0.0, 1.0),
+        max_drawdown: None,
+        model: match kind {
+            AgentKind::Rebalancer => config.rebalancer_model.clone(),
+            _ => None,
+        },
+        min_confidence: None,
+        volatility_threshold: None,
+        invariants: config.invariants.clone(),
+        vault_id: None,
+    })
+    .collect()
+}
+
+/// Agents configured in the `instances` sections of `config`
+///
+/// Settings an agent's section leaves out are taken from `config`. Falls back
+/// to [`default_specs`] when no agents are configured individually.
+pub fn configured_specs(config: &AgentConfig) -> Vec<AgentSpec> {
+    if config.instances.is_empty() {
+        return default_specs(config);
+    }
+
+    config
+        .instances
+        .iter()
+        .map(|(id, section)| AgentSpec {
+            id: id.clone(),
+            kind: section.kind,
+            initial_capital: section.initial_capital.unwrap_or(DEFAULT_AGENT_CAPITAL),
+            risk_tolerance: section.risk_tolerance.unwrap_or(config.risk_tolerance),
+            max_drawdown: section.max_drawdown,
+            model: section.model.clone(),
+            min_confidence: section.min_confidence,
+            volatility_threshold: section.volatility_threshold,
+            invariants: section
+                .invariants
+                .clone()
+                .unwrap_or_else(|| config.invariants.clone()),
+            vault_id: section.vault_id.clone(),
+        })
+        .collect()
+}
+
+/// Snapshot of a registered agent
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentInfo {
+    /// Agent kind
+    #[serde(rename = "type")]
+    pub kind: AgentKind,
+
+    /// Vault the agent's PnL is attributed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_id: Option<String>,
+
+    /// Current state
+    #[serde(flatten)]
+    pub state: AgentState,
+}
+
+struct Entry {
+    kind: AgentKind,
+    agent: Box<dyn Agent>,
+    vault_id: Option<String>,
+}
+
+impl Entry {
+    fn info(&self) -> AgentInfo {
+        AgentInfo {
+            kind: self.kind,
+            vault_id: self.vault_id.clone(),
+            state: self.agent.state().clone(),
+        }
+    }
+}
+
+/// Thread-safe registry of agents keyed by ID
+#[derive(Default)]
+pub struct AgentRegistry {
+    agents: RwLock<BTreeMap<String, Entry>>,
+}
+
+impl AgentRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry seeded with [`configured_specs`]
+    ///
+    /// Empty when agents are disabled in `config`. Fails if a configured
+    /// model cannot be loaded.
+    pub fn from_config(config: &AgentConfig) -> Result<Self> {
+        let mut agents = BTreeMap::new();
+        if config.enabled {
+            for spec in configured_specs(config) {
+                let agent = spec.build()?;
+                let entry = Entry {
+                    kind: spec.kind,
+                    agent,
+                    vault_id: spec.vault_id,
+                };
+                agents.insert(spec.id, entry);
+            }
+        }
+
+        Ok(Self {
+            agents: RwLock::new(agents),
+        })
+    }
+
+    /// Register an agent, rejecting duplicate IDs
+    pub async fn register(&self, kind: AgentKind, agent: Box<dyn Agent>) -> Result<AgentInfo> {
+        self.insert(Entry {
+            kind,
+            agent,
+            vault_id: None,
+        })
+        .await
+    }
+
+    /// Instantiate and register the agent described by `spec`
+    pub async fn create(&self, spec: &AgentSpec) -> Result<AgentInfo> {
+        self.insert(Entry {
+            kind: spec.kind,
+            agent: spec.build()?,
+            vault_id: spec.vault_id.clone(),
+        })
+        .await
+    }
+
+    async fn insert(&self, entry: Entry) -> Result<AgentInfo> {
+        let mut agents = self.agents.write().await;
+        let id = entry.agent.id().to_string();
+        if agents.contains_key(&id) {
+            return Err(ManusError::AgentExists(id));
+        }
+
+        let info = entry.info();
+        agents.insert(id, entry);
+        Ok(info)
+    }
+
+    /// Remove an agent, returning whether it was registered
+    pub async fn remove(&self, id: &str) -> bool {
+        self.agents.write().await.remove(id).is_some()
+    }
+
+    /// Snapshot of one agent
+    pub async fn get(&self, id: &str) -> Option<AgentInfo> {
+        self.agents.read().await.get(id).map(Entry::info)
+    }
+
+    /// Snapshots of all agents, in ID order
+    pub async fn list(&self) -> Vec<AgentInfo> {
+        self.agents.read().await.values().map(Entry::info).collect()
+    }
+
+    /// IDs of all registered agents, in order
+    pub async fn ids(&self) -> Vec<String> {
+        self.agents.read().await.keys().cloned().collect()
+    }
+
+    /// Number of registered agents
+    pub async fn len(&self) -> usize {
+        self.agents.read().await.len()
+    }
+
+    /// Whether no agents are registered
+    pub async fn is_empty(&self) -> bool {
+        self.agents.read().await.is_empty()
+    }
+
+    /// First veto of `action`, proposed by agent `id`, by another risk manager
+    ///
+    /// Returns the ID of the vetoing risk manager and its reason.
+    pub async fn review(&self, id: &str, action: &AgentAction) -> Option<(String, String)> {
+        let agents = self.agents.read().await;
+        agents
+            .iter()
+            .filter(|(reviewer, entry)| entry.kind == AgentKind::RiskManager && *reviewer != id)
+            .find_map(|(reviewer, entry)| {
+                let reason = entry.agent.review(action)?;
+                Some((reviewer.clone(), reason))
+            })
+    }
+
+    /// Run `f` against an agent with exclusive access
+    ///
+    /// Returns `None` if no agent with `id` is registered.
+    pub async fn with_agent<R>(&self, id: &str, f: impl FnOnce(&mut dyn Agent) -> R) -> Option<R> {
+        let mut agents = self.agents.write().await;
+        agents.get_mut(id).map(|entry| f(entry.agent.as_mut()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(id: &str, kind: AgentKind) -> AgentSpec {
+        AgentSpec {
+            id: id.to_string(),
+            kind,
+            initial_capital: 1_000,
+            risk_tolerance: 0.5,
+            max_drawdown: None,
+            model: None,
+            min_confidence: None,
+            volatility_threshold: None,
+            invariants: InvariantSet::default(),
+            vault_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_duplicates() {
+        let registry = AgentRegistry::new();
+        registry
+            .register(
+                AgentKind::Autonomous,
+                Box::new(AutonomousAgent::new("a".to_string(), 1_000, 0.5)),
+            )
+            .await
+            .unwrap();
+
+        let duplicate = AutonomousAgent::new("a".to_string(), 5, 0.1);
+        assert!(matches!(
+            registry
+                .register(AgentKind::Autonomous, Box::new(duplicate))
+                .await,
+            Err(ManusError::AgentExists(id)) if id == "a"
+        ));
+        assert_eq!(registry.ids().await, vec!["a".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_drive_agents() {
+        let registry = AgentRegistry::from_config(&AgentConfig {
+            enabled: true,
+            rebalance_interval: 60,
+            risk_tolerance: 0.3,
+            max_restarts: 5,
+            rebalancer_model: None,
+            invariants: InvariantSet::default(),
+            instances: Default::default(),
+        })
+        .unwrap();
+        assert!(registry.get("rebalancer_001").await.is_some());
+
+        let info = registry
+            .create(&spec("analyzer", AgentKind::MarketAnalyzer))
+            .await
+            .unwrap();
+        assert_eq!(info.kind, AgentKind::MarketAnalyzer);
+        assert_eq!(info.state.capital, 1_000);
+
+        let action = registry
+            .with_agent("analyzer", |agent| agent.decide())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(action, AgentAction::Hold));
+        assert!(registry
+            .with_agent("missing", |agent| agent.decide())
+            .await
+            .is_none());
+
+        let mut invalid = spec("bad", AgentKind::Rebalancer);
+        invalid.risk_tolerance = 1.5;
+        assert!(registry.create(&invalid).await.is_err());
+
+        assert!(registry.remove("analyzer").await);
+        assert!(!registry.remove("analyzer").await);
+    }
+}
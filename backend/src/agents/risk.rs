@@ -0,0 +1,375 @@
+//! Equity-curve risk tracking
+//!
+//! An [`EquityTracker`] records an agent's marked-to-market equity over time,
+//! keeping the all-time high-water mark and a rolling window of recent values.
+//! [`RiskLimits::assess`] turns the tracked curve into drawdown and
+//! historical VaR/CVaR estimates and picks a graduated [`RiskResponse`].
+
+use crate::agents::rebalancer::{BASE_ASSET, QUOTE_ASSET};
+use crate::agents::registry::DEFAULT_MAX_DRAWDOWN;
+use crate::agents::AgentAction;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Equity observations kept for rolling statistics
+pub const DEFAULT_RISK_WINDOW: usize = 64;
+
+/// Returns needed before VaR and CVaR are estimated
+const MIN_RETURNS: usize = 10;
+
+/// Escalating responses to risk, mildest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskResponse {
+    /// Within limits
+    Normal,
+    /// Cut exposure to risky assets
+    ReduceExposure,
+    /// Stop opening or growing positions
+    Pause,
+    /// Liquidate everything
+    EmergencyWithdraw,
+}
+
+impl RiskResponse {
+    /// Action carrying out the response for a portfolio with `exposure` of its
+    /// equity in risky assets
+    ///
+    /// Reducing exposure rebalances down to `reduced_exposure` in the base
+    /// asset; pausing holds while other agents' trades are vetoed.
+    pub fn action(&self, exposure: f64, reduced_exposure: f64) -> AgentAction {
+        match self {
+            RiskResponse::Normal | RiskResponse::Pause => AgentAction::Hold,
+            RiskResponse::ReduceExposure if exposure > reduced_exposure => AgentAction::Rebalance {
+                targets: vec![
+                    (BASE_ASSET.to_string(), reduced_exposure),
+                    (QUOTE_ASSET.to_string(), 1.0 - reduced_exposure),
+                ],
+            },
+            RiskResponse::ReduceExposure => AgentAction::Hold,
+            RiskResponse::EmergencyWithdraw => AgentAction::EmergencyWithdraw,
+        }
+    }
+}
+
+/// Rolling equity curve with an all-time high-water mark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityTracker {
+    capacity: usize,
+    equity: VecDeque<f64>,
+    high_water_mark: f64,
+}
+
+impl EquityTracker {
+    /// Track the last `capacity` observations, starting from `initial` equity
+    pub fn new(initial: f64, capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(2),
+            equity: VecDeque::from([initial]),
+            high_water_mark: initial,
+        }
+    }
+
+    /// Record the latest equity
+    pub fn record(&mut self, equity: f64) {
+        if self.equity.len() == self.capacity {
+            self.equity.pop_front();
+        }
+        self.equity.push_back(equity);
+        self.high_water_mark = self.high_water_mark.max(equity);
+    }
+
+    /// Latest equity
+    pub fn current(&self) -> f64 {
+        self.equity.back().copied().unwrap_or(0.0)
+    }
+
+    /// Highest equity ever recorded
+    pub fn high_water_mark(&self) -> f64 {
+        self.high_water_mark
+    }
+
+    /// Decline from the high-water mark (0.0 - 1.0)
+    pub fn drawdown(&self) -> f64 {
+        decline(self.high_water_mark, self.current())
+    }
+
+    /// Decline from the highest equity within the rolling window (0.0 - 1.0)
+    pub fn rolling_drawdown(&self) -> f64 {
+        let peak = self.equity.iter().copied().fold(f64::MIN, f64::max);
+        decline(peak, self.current())
+    }
+
+    /// Per-observation returns within the window, oldest first
+    pub fn returns(&self) -> Vec<f64> {
+        self.equity
+            .iter()
+            .zip(self.equity.iter().skip(1))
+            .filter(|(previous, _)| **previous > 0.0)
+            .map(|(previous, current)| current / previous - 1.0)
+            .collect()
+    }
+
+    /// Historical value at risk: the loss not exceeded with `confidence`
+    ///
+    /// Expressed as a positive fraction of equity; zero until enough returns
+    /// have been recorded.
+    pub fn value_at_risk(&self, confidence: f64) -> f64 {
+        let losses = self.sorted_losses();
+        if losses.len() < MIN_RETURNS {
+            return 0.0;
+        }
+        losses[tail_start(losses.len(), confidence)].max(0.0)
+    }
+
+    /// Conditional value at risk: the mean loss beyond the VaR
+    pub fn conditional_value_at_risk(&self, confidence: f64) -> f64 {
+        let losses = self.sorted_losses();
+        if losses.len() < MIN_RETURNS {
+            return 0.0;
+        }
+        let tail = &losses[tail_start(losses.len(), confidence)..];
+        (tail.iter().sum::<f64>() / tail.len() as f64).max(0.0)
+    }
+
+    /// Losses (negated returns), smallest first
+    fn sorted_losses(&self) -> Vec<f64> {
+        let mut losses: Vec<f64> = self.returns().iter().map(|r| -r).collect();
+        losses.sort_by(f64::total_cmp);
+        losses
+    }
+}
+
+/// Index of the first loss in the `1 - confidence` tail
+fn tail_start(len: usize, confidence: f64) -> usize {
+    let index = (confidence.clamp(0.0, 1.0) * len as f64).floor() as usize;
+    index.min(len - 1)
+}
+
+fn decline(peak: f64, current: f64) -> f64 {
+    if peak > 0.0 {
+        ((peak - current) / peak).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Thresholds mapping an equity curve to a [`RiskResponse`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// Drawdown from the high-water mark that triggers an emergency withdrawal
+    pub max_drawdown: f64,
+    /// Share of `max_drawdown` at which exposure is reduced
+    pub reduce_at: f64,
+    /// Share of `max_drawdown` that, lost within the rolling window, pauses trading
+    pub pause_at: f64,
+    /// Confidence level of VaR and CVaR
+    pub confidence: f64,
+    /// Per-observation VaR above which exposure is reduced
+    pub max_var: f64,
+    /// Per-observation CVaR above which trading is paused
+    pub max_cvar: f64,
+    /// Share of equity left in the base asset when reducing exposure
+    pub reduced_exposure: f64,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_drawdown: DEFAULT_MAX_DRAWDOWN,
+            reduce_at: 0.5,
+            pause_at: 0.75,
+            confidence: 0.95,
+            max_var: 0.05,
+            max_cvar: 0.08,
+            reduced_exposure: 0.3,
+        }
+    }
+}
+
+/// Risk measures and the response they call for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// Latest equity
+    pub equity: f64,
+    /// Highest equity ever recorded
+    pub high_water_mark: f64,
+    /// Decline from the high-water mark
+    pub drawdown: f64,
+    /// Decline from the peak of the rolling window
+    pub rolling_drawdown: f64,
+    /// Historical value at risk
+    pub var: f64,
+    /// Historical conditional value at risk
+    pub cvar: f64,
+    /// Response called for
+    pub response: RiskResponse,
+    /// Why the response was chosen, empty when normal
+    pub reasons: Vec<String>,
+}
+
+impl RiskLimits {
+    /// Limits with the default thresholds and `max_drawdown`
+    pub fn with_max_drawdown(max_drawdown: f64) -> Self {
+        Self {
+            max_drawdown,
+            ..Self::default()
+        }
+    }
+
+    /// Measure `tracker` against the limits
+    ///
+    /// The most severe breached threshold decides the response.
+    pub fn assess(&self, tracker: &EquityTracker) -> RiskAssessment {
+        let drawdown = tracker.drawdown();
+        let rolling_drawdown = tracker.rolling_drawdown();
+        let var = tracker.value_at_risk(self.confidence);
+        let cvar = tracker.conditional_value_at_risk(self.confidence);
+
+        let mut response = RiskResponse::Normal;
+        let mut reasons = Vec::new();
+        let mut escalate = |level: RiskResponse, reason: String| {
+            response = response.max(level);
+            reasons.push(reason);
+        };
+
+        if drawdown >= self.max_drawdown {
+            escalate(
+                RiskResponse::EmergencyWithdraw,
+                format!(
+                    "Drawdown {:.2}% from high-water mark reached limit of {:.2}%",
+                    drawdown * 100.0,
+                    self.max_drawdown * 100.0
+                ),
+            );
+        }
+        if rolling_drawdown >= self.pause_at * self.max_drawdown {
+            escalate(
+                RiskResponse::Pause,
+                format!(
+                    "Rolling drawdown {:.2}% reached {:.2}%",
+                    rolling_drawdown * 100.0,
+                    self.pause_at * self.max_drawdown * 100.0
+                ),
+            );
+        }
+        if cvar > self.max_cvar {
+            escalate(
+                RiskResponse::Pause,
+                format!(
+                    "CVaR {:.2}% above {:.2}%",
+                    cvar * 100.0,
+                    self.max_cvar * 100.0
+                ),
+            );
+        }
+        if drawdown >= self.reduce_at * self.max_drawdown {
+            escalate(
+                RiskResponse::ReduceExposure,
+                format!(
+                    "Drawdown {:.2}% reached {:.2}%",
+                    drawdown * 100.0,
+                    self.reduce_at * self.max_drawdown * 100.0
+                ),
+            );
+        }
+        if var > self.max_var {
+            escalate(
+                RiskResponse::ReduceExposure,
+                format!("VaR {:.2}% above {:.2}%", var * 100.0, self.max_var * 100.0),
+            );
+        }
+
+        RiskAssessment {
+            equity: tracker.current(),
+            high_water_mark: tracker.high_water_mark(),
+            drawdown,
+            rolling_drawdown,
+            var,
+            cvar,
+            response,
+            reasons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_water_mark_and_drawdowns() {
+        let mut tracker = EquityTracker::new(100.0, 3);
+        for equity in [120.0, 90.0, 100.0, 105.0] {
+            tracker.record(equity);
+        }
+        assert_eq!(tracker.high_water_mark(), 120.0);
+        assert!((tracker.drawdown() - 0.125).abs() < 1e-12);
+        // 120 has left the window; the rolling peak is the latest value
+        assert_eq!(tracker.rolling_drawdown(), 0.0);
+        assert_eq!(tracker.returns().len(), 2);
+    }
+
+    #[test]
+    fn test_var_and_cvar() {
+        let mut tracker = EquityTracker::new(100.0, 32);
+        assert_eq!(tracker.value_at_risk(0.95), 0.0);
+
+        // Nineteen flat steps and one 10% loss
+        let mut equity = 100.0;
+        for step in 0..20 {
+            if step == 10 {
+                equity *= 0.9;
+            }
+            tracker.record(equity);
+        }
+        assert!((tracker.value_at_risk(0.95) - 0.1).abs() < 1e-12);
+        assert!((tracker.conditional_value_at_risk(0.95) - 0.1).abs() < 1e-12);
+        assert_eq!(tracker.value_at_risk(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_graduated_responses() {
+        let limits = RiskLimits::with_max_drawdown(0.2);
+        let assess = |path: &[f64]| {
+            let mut tracker = EquityTracker::new(100.0, 4);
+            for &equity in path {
+                tracker.record(equity);
+            }
+            limits.assess(&tracker)
+        };
+
+        assert_eq!(assess(&[101.0, 102.0]).response, RiskResponse::Normal);
+        // Slow bleed: 12% from the high-water mark, but little in the window
+        let slow = assess(&[98.0, 96.0, 94.0, 92.0, 90.0, 88.0]);
+        assert_eq!(slow.response, RiskResponse::ReduceExposure);
+        assert!(!slow.reasons.is_empty());
+        // Sudden 16% fall within the window
+        assert_eq!(assess(&[100.0, 84.0]).response, RiskResponse::Pause);
+        assert_eq!(
+            assess(&[90.0, 80.0]).response,
+            RiskResponse::EmergencyWithdraw
+        );
+    }
+
+    #[test]
+    fn test_response_actions() {
+        assert!(matches!(
+            RiskResponse::ReduceExposure.action(0.6, 0.3),
+            AgentAction::Rebalance { .. }
+        ));
+        assert!(matches!(
+            RiskResponse::ReduceExposure.action(0.2, 0.3),
+            AgentAction::Hold
+        ));
+        assert!(matches!(
+            RiskResponse::Pause.action(0.6, 0.3),
+            AgentAction::Hold
+        ));
+        assert!(matches!(
+            RiskResponse::EmergencyWithdraw.action(0.0, 0.3),
+            AgentAction::EmergencyWithdraw
+        ));
+    }
+}
@@ -1,4 +1,659 @@
 //! Strategy optimization for AI agents
+//!
+//! An [`Optimizer`] searches a [`ParameterSpace`] for the parameters that
+//! maximize an [`Objective`], usually a [`BacktestObjective`] replaying
+//! historical market data through an agent built from each candidate. Grid
+//! search, random search and Bayesian optimization (a Gaussian process
+//! surrogate with expected improvement) are supported; every method returns
+//! all evaluated candidates ranked best first.
 
-// TODO: Implement strategy optimization algorithms
+use crate::agents::ml_agent::MarketData;
+use crate::agents::Agent;
+use crate::backtest::{Backtest, BacktestReport};
+use crate::error::{ManusError, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// Parameter values by name
+pub type Parameters = BTreeMap<String, f64>;
+
+/// Length scale of the Gaussian process kernel, in unit-cube coordinates
+const KERNEL_LENGTH_SCALE: f64 = 0.2;
+
+/// Observation noise added to the kernel diagonal
+const KERNEL_NOISE: f64 = 1e-6;
+
+/// Random candidates scored by expected improvement per Bayesian iteration
+const ACQUISITION_CANDIDATES: usize = 256;
+
+/// Minimum improvement expected improvement rewards, in standardized units
+const EXPLORATION: f64 = 0.01;
+
+/// One searchable parameter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    /// Name
+    pub name: String,
+    /// Lowest value searched
+    pub min: f64,
+    /// Highest value searched
+    pub max: f64,
+    /// Whether values are rounded to whole numbers, e.g. the Move `Strategy` `param_a`
+    pub integer: bool,
+}
+
+impl Parameter {
+    /// Value at `position` (0.0 - 1.0) between `min` and `max`
+    fn value(&self, position: f64) -> f64 {
+        let value = self.min + (self.max - self.min) * position.clamp(0.0, 1.0);
+        if self.integer {
+            value.round()
+        } else {
+            value
+        }
+    }
+}
+
+/// Box-bounded space of parameters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSpace {
+    parameters: Vec<Parameter>,
+}
+
+impl ParameterSpace {
+    /// Empty space
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a continuous parameter in `min..=max`
+    pub fn with(mut self, name: &str, min: f64, max: f64) -> Self {
+        self.parameters.push(Parameter {
+            name: name.to_string(),
+            min,
+            max,
+            integer: false,
+        });
+        self
+    }
+
+    /// Add a whole-number parameter in `min..=max`
+    pub fn with_integer(mut self, name: &str, min: u64, max: u64) -> Self {
+        self.parameters.push(Parameter {
+            name: name.to_string(),
+            min: min as f64,
+            max: max as f64,
+            integer: true,
+        });
+        self
+    }
+
+    /// Parameters in the order they were added
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    /// Number of dimensions
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// Whether the space has no dimensions
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.is_empty() {
+            return Err(ManusError::Agent("Parameter space is empty".to_string()));
+        }
+        for parameter in &self.parameters {
+            if !(parameter.min.is_finite() && parameter.max.is_finite())
+                || parameter.min > parameter.max
+            {
+                return Err(ManusError::Agent(format!(
+                    "Invalid range {}..={} for parameter {}",
+                    parameter.min, parameter.max, parameter.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parameters at a point of the unit cube
+    fn parameters_at(&self, point: &[f64]) -> Parameters {
+        self.parameters
+            .iter()
+            .zip(point)
+            .map(|(parameter, &position)| (parameter.name.clone(), parameter.value(position)))
+            .collect()
+    }
+}
+
+/// How an [`Optimizer`] explores the space
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SearchMethod {
+    /// Evenly spaced points along every dimension
+    Grid {
+        /// Points per dimension
+        points: usize,
+    },
+    /// Uniformly sampled points
+    Random {
+        /// Points evaluated
+        samples: usize,
+        /// RNG seed
+        seed: u64,
+    },
+    /// Random warm-up, then points maximizing expected improvement under a
+    /// Gaussian process fitted to the evaluations so far
+    Bayesian {
+        /// Random points evaluated before the surrogate is used
+        initial: usize,
+        /// Points chosen by expected improvement
+        iterations: usize,
+        /// RNG seed
+        seed: u64,
+    },
+}
+
+impl Default for SearchMethod {
+    fn default() -> Self {
+        SearchMethod::Bayesian {
+            initial: 8,
+            iterations: 16,
+            seed: 42,
+        }
+    }
+}
+
+/// What a candidate achieved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// Value being maximized
+    pub score: f64,
+    /// Profit and loss
+    pub pnl: f64,
+    /// Annualized Sharpe ratio
+    pub sharpe: f64,
+    /// Largest peak-to-trough decline (0.0 - 1.0)
+    pub max_drawdown: f64,
+    /// Traded notional divided by average equity
+    pub turnover: f64,
+}
+
+/// Evaluated candidate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    /// Candidate parameters
+    pub parameters: Parameters,
+    /// Results
+    pub metrics: Metrics,
+}
+
+/// Function being maximized
+pub trait Objective {
+    /// Evaluate `parameters`
+    fn evaluate(&self, parameters: &Parameters) -> Result<Metrics>;
+}
+
+impl<F> Objective for F
+where
+    F: Fn(&Parameters) -> Result<Metrics>,
+{
+    fn evaluate(&self, parameters: &Parameters) -> Result<Metrics> {
+        self(parameters)
+    }
+}
+
+/// Backtest statistic used as the score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Annualized Sharpe ratio
+    #[default]
+    Sharpe,
+    /// Return on initial value
+    Return,
+    /// Return divided by max drawdown, floored at 1%
+    Calmar,
+}
+
+impl Metric {
+    /// Score a backtest report
+    pub fn score(&self, report: &BacktestReport) -> f64 {
+        let total_return = if report.initial_value > 0.0 {
+            report.pnl / report.initial_value
+        } else {
+            0.0
+        };
+        match self {
+            Metric::Sharpe => report.sharpe,
+            Metric::Return => total_return,
+            Metric::Calmar => total_return / report.max_drawdown.max(0.01),
+        }
+    }
+}
+
+/// Scores candidates by backtesting the agent they configure
+pub struct BacktestObjective<'a, F> {
+    series: &'a [MarketData],
+    backtest: Backtest,
+    metric: Metric,
+    build: F,
+}
+
+impl<'a, F> BacktestObjective<'a, F>
+where
+    F: Fn(&Parameters) -> Result<Box<dyn Agent>>,
+{
+    /// Backtest agents built by `build` over `series`
+    pub fn new(series: &'a [MarketData], build: F) -> Self {
+        Self {
+            series,
+            backtest: Backtest::new(),
+            metric: Metric::default(),
+            build,
+        }
+    }
+
+    /// Replay with `backtest`
+    pub fn with_backtest(mut self, backtest: Backtest) -> Self {
+        self.backtest = backtest;
+        self
+    }
+
+    /// Maximize `metric`
+    pub fn with_metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+}
+
+impl<F> Objective for BacktestObjective<'_, F>
+where
+    F: Fn(&Parameters) -> Result<Box<dyn Agent>>,
+{
+    fn evaluate(&self, parameters: &Parameters) -> Result<Metrics> {
+        let mut agent = (self.build)(parameters)?;
+        let report = self.backtest.run(agent.as_mut(), self.series)?;
+        Ok(Metrics {
+            score: self.metric.score(&report),
+            pnl: report.pnl,
+            sharpe: report.sharpe,
+            max_drawdown: report.max_drawdown,
+            turnover: report.turnover,
+        })
+    }
+}
+
+/// Searches a parameter space
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    space: ParameterSpace,
+    method: SearchMethod,
+}
+
+impl Optimizer {
+    /// Search `space` with `method`
+    pub fn new(space: ParameterSpace, method: SearchMethod) -> Self {
+        Self { space, method }
+    }
+
+    /// Evaluate candidates and rank them, best score first
+    ///
+    /// Candidates with a non-finite score rank last.
+    pub fn run(&self, objective: &dyn Objective) -> Result<Vec<Evaluation>> {
+        self.space.validate()?;
+        let dimensions = self.space.len();
+
+        let mut trials = Vec::new();
+        match self.method {
+            SearchMethod::Grid { points } => {
+                for point in grid(dimensions, points.max(1)) {
+                    self.evaluate(objective, point, &mut trials)?;
+                }
+            }
+            SearchMethod::Random { samples, seed } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                for _ in 0..samples.max(1) {
+                    self.evaluate(objective, random_point(&mut rng, dimensions), &mut trials)?;
+                }
+            }
+            SearchMethod::Bayesian {
+                initial,
+                iterations,
+                seed,
+            } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                for _ in 0..initial.max(2) {
+                    self.evaluate(objective, random_point(&mut rng, dimensions), &mut trials)?;
+                }
+                for _ in 0..iterations {
+                    let candidates: Vec<Vec<f64>> = (0..ACQUISITION_CANDIDATES)
+                        .map(|_| random_point(&mut rng, dimensions))
+                        .collect();
+                    let next = match GaussianProcess::fit(&trials) {
+                        Some(surrogate) => surrogate.best_candidate(candidates),
+                        // Degenerate observations, keep exploring at random
+                        None => candidates.into_iter().next(),
+                    };
+                    if let Some(point) = next {
+                        self.evaluate(objective, point, &mut trials)?;
+                    }
+                }
+            }
+        }
+
+        let mut evaluations: Vec<Evaluation> = trials
+            .into_iter()
+            .map(|(_, evaluation)| evaluation)
+            .collect();
+        evaluations.sort_by(|a, b| rank(b.metrics.score).total_cmp(&rank(a.metrics.score)));
+        Ok(evaluations)
+    }
+
+    /// Evaluate the candidate at `point` of the unit cube
+    fn evaluate(
+        &self,
+        objective: &dyn Objective,
+        point: Vec<f64>,
+        trials: &mut Vec<(Vec<f64>, Evaluation)>,
+    ) -> Result<()> {
+        let parameters = self.space.parameters_at(&point);
+        let metrics = objective.evaluate(&parameters)?;
+        trials.push((
+            point,
+            Evaluation {
+                parameters,
+                metrics,
+            },
+        ));
+        Ok(())
+    }
+}
+
+fn rank(score: f64) -> f64 {
+    if score.is_finite() {
+        score
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+/// Cartesian grid of `points` positions per dimension
+fn grid(dimensions: usize, points: usize) -> Vec<Vec<f64>> {
+    let positions: Vec<f64> = if points == 1 {
+        vec![0.5]
+    } else {
+        (0..points)
+            .map(|i| i as f64 / (points - 1) as f64)
+            .collect()
+    };
+
+    let mut grid = vec![Vec::with_capacity(dimensions)];
+    for _ in 0..dimensions {
+        grid = grid
+            .into_iter()
+            .flat_map(|prefix| {
+                positions.iter().map(move |&position| {
+                    let mut point = prefix.clone();
+                    point.push(position);
+                    point
+                })
+            })
+            .collect();
+    }
+    grid
+}
+
+fn random_point(rng: &mut StdRng, dimensions: usize) -> Vec<f64> {
+    (0..dimensions).map(|_| rng.gen::<f64>()).collect()
+}
+
+/// Gaussian process regression with a squared exponential kernel
+struct GaussianProcess {
+    points: Vec<Vec<f64>>,
+    /// Lower Cholesky factor of the kernel matrix
+    cholesky: Vec<Vec<f64>>,
+    /// Kernel matrix inverse times the standardized scores
+    weights: Vec<f64>,
+    /// Best standardized score
+    best: f64,
+}
+
+impl GaussianProcess {
+    /// Fit to finite scores, standardized to zero mean and unit variance
+    fn fit(trials: &[(Vec<f64>, Evaluation)]) -> Option<Self> {
+        let (points, scores): (Vec<Vec<f64>>, Vec<f64>) = trials
+            .iter()
+            .filter(|(_, evaluation)| evaluation.metrics.score.is_finite())
+            .map(|(point, evaluation)| (point.clone(), evaluation.metrics.score))
+            .unzip();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+        let std =
+            (scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / scores.len() as f64).sqrt();
+        if std == 0.0 {
+            return None;
+        }
+        let targets: Vec<f64> = scores.iter().map(|s| (s - mean) / std).collect();
+
+        let n = points.len();
+        let mut matrix = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                matrix[i][j] = kernel(&points[i], &points[j]);
+            }
+            matrix[i][i] += KERNEL_NOISE;
+        }
+        let cholesky = cholesky(&matrix)?;
+        let weights = solve_upper(&cholesky, &solve_lower(&cholesky, &targets));
+
+        Some(Self {
+            points,
+            cholesky,
+            weights,
+            best: targets.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// Posterior mean and standard deviation at `point`
+    fn predict(&self, point: &[f64]) -> (f64, f64) {
+        let covariances: Vec<f64> = self.points.iter().map(|p| kernel(p, point)).collect();
+        let mean = covariances
+            .iter()
+            .zip(&self.weights)
+            .map(|(k, w)| k * w)
+            .sum();
+        let v = solve_lower(&self.cholesky, &covariances);
+        let variance = 1.0 - v.iter().map(|x| x * x).sum::<f64>();
+        (mean, variance.max(1e-12).sqrt())
+    }
+
+    fn expected_improvement(&self, point: &[f64]) -> f64 {
+        let (mean, std) = self.predict(point);
+        let improvement = mean - self.best - EXPLORATION;
+        let z = improvement / std;
+        improvement * normal_cdf(z) + std * normal_pdf(z)
+    }
+
+    fn best_candidate(&self, candidates: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+        candidates
+            .into_iter()
+            .map(|point| (self.expected_improvement(&point), point))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, point)| point)
+    }
+}
+
+fn kernel(a: &[f64], b: &[f64]) -> f64 {
+    let distance: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-distance / (2.0 * KERNEL_LENGTH_SCALE * KERNEL_LENGTH_SCALE)).exp()
+}
+
+/// Lower triangular `L` with `L Lᵀ = matrix`, if positive definite
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                let diagonal = matrix[i][i] - sum;
+                if diagonal <= 0.0 {
+                    return None;
+                }
+                lower[i][j] = diagonal.sqrt();
+            } else {
+                lower[i][j] = (matrix[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// Solve `L x = b` by forward substitution
+fn solve_lower(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| lower[i][k] * x[k]).sum();
+        x[i] = (b[i] - sum) / lower[i][i];
+    }
+    x
+}
+
+/// Solve `Lᵀ x = b` by back substitution
+fn solve_upper(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let n = b.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = (i + 1..n).map(|k| lower[k][i] * x[k]).sum();
+        x[i] = (b[i] - sum) / lower[i][i];
+    }
+    x
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz and Stegun 7.1.26, accurate to 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let value = 1.0 - polynomial * (-x * x).exp();
+    if x < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concave objective peaking at a = 0.3, b = 7
+    fn quadratic(parameters: &Parameters) -> Result<Metrics> {
+        let (a, b) = (parameters["a"], parameters["b"]);
+        Ok(Metrics {
+            score: -(a - 0.3).powi(2) - ((b - 7.0) / 10.0).powi(2),
+            ..Metrics::default()
+        })
+    }
+
+    fn space() -> ParameterSpace {
+        ParameterSpace::new()
+            .with("a", 0.0, 1.0)
+            .with_integer("b", 0, 10)
+    }
+
+    #[test]
+    fn test_grid_search() {
+        let ranked = Optimizer::new(space(), SearchMethod::Grid { points: 11 })
+            .run(&quadratic)
+            .unwrap();
+        assert_eq!(ranked.len(), 121);
+        assert!((ranked[0].parameters["a"] - 0.3).abs() < 1e-9);
+        assert_eq!(ranked[0].parameters["b"], 7.0);
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].metrics.score >= pair[1].metrics.score));
+    }
+
+    #[test]
+    fn test_random_search_is_seeded() {
+        let method = SearchMethod::Random {
+            samples: 20,
+            seed: 7,
+        };
+        let first = Optimizer::new(space(), method).run(&quadratic).unwrap();
+        let second = Optimizer::new(space(), method).run(&quadratic).unwrap();
+        assert_eq!(first, second);
+        assert!(first
+            .iter()
+            .all(|evaluation| evaluation.parameters["b"].fract() == 0.0));
+    }
+
+    #[test]
+    fn test_bayesian_beats_its_warm_up() {
+        let method = SearchMethod::Bayesian {
+            initial: 5,
+            iterations: 20,
+            seed: 3,
+        };
+        let ranked = Optimizer::new(space(), method).run(&quadratic).unwrap();
+        assert_eq!(ranked.len(), 25);
+
+        let warm_up = Optimizer::new(
+            space(),
+            SearchMethod::Random {
+                samples: 5,
+                seed: 3,
+            },
+        )
+        .run(&quadratic)
+        .unwrap();
+        assert!(ranked[0].metrics.score >= warm_up[0].metrics.score);
+        assert!(ranked[0].metrics.score > -0.01);
+    }
+
+    #[test]
+    fn test_invalid_space() {
+        let method = SearchMethod::default();
+        assert!(Optimizer::new(ParameterSpace::new(), method)
+            .run(&quadratic)
+            .is_err());
+        assert!(
+            Optimizer::new(ParameterSpace::new().with("a", 1.0, 0.0), method)
+                .run(&quadratic)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_linear_algebra() {
+        let matrix = vec![vec![4.0, 2.0], vec![2.0, 3.0]];
+        let lower = cholesky(&matrix).unwrap();
+        let x = solve_upper(&lower, &solve_lower(&lower, &[2.0, 1.0]));
+        assert!((4.0 * x[0] + 2.0 * x[1] - 2.0).abs() < 1e-12);
+        assert!((2.0 * x[0] + 3.0 * x[1] - 1.0).abs() < 1e-12);
+        assert!(cholesky(&[vec![0.0]]).is_none());
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+    }
+}
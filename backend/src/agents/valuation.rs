@@ -0,0 +1,476 @@
+//! Mark-to-market valuation and portfolio invariants
+//!
+//! Positions are valued at prices from a [`PriceOracle`], falling back to
+//! their cost basis for assets the oracle cannot price. An [`InvariantSet`]
+//! checks the resulting [`Valuation`] against drawdown, exposure, cash and
+//! leverage limits and returns every breach in an [`InvariantReport`].
+
+use crate::agents::rebalancer::QUOTE_ASSET;
+use crate::agents::registry::DEFAULT_MAX_DRAWDOWN;
+use crate::agents::{AgentAction, AgentState};
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Slack allowed when comparing ratios against their limits
+const TOLERANCE: f64 = 1e-9;
+
+/// Source of current asset prices, in capital units per unit of asset
+pub trait PriceOracle {
+    /// Current price of `asset`, if known
+    fn price(&self, asset: &str) -> Option<f64>;
+}
+
+/// Oracle that knows no prices, so positions are valued at cost
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CostBasis;
+
+impl PriceOracle for CostBasis {
+    fn price(&self, _asset: &str) -> Option<f64> {
+        None
+    }
+}
+
+impl PriceOracle for HashMap<String, f64> {
+    fn price(&self, asset: &str) -> Option<f64> {
+        self.get(asset).copied()
+    }
+}
+
+/// Value of one position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionValue {
+    /// Asset identifier
+    pub asset: String,
+    /// Amount held
+    pub amount: u64,
+    /// Price the position was valued at
+    pub price: f64,
+    /// Amount times price
+    pub value: f64,
+    /// Whether the price came from the oracle rather than the cost basis
+    pub marked: bool,
+}
+
+/// Portfolio marked to market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Valuation {
+    /// Capital plus positions in the quote asset
+    pub cash: f64,
+    /// Every position other than the quote asset
+    pub positions: Vec<PositionValue>,
+    /// Cash plus position values
+    pub equity: f64,
+}
+
+impl Valuation {
+    /// Value `state` at `oracle` prices
+    pub fn new(state: &AgentState, oracle: &dyn PriceOracle) -> Self {
+        let mut cash = state.capital as f64;
+        let mut positions = Vec::new();
+        for position in &state.positions {
+            if position.asset == QUOTE_ASSET {
+                cash += position.amount as f64;
+                continue;
+            }
+            let marked = oracle
+                .price(&position.asset)
+                .filter(|price| price.is_finite());
+            let price = marked.unwrap_or(position.entry_price);
+            positions.push(PositionValue {
+                asset: position.asset.clone(),
+                amount: position.amount,
+                price,
+                value: position.amount as f64 * price,
+                marked: marked.is_some(),
+            });
+        }
+
+        let equity = cash + positions.iter().map(|p| p.value).sum::<f64>();
+        Self {
+            cash,
+            positions,
+            equity,
+        }
+    }
+
+    /// Total value of non-cash positions
+    pub fn gross_exposure(&self) -> f64 {
+        self.positions.iter().map(|p| p.value.abs()).sum()
+    }
+
+    /// Share of equity held in `asset`
+    pub fn exposure(&self, asset: &str) -> f64 {
+        let value: f64 = self
+            .positions
+            .iter()
+            .filter(|p| p.asset == asset)
+            .map(|p| p.value)
+            .sum();
+        ratio(value, self.equity)
+    }
+
+    /// Gross exposure divided by equity
+    pub fn leverage(&self) -> f64 {
+        ratio(self.gross_exposure(), self.equity)
+    }
+
+    /// Share of equity held as cash
+    pub fn cash_ratio(&self) -> f64 {
+        ratio(self.cash, self.equity)
+    }
+
+    /// Loss of equity relative to `initial_capital` (0.0 when in profit)
+    pub fn drawdown(&self, initial_capital: u64) -> f64 {
+        if initial_capital == 0 {
+            return 0.0;
+        }
+        ((initial_capital as f64 - self.equity) / initial_capital as f64).max(0.0)
+    }
+}
+
+/// Ratio of `value` to `equity`; without equity, any value is unbounded
+fn ratio(value: f64, equity: f64) -> f64 {
+    if equity > 0.0 {
+        value / equity
+    } else if value > 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    }
+}
+
+/// Kinds of portfolio invariant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Invariant {
+    /// Equity lost since inception
+    MaxDrawdown,
+    /// Share of equity in any one asset
+    MaxExposure,
+    /// Share of equity held as cash
+    MinCashBuffer,
+    /// Gross exposure relative to equity
+    MaxLeverage,
+}
+
+/// One breached invariant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    /// Invariant breached
+    pub invariant: Invariant,
+    /// Asset concerned, for exposure limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+    /// Configured limit
+    pub limit: f64,
+    /// Value observed
+    pub actual: f64,
+}
+
+impl InvariantViolation {
+    /// How far the observed value is past the limit
+    pub fn excess(&self) -> f64 {
+        match self.invariant {
+            Invariant::MinCashBuffer => self.limit - self.actual,
+            _ => self.actual - self.limit,
+        }
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.invariant {
+            Invariant::MaxDrawdown => write!(
+                f,
+                "drawdown {:.2}% exceeds {:.2}%",
+                self.actual * 100.0,
+                self.limit * 100.0
+            ),
+            Invariant::MaxExposure => write!(
+                f,
+                "{} exposure {:.2}% exceeds {:.2}%",
+                self.asset.as_deref().unwrap_or("asset"),
+                self.actual * 100.0,
+                self.limit * 100.0
+            ),
+            Invariant::MinCashBuffer => write!(
+                f,
+                "cash buffer {:.2}% is below {:.2}%",
+                self.actual * 100.0,
+                self.limit * 100.0
+            ),
+            Invariant::MaxLeverage => {
+                write!(f, "leverage {:.2}x exceeds {:.2}x", self.actual, self.limit)
+            }
+        }
+    }
+}
+
+/// Limits a portfolio must stay within; unset limits are not checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvariantSet {
+    /// Largest loss of equity since inception (0.0 - 1.0)
+    pub max_drawdown: Option<f64>,
+    /// Largest share of equity in one asset (0.0 - 1.0)
+    pub max_exposure: Option<f64>,
+    /// Smallest share of equity held as cash (0.0 - 1.0)
+    pub min_cash_buffer: Option<f64>,
+    /// Largest gross exposure relative to equity
+    pub max_leverage: Option<f64>,
+}
+
+impl Default for InvariantSet {
+    fn default() -> Self {
+        Self {
+            max_drawdown: Some(DEFAULT_MAX_DRAWDOWN),
+            max_exposure: None,
+            min_cash_buffer: None,
+            // Agents trade from their own capital
+            max_leverage: Some(1.0),
+        }
+    }
+}
+
+impl InvariantSet {
+    /// Set the largest loss of equity since inception
+    pub fn with_max_drawdown(mut self, max_drawdown: f64) -> Self {
+        self.max_drawdown = Some(max_drawdown);
+        self
+    }
+
+    /// Set the largest share of equity in one asset
+    pub fn with_max_exposure(mut self, max_exposure: f64) -> Self {
+        self.max_exposure = Some(max_exposure);
+        self
+    }
+
+    /// Set the smallest share of equity held as cash
+    pub fn with_min_cash_buffer(mut self, min_cash_buffer: f64) -> Self {
+        self.min_cash_buffer = Some(min_cash_buffer);
+        self
+    }
+
+    /// Set the largest gross exposure relative to equity
+    pub fn with_max_leverage(mut self, max_leverage: f64) -> Self {
+        self.max_leverage = Some(max_leverage);
+        self
+    }
+
+    /// Check `state` valued at `oracle` prices
+    pub fn check(&self, state: &AgentState, oracle: &dyn PriceOracle) -> InvariantReport {
+        let valuation = Valuation::new(state, oracle);
+        let mut violations = Vec::new();
+        let mut breach = |invariant, asset: Option<&str>, limit: f64, actual: f64| {
+            violations.push(InvariantViolation {
+                invariant,
+                asset: asset.map(str::to_string),
+                limit,
+                actual,
+            })
+        };
+
+        if let Some(limit) = self.max_drawdown {
+            let drawdown = valuation.drawdown(state.initial_capital);
+            if drawdown > limit + TOLERANCE {
+                breach(Invariant::MaxDrawdown, None, limit, drawdown);
+            }
+        }
+        if let Some(limit) = self.max_exposure {
+            for position in &valuation.positions {
+                let exposure = valuation.exposure(&position.asset);
+                if exposure > limit + TOLERANCE {
+                    breach(
+                        Invariant::MaxExposure,
+                        Some(&position.asset),
+                        limit,
+                        exposure,
+                    );
+                }
+            }
+        }
+        if let Some(limit) = self.min_cash_buffer {
+            let cash = valuation.cash_ratio();
+            if cash < limit - TOLERANCE {
+                breach(Invariant::MinCashBuffer, None, limit, cash);
+            }
+        }
+        if let Some(limit) = self.max_leverage {
+            let leverage = valuation.leverage();
+            if leverage > limit + TOLERANCE {
+                breach(Invariant::MaxLeverage, None, limit, leverage);
+            }
+        }
+
+        InvariantReport {
+            agent_id: state.id.clone(),
+            valuation,
+            violations,
+        }
+    }
+}
+
+/// Result of checking an agent's invariants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvariantReport {
+    /// Agent ID
+    pub agent_id: String,
+    /// Valuation the invariants were checked against
+    pub valuation: Valuation,
+    /// Breached invariants
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl InvariantReport {
+    /// Whether every invariant holds
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Violations in `self` that were not present in `before`, or are further past their limit
+    pub fn introduced_since(&self, before: &InvariantReport) -> Vec<&InvariantViolation> {
+        self.introduced(before, true)
+    }
+
+    /// Violations `action` introduced in `self`, starting from `before`
+    ///
+    /// Risk-reducing actions pay fees to get out, so they may deepen a breach
+    /// that was already present; only new violations count against them.
+    pub fn introduced_by(
+        &self,
+        action: &AgentAction,
+        before: &InvariantReport,
+    ) -> Vec<&InvariantViolation> {
+        self.introduced(before, !action.reduces_risk())
+    }
+
+    fn introduced(&self, before: &InvariantReport, deepened: bool) -> Vec<&InvariantViolation> {
+        self.violations
+            .iter()
+            .filter(|violation| {
+                !before.violations.iter().any(|previous| {
+                    previous.invariant == violation.invariant
+                        && previous.asset == violation.asset
+                        && (!deepened || violation.excess() <= previous.excess() + TOLERANCE)
+                })
+            })
+            .collect()
+    }
+
+    /// `Ok` if every invariant holds, otherwise an error listing the violations
+    pub fn into_result(self) -> Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(ManusError::Agent(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "All invariants hold for {}", self.agent_id);
+        }
+        write!(f, "Invariant violation for {}: ", self.agent_id)?;
+        for (index, violation) in self.violations.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::rebalancer::BASE_ASSET;
+    use crate::agents::Position;
+
+    fn state(capital: u64, sui: u64, entry_price: f64) -> AgentState {
+        AgentState {
+            id: "agent".to_string(),
+            capital,
+            initial_capital: 1_000,
+            positions: vec![Position {
+                asset: BASE_ASSET.to_string(),
+                amount: sui,
+                entry_price,
+            }],
+            risk_tolerance: 0.5,
+        }
+    }
+
+    fn prices(price: f64) -> HashMap<String, f64> {
+        HashMap::from([(BASE_ASSET.to_string(), price)])
+    }
+
+    #[test]
+    fn test_valuation_marks_to_market() {
+        let state = state(500, 500, 1.0);
+        assert_eq!(Valuation::new(&state, &CostBasis).equity, 1_000.0);
+
+        let valuation = Valuation::new(&state, &prices(0.5));
+        assert_eq!(valuation.equity, 750.0);
+        assert!(valuation.positions[0].marked);
+        assert!((valuation.exposure(BASE_ASSET) - 1.0 / 3.0).abs() < 1e-12);
+        assert!((valuation.cash_ratio() - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(valuation.drawdown(1_000), 0.25);
+    }
+
+    #[test]
+    fn test_cost_basis_hides_real_losses() {
+        let state = state(500, 500, 1.0);
+        let invariants = InvariantSet::default();
+        assert!(invariants.check(&state, &CostBasis).is_ok());
+
+        let report = invariants.check(&state, &prices(0.5));
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].invariant, Invariant::MaxDrawdown);
+        assert_eq!(report.violations[0].actual, 0.25);
+        assert!(report.clone().into_result().is_err());
+        assert_eq!(
+            report.to_string(),
+            "Invariant violation for agent: drawdown 25.00% exceeds 20.00%"
+        );
+    }
+
+    #[test]
+    fn test_configured_limits() {
+        let invariants = InvariantSet::default()
+            .with_max_exposure(0.5)
+            .with_min_cash_buffer(0.4)
+            .with_max_leverage(0.5);
+        let report = invariants.check(&state(300, 700, 1.0), &prices(1.0));
+        let kinds: Vec<Invariant> = report.violations.iter().map(|v| v.invariant).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Invariant::MaxExposure,
+                Invariant::MinCashBuffer,
+                Invariant::MaxLeverage
+            ]
+        );
+        assert_eq!(report.violations[0].asset.as_deref(), Some(BASE_ASSET));
+
+        let before = invariants.check(&state(500, 500, 1.0), &prices(1.0));
+        assert!(before.is_ok());
+        assert_eq!(report.introduced_since(&before).len(), 3);
+        assert!(report.introduced_since(&report).is_empty());
+
+        // Breaches already present count again once they deepen
+        let deeper = invariants.check(&state(200, 800, 1.0), &prices(1.0));
+        assert_eq!(deeper.introduced_since(&report).len(), 3);
+        assert!(report.introduced_since(&deeper).is_empty());
+        assert!(deeper
+            .introduced_by(&AgentAction::EmergencyWithdraw, &report)
+            .is_empty());
+
+        let json = serde_json::to_value(&report.violations[1]).unwrap();
+        assert_eq!(json["invariant"], "min_cash_buffer");
+    }
+}
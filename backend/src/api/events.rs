@@ -0,0 +1,263 @@
+//! Typed events streamed to dashboards over `/ws`
+
+use crate::agents::ml_agent::MLDecision;
+use crate::agents::valuation::InvariantViolation;
+use crate::agents::AgentAction;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before it starts lagging
+const EVENT_BUFFER: usize = 1024;
+
+/// Subscription topics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    /// Actions chosen or executed by agents
+    AgentDecisions,
+    /// Confidence and risk scores behind ML decisions
+    MlDecisions,
+    /// Invariant violations reported by `Agent::invariant_report`
+    Invariants,
+    /// Vault balance changes after deposits and withdrawals
+    Vaults,
+}
+
+impl Topic {
+    /// Every topic, the default subscription
+    pub const ALL: [Topic; 4] = [
+        Topic::AgentDecisions,
+        Topic::MlDecisions,
+        Topic::Invariants,
+        Topic::Vaults,
+    ];
+}
+
+/// Event published on the bus
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// An agent decided on, or executed, an action
+    AgentDecision {
+        /// Agent ID
+        agent_id: String,
+        /// Chosen action
+        action: AgentAction,
+        /// Whether the action was executed rather than only proposed
+        executed: bool,
+    },
+
+    /// Scores behind an ML agent's most recent decision
+    MlDecision {
+        /// Agent ID
+        agent_id: String,
+        /// Decision details
+        #[serde(flatten)]
+        decision: MLDecision,
+    },
+
+    /// An agent failed its invariant check
+    InvariantViolation {
+        /// Agent ID
+        agent_id: String,
+        /// Violation message
+        message: String,
+        /// Each breached invariant with its limit and observed value
+        violations: Vec<InvariantViolation>,
+    },
+
+    /// A vault's balance changed
+    VaultBalance {
+        /// Vault ID
+        vault_id: String,
+        /// Digest of the transaction that changed it
+        transaction_digest: String,
+        /// Balance held by the vault
+        balance: u64,
+        /// Total underlying value
+        total_value: u64,
+        /// Total shares issued
+        total_shares: u64,
+    },
+}
+
+impl Event {
+    /// Topic the event is published under
+    pub fn topic(&self) -> Topic {
+        match self {
+            Event::AgentDecision { .. } => Topic::AgentDecisions,
+            Event::MlDecision { .. } => Topic::MlDecisions,
+            Event::InvariantViolation { .. } => Topic::Invariants,
+            Event::VaultBalance { .. } => Topic::Vaults,
+        }
+    }
+}
+
+/// Wire format of an event: its topic alongside the tagged payload
+#[derive(Debug, Clone, Serialize)]
+pub struct Envelope<'a> {
+    /// Topic the event was published under
+    pub topic: Topic,
+    /// Event payload
+    #[serde(flatten)]
+    pub event: &'a Event,
+}
+
+impl<'a> From<&'a Event> for Envelope<'a> {
+    fn from(event: &'a Event) -> Self {
+        Self {
+            topic: event.topic(),
+            event,
+        }
+    }
+}
+
+/// Broadcast bus fanning events out to WebSocket subscribers
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    /// Create a bus with the default buffer size
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    /// Publish an event to current subscribers
+    ///
+    /// Events published while nobody is subscribed are dropped.
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribe to all subsequent events
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Message sent by WebSocket clients to change their subscription
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Add topics
+    Subscribe {
+        /// Topics to add
+        topics: Vec<Topic>,
+    },
+    /// Remove topics
+    Unsubscribe {
+        /// Topics to remove
+        topics: Vec<Topic>,
+    },
+}
+
+/// Topics a single WebSocket connection is subscribed to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    topics: BTreeSet<Topic>,
+}
+
+impl Subscription {
+    /// Subscription to the given topics
+    pub fn new(topics: impl IntoIterator<Item = Topic>) -> Self {
+        Self {
+            topics: topics.into_iter().collect(),
+        }
+    }
+
+    /// Parse a comma-separated topic list such as `agent_decisions,vaults`
+    ///
+    /// `None` subscribes to every topic.
+    pub fn parse(query: Option<&str>) -> Result<Self, String> {
+        let Some(query) = query else {
+            return Ok(Self::new(Topic::ALL));
+        };
+
+        query
+            .split(',')
+            .map(str::trim)
+            .filter(|topic| !topic.is_empty())
+            .map(|topic| {
+                serde_json::from_value(serde_json::Value::String(topic.to_string()))
+                    .map_err(|_| format!("Unknown topic: {}", topic))
+            })
+            .collect::<Result<BTreeSet<_>, _>>()
+            .map(|topics| Self { topics })
+    }
+
+    /// Apply a client message
+    pub fn apply(&mut self, message: ClientMessage) {
+        match message {
+            ClientMessage::Subscribe { topics } => self.topics.extend(topics),
+            ClientMessage::Unsubscribe { topics } => {
+                for topic in topics {
+                    self.topics.remove(&topic);
+                }
+            }
+        }
+    }
+
+    /// Whether `event` should be forwarded
+    pub fn matches(&self, event: &Event) -> bool {
+        self.topics.contains(&event.topic())
+    }
+
+    /// Subscribed topics, in order
+    pub fn topics(&self) -> Vec<Topic> {
+        self.topics.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation() -> Event {
+        Event::InvariantViolation {
+            agent_id: "a".to_string(),
+            message: "capital loss".to_string(),
+            violations: vec![],
+        }
+    }
+
+    #[test]
+    fn test_subscription_parsing_and_updates() {
+        assert_eq!(
+            Subscription::parse(None).unwrap().topics(),
+            Topic::ALL.to_vec()
+        );
+        assert!(Subscription::parse(Some("vaults,bogus")).is_err());
+
+        let mut subscription = Subscription::parse(Some("vaults, agent_decisions")).unwrap();
+        assert!(!subscription.matches(&violation()));
+
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"op": "subscribe", "topics": ["invariants"]}"#).unwrap();
+        subscription.apply(message);
+        assert!(subscription.matches(&violation()));
+
+        subscription.apply(ClientMessage::Unsubscribe {
+            topics: vec![Topic::Invariants],
+        });
+        assert!(!subscription.matches(&violation()));
+    }
+
+    #[test]
+    fn test_envelope_wire_format() {
+        let event = violation();
+        let json = serde_json::to_value(Envelope::from(&event)).unwrap();
+        assert_eq!(json["topic"], "invariants");
+        assert_eq!(json["type"], "invariant_violation");
+        assert_eq!(json["agent_id"], "a");
+    }
+}
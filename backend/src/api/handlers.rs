@@ -1,7 +1,20 @@
 //! API request handlers
 
+use crate::agents::checkpoint::{Checkpoint, Checkpointer};
+use crate::agents::circuit_breaker::CircuitBreaker;
+use crate::agents::ml_agent::MLDecision;
+use crate::agents::orchestrator::{execute_checked, Rollback};
+use crate::agents::order_manager::{ManagedOrder, OrderManager, ReconcileReport};
+use crate::agents::registry::{AgentInfo, AgentSpec};
+use crate::agents::{AgentAction, AgentState};
+use crate::api::events::Event;
+use crate::api::AppState;
+use crate::attribution::Attribution;
+use crate::error::ManusError;
+use crate::storage::{ActionRecord, TransactionKind, TransactionRecord, VaultSnapshot};
+use crate::sui::{vault_math, VaultInfo, VaultReceipt};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -22,15 +35,6 @@ pub async fn health_check() -> Json<HealthResponse> {
     })
 }
 
-/// Vault information
-#[derive(Serialize)]
-pub struct VaultInfo {
-    id: String,
-    total_value: u64,
-    total_shares: u64,
-    strategy: String,
-}
-
 /// List all vaults
 pub async fn list_vaults() -> Json<Vec<VaultInfo>> {
     // TODO: Implement actual vault listing from Sui blockchain
@@ -38,9 +42,48 @@ pub async fn list_vaults() -> Json<Vec<VaultInfo>> {
 }
 
 /// Get vault by ID
-pub async fn get_vault(Path(id): Path<String>) -> Result<Json<VaultInfo>, StatusCode> {
-    // TODO: Implement actual vault retrieval from Sui blockchain
-    Err(StatusCode::NOT_IMPLEMENTED)
+pub async fn get_vault(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<VaultInfo>, StatusCode> {
+    state.sui.get_vault(&id).await.map(Json).map_err(sui_error)
+}
+
+/// Time window of an attribution, in milliseconds since the Unix epoch
+#[derive(Deserialize)]
+pub struct AttributionQuery {
+    /// Start of the window; the first recorded snapshot when omitted
+    from: Option<u64>,
+    /// End of the window; the latest recorded snapshot when omitted
+    to: Option<u64>,
+}
+
+/// Break a vault's PnL down into fees, impermanent loss, price drift and rebalancing cost
+pub async fn vault_attribution(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AttributionQuery>,
+) -> Result<Json<Attribution>, StatusCode> {
+    let (from, to) = (query.from.unwrap_or(0), query.to.unwrap_or(u64::MAX));
+    if from > to {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    state
+        .attribution
+        .attribute(&id, from, to)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            ManusError::Agent(e) => {
+                tracing::debug!("No attribution for vault {}: {}", id, e);
+                StatusCode::NOT_FOUND
+            }
+            e => {
+                tracing::error!("Attribution of vault {} failed: {}", id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
 }
 
 /// Deposit request
@@ -55,12 +98,60 @@ pub struct DepositRequest {
 pub struct DepositResponse {
     transaction_digest: String,
     shares_minted: u64,
+    gas_used: u64,
 }
 
 /// Handle deposit
-pub async fn deposit(Json(req): Json<DepositRequest>) -> Result<Json<DepositResponse>, StatusCode> {
-    // TODO: Implement actual deposit transaction to Sui blockchain
-    Err(StatusCode::NOT_IMPLEMENTED)
+pub async fn deposit(
+    State(state): State<AppState>,
+    Json(req): Json<DepositRequest>,
+) -> Result<Json<DepositResponse>, StatusCode> {
+    // Quote first so deposits the vault would abort never reach the network
+    let quote = state
+        .sui
+        .quote_deposit(&req.vault_id, req.amount)
+        .await
+        .map_err(sui_error)?;
+
+    let timer = state.metrics.transaction_latency.start_timer();
+    let receipt = state
+        .sui
+        .deposit(&req.vault_id, quote.amount)
+        .await
+        .map_err(sui_error)?;
+    timer.observe_duration();
+    state.metrics.transactions_total.inc();
+
+    record_transaction(&state, &req.vault_id, TransactionKind::Deposit, &receipt).await;
+
+    if receipt.shares != quote.shares {
+        tracing::warn!(
+            "Deposit {} minted {} shares, quoted {}",
+            receipt.digest,
+            receipt.shares,
+            quote.shares
+        );
+    }
+
+    Ok(Json(DepositResponse {
+        transaction_digest: receipt.digest,
+        shares_minted: receipt.shares,
+        gas_used: receipt.gas_used,
+    }))
+}
+
+/// Quote the shares a deposit would mint
+pub async fn quote_deposit(
+    State(state): State<AppState>,
+    Json(req): Json<DepositRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let quote = state
+        .sui
+        .quote_deposit(&req.vault_id, req.amount)
+        .await
+        .map_err(sui_error)?;
+
+    Ok(Json(QuoteResponse::new(req.vault_id, quote)))
 }
 
 /// Withdrawal request
@@ -75,12 +166,511 @@ pub struct WithdrawRequest {
 pub struct WithdrawResponse {
     transaction_digest: String,
     amount_withdrawn: u64,
+    gas_used: u64,
 }
 
 /// Handle withdrawal
-pub async fn withdraw(Json(req): Json<WithdrawRequest>) -> Result<Json<WithdrawResponse>, StatusCode> {
-    // TODO: Implement actual withdrawal transaction to Sui blockchain
-    Err(StatusCode::NOT_IMPLEMENTED)
+pub async fn withdraw(
+    State(state): State<AppState>,
+    Json(req): Json<WithdrawRequest>,
+) -> Result<Json<WithdrawResponse>, StatusCode> {
+    let quote = state
+        .sui
+        .quote_withdrawal(&req.vault_id, req.shares)
+        .await
+        .map_err(sui_error)?;
+
+    let timer = state.metrics.transaction_latency.start_timer();
+    let receipt = state
+        .sui
+        .withdraw(&req.vault_id, quote.shares)
+        .await
+        .map_err(sui_error)?;
+    timer.observe_duration();
+    state.metrics.transactions_total.inc();
+
+    record_transaction(&state, &req.vault_id, TransactionKind::Withdrawal, &receipt).await;
+
+    if receipt.amount != quote.amount {
+        tracing::warn!(
+            "Withdrawal {} returned {}, quoted {}",
+            receipt.digest,
+            receipt.amount,
+            quote.amount
+        );
+    }
+
+    Ok(Json(WithdrawResponse {
+        transaction_digest: receipt.digest,
+        amount_withdrawn: receipt.amount,
+        gas_used: receipt.gas_used,
+    }))
+}
+
+/// Quote the amount burning shares would withdraw
+pub async fn quote_withdrawal(
+    State(state): State<AppState>,
+    Json(req): Json<WithdrawRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let quote = state
+        .sui
+        .quote_withdrawal(&req.vault_id, req.shares)
+        .await
+        .map_err(sui_error)?;
+
+    Ok(Json(QuoteResponse::new(req.vault_id, quote)))
+}
+
+/// Deposit or withdrawal quote
+#[derive(Serialize)]
+pub struct QuoteResponse {
+    vault_id: String,
+    shares: u64,
+    amount: u64,
+}
+
+impl QuoteResponse {
+    fn new(vault_id: String, quote: vault_math::Quote) -> Self {
+        Self {
+            vault_id,
+            shares: quote.shares,
+            amount: quote.amount,
+        }
+    }
+}
+
+/// Persist a vault transaction and publish the vault's new balance
+async fn record_transaction(
+    state: &AppState,
+    vault_id: &str,
+    kind: TransactionKind,
+    receipt: &VaultReceipt,
+) {
+    if let Some(repository) = &state.repository {
+        let record = TransactionRecord::new(vault_id, kind, receipt);
+        persisted(repository.record_transaction(&record).await, "transaction");
+    }
+    publish_vault_balance(state, vault_id, &receipt.digest).await;
+}
+
+/// Publish a vault's post-transaction balance for `/ws` subscribers
+async fn publish_vault_balance(state: &AppState, vault_id: &str, digest: &str) {
+    match state.sui.get_vault(vault_id).await {
+        Ok(vault) => {
+            if let Some(repository) = &state.repository {
+                let snapshot = VaultSnapshot::new(&vault, Some(digest));
+                persisted(
+                    repository.record_vault_snapshot(&snapshot).await,
+                    "vault snapshot",
+                );
+            }
+            state.events.publish(Event::VaultBalance {
+                vault_id: vault.id,
+                transaction_digest: digest.to_string(),
+                balance: vault.balance,
+                total_value: vault.total_value,
+                total_shares: vault.total_shares,
+            })
+        }
+        Err(e) => tracing::warn!(
+            "Failed to refresh vault {} after {}: {}",
+            vault_id,
+            digest,
+            e
+        ),
+    }
+}
+
+/// Log a failure to persist a record
+///
+/// What the record describes has already happened, so the request still
+/// succeeds.
+fn persisted(result: crate::error::Result<()>, record: &str) {
+    if let Err(e) = result {
+        tracing::warn!("Failed to persist {}: {}", record, e);
+    }
+}
+
+/// Map Sui errors to HTTP status codes
+///
+/// Vault checks that would abort on-chain are the caller's fault, unknown
+/// objects are not found, a missing signing key is a deployment problem, and
+/// anything else is upstream.
+fn sui_error(error: ManusError) -> StatusCode {
+    match error {
+        ManusError::MoveAbort { .. } | ManusError::Arithmetic(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ManusError::ObjectNotFound(_) => StatusCode::NOT_FOUND,
+        ManusError::MissingSigner => StatusCode::SERVICE_UNAVAILABLE,
+        error => {
+            tracing::warn!("Sui request failed: {}", error);
+            StatusCode::BAD_GATEWAY
+        }
+    }
+}
+
+/// List registered agents
+pub async fn list_agents(State(state): State<AppState>) -> Json<Vec<AgentInfo>> {
+    Json(state.agents.list().await)
+}
+
+/// Get agent by ID
+pub async fn get_agent(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<AgentInfo>, StatusCode> {
+    state
+        .agents
+        .get(&id)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Create and register an agent
+///
+/// Model artifacts are read from the server's filesystem, so specs naming
+/// one are rejected; trained models are only loaded from configuration.
+/// IDs that are already registered are rejected with 409.
+pub async fn create_agent(
+    State(state): State<AppState>,
+    Json(spec): Json<AgentSpec>,
+) -> Result<(StatusCode, Json<AgentInfo>), StatusCode> {
+    if spec.model.is_some() {
+        tracing::warn!("Rejected agent {} naming a model artifact", spec.id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let info = state.agents.create(&spec).await.map_err(|e| {
+        tracing::warn!("Failed to create agent {}: {}", spec.id, e);
+        match e {
+            ManusError::AgentExists(_) => StatusCode::CONFLICT,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    })?;
+    if let Some(repository) = &state.repository {
+        persisted(repository.save_agent(&spec).await, "agent");
+    }
+
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// Remove an agent
+pub async fn delete_agent(State(state): State<AppState>, Path(id): Path<String>) -> StatusCode {
+    if state.agents.remove(&id).await {
+        if let Some(repository) = &state.repository {
+            persisted(repository.delete_agent(&id).await.map(|_| ()), "agent");
+        }
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Delete an agent's checkpoint
+///
+/// A runner holding the agent because its vault diverged from the checkpoint
+/// releases it, and checkpoints it afresh after its next execution.
+pub async fn clear_checkpoint(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let cleared = checkpointer(&state)?
+        .clear(&id)
+        .await
+        .map_err(checkpoint_error)?;
+    if cleared {
+        tracing::info!("Cleared checkpoint of agent {}", id);
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Accept the vault balances on chain as an agent's checkpointed balances
+///
+/// A runner holding the agent because its vault diverged from the checkpoint
+/// releases it, keeping the checkpointed agent state.
+pub async fn rebaseline_checkpoint(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Checkpoint>, StatusCode> {
+    let vault_id = state.agents.get(&id).await.and_then(|info| info.vault_id);
+    let checkpoint = checkpointer(&state)?
+        .rebaseline(&id, vault_id.as_deref())
+        .await
+        .map_err(checkpoint_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    tracing::info!("Re-baselined checkpoint of agent {}", id);
+    Ok(Json(checkpoint))
+}
+
+/// Checkpoints in the configured database, checked against the Sui client
+fn checkpointer(state: &AppState) -> Result<Checkpointer, StatusCode> {
+    let repository = state
+        .repository
+        .clone()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    Ok(Checkpointer::new(repository).with_sui_client(state.sui.clone()))
+}
+
+/// Agents without a vault can't be re-baselined; database and chain failures are ours
+fn checkpoint_error(error: ManusError) -> StatusCode {
+    match error {
+        ManusError::Agent(_) => agent_error(error),
+        ManusError::Database(e) => {
+            tracing::error!("Checkpoint storage failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        error => sui_error(error),
+    }
+}
+
+/// Agent decision response
+#[derive(Serialize)]
+pub struct DecisionResponse {
+    agent_id: String,
+    action: AgentAction,
+}
+
+/// Ask an agent for its next action without executing it
+pub async fn agent_decide(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DecisionResponse>, StatusCode> {
+    let (action, decision) = state
+        .agents
+        .with_agent(&id, |agent| {
+            agent
+                .decide()
+                .map(|action| (action, agent.last_decision().cloned()))
+        })
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?
+        .map_err(agent_error)?;
+
+    record_decision(&state, &id, decision).await;
+    record_action(&state, &id, &action, false, None).await;
+    state.events.publish(Event::AgentDecision {
+        agent_id: id.clone(),
+        action: action.clone(),
+        executed: false,
+    });
+
+    Ok(Json(DecisionResponse {
+        agent_id: id,
+        action,
+    }))
+}
+
+/// Agent execution request
+#[derive(Deserialize, Default)]
+pub struct ExecuteRequest {
+    /// Action to execute; the agent decides when omitted
+    action: Option<AgentAction>,
+}
+
+/// Agent execution response
+#[derive(Serialize)]
+pub struct ExecuteResponse {
+    agent_id: String,
+    action: AgentAction,
+    state: AgentState,
+}
+
+/// Execute an action, or the agent's own decision, and check its invariants
+///
+/// Like the orchestrator, only risk-reducing actions run while the circuit
+/// breaker is active, and other risk managers may veto the action; either
+/// rejects the request with 409. Actions that fail, or that introduce or
+/// deepen an invariant violation, are rolled back and reject it with 422.
+pub async fn agent_execute(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<ExecuteRequest>>,
+) -> Result<Json<ExecuteResponse>, StatusCode> {
+    let requested = body.and_then(|Json(req)| req.action);
+    let vault_id = state
+        .agents
+        .get(&id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?
+        .vault_id;
+    let action = match requested {
+        Some(action) => action,
+        None => {
+            let (action, decision) = state
+                .agents
+                .with_agent(&id, |agent| {
+                    let action = agent.decide()?;
+                    Ok::<_, ManusError>((action, agent.last_decision().cloned()))
+                })
+                .await
+                .ok_or(StatusCode::NOT_FOUND)?
+                .map_err(agent_error)?;
+            record_decision(&state, &id, decision).await;
+            action
+        }
+    };
+
+    if let Some(reason) = rejection(&state, &id, &action).await {
+        tracing::warn!("Not running {:?} from {}: {}", action, id, reason);
+        record_action(&state, &id, &action, false, Some(reason)).await;
+        state.events.publish(Event::AgentDecision {
+            agent_id: id,
+            action,
+            executed: false,
+        });
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let run = state
+        .agents
+        .with_agent(&id, |agent| {
+            execute_checked(agent, &action).map(|execution| (execution, agent.state().clone()))
+        })
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let (execution, agent_state) = match run {
+        Ok(run) => run,
+        Err(Rollback::Failed(e)) => {
+            record_action(&state, &id, &action, false, Some(e.to_string())).await;
+            return Err(agent_error(e));
+        }
+        Err(Rollback::Violation(report)) => {
+            tracing::warn!("{}", report);
+            record_action(&state, &id, &action, false, Some(report.to_string())).await;
+            state.events.publish(Event::InvariantViolation {
+                agent_id: id,
+                message: report.to_string(),
+                violations: report.violations,
+            });
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    };
+
+    record_action(&state, &id, &action, true, None).await;
+    if let Some(repository) = &state.repository {
+        persisted(repository.record_state(&agent_state).await, "agent state");
+    }
+    if let (Some(vault_id), Some((before, after))) = (vault_id, execution.snapshots) {
+        if let Err(e) = state
+            .attribution
+            .record_execution(&vault_id, before, after)
+            .await
+        {
+            tracing::warn!("Failed to record execution on vault {}: {}", vault_id, e);
+        }
+    }
+    state.events.publish(Event::AgentDecision {
+        agent_id: id.clone(),
+        action: action.clone(),
+        executed: true,
+    });
+
+    Ok(Json(ExecuteResponse {
+        agent_id: id,
+        action,
+        state: agent_state,
+    }))
+}
+
+/// Why `action` from agent `id` may not run, if it may not
+///
+/// Only risk-reducing actions run while the circuit breaker is active, and
+/// any other risk manager may veto an action.
+async fn rejection(state: &AppState, id: &str, action: &AgentAction) -> Option<String> {
+    let halted = state
+        .circuit_breaker
+        .as_ref()
+        .is_some_and(CircuitBreaker::is_active);
+    if halted && !action.reduces_risk() {
+        return Some("Circuit breaker active".to_string());
+    }
+
+    let (reviewer, reason) = state.agents.review(id, action).await?;
+    Some(format!("Vetoed by {}: {}", reviewer, reason))
+}
+
+/// Persist and publish the scores behind an agent's decision
+async fn record_decision(state: &AppState, agent_id: &str, decision: Option<MLDecision>) {
+    let Some(decision) = decision else {
+        return;
+    };
+    if let Some(repository) = &state.repository {
+        persisted(
+            repository.record_decision(agent_id, &decision).await,
+            "decision",
+        );
+    }
+    state.events.publish(Event::MlDecision {
+        agent_id: agent_id.to_string(),
+        decision,
+    });
+}
+
+/// Persist an action an agent proposed or executed
+async fn record_action(
+    state: &AppState,
+    agent_id: &str,
+    action: &AgentAction,
+    executed: bool,
+    error: Option<String>,
+) {
+    if let Some(repository) = &state.repository {
+        let record = ActionRecord {
+            agent_id: agent_id.to_string(),
+            action: action.clone(),
+            executed,
+            error,
+        };
+        persisted(repository.record_action(&record).await, "action");
+    }
+}
+
+/// Agent failures, including invariant violations, reject the request
+fn agent_error(error: ManusError) -> StatusCode {
+    tracing::warn!("Agent request failed: {}", error);
+    StatusCode::UNPROCESSABLE_ENTITY
+}
+
+/// List tracked DeepBook orders
+pub async fn list_orders(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ManagedOrder>>, StatusCode> {
+    Ok(Json(order_manager(&state)?.orders().await))
+}
+
+/// Get a tracked order by client order ID
+pub async fn get_order(
+    State(state): State<AppState>,
+    Path(client_order_id): Path<u64>,
+) -> Result<Json<ManagedOrder>, StatusCode> {
+    order_manager(&state)?
+        .get(client_order_id)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Reconcile tracked orders with those resting on chain now
+pub async fn reconcile_orders(
+    State(state): State<AppState>,
+) -> Result<Json<ReconcileReport>, StatusCode> {
+    order_manager(&state)?
+        .reconcile()
+        .await
+        .map(Json)
+        .map_err(sui_error)
+}
+
+/// Order endpoints are unavailable unless a DeepBook pool is configured
+fn order_manager(state: &AppState) -> Result<&OrderManager, StatusCode> {
+    state
+        .orders
+        .as_deref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
 }
 
 /// Strategy information
@@ -116,4 +706,3 @@ pub async fn get_metrics() -> Json<Metrics> {
         uptime: 0.9999,
     })
 }
-
@@ -1,26 +1,676 @@
 //! API server implementation
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 
+pub mod events;
 pub mod handlers;
 pub mod routes;
+pub mod state;
+pub mod ws;
+
+pub use state::AppState;
 
 /// Create the API router
-pub fn create_router() -> Router {
+pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(handlers::health_check))
         .route("/api/v1/vaults", get(handlers::list_vaults))
         .route("/api/v1/vaults/:id", get(handlers::get_vault))
+        .route(
+            "/api/v1/vaults/:id/attribution",
+            get(handlers::vault_attribution),
+        )
         .route("/api/v1/deposit", post(handlers::deposit))
+        .route("/api/v1/deposit/quote", post(handlers::quote_deposit))
         .route("/api/v1/withdraw", post(handlers::withdraw))
+        .route("/api/v1/withdraw/quote", post(handlers::quote_withdrawal))
+        .route(
+            "/api/v1/agents",
+            get(handlers::list_agents).post(handlers::create_agent),
+        )
+        .route(
+            "/api/v1/agents/:id",
+            get(handlers::get_agent).delete(handlers::delete_agent),
+        )
+        .route(
+            "/api/v1/agents/:id/checkpoint",
+            delete(handlers::clear_checkpoint),
+        )
+        .route(
+            "/api/v1/agents/:id/checkpoint/rebaseline",
+            post(handlers::rebaseline_checkpoint),
+        )
+        .route("/api/v1/agents/:id/decide", post(handlers::agent_decide))
+        .route("/api/v1/agents/:id/execute", post(handlers::agent_execute))
+        .route("/api/v1/orders", get(handlers::list_orders))
+        .route("/api/v1/orders/reconcile", post(handlers::reconcile_orders))
+        .route("/api/v1/orders/:client_order_id", get(handlers::get_order))
         .route("/api/v1/strategies", get(handlers::list_strategies))
         .route("/api/v1/metrics", get(handlers::get_metrics))
+        .route("/ws", get(ws::ws_handler))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
+        .with_state(state)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::checkpoint::{Checkpoint, Checkpointer};
+    use crate::agents::circuit_breaker::RiskControlSync;
+    use crate::agents::market_maker::OrderIntent;
+    use crate::agents::ml_agent::RiskManagerAgent;
+    use crate::agents::orchestrator::MarketWindow;
+    use crate::agents::order_manager::OrderManager;
+    use crate::agents::rebalancer::Side;
+    use crate::agents::registry::AgentKind;
+    use crate::agents::{Agent, AgentAction, AgentState};
+    use crate::config::Config;
+    use crate::storage::Repository;
+    use crate::sui::{MockSuiNode, SuiClient, SuiKeypair};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use serde_json::{json, Value};
+    use std::future::IntoFuture;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::ServiceExt;
+
+    const SUI: &str = "0x2::sui::SUI";
+
+    fn setup() -> (AppState, String) {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[11u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 1_000_000, "grid");
+        node.mint_coin(&keypair.address(), SUI, 1_000_000_000);
+
+        let client = SuiClient::with_transport(node).with_keypair(keypair);
+        (
+            AppState::with_sui_client(Config::default(), client).unwrap(),
+            vault_id,
+        )
+    }
+
+    async fn post(state: &AppState, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        send(state, request).await
+    }
+
+    async fn get(state: &AppState, uri: &str) -> (StatusCode, Value) {
+        send(state, Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    async fn send(state: &AppState, request: Request<Body>) -> (StatusCode, Value) {
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_deposit_and_withdraw_handlers() {
+        let (state, vault_id) = setup();
+
+        let (status, quote) = post(
+            &state,
+            "/api/v1/deposit/quote",
+            json!({"vault_id": vault_id, "amount": 2_500}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(quote["shares"], 2_500);
+
+        let (status, deposit) = post(
+            &state,
+            "/api/v1/deposit",
+            json!({"vault_id": vault_id, "amount": 2_500}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(deposit["shares_minted"], 2_500);
+        assert!(!deposit["transaction_digest"].as_str().unwrap().is_empty());
+        assert_eq!(state.metrics.transactions_total.get(), 1.0);
+
+        let (status, withdrawal) = post(
+            &state,
+            "/api/v1/withdraw",
+            json!({"vault_id": vault_id, "shares": 2_500}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(withdrawal["amount_withdrawn"], 2_500);
+    }
+
+    #[tokio::test]
+    async fn test_get_vault() {
+        let (state, vault_id) = setup();
+
+        let (status, vault) = get(&state, &format!("/api/v1/vaults/{}", vault_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(vault["strategy"], "grid");
+        assert_eq!(vault["max_capacity"], 1_000_000);
+        assert_eq!(vault["coin_type"], SUI);
+
+        let (status, _) = get(&state, &format!("/api/v1/vaults/0x{}", "ab".repeat(32))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_deposit_rejected_by_quote() {
+        let (state, vault_id) = setup();
+
+        let (status, _) = post(
+            &state,
+            "/api/v1/deposit",
+            json!({"vault_id": vault_id, "amount": 2_000_000}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(state.metrics.transactions_total.get(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_agent_endpoints() {
+        let (state, _) = setup();
+
+        let (status, agents) = get(&state, "/api/v1/agents").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(agents.is_array());
+
+        let (status, agent) = get(&state, "/api/v1/agents/rebalancer_001").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(agent["id"], "rebalancer_001");
+        assert_eq!(agent["type"], "rebalancer");
+
+        let new_agent = json!({
+            "id": "test_agent_001",
+            "type": "rebalancer",
+            "initial_capital": 1_000_000,
+            "risk_tolerance": 0.5
+        });
+        let (status, created) = post(&state, "/api/v1/agents", new_agent.clone()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["id"], "test_agent_001");
+        let (status, _) = post(&state, "/api/v1/agents", new_agent).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        // Model artifacts are not read from paths given over the API
+        let with_model = json!({
+            "id": "test_agent_002",
+            "type": "rebalancer",
+            "initial_capital": 1_000_000,
+            "risk_tolerance": 0.5,
+            "model": "/etc/passwd"
+        });
+        let (status, _) = post(&state, "/api/v1/agents", with_model).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, decision) =
+            post(&state, "/api/v1/agents/rebalancer_001/decide", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(decision.get("action").is_some());
+
+        let adjust = json!({"action": {"AdjustRisk": {"new_tolerance": 0.25}}});
+        let (status, executed) =
+            post(&state, "/api/v1/agents/test_agent_001/execute", adjust).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(executed["state"]["risk_tolerance"], 0.25);
+
+        let (status, _) = post(&state, "/api/v1/agents/missing/decide", Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let request = Request::delete("/api/v1/agents/test_agent_001")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&state, request).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(
+            get(&state, "/api/v1/agents/test_agent_001").await.0,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_conflicts() {
+        let (state, _) = setup();
+        let spec = json!({
+            "id": "twin",
+            "type": "autonomous",
+            "initial_capital": 1_000,
+            "risk_tolerance": 0.5
+        });
+
+        // Concurrent requests for one ID: exactly one registers it
+        let (first, second) = tokio::join!(
+            post(&state, "/api/v1/agents", spec.clone()),
+            post(&state, "/api/v1/agents", spec.clone())
+        );
+        let mut statuses = [first.0, second.0];
+        statuses.sort();
+        assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT]);
+        assert_eq!(
+            post(&state, "/api/v1/agents", spec).await.0,
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handlers_publish_events() {
+        let (state, vault_id) = setup();
+        let mut events = state.events.subscribe();
+
+        post(&state, "/api/v1/agents/rebalancer_001/decide", Value::Null).await;
+        let ml = events.recv().await.unwrap();
+        assert_eq!(ml.topic(), events::Topic::MlDecisions);
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            events::Event::AgentDecision {
+                executed: false,
+                ..
+            }
+        ));
+
+        post(
+            &state,
+            "/api/v1/deposit",
+            json!({"vault_id": vault_id, "amount": 700}),
+        )
+        .await;
+        match events.recv().await.unwrap() {
+            events::Event::VaultBalance {
+                balance,
+                total_shares,
+                ..
+            } => {
+                assert_eq!((balance, total_shares), (700, 700));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        state
+            .agents
+            .register(
+                AgentKind::Autonomous,
+                Box::new(LossyAgent(AgentState {
+                    id: "lossy".to_string(),
+                    capital: 1_000,
+                    initial_capital: 1_000,
+                    positions: vec![],
+                    risk_tolerance: 0.5,
+                })),
+            )
+            .await
+            .unwrap();
+        // The loss would breach the drawdown limit, so it is rolled back and rejected
+        let (status, _) = post(&state, "/api/v1/agents/lossy/execute", Value::Null).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            state.agents.get("lossy").await.unwrap().state.capital,
+            1_000
+        );
+        assert_eq!(
+            events.recv().await.unwrap().topic(),
+            events::Topic::Invariants
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_order_endpoints() {
+        let (state, _) = setup();
+        let (status, _) = get(&state, "/api/v1/orders").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let node = Arc::new(MockSuiNode::new());
+        node.set_coin_decimals("0xdba3::usdc::USDC", 6);
+        let keypair = SuiKeypair::from_bytes(&[16u8; 32]);
+        let pool_id =
+            node.create_pool(SUI, "0xdba3::usdc::USDC", 1_000, 100_000_000, 1_000_000_000);
+        let manager_id = node.create_balance_manager(&keypair.address());
+        node.mint_coin(&keypair.address(), SUI, 1_000_000_000);
+        let client = Arc::new(SuiClient::with_transport(node.clone()).with_keypair(keypair));
+        let account = client.pool_account(&pool_id, &manager_id).await.unwrap();
+        let orders = Arc::new(OrderManager::new(client, account));
+        orders
+            .submit(&[OrderIntent::Place {
+                client_order_id: 1,
+                side: Side::Buy,
+                price: 2.0,
+                quantity: 10.0,
+            }])
+            .await
+            .unwrap();
+        let state = state.with_order_manager(orders);
+
+        let (status, listed) = get(&state, "/api/v1/orders").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(listed[0]["status"], "open");
+        let (status, order) = get(&state, "/api/v1/orders/1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(order["side"], "buy");
+        assert_eq!(
+            get(&state, "/api/v1/orders/2").await.0,
+            StatusCode::NOT_FOUND
+        );
+
+        let order_id: u128 = order["order_id"].as_str().unwrap().parse().unwrap();
+        node.drop_order(&pool_id, order_id);
+        let (status, report) = post(&state, "/api/v1/orders/reconcile", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["closed"], json!([1]));
+        assert_eq!(
+            get(&state, "/api/v1/orders/1").await.1["status"],
+            "cancelled"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vault_attribution() {
+        let (state, vault_id) = setup();
+        let (status, info) = post(
+            &state,
+            "/api/v1/agents",
+            json!({
+                "id": "lp",
+                "type": "range_provider",
+                "initial_capital": 1_000_000,
+                "risk_tolerance": 0.5,
+                "vault_id": vault_id,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(info["vault_id"], vault_id);
+
+        let uri = format!("/api/v1/vaults/{}/attribution", vault_id);
+        assert_eq!(get(&state, &uri).await.0, StatusCode::NOT_FOUND);
+
+        let mut window = MarketWindow::new(8);
+        for price in [1.0, 1.02, 1.05] {
+            let market_data = window.push(price, 10_000.0, 1_000_000.0);
+            state
+                .agents
+                .with_agent("lp", |agent| agent.observe(&market_data))
+                .await;
+            let (status, _) = post(&state, "/api/v1/agents/lp/execute", Value::Null).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let (status, attribution) = get(&state, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(attribution["trades"].as_u64().unwrap() >= 1);
+        assert!(attribution["fees"].as_f64().unwrap() > 0.0);
+        assert!(attribution["rebalancing_cost"].as_f64().unwrap() > 0.0);
+        let component = |name: &str| attribution[name].as_f64().unwrap();
+        let explained = component("price_drift") + component("fees")
+            - component("rebalancing_cost")
+            - component("impermanent_loss");
+        assert!((explained - component("pnl")).abs() < 1e-6);
+
+        assert_eq!(
+            get(&state, &format!("{}?from=10&to=5", uri)).await.0,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            get(&state, &format!("{}?to=1", uri)).await.0,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handlers_persist_records() {
+        let (state, vault_id) = setup();
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let state = state.with_repository(repository.clone());
+
+        let spec = json!({
+            "id": "lp",
+            "type": "range_provider",
+            "initial_capital": 1_000_000,
+            "risk_tolerance": 0.5,
+        });
+        assert_eq!(
+            post(&state, "/api/v1/agents", spec).await.0,
+            StatusCode::CREATED
+        );
+        assert_eq!(repository.load_agents().await.unwrap()[0].id, "lp");
+
+        assert_eq!(
+            post(&state, "/api/v1/agents/lp/execute", Value::Null)
+                .await
+                .0,
+            StatusCode::OK
+        );
+        let actions = repository.actions("lp", 10).await.unwrap();
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].value.executed);
+        assert!(repository.latest_state("lp").await.unwrap().is_some());
+
+        let (status, deposit) = post(
+            &state,
+            "/api/v1/deposit",
+            json!({"vault_id": vault_id, "amount": 2_500}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let transactions = repository.transactions(&vault_id, 10).await.unwrap();
+        assert_eq!(
+            transactions[0].value.digest,
+            deposit["transaction_digest"].as_str().unwrap()
+        );
+        let snapshots = repository.vault_snapshots(&vault_id, 10).await.unwrap();
+        assert_eq!(snapshots[0].value.total_shares, 2_500);
+
+        let request = Request::delete("/api/v1/agents/lp")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&state, request).await.0, StatusCode::NO_CONTENT);
+        assert!(repository.load_agents().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agent_execute_gates() {
+        let (state, _) = setup();
+        let rebalance =
+            json!({"action": {"Rebalance": {"targets": [["SUI", 0.5], ["USDC", 0.5]]}}});
+
+        // A breaker that never synced halts everything but risk reduction
+        let (_sync, breaker) = RiskControlSync::new(state.sui.clone(), "0x1");
+        let halted = state.clone().with_circuit_breaker(breaker);
+        let (status, _) = post(
+            &halted,
+            "/api/v1/agents/rebalancer_001/execute",
+            rebalance.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let hold = json!({"action": "Hold"});
+        let (status, _) = post(&halted, "/api/v1/agents/rebalancer_001/execute", hold).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Risk managers review executions requested through the API
+        state
+            .agents
+            .register(
+                AgentKind::RiskManager,
+                Box::new(
+                    RiskManagerAgent::new("risk".to_string(), 1_000, 0.2).with_max_volatility(0.1),
+                ),
+            )
+            .await
+            .unwrap();
+        let mut window = MarketWindow::new(8);
+        let mut volatile = window.push(1.0, 1_000.0, 5_000.0);
+        volatile.volatility = 0.5;
+        state
+            .agents
+            .with_agent("risk", |agent| agent.observe(&volatile))
+            .await;
+        let mut events = state.events.subscribe();
+        let (status, _) = post(&state, "/api/v1/agents/rebalancer_001/execute", rebalance).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            events::Event::AgentDecision {
+                executed: false,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_endpoints() {
+        let (state, vault_id) = setup();
+        let rebaseline = "/api/v1/agents/lp/checkpoint/rebaseline";
+        let clear = || {
+            Request::delete("/api/v1/agents/lp/checkpoint")
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(
+            post(&state, rebaseline, Value::Null).await.0,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let state = state.with_repository(repository.clone());
+        let spec = json!({
+            "id": "lp",
+            "type": "range_provider",
+            "initial_capital": 1_000_000,
+            "risk_tolerance": 0.5,
+            "vault_id": vault_id,
+        });
+        post(&state, "/api/v1/agents", spec).await;
+        assert_eq!(
+            post(&state, rebaseline, Value::Null).await.0,
+            StatusCode::NOT_FOUND
+        );
+
+        // The runner checkpointed the agent before funds moved
+        let checkpointer = Checkpointer::new(repository.clone()).with_sui_client(state.sui.clone());
+        checkpointer.checkpoint(&state.agents, "lp").await.unwrap();
+        post(
+            &state,
+            "/api/v1/deposit",
+            json!({"vault_id": vault_id, "amount": 700}),
+        )
+        .await;
+        assert!(checkpointer
+            .divergence(&state.agents, "lp")
+            .await
+            .unwrap()
+            .is_some());
+
+        let (status, checkpoint) = post(&state, rebaseline, Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(checkpoint["vault"]["balance"], 700);
+        assert!(checkpointer
+            .divergence(&state.agents, "lp")
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(send(&state, clear()).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&state, clear()).await.0, StatusCode::NOT_FOUND);
+        assert!(repository.latest_checkpoint("lp").await.unwrap().is_none());
+    }
+
+    /// Agent whose every execution loses more than the default drawdown limit
+    struct LossyAgent(AgentState);
+
+    impl Agent for LossyAgent {
+        fn id(&self) -> &str {
+            &self.0.id
+        }
+
+        fn state(&self) -> &AgentState {
+            &self.0
+        }
+
+        fn decide(&mut self) -> crate::error::Result<AgentAction> {
+            Ok(AgentAction::Hold)
+        }
+
+        fn execute(&mut self, _action: AgentAction) -> crate::error::Result<()> {
+            self.0.capital -= 300;
+            Ok(())
+        }
+
+        fn restore(&mut self, checkpoint: &Checkpoint) -> crate::error::Result<()> {
+            checkpoint.restore_state(&mut self.0)
+        }
+    }
+
+    /// Read one unmasked server frame, returning its text payload
+    async fn read_frame(stream: &mut tokio::net::TcpStream) -> String {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).await.unwrap();
+        assert_eq!(header[0], 0x81, "expected a final text frame");
+
+        let len = match header[1] & 0x7f {
+            126 => {
+                let mut extended = [0u8; 2];
+                stream.read_exact(&mut extended).await.unwrap();
+                u16::from_be_bytes(extended) as usize
+            }
+            127 => panic!("frame too large"),
+            len => len as usize,
+        };
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await.unwrap();
+        String::from_utf8(payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ws_streams_subscribed_topics() {
+        let (state, _) = setup();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, create_router(state.clone())).into_future());
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let handshake = format!(
+            "GET /ws?topics=invariants HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+             Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            addr
+        );
+        stream.write_all(handshake.as_bytes()).await.unwrap();
+
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            response.push(stream.read_u8().await.unwrap());
+        }
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+
+        let greeting: Value = serde_json::from_str(&read_frame(&mut stream).await).unwrap();
+        assert_eq!(greeting["topics"], json!(["invariants"]));
+
+        state.events.publish(events::Event::VaultBalance {
+            vault_id: "0x1".to_string(),
+            transaction_digest: "digest".to_string(),
+            balance: 1,
+            total_value: 1,
+            total_shares: 1,
+        });
+        state.events.publish(events::Event::InvariantViolation {
+            agent_id: "lossy".to_string(),
+            message: "capital loss".to_string(),
+            violations: vec![],
+        });
+
+        let event: Value = serde_json::from_str(&read_frame(&mut stream).await).unwrap();
+        assert_eq!(event["topic"], "invariants");
+        assert_eq!(event["agent_id"], "lossy");
+    }
+}
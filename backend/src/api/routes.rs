@@ -1,5 +1,19 @@
 //! API route definitions
 
-// Route definitions are in mod.rs for now
-// This module can be expanded for more complex routing logic
+use crate::api::AppState;
+use crate::config::Config;
+use axum::Router;
 
+/// Create the API router for `config`
+///
+/// Convenience over [`super::create_router`] for callers that do not need to
+/// customise the [`AppState`].
+///
+/// # Panics
+///
+/// Panics if `sui.keystore` is set but is not a valid keystore entry, or a
+/// configured agent model cannot be loaded.
+pub fn create_router(config: Config) -> Router {
+    let state = AppState::new(config).expect("invalid API configuration");
+    super::create_router(state)
+}
@@ -0,0 +1,93 @@
+//! Shared application state injected into API handlers
+
+use crate::agents::circuit_breaker::CircuitBreaker;
+use crate::agents::order_manager::OrderManager;
+use crate::agents::registry::AgentRegistry;
+use crate::api::events::EventBus;
+use crate::attribution::AttributionStore;
+use crate::config::Config;
+use crate::error::Result;
+use crate::monitoring::Metrics;
+use crate::storage::Repository;
+use crate::sui::{HttpTransport, SuiClient, SuiKeypair};
+use std::sync::Arc;
+
+/// State shared by all API handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// Loaded configuration
+    pub config: Arc<Config>,
+
+    /// Client for the configured Sui network
+    pub sui: Arc<SuiClient>,
+
+    /// Prometheus metrics
+    pub metrics: Arc<Metrics>,
+
+    /// Running agents
+    pub agents: Arc<AgentRegistry>,
+
+    /// Events streamed over `/ws`
+    pub events: EventBus,
+
+    /// Tracked DeepBook orders, when a pool is configured
+    pub orders: Option<Arc<OrderManager>>,
+
+    /// Recorded trades and positions of agents managing vaults
+    pub attribution: Arc<AttributionStore>,
+
+    /// Database agents, decisions and vault activity are persisted to, if any
+    pub repository: Option<Arc<Repository>>,
+
+    /// Mirrored on-chain circuit breaker, halting agent executions while active
+    pub circuit_breaker: Option<CircuitBreaker>,
+}
+
+impl AppState {
+    /// Build state from configuration, signing with `sui.keystore` when set
+    pub fn new(config: Config) -> Result<Self> {
+        let mut sui =
+            SuiClient::with_transport(Arc::new(HttpTransport::new(&config.sui.network_url)));
+        if let Some(keystore) = &config.sui.keystore {
+            sui = sui.with_keypair(SuiKeypair::from_base64(keystore)?);
+        }
+
+        Self::with_sui_client(config, sui)
+    }
+
+    /// Build state around an existing Sui client
+    ///
+    /// Agents are seeded from `config.agents`.
+    pub fn with_sui_client(config: Config, sui: SuiClient) -> Result<Self> {
+        Ok(Self {
+            sui: Arc::new(sui),
+            metrics: Arc::new(Metrics::new()),
+            agents: Arc::new(AgentRegistry::from_config(&config.agents)?),
+            events: EventBus::new(),
+            orders: None,
+            attribution: Arc::new(AttributionStore::new()),
+            repository: None,
+            circuit_breaker: None,
+            config: Arc::new(config),
+        })
+    }
+
+    /// Serve the orders tracked by `orders`
+    pub fn with_order_manager(mut self, orders: Arc<OrderManager>) -> Self {
+        self.orders = Some(orders);
+        self
+    }
+
+    /// Persist agents, decisions, vault activity and attribution records to `repository`
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.attribution = Arc::new(AttributionStore::new().with_repository(repository.clone()));
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Only run risk-reducing agent actions while `circuit_breaker` is active
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+}
@@ -0,0 +1,99 @@
+//! WebSocket endpoint streaming [`Event`]s to subscribed clients
+
+use crate::api::events::{ClientMessage, Envelope, Event, Subscription};
+use crate::api::AppState;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::Response,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Query parameters accepted by `/ws`
+#[derive(Deserialize)]
+pub struct WsParams {
+    /// Comma-separated initial topics; every topic when omitted
+    topics: Option<String>,
+}
+
+/// Upgrade to a WebSocket streaming events for the requested topics
+///
+/// Clients change topics later by sending
+/// `{"op": "subscribe" | "unsubscribe", "topics": [...]}`.
+pub async fn ws_handler(
+    State(state): State<AppState>,
+    Query(params): Query<WsParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    let subscription = Subscription::parse(params.topics.as_deref()).map_err(|e| {
+        tracing::debug!("Rejected WebSocket subscription: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    Ok(upgrade.on_upgrade(move |socket| stream_events(socket, state, subscription)))
+}
+
+async fn stream_events(mut socket: WebSocket, state: AppState, mut subscription: Subscription) {
+    let mut events = state.events.subscribe();
+    if send_json(
+        &mut socket,
+        json!({"type": "subscribed", "topics": subscription.topics()}),
+    )
+    .await
+    .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            received = events.recv() => match received {
+                Ok(event) => {
+                    if subscription.matches(&event) && send_event(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("WebSocket subscriber lagged, dropped {} events", skipped);
+                    if send_json(&mut socket, json!({"type": "lagged", "skipped": skipped})).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(message) => {
+                            subscription.apply(message);
+                            json!({"type": "subscribed", "topics": subscription.topics()})
+                        }
+                        Err(e) => json!({"type": "error", "message": e.to_string()}),
+                    };
+                    if send_json(&mut socket, reply).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &Event) -> Result<(), axum::Error> {
+    send_json(
+        socket,
+        serde_json::to_value(Envelope::from(event)).unwrap_or_default(),
+    )
+    .await
+}
+
+async fn send_json(socket: &mut WebSocket, value: serde_json::Value) -> Result<(), axum::Error> {
+    socket.send(Message::Text(value.to_string())).await
+}
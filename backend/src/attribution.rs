@@ -0,0 +1,479 @@
+//! Performance attribution of vault PnL
+//!
+//! Agents managing a vault have their holdings recorded as
+//! [`PositionSnapshot`]s around every execution, and each execution as a
+//! [`TradeRecord`]. [`VaultLedger::attribute`] walks the snapshots of a time
+//! window and splits the change in value between consecutive snapshots into:
+//!
+//! - price drift: what the base asset held at the start of the interval
+//!   gained or lost as the price moved
+//! - fees earned providing liquidity
+//! - rebalancing cost: fees and slippage paid trading, measured against the
+//!   price the agent marked its holdings at
+//! - impermanent loss: the remainder, which for liquidity providers is the
+//!   shortfall against holding the tokens they started the interval with
+//!
+//! so that `pnl = price_drift + fees - rebalancing_cost - impermanent_loss`.
+//!
+//! An [`AttributionStore`] keeps the latest [`MAX_LEDGER_ENTRIES`] records of
+//! each vault in memory, or persists them to a [`Repository`] and attributes
+//! from there, so attribution survives restarts.
+
+use crate::agents::rebalancer::BASE_ASSET;
+use crate::agents::Agent;
+use crate::error::{ManusError, Result};
+use crate::storage::Repository;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// Records attributed at once, and kept per vault in memory
+pub const MAX_LEDGER_ENTRIES: usize = 10_000;
+
+/// Milliseconds since the Unix epoch, the clock snapshots are recorded against
+pub fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Holdings of a vault's agent at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PositionSnapshot {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Price the base asset was marked at
+    pub price: f64,
+    /// Base units held
+    pub base: f64,
+    /// Capital, plus other positions at their cost basis
+    pub quote: f64,
+    /// Fees earned over the agent's lifetime, in quote units
+    pub fees_earned: f64,
+}
+
+impl PositionSnapshot {
+    /// Snapshot of `agent` at `timestamp_ms`, if it marks the base asset at a price
+    pub fn capture(agent: &dyn Agent, timestamp_ms: u64) -> Option<Self> {
+        let price = agent.mark_price(BASE_ASSET)?;
+        let state = agent.state();
+        let (mut base, mut quote) = (0.0, state.capital as f64);
+        for position in &state.positions {
+            if position.asset == BASE_ASSET {
+                base += position.amount as f64;
+            } else {
+                quote += position.amount as f64 * position.entry_price;
+            }
+        }
+        Some(Self {
+            timestamp_ms,
+            price,
+            base,
+            quote,
+            fees_earned: agent.fees_earned(),
+        })
+    }
+
+    /// Value in quote units
+    pub fn value(&self) -> f64 {
+        self.base * self.price + self.quote
+    }
+}
+
+/// Change in holdings from one execution
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Base units bought, negative when sold
+    pub base: f64,
+    /// Quote units received, negative when spent
+    pub quote: f64,
+    /// Price the base asset was marked at when trading
+    pub mark_price: f64,
+}
+
+impl TradeRecord {
+    /// Trade turning the holdings of `before` into those of `after`
+    pub fn between(before: &PositionSnapshot, after: &PositionSnapshot) -> Self {
+        Self {
+            timestamp_ms: after.timestamp_ms,
+            base: after.base - before.base,
+            quote: after.quote - before.quote,
+            mark_price: before.price,
+        }
+    }
+
+    /// Value lost to fees and slippage against the marked price
+    pub fn cost(&self) -> f64 {
+        -(self.base * self.mark_price + self.quote)
+    }
+
+    /// Whether holdings changed at all
+    pub fn is_empty(&self) -> bool {
+        self.base.abs() < 1e-9 && self.quote.abs() < 1e-9
+    }
+}
+
+/// Breakdown of a vault's PnL over a time window, in quote units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribution {
+    /// Vault ID
+    pub vault_id: String,
+    /// Time of the first snapshot in the window
+    pub from_ms: u64,
+    /// Time of the last snapshot in the window
+    pub to_ms: u64,
+    /// Value at the first snapshot
+    pub start_value: f64,
+    /// Value at the last snapshot
+    pub end_value: f64,
+    /// Change in value
+    pub pnl: f64,
+    /// Fees earned providing liquidity
+    pub fees: f64,
+    /// Shortfall against holding the tokens held at the start of each interval
+    pub impermanent_loss: f64,
+    /// Gain or loss from price moves of the base asset held
+    pub price_drift: f64,
+    /// Fees and slippage paid trading
+    pub rebalancing_cost: f64,
+    /// Executions that changed holdings
+    pub trades: usize,
+}
+
+/// Snapshot, with the trade that led to it when recorded after an execution
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Holdings
+    pub snapshot: PositionSnapshot,
+    /// Trade that led to the holdings
+    pub trade: Option<TradeRecord>,
+}
+
+impl LedgerEntry {
+    /// Entry of holdings not following a trade
+    pub fn snapshot(snapshot: PositionSnapshot) -> Self {
+        Self {
+            snapshot,
+            trade: None,
+        }
+    }
+
+    /// Entries of an execution that took holdings from `before` to `after`
+    ///
+    /// Executions that changed nothing are not recorded as trades.
+    pub fn execution(before: PositionSnapshot, after: PositionSnapshot) -> [Self; 2] {
+        let trade = TradeRecord::between(&before, &after);
+        [
+            Self::snapshot(before),
+            Self {
+                snapshot: after,
+                trade: Some(trade).filter(|trade| !trade.is_empty()),
+            },
+        ]
+    }
+}
+
+/// Recorded snapshots and trades of one vault
+#[derive(Debug, Clone, Default)]
+pub struct VaultLedger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl VaultLedger {
+    /// Empty ledger
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded snapshots, oldest first
+    pub fn snapshots(&self) -> impl Iterator<Item = &PositionSnapshot> {
+        self.entries.iter().map(|entry| &entry.snapshot)
+    }
+
+    /// Recorded trades, oldest first
+    pub fn trades(&self) -> impl Iterator<Item = &TradeRecord> {
+        self.entries.iter().filter_map(|entry| entry.trade.as_ref())
+    }
+
+    /// Record holdings, keeping snapshots in time order
+    pub fn record_snapshot(&mut self, snapshot: PositionSnapshot) {
+        self.insert(LedgerEntry::snapshot(snapshot));
+    }
+
+    /// Record an execution that took holdings from `before` to `after`
+    ///
+    /// Both snapshots are recorded; executions that changed nothing are not
+    /// recorded as trades.
+    pub fn record_execution(&mut self, before: PositionSnapshot, after: PositionSnapshot) {
+        for entry in LedgerEntry::execution(before, after) {
+            self.insert(entry);
+        }
+    }
+
+    /// Record an entry, keeping snapshots in time order
+    pub fn insert(&mut self, entry: LedgerEntry) {
+        let timestamp_ms = entry.snapshot.timestamp_ms;
+        let index = self
+            .entries
+            .partition_point(|recorded| recorded.snapshot.timestamp_ms <= timestamp_ms);
+        self.entries.insert(index, entry);
+    }
+
+    /// Drop the oldest entries beyond the latest `capacity`
+    fn trim(&mut self, capacity: usize) {
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+
+    /// Attribute the change in value between the snapshots in `from_ms..=to_ms`
+    ///
+    /// A trade is charged to the interval ending at the snapshot recorded
+    /// after it. Fails if no snapshot was recorded in the window.
+    pub fn attribute(&self, vault_id: &str, from_ms: u64, to_ms: u64) -> Result<Attribution> {
+        let window: Vec<&LedgerEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| (from_ms..=to_ms).contains(&entry.snapshot.timestamp_ms))
+            .collect();
+        let (Some(first), Some(last)) = (window.first(), window.last()) else {
+            return Err(ManusError::Agent(format!(
+                "No positions recorded for vault {} between {} and {}",
+                vault_id, from_ms, to_ms
+            )));
+        };
+        let (first, last) = (&first.snapshot, &last.snapshot);
+
+        let mut attribution = Attribution {
+            vault_id: vault_id.to_string(),
+            from_ms: first.timestamp_ms,
+            to_ms: last.timestamp_ms,
+            start_value: first.value(),
+            end_value: last.value(),
+            pnl: last.value() - first.value(),
+            fees: 0.0,
+            impermanent_loss: 0.0,
+            price_drift: 0.0,
+            rebalancing_cost: 0.0,
+            trades: 0,
+        };
+        for pair in window.windows(2) {
+            let (start, end) = (&pair[0].snapshot, &pair[1].snapshot);
+            let cost = pair[1].trade.map_or(0.0, |trade| trade.cost());
+            attribution.trades += usize::from(pair[1].trade.is_some());
+
+            let drift = start.base * (end.price - start.price);
+            let fees = end.fees_earned - start.fees_earned;
+            let pnl = end.value() - start.value();
+            attribution.price_drift += drift;
+            attribution.fees += fees;
+            attribution.rebalancing_cost += cost;
+            attribution.impermanent_loss += drift + fees - cost - pnl;
+        }
+
+        Ok(attribution)
+    }
+}
+
+/// Thread-safe ledgers of every vault
+#[derive(Debug, Default)]
+pub struct AttributionStore {
+    vaults: RwLock<BTreeMap<String, VaultLedger>>,
+    repository: Option<Arc<Repository>>,
+}
+
+impl AttributionStore {
+    /// Create a store keeping records in memory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist records to `repository`, and attribute from it
+    pub fn with_repository(mut self, repository: Arc<Repository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Record holdings of the agent managing `vault_id`
+    pub async fn record_snapshot(&self, vault_id: &str, snapshot: PositionSnapshot) -> Result<()> {
+        self.record(vault_id, &[LedgerEntry::snapshot(snapshot)])
+            .await
+    }
+
+    /// Record an execution by the agent managing `vault_id`
+    pub async fn record_execution(
+        &self,
+        vault_id: &str,
+        before: PositionSnapshot,
+        after: PositionSnapshot,
+    ) -> Result<()> {
+        self.record(vault_id, &LedgerEntry::execution(before, after))
+            .await
+    }
+
+    async fn record(&self, vault_id: &str, entries: &[LedgerEntry]) -> Result<()> {
+        if let Some(repository) = &self.repository {
+            for entry in entries {
+                repository.record_position(vault_id, entry).await?;
+            }
+            return Ok(());
+        }
+
+        let mut vaults = self.vaults.write().await;
+        let ledger = vaults.entry(vault_id.to_string()).or_default();
+        for entry in entries {
+            ledger.insert(*entry);
+        }
+        ledger.trim(MAX_LEDGER_ENTRIES);
+        Ok(())
+    }
+
+    /// Attribute `vault_id`'s PnL between `from_ms` and `to_ms`
+    ///
+    /// Only the latest [`MAX_LEDGER_ENTRIES`] records in the window are
+    /// attributed. Fails if none were recorded.
+    pub async fn attribute(&self, vault_id: &str, from_ms: u64, to_ms: u64) -> Result<Attribution> {
+        let Some(repository) = &self.repository else {
+            return self
+                .vaults
+                .read()
+                .await
+                .get(vault_id)
+                .map_or_else(VaultLedger::new, VaultLedger::clone)
+                .attribute(vault_id, from_ms, to_ms);
+        };
+
+        let mut ledger = VaultLedger::new();
+        for entry in repository
+            .positions(vault_id, from_ms, to_ms, MAX_LEDGER_ENTRIES as u32)
+            .await?
+        {
+            ledger.insert(entry);
+        }
+        ledger.attribute(vault_id, from_ms, to_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::concentrated::RangeProviderAgent;
+    use crate::agents::orchestrator::MarketWindow;
+    use crate::agents::rebalancer::ExecutionEngine;
+
+    fn snapshot(timestamp_ms: u64, price: f64, base: f64, quote: f64) -> PositionSnapshot {
+        PositionSnapshot {
+            timestamp_ms,
+            price,
+            base,
+            quote,
+            fees_earned: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_attribution_decomposes_pnl() {
+        let mut ledger = VaultLedger::new();
+        ledger.record_snapshot(snapshot(0, 1.0, 0.0, 1_000.0));
+        // Buy 500 base at 1.01 plus a fee of 5
+        ledger.record_execution(
+            snapshot(10, 1.0, 0.0, 1_000.0),
+            snapshot(10, 1.0, 500.0, 490.0),
+        );
+        ledger.record_snapshot(PositionSnapshot {
+            fees_earned: 3.0,
+            ..snapshot(20, 1.2, 500.0, 493.0)
+        });
+
+        let attribution = ledger.attribute("vault", 0, 20).unwrap();
+        assert_eq!(attribution.trades, 1);
+        assert!((attribution.rebalancing_cost - 10.0).abs() < 1e-9);
+        assert!((attribution.price_drift - 100.0).abs() < 1e-9);
+        assert!((attribution.fees - 3.0).abs() < 1e-9);
+        assert!(attribution.impermanent_loss.abs() < 1e-9);
+        assert!((attribution.pnl - 93.0).abs() < 1e-9);
+
+        // Windows only cover the snapshots inside them
+        let later = ledger.attribute("vault", 15, 30).unwrap();
+        assert_eq!(later.trades, 0);
+        assert_eq!(later.pnl, 0.0);
+        assert!(ledger.attribute("vault", 30, 40).is_err());
+
+        // The trade belongs to the window ending at the snapshot after it
+        let execution = ledger.attribute("vault", 10, 10).unwrap();
+        assert_eq!(execution.trades, 1);
+        assert!((execution.pnl + 10.0).abs() < 1e-9);
+        assert!(execution.impermanent_loss.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_range_provider_attribution_adds_up() {
+        let mut agent = RangeProviderAgent::new("lp".to_string(), 1_000_000)
+            .with_engine(ExecutionEngine::new().with_fee_bps(10).with_slippage_bps(0));
+        let mut window = MarketWindow::new(8);
+        let mut ledger = VaultLedger::new();
+        for step in 0..30u64 {
+            let price = 1.0 + 0.05 * (step as f64 / 10.0);
+            agent.observe(&window.push(price, 20_000.0, 1_000_000.0));
+            let before = PositionSnapshot::capture(&agent, step * 2).unwrap();
+            let action = agent.decide().unwrap();
+            agent.execute(action).unwrap();
+            let after = PositionSnapshot::capture(&agent, step * 2 + 1).unwrap();
+            ledger.record_execution(before, after);
+        }
+
+        let attribution = ledger.attribute("vault", 0, u64::MAX).unwrap();
+        assert!(attribution.trades > 0);
+        assert!(attribution.fees > 0.0);
+        assert!(attribution.rebalancing_cost > 0.0);
+        assert!(attribution.price_drift > 0.0);
+        // A rising price leaves the provider short of holding
+        assert!(attribution.impermanent_loss > 0.0);
+        let explained = attribution.price_drift + attribution.fees
+            - attribution.rebalancing_cost
+            - attribution.impermanent_loss;
+        assert!((explained - attribution.pnl).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_store_bounds_and_persists_records() {
+        let store = AttributionStore::new();
+        for step in 0..=MAX_LEDGER_ENTRIES as u64 {
+            store
+                .record_snapshot("vault", snapshot(step, 1.0, 0.0, 1_000.0))
+                .await
+                .unwrap();
+        }
+        // The oldest snapshot was dropped
+        assert!(store.attribute("vault", 0, 0).await.is_err());
+        let attribution = store.attribute("vault", 0, u64::MAX).await.unwrap();
+        assert_eq!(attribution.from_ms, 1);
+        assert!(store.attribute("other", 0, u64::MAX).await.is_err());
+
+        let repository = Arc::new(Repository::connect_url("sqlite::memory:", 1).await.unwrap());
+        let store = AttributionStore::new().with_repository(repository.clone());
+        store
+            .record_execution(
+                "vault",
+                snapshot(10, 1.0, 0.0, 1_000.0),
+                snapshot(10, 1.0, 500.0, 490.0),
+            )
+            .await
+            .unwrap();
+        store
+            .record_snapshot("vault", snapshot(20, 1.2, 500.0, 490.0))
+            .await
+            .unwrap();
+
+        // A new store attributes what an earlier one recorded
+        let restarted = AttributionStore::new().with_repository(repository);
+        let attribution = restarted.attribute("vault", 0, u64::MAX).await.unwrap();
+        assert_eq!(attribution.trades, 1);
+        assert!((attribution.price_drift - 100.0).abs() < 1e-9);
+        assert!(restarted.attribute("other", 0, u64::MAX).await.is_err());
+    }
+}
@@ -0,0 +1,396 @@
+//! Backtesting agents against historical market data
+//!
+//! [`Backtest::run`] replays a series of [`MarketData`] snapshots through an
+//! [`Agent`]: each step the agent observes the snapshot, is told about
+//! regime changes as it would be by the orchestrator, decides, and executes
+//! through its own execution engine. The portfolio is marked at the
+//! snapshot's latest price to build the equity curve the report is based on.
+
+use crate::agents::ml_agent::{MLDecision, MarketData, OrderBookTop};
+use crate::agents::orchestrator::MarketWindow;
+use crate::agents::rebalancer::{BASE_ASSET, QUOTE_ASSET};
+use crate::agents::regime::{Regime, RegimeClassifier};
+use crate::agents::{Agent, AgentAction, AgentState};
+use crate::error::{ManusError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Ticks per snapshot when building a series from CSV
+pub const DEFAULT_WINDOW: usize = 32;
+
+/// Periods per year used to annualize the Sharpe ratio (daily snapshots)
+pub const DEFAULT_PERIODS_PER_YEAR: f64 = 365.0;
+
+/// One replayed step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    /// Step index
+    pub step: usize,
+    /// Price the portfolio was marked at
+    pub price: f64,
+    /// Market regime confirmed by a [`RegimeClassifier`]
+    pub regime: Regime,
+    /// Action chosen by the agent
+    pub action: AgentAction,
+    /// Scores behind the decision, for ML agents
+    pub decision: Option<MLDecision>,
+    /// Whether the action was executed
+    pub executed: bool,
+    /// Why execution failed, if it did
+    pub error: Option<String>,
+    /// Portfolio value after the step
+    pub equity: f64,
+}
+
+/// Performance summary of a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// Agent ID
+    pub agent_id: String,
+    /// Steps replayed
+    pub steps: usize,
+    /// Portfolio value before the first step
+    pub initial_value: f64,
+    /// Portfolio value after the last step
+    pub final_value: f64,
+    /// Profit and loss
+    pub pnl: f64,
+    /// Largest peak-to-trough decline of the equity curve (0.0 - 1.0)
+    pub max_drawdown: f64,
+    /// Annualized Sharpe ratio of per-step returns
+    pub sharpe: f64,
+    /// Traded notional divided by average equity
+    pub turnover: f64,
+    /// Per-step decision log
+    pub decisions: Vec<StepRecord>,
+}
+
+/// Replays market data through agents
+#[derive(Debug, Clone)]
+pub struct Backtest {
+    periods_per_year: f64,
+}
+
+impl Default for Backtest {
+    fn default() -> Self {
+        Self {
+            periods_per_year: DEFAULT_PERIODS_PER_YEAR,
+        }
+    }
+}
+
+impl Backtest {
+    /// Create a backtest for daily snapshots
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many snapshots make up a year
+    pub fn with_periods_per_year(mut self, periods_per_year: f64) -> Self {
+        self.periods_per_year = periods_per_year;
+        self
+    }
+
+    /// Replay `series` through `agent`
+    ///
+    /// Failed executions are logged and the agent keeps its prior state.
+    pub fn run(&self, agent: &mut dyn Agent, series: &[MarketData]) -> Result<BacktestReport> {
+        let first_price = series
+            .first()
+            .map(latest_price)
+            .transpose()?
+            .ok_or_else(|| ManusError::Agent("Backtest series is empty".to_string()))?;
+        let mut classifier = RegimeClassifier::default();
+
+        let initial_value = mark(agent.state(), first_price);
+        let mut equity = vec![initial_value];
+        let mut traded = 0.0;
+        let mut decisions = Vec::with_capacity(series.len());
+
+        for (step, market_data) in series.iter().enumerate() {
+            let price = latest_price(market_data)?;
+            agent.observe(market_data);
+            if let Some(transition) = classifier.update(market_data) {
+                agent.on_regime_change(&transition);
+            }
+
+            let action = agent.decide()?;
+            let decision = agent.last_decision().cloned();
+            let before = holdings(agent.state());
+            let result = agent.execute(action.clone());
+            traded += turnover(&before, &holdings(agent.state()), price);
+
+            let value = mark(agent.state(), price);
+            equity.push(value);
+            decisions.push(StepRecord {
+                step,
+                price,
+                regime: classifier.regime(),
+                action,
+                decision,
+                executed: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+                equity: value,
+            });
+        }
+
+        let final_value = *equity.last().unwrap_or(&initial_value);
+        let average_equity = equity.iter().sum::<f64>() / equity.len() as f64;
+
+        Ok(BacktestReport {
+            agent_id: agent.id().to_string(),
+            steps: series.len(),
+            initial_value,
+            final_value,
+            pnl: final_value - initial_value,
+            max_drawdown: max_drawdown(&equity),
+            sharpe: sharpe(&equity, self.periods_per_year),
+            turnover: if average_equity > 0.0 {
+                traded / average_equity
+            } else {
+                0.0
+            },
+            decisions,
+        })
+    }
+}
+
+/// Load a series from a `.json` array of snapshots or a `.csv` of ticks
+///
+/// CSV ticks are folded into snapshots of the last `window` ticks.
+pub fn load_series(path: &Path, window: usize) -> Result<Vec<MarketData>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| ManusError::Internal(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_str(&contents)
+            .map_err(|e| ManusError::Internal(format!("Invalid market data JSON: {}", e))),
+        Some("csv") => parse_csv(&contents, window),
+        _ => Err(ManusError::Internal(format!(
+            "Unsupported market data file {}, expected .json or .csv",
+            path.display()
+        ))),
+    }
+}
+
+/// Parse CSV ticks with `price`, `volume` and `liquidity` columns
+///
+/// Ticks carry the top of the order book when the CSV also has `bid`, `ask`,
+/// `bid_depth` and `ask_depth` columns. Other columns, such as timestamps,
+/// are ignored.
+pub fn parse_csv(contents: &str, window: usize) -> Result<Vec<MarketData>> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| ManusError::Internal("CSV has no header".to_string()))?
+        .split(',')
+        .map(str::trim)
+        .collect();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or_else(|| ManusError::Internal(format!("CSV is missing a {} column", name)))
+    };
+    let (price, volume, liquidity) = (column("price")?, column("volume")?, column("liquidity")?);
+    let book = match (
+        column("bid"),
+        column("ask"),
+        column("bid_depth"),
+        column("ask_depth"),
+    ) {
+        (Ok(bid), Ok(ask), Ok(bid_depth), Ok(ask_depth)) => Some((bid, ask, bid_depth, ask_depth)),
+        _ => None,
+    };
+
+    let mut window = MarketWindow::new(window);
+    lines
+        .enumerate()
+        .map(|(row, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let field = |index: usize| -> Result<f64> {
+                fields
+                    .get(index)
+                    .and_then(|field| field.parse().ok())
+                    .ok_or_else(|| {
+                        ManusError::Internal(format!(
+                            "Invalid {} on CSV row {}",
+                            header[index],
+                            row + 2
+                        ))
+                    })
+            };
+            let top = match book {
+                Some((bid, ask, bid_depth, ask_depth)) => Some(OrderBookTop {
+                    bid: field(bid)?,
+                    ask: field(ask)?,
+                    bid_depth: field(bid_depth)?,
+                    ask_depth: field(ask_depth)?,
+                }),
+                None => None,
+            };
+            Ok(window.push_with_book(field(price)?, field(volume)?, field(liquidity)?, top))
+        })
+        .collect()
+}
+
+fn latest_price(market_data: &MarketData) -> Result<f64> {
+    market_data
+        .prices
+        .last()
+        .copied()
+        .ok_or_else(|| ManusError::Agent("Market snapshot has no prices".to_string()))
+}
+
+/// Units held per asset, the quote asset being capital
+fn holdings(state: &AgentState) -> HashMap<String, u64> {
+    let mut holdings = HashMap::new();
+    for position in &state.positions {
+        *holdings.entry(position.asset.clone()).or_insert(0) += position.amount;
+    }
+    holdings
+}
+
+/// Notional traded to move from `before` to `after`
+fn turnover(before: &HashMap<String, u64>, after: &HashMap<String, u64>, price: f64) -> f64 {
+    before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|asset| {
+            let old = before.get(asset).copied().unwrap_or(0) as f64;
+            let new = after.get(asset).copied().unwrap_or(0) as f64;
+            (new - old).abs() * asset_price(asset, price)
+        })
+        .sum()
+}
+
+/// Portfolio value with the base asset marked at `price`
+fn mark(state: &AgentState, price: f64) -> f64 {
+    state.capital as f64
+        + state
+            .positions
+            .iter()
+            .map(|p| match p.asset.as_str() {
+                BASE_ASSET | QUOTE_ASSET => p.amount as f64 * asset_price(&p.asset, price),
+                // No price history for other assets, hold them at cost
+                _ => p.amount as f64 * p.entry_price,
+            })
+            .sum::<f64>()
+}
+
+fn asset_price(asset: &str, base_price: f64) -> f64 {
+    if asset == QUOTE_ASSET {
+        1.0
+    } else {
+        base_price
+    }
+}
+
+/// Largest peak-to-trough decline as a fraction of the peak
+pub fn max_drawdown(equity: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for &value in equity {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak);
+        }
+    }
+    drawdown
+}
+
+/// Annualized Sharpe ratio of the equity curve's per-step returns
+///
+/// Zero when returns have no variance.
+pub fn sharpe(equity: &[f64], periods_per_year: f64) -> f64 {
+    let returns: Vec<f64> = equity
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    if variance == 0.0 {
+        return 0.0;
+    }
+    mean / variance.sqrt() * periods_per_year.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::ml_agent::RebalancerAgent;
+    use crate::agents::rebalancer::ExecutionEngine;
+    use crate::agents::AutonomousAgent;
+
+    const CSV: &str = "timestamp,price,volume,liquidity
+1,1.00,1000,5000
+2,1.02,1100,5000
+3,1.04,1200,5000
+4,1.06,1300,5000
+5,1.08,1400,5000
+6,1.10,1500,5000
+";
+
+    #[test]
+    fn test_parse_csv() {
+        let series = parse_csv(CSV, 3).unwrap();
+        assert_eq!(series.len(), 6);
+        assert_eq!(series[5].prices, vec![1.06, 1.08, 1.10]);
+        assert_eq!(series[5].liquidity, 5000.0);
+
+        assert!(series[5].book.is_none());
+
+        let series = parse_csv(
+            "price,volume,liquidity,bid,ask,bid_depth,ask_depth\n1.0,10,50,0.99,1.01,30,10\n",
+            3,
+        )
+        .unwrap();
+        assert_eq!(series[0].book.unwrap().imbalance(), 0.5);
+
+        assert!(parse_csv("price,volume\n1,2\n", 3).is_err());
+        assert!(parse_csv("price,volume,liquidity\n1,x,3\n", 3).is_err());
+    }
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(max_drawdown(&[100.0, 120.0, 90.0, 130.0]), 0.25);
+        assert_eq!(max_drawdown(&[1.0, 2.0, 3.0]), 0.0);
+        assert_eq!(sharpe(&[1.0, 1.0, 1.0], 365.0), 0.0);
+        assert!(sharpe(&[1.0, 1.01, 1.03, 1.04], 365.0) > 0.0);
+    }
+
+    #[test]
+    fn test_backtest_rebalancer_in_rising_market() {
+        let series = parse_csv(CSV, 5).unwrap();
+        let mut agent = RebalancerAgent::new("rebalancer".to_string(), 1_000_000)
+            .with_engine(ExecutionEngine::new().with_fee_bps(0).with_slippage_bps(0));
+
+        let report = Backtest::new().run(&mut agent, &series).unwrap();
+        assert_eq!(report.steps, 6);
+        assert_eq!(report.decisions.len(), 6);
+        assert!(report.pnl > 0.0);
+        assert!(report.turnover > 0.0);
+        assert!(report.decisions[0].decision.is_some());
+        assert_eq!(report.decisions[5].regime, Regime::Trending);
+        assert!(matches!(
+            report.decisions[0].action,
+            AgentAction::Rebalance { .. }
+        ));
+
+        // Holding cash earns nothing
+        let mut idle = AutonomousAgent::new("idle".to_string(), 1_000_000, 0.5);
+        let report = Backtest::new().run(&mut idle, &series).unwrap();
+        assert_eq!(report.pnl, 0.0);
+        assert_eq!(report.turnover, 0.0);
+        assert!(Backtest::new().run(&mut idle, &[]).is_err());
+    }
+}
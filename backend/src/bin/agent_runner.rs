@@ -1,53 +1,223 @@
 //! Manus AI Agent Runner
+//!
+//! ```text
+//! manus-agent-runner [--config <file.yaml|file.toml>] [--set <key>=<value>]... [--simulate]
+//! manus-agent-runner config check
+//! ```
+//!
+//! Agents trade on the level 2 book of `sui.deepbook_pool_id`. Without a pool
+//! the runner refuses to start, unless `--simulate` asks for a synthetic
+//! random walk instead. With `sui.balance_manager_id` also set, the market
+//! maker's quotes are placed on the pool from that balance manager.
 
-use manus_liquidity_backend::{agents::*, config::Config, init};
-use tracing::info;
+use manus_liquidity_backend::{
+    agents::checkpoint::Checkpointer,
+    agents::circuit_breaker::RiskControlSync,
+    agents::orchestrator::{MarketWindow, Orchestrator},
+    agents::order_book::OrderBook,
+    agents::order_manager::OrderManager,
+    agents::registry::{configured_specs, AgentKind, AgentRegistry},
+    attribution::AttributionStore,
+    config::CommandLine,
+    error::Result,
+    init,
+    storage::Repository,
+    sui::{HttpTransport, SuiClient, SuiKeypair},
+};
+use rand::Rng;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Ticks kept in the market window fed to agents
+const MARKET_WINDOW: usize = 32;
+
+/// Price levels read on each side of the book, from the mid
+const BOOK_TICKS: u64 = 50;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let (simulate, args): (Vec<String>, Vec<String>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg == "--simulate");
+    let simulate = !simulate.is_empty();
+    let command_line = CommandLine::parse(args.into_iter())?;
+    if let Some(check) = command_line.run_check() {
+        match check {
+            Ok(()) => println!("Configuration is valid"),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     // Initialize backend
     init().await?;
-    
+
     // Load configuration
-    let config = Config::load().unwrap_or_default();
-    
+    let config = command_line.loader().load()?;
+
     if !config.agents.enabled {
         info!("Agents are disabled in configuration");
         return Ok(());
     }
-    
+    if config.sui.deepbook_pool_id.is_none() && !simulate {
+        anyhow::bail!(
+            "No market feed: set sui.deepbook_pool_id, or pass --simulate to trade a synthetic random walk"
+        );
+    }
+
     info!("Starting Manus AI Agent Runner");
     info!("Rebalance interval: {}s", config.agents.rebalance_interval);
     info!("Risk tolerance: {}", config.agents.risk_tolerance);
-    
-    // Create agent
-    let mut agent = AutonomousAgent::new(
-        "main-agent".to_string(),
-        1_000_000, // Initial capital
-        config.agents.risk_tolerance,
-    );
-    
-    info!("Agent initialized: {}", agent.id());
-    
-    // Main agent loop
-    loop {
-        // Decide action
-        match agent.decide() {
-            Ok(action) => {
-                info!("Agent decision: {:?}", action);
-                
-                // Execute action
-                if let Err(e) = agent.execute(action) {
-                    tracing::error!("Failed to execute action: {}", e);
+
+    // Create agents
+    let registry = Arc::new(AgentRegistry::from_config(&config.agents)?);
+    let mut orchestrator = Orchestrator::new(&config.agents, registry.clone());
+    info!("Agents initialized: {:?}", registry.ids().await);
+
+    let mut client =
+        SuiClient::with_transport(Arc::new(HttpTransport::new(&config.sui.network_url)));
+    if let Some(keystore) = &config.sui.keystore {
+        client = client.with_keypair(SuiKeypair::from_base64(keystore)?);
+    }
+    let client = Arc::new(client);
+
+    // Resume agents from their checkpoints, checking managed vaults against chain state,
+    // and persist the positions their PnL is attributed from
+    match Repository::connect(&config.database).await {
+        Ok(repository) => {
+            let repository = Arc::new(repository);
+            let checkpointer =
+                Checkpointer::new(repository.clone()).with_sui_client(client.clone());
+            orchestrator = orchestrator
+                .with_checkpointer(Arc::new(checkpointer))
+                .with_attribution(Arc::new(
+                    AttributionStore::new().with_repository(repository),
+                ));
+        }
+        Err(e) => warn!(
+            "Database unavailable, agents will not be checkpointed or attributed: {}",
+            e
+        ),
+    }
+
+    // Mirror the on-chain circuit breaker, tripping it when risk limits are breached
+    if let Some(risk_control_id) = &config.sui.risk_control_id {
+        let (sync, breaker) = RiskControlSync::new(client.clone(), risk_control_id);
+        let sync =
+            sync.with_poll_interval(Duration::from_secs(config.sui.risk_control_poll_interval));
+        if let Err(e) = sync.refresh().await {
+            warn!(
+                "Initial risk control sync failed, trading halts until it syncs: {}",
+                e
+            );
+        }
+        info!("Circuit breaker {}: {:?}", risk_control_id, breaker.state());
+        tokio::spawn(sync.run());
+        orchestrator = orchestrator.with_circuit_breaker(breaker);
+    }
+
+    // Shared market feed, sampled once per rebalance interval
+    let mut window = MarketWindow::new(MARKET_WINDOW);
+    let interval = Duration::from_secs(config.agents.rebalance_interval);
+    let (market, books) = match config.sui.deepbook_pool_id.clone() {
+        Some(pool_id) => {
+            let book = read_book(&client, &pool_id).await?;
+            let (feed, market) = watch::channel(window.push_book(&book)?);
+            let (book_feed, books) = watch::channel(book);
+            info!("Market feed from DeepBook pool {}", pool_id);
+            let client = client.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    // A failed read leaves agents on the last book until the next one
+                    let book = match read_book(&client, &pool_id).await {
+                        Ok(book) => book,
+                        Err(e) => {
+                            warn!("Failed to read pool {}: {}", pool_id, e);
+                            continue;
+                        }
+                    };
+                    match window.push_book(&book) {
+                        Ok(market_data) => {
+                            book_feed.send_replace(book);
+                            if feed.send(market_data).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("Failed to read pool {}: {}", pool_id, e),
+                    }
                 }
-            }
-            Err(e) => {
-                tracing::error!("Failed to decide action: {}", e);
-            }
+            });
+            (market, Some(books))
+        }
+        None => {
+            warn!("Simulating the market feed with a synthetic random walk");
+            let (feed, market) = watch::channel(window.push(1.0, 1000.0, 5000.0));
+            tokio::spawn(async move {
+                let mut price = 1.0;
+                loop {
+                    tokio::time::sleep(interval).await;
+                    let (change, volume) = {
+                        let mut rng = rand::thread_rng();
+                        (rng.gen_range(-0.02..0.02), rng.gen_range(800.0..1500.0))
+                    };
+                    price *= 1.0 + change;
+                    if feed.send(window.push(price, volume, 5000.0)).is_err() {
+                        break;
+                    }
+                }
+            });
+            (market, None)
         }
-        
-        // Sleep until next rebalance
-        tokio::time::sleep(tokio::time::Duration::from_secs(config.agents.rebalance_interval)).await;
+    };
+
+    // Place market makers' quotes on the configured DeepBook pool, reconciling
+    // orders with chain state
+    if let (Some(pool_id), Some(balance_manager_id), Some(books)) = (
+        &config.sui.deepbook_pool_id,
+        &config.sui.balance_manager_id,
+        books,
+    ) {
+        let market_makers = configured_specs(&config.agents)
+            .iter()
+            .filter(|spec| spec.kind == AgentKind::MarketMaker)
+            .count();
+        if market_makers > 1 {
+            anyhow::bail!(
+                "{} market makers configured, but balance manager {} backs only one",
+                market_makers,
+                balance_manager_id
+            );
+        }
+
+        let account = client.pool_account(pool_id, balance_manager_id).await?;
+        let orders = Arc::new(OrderManager::new(client.clone(), account));
+        match orders.start().await {
+            Ok(report) => info!("Order reconciliation on pool {}: {:?}", pool_id, report),
+            Err(e) => warn!("Initial order reconciliation failed: {}", e),
+        }
+        tokio::spawn(orders.clone().run(
+            Duration::from_secs(config.sui.order_poll_interval),
+            Duration::from_secs(config.sui.order_reconcile_interval),
+        ));
+        orchestrator = orchestrator.with_order_manager(orders, books);
     }
+
+    tokio::select! {
+        result = orchestrator.run(market) => result?,
+        _ = tokio::signal::ctrl_c() => info!("Shutting down agents"),
+    }
+
+    Ok(())
 }
 
+/// Current level 2 book of DeepBook pool `pool_id`
+async fn read_book(client: &SuiClient, pool_id: &str) -> Result<OrderBook> {
+    let snapshot = client.get_level2_snapshot(pool_id, BOOK_TICKS).await?;
+    OrderBook::from_snapshot(&snapshot)
+}
@@ -1,27 +1,106 @@
 //! Manus AI API Server
+//!
+//! ```text
+//! manus-api-server [--config <file.yaml|file.toml>] [--set <key>=<value>]...
+//! manus-api-server config check
+//! ```
 
-use manus_liquidity_backend::{api, config::Config, init};
+use manus_liquidity_backend::{
+    agents::circuit_breaker::RiskControlSync,
+    agents::order_manager::OrderManager,
+    api::{self, AppState},
+    config::CommandLine,
+    init,
+    storage::Repository,
+};
 use std::net::SocketAddr;
-use tracing::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let command_line = CommandLine::parse(std::env::args().skip(1))?;
+    if let Some(check) = command_line.run_check() {
+        match check {
+            Ok(()) => println!("Configuration is valid"),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    
     // Initialize backend
     init().await?;
     
     // Load configuration
-    let config = Config::load().unwrap_or_default();
+    let config = command_line.loader().load()?;
     
     info!("Starting Manus AI API Server");
     info!("Sui Network: {}", config.sui.network_url);
     info!("PQC Enabled: {}", config.security.pqc_enabled);
     info!("ZK Proofs Enabled: {}", config.security.zk_proofs_enabled);
     
-    // Create router
-    let app = api::create_router();
-    
     // Bind address
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
+    
+    // Create router
+    let mut state = AppState::new(config.clone())?;
+    
+    // Persist to the configured database, restoring agents created before a restart
+    match Repository::connect(&config.database).await {
+        Ok(repository) => {
+            for spec in repository.load_agents().await? {
+                if spec.model.is_some() {
+                    warn!("Not restoring agent {}, it names a model artifact", spec.id);
+                    continue;
+                }
+                if state.agents.get(&spec.id).await.is_none() {
+                    if let Err(e) = state.agents.create(&spec).await {
+                        warn!("Failed to restore agent {}: {}", spec.id, e);
+                    }
+                }
+            }
+            state = state.with_repository(Arc::new(repository));
+        }
+        Err(e) => warn!("Database unavailable, not persisting records: {}", e),
+    }
+    
+    // Mirror the on-chain circuit breaker, halting agent executions while it is active
+    if let Some(risk_control_id) = &config.sui.risk_control_id {
+        let (sync, breaker) = RiskControlSync::new(state.sui.clone(), risk_control_id);
+        let sync =
+            sync.with_poll_interval(Duration::from_secs(config.sui.risk_control_poll_interval));
+        if let Err(e) = sync.refresh().await {
+            warn!(
+                "Initial risk control sync failed, agent executions halt until it syncs: {}",
+                e
+            );
+        }
+        tokio::spawn(sync.run());
+        state = state.with_circuit_breaker(breaker);
+    }
+    
+    // Serve orders on the configured DeepBook pool, reconciling them with chain state
+    if let (Some(pool_id), Some(balance_manager_id)) =
+        (&config.sui.deepbook_pool_id, &config.sui.balance_manager_id)
+    {
+        let account = state.sui.pool_account(pool_id, balance_manager_id).await?;
+        let orders = Arc::new(OrderManager::new(state.sui.clone(), account));
+        if let Err(e) = orders.start().await {
+            warn!("Initial order reconciliation failed: {}", e);
+        }
+        tokio::spawn(orders.clone().run(
+            Duration::from_secs(config.sui.order_poll_interval),
+            Duration::from_secs(config.sui.order_reconcile_interval),
+        ));
+        state = state.with_order_manager(orders);
+    }
+    
+    let app = api::create_router(state);
+    
     info!("Listening on {}", addr);
     
     // Start server
@@ -0,0 +1,144 @@
+//! Manus AI Backtester
+//!
+//! Replays historical market data through agents and compares their results.
+//!
+//! ```text
+//! manus-backtest <series.csv|series.json> [--agent <type>]... [--capital <units>]
+//!     [--risk-tolerance <0-1>] [--fee-bps <bps>] [--slippage-bps <bps>]
+//!     [--window <ticks>] [--periods-per-year <n>] [--model <artifact.json>]
+//!     [--output <report.json>]
+//! ```
+
+use anyhow::{anyhow, bail, Context};
+use manus_liquidity_backend::{
+    agents::rebalancer::{ExecutionEngine, DEFAULT_FEE_BPS, DEFAULT_SLIPPAGE_BPS},
+    agents::registry::{AgentKind, AgentSpec, DEFAULT_AGENT_CAPITAL},
+    agents::valuation::InvariantSet,
+    backtest::{self, Backtest, DEFAULT_PERIODS_PER_YEAR, DEFAULT_WINDOW},
+};
+use std::path::PathBuf;
+
+/// Parsed command line
+struct Options {
+    series: PathBuf,
+    agents: Vec<AgentKind>,
+    capital: u64,
+    risk_tolerance: f64,
+    fee_bps: u32,
+    slippage_bps: u32,
+    window: usize,
+    periods_per_year: f64,
+    model: Option<String>,
+    output: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut series = None;
+        let mut options = Options {
+            series: PathBuf::new(),
+            agents: Vec::new(),
+            capital: DEFAULT_AGENT_CAPITAL,
+            risk_tolerance: 0.5,
+            fee_bps: DEFAULT_FEE_BPS,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            window: DEFAULT_WINDOW,
+            periods_per_year: DEFAULT_PERIODS_PER_YEAR,
+            model: None,
+            output: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("{} requires a value", arg))
+            };
+            match arg.as_str() {
+                "--agent" => {
+                    let kind = value()?;
+                    options.agents.push(
+                        serde_json::from_value(serde_json::Value::String(kind.clone()))
+                            .map_err(|_| anyhow!("Unknown agent type {}", kind))?,
+                    );
+                }
+                "--capital" => options.capital = value()?.parse()?,
+                "--risk-tolerance" => options.risk_tolerance = value()?.parse()?,
+                "--fee-bps" => options.fee_bps = value()?.parse()?,
+                "--slippage-bps" => options.slippage_bps = value()?.parse()?,
+                "--window" => options.window = value()?.parse()?,
+                "--periods-per-year" => options.periods_per_year = value()?.parse()?,
+                "--model" => options.model = Some(value()?),
+                "--output" => options.output = Some(value()?.into()),
+                flag if flag.starts_with("--") => bail!("Unknown option {}", flag),
+                path => series = Some(PathBuf::from(path)),
+            }
+        }
+
+        options.series = series.ok_or_else(|| anyhow!("Missing market data file"))?;
+        if options.agents.is_empty() {
+            options.agents = vec![AgentKind::Rebalancer, AgentKind::Autonomous];
+        }
+        Ok(options)
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    let series = backtest::load_series(&options.series, options.window)
+        .with_context(|| format!("Loading {}", options.series.display()))?;
+
+    let engine = ExecutionEngine::new()
+        .with_fee_bps(options.fee_bps)
+        .with_slippage_bps(options.slippage_bps);
+    let runner = Backtest::new().with_periods_per_year(options.periods_per_year);
+
+    let mut reports = Vec::new();
+    for kind in &options.agents {
+        let spec = AgentSpec {
+            id: serde_json::to_value(kind)?
+                .as_str()
+                .unwrap_or("agent")
+                .to_string(),
+            kind: *kind,
+            initial_capital: options.capital,
+            risk_tolerance: options.risk_tolerance,
+            max_drawdown: None,
+            // Trained models only drive rebalancers
+            model: match kind {
+                AgentKind::Rebalancer => options.model.clone(),
+                _ => None,
+            },
+            min_confidence: None,
+            volatility_threshold: None,
+            invariants: InvariantSet::default(),
+            vault_id: None,
+        };
+        let mut agent = spec.build_with_engine(engine.clone())?;
+        reports.push(runner.run(agent.as_mut(), &series)?);
+    }
+
+    println!(
+        "{:<20} {:>8} {:>16} {:>10} {:>10} {:>10} {:>10}",
+        "agent", "steps", "pnl", "return", "max_dd", "sharpe", "turnover"
+    );
+    for report in &reports {
+        println!(
+            "{:<20} {:>8} {:>16.2} {:>9.2}% {:>9.2}% {:>10.3} {:>10.3}",
+            report.agent_id,
+            report.steps,
+            report.pnl,
+            report.pnl / report.initial_value * 100.0,
+            report.max_drawdown * 100.0,
+            report.sharpe,
+            report.turnover
+        );
+    }
+
+    if let Some(output) = &options.output {
+        std::fs::write(output, serde_json::to_string_pretty(&reports)?)
+            .with_context(|| format!("Writing {}", output.display()))?;
+        println!("Decision logs written to {}", output.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Manus AI Model Trainer
+//!
+//! Trains a rebalancer return model on historical market data, validates it
+//! walk-forward and saves it as the next version in the model store.
+//!
+//! ```text
+//! manus-train <series.csv|series.json> [--name <name>] [--model linear|ridge|random_forest]
+//!     [--features baseline|technical|microstructure|full] [--dir <model dir>]
+//!     [--horizon <snapshots>] [--folds <n>] [--window <ticks>]
+//! ```
+
+use anyhow::{anyhow, bail, Context};
+use manus_liquidity_backend::{
+    agents::features::FeatureSet,
+    agents::ml_agent::RebalancerAgent,
+    agents::model::{self, ModelKind, ModelStore, ReturnModel, TrainingSet},
+    backtest::{self, DEFAULT_WINDOW},
+};
+use std::path::PathBuf;
+
+/// Parsed command line
+struct Options {
+    series: PathBuf,
+    name: String,
+    kind: ModelKind,
+    features: FeatureSet,
+    dir: PathBuf,
+    horizon: usize,
+    folds: usize,
+    window: usize,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut series = None;
+        let mut options = Options {
+            series: PathBuf::new(),
+            name: "rebalancer".to_string(),
+            kind: ModelKind::Ridge,
+            features: FeatureSet::baseline(),
+            dir: PathBuf::from("models"),
+            horizon: 1,
+            folds: 5,
+            window: DEFAULT_WINDOW,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("{} requires a value", arg))
+            };
+            match arg.as_str() {
+                "--name" => options.name = value()?,
+                "--model" => {
+                    let kind = value()?;
+                    options.kind = serde_json::from_value(serde_json::Value::String(kind.clone()))
+                        .map_err(|_| anyhow!("Unknown model type {}", kind))?;
+                }
+                "--features" => {
+                    let name = value()?;
+                    options.features = FeatureSet::named(&name).ok_or_else(|| {
+                        anyhow!(
+                            "Unknown feature set {}, expected one of {}",
+                            name,
+                            FeatureSet::NAMES.join(", ")
+                        )
+                    })?;
+                }
+                "--dir" => options.dir = value()?.into(),
+                "--horizon" => options.horizon = value()?.parse()?,
+                "--folds" => options.folds = value()?.parse()?,
+                "--window" => options.window = value()?.parse()?,
+                flag if flag.starts_with("--") => bail!("Unknown option {}", flag),
+                path => series = Some(PathBuf::from(path)),
+            }
+        }
+
+        options.series = series.ok_or_else(|| anyhow!("Missing market data file"))?;
+        Ok(options)
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    let series = backtest::load_series(&options.series, options.window)
+        .with_context(|| format!("Loading {}", options.series.display()))?;
+
+    // Features come from the same agent code used at inference time
+    let agent =
+        RebalancerAgent::new("trainer".to_string(), 0).with_feature_set(options.features.clone());
+    let data = TrainingSet::from_series(&agent, &series, options.horizon)?;
+    println!(
+        "Built {} samples from {} snapshots",
+        data.len(),
+        series.len()
+    );
+
+    let validation = model::walk_forward(options.kind, &data, options.folds)?;
+    for (index, fold) in validation.folds.iter().enumerate() {
+        println!(
+            "fold {}: train {:>6} test {:>6} mse {:.3e} mae {:.3e} direction {:.1}%",
+            index + 1,
+            fold.train_size,
+            fold.test_size,
+            fold.mse,
+            fold.mae,
+            fold.directional_accuracy * 100.0
+        );
+    }
+    println!(
+        "mean:  mse {:.3e} mae {:.3e} direction {:.1}%",
+        validation.mse,
+        validation.mae,
+        validation.directional_accuracy * 100.0
+    );
+
+    let model = ReturnModel::fit(options.kind, &data)?;
+    let store = ModelStore::new(&options.dir);
+    let artifact = store.save(
+        &options.name,
+        model,
+        options.features,
+        data.len(),
+        Some(validation),
+    )?;
+    println!(
+        "Saved {} v{} to {}",
+        artifact.metadata.name,
+        artifact.metadata.version,
+        store
+            .path(&artifact.metadata.name, artifact.metadata.version)
+            .display()
+    );
+
+    Ok(())
+}
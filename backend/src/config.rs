@@ -1,6 +1,19 @@
 //! Configuration management for Manus AI backend
 
+use crate::agents::registry::AgentKind;
+use crate::agents::valuation::InvariantSet;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of environment variables overriding configuration keys
+///
+/// Nested keys are separated by `__`, e.g. `MANUS_DATABASE__URL`.
+pub const ENV_PREFIX: &str = "MANUS";
+
+/// Environment variable naming the config file to load
+pub const CONFIG_FILE_VAR: &str = "MANUS_CONFIG";
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +58,45 @@ pub struct SuiConfig {
     
     /// Package ID for deployed contracts
     pub package_id: Option<String>,
+    
+    /// Base64 keystore entry used to sign transactions
+    pub keystore: Option<String>,
+    
+    /// `RiskControl` object whose circuit breaker gates agent execution
+    #[serde(default)]
+    pub risk_control_id: Option<String>,
+    
+    /// Delay between reads of the `RiskControl` object (seconds)
+    #[serde(default = "default_risk_control_poll_interval")]
+    pub risk_control_poll_interval: u64,
+    
+    /// DeepBook pool whose orders are tracked
+    #[serde(default)]
+    pub deepbook_pool_id: Option<String>,
+    
+    /// `BalanceManager` orders on `deepbook_pool_id` are placed through
+    #[serde(default)]
+    pub balance_manager_id: Option<String>,
+    
+    /// Delay between polls of DeepBook order events (seconds)
+    #[serde(default = "default_order_poll_interval")]
+    pub order_poll_interval: u64,
+    
+    /// Delay between reconciliations of tracked orders with on-chain state (seconds)
+    #[serde(default = "default_order_reconcile_interval")]
+    pub order_reconcile_interval: u64,
+}
+
+fn default_risk_control_poll_interval() -> u64 {
+    30
+}
+
+fn default_order_poll_interval() -> u64 {
+    5
+}
+
+fn default_order_reconcile_interval() -> u64 {
+    60
 }
 
 /// Database configuration
@@ -68,6 +120,70 @@ pub struct AgentConfig {
     
     /// Risk tolerance (0.0 - 1.0)
     pub risk_tolerance: f64,
+    
+    /// Times the orchestrator restarts a crashed agent before giving up
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    
+    /// Trained model artifact loaded by the rebalancer at startup
+    #[serde(default)]
+    pub rebalancer_model: Option<String>,
+    
+    /// Portfolio limits every agent's executions must respect
+    #[serde(default)]
+    pub invariants: InvariantSet,
+    
+    /// Individually configured agents, keyed by agent ID
+    ///
+    /// When empty, one agent of every type is created from the settings above.
+    #[serde(default)]
+    pub instances: BTreeMap<String, AgentSection>,
+}
+
+/// Settings of one agent under `agents.instances`
+///
+/// Unset values fall back to the shared settings in [`AgentConfig`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSection {
+    /// Agent kind
+    #[serde(rename = "type")]
+    pub kind: AgentKind,
+    
+    /// Initial capital (in base units)
+    #[serde(default)]
+    pub initial_capital: Option<u64>,
+    
+    /// Risk tolerance (0.0 - 1.0)
+    #[serde(default)]
+    pub risk_tolerance: Option<f64>,
+    
+    /// Maximum drawdown before a risk manager withdraws (0.0 - 1.0)
+    #[serde(default)]
+    pub max_drawdown: Option<f64>,
+    
+    /// Path of a trained model artifact, for rebalancers
+    #[serde(default)]
+    pub model: Option<String>,
+    
+    /// Confidence a rebalancer needs before rebalancing (0.0 - 1.0)
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    
+    /// Volatility above which a rebalancer shifts towards the quote asset
+    #[serde(default)]
+    pub volatility_threshold: Option<f64>,
+    
+    /// Portfolio limits replacing `agents.invariants` for this agent
+    #[serde(default)]
+    pub invariants: Option<InvariantSet>,
+    
+    /// Vault whose PnL is attributed to the agent's trades and positions
+    #[serde(default)]
+    pub vault_id: Option<String>,
+}
+
+fn default_max_restarts() -> u32 {
+    5
 }
 
 /// Security configuration
@@ -95,6 +211,13 @@ impl Default for Config {
                 network_url: "https://fullnode.testnet.sui.io:443".to_string(),
                 wallet_address: None,
                 package_id: None,
+                keystore: None,
+                risk_control_id: None,
+                risk_control_poll_interval: default_risk_control_poll_interval(),
+                deepbook_pool_id: None,
+                balance_manager_id: None,
+                order_poll_interval: default_order_poll_interval(),
+                order_reconcile_interval: default_order_reconcile_interval(),
             },
             database: DatabaseConfig {
                 url: "postgres://localhost/manus_liquidity".to_string(),
@@ -104,6 +227,10 @@ impl Default for Config {
                 enabled: true,
                 rebalance_interval: 300, // 5 minutes
                 risk_tolerance: 0.5,
+                max_restarts: default_max_restarts(),
+                rebalancer_model: None,
+                invariants: InvariantSet::default(),
+                instances: BTreeMap::new(),
             },
             security: SecurityConfig {
                 pqc_enabled: true,
@@ -116,14 +243,492 @@ impl Default for Config {
 
 impl Config {
     /// Load configuration from environment and config file
+    ///
+    /// The file is named by `MANUS_CONFIG`; see [`ConfigLoader`] for how
+    /// sources are layered.
     pub fn load() -> anyhow::Result<Self> {
-        dotenv::dotenv().ok();
+        Ok(CommandLine::default().loader().load()?)
+    }
+    
+    /// Check the configuration, reporting every problem found
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut issues = Vec::new();
+        
+        check(
+            &mut issues,
+            self.server.port != 0,
+            "server.port",
+            "must be non-zero",
+        );
+        
+        check(
+            &mut issues,
+            has_scheme(&self.sui.network_url, &["http", "https"]),
+            "sui.network_url",
+            format!("{:?} is not an http(s) URL", self.sui.network_url),
+        );
+        check_interval(
+            &mut issues,
+            "sui.risk_control_poll_interval",
+            self.sui.risk_control_poll_interval,
+        );
+        check_interval(
+            &mut issues,
+            "sui.order_poll_interval",
+            self.sui.order_poll_interval,
+        );
+        check_interval(
+            &mut issues,
+            "sui.order_reconcile_interval",
+            self.sui.order_reconcile_interval,
+        );
+        check(
+            &mut issues,
+            self.sui.deepbook_pool_id.is_some() == self.sui.balance_manager_id.is_some(),
+            "sui.balance_manager_id",
+            "must be set together with sui.deepbook_pool_id",
+        );
+        
+        check(
+            &mut issues,
+            has_scheme(&self.database.url, &["postgres", "postgresql", "sqlite"]),
+            "database.url",
+            "must be a postgres:// or sqlite: URL",
+        );
+        check(
+            &mut issues,
+            self.database.max_connections > 0,
+            "database.max_connections",
+            "must be non-zero",
+        );
+        
+        let agents = &self.agents;
+        check_interval(
+            &mut issues,
+            "agents.rebalance_interval",
+            agents.rebalance_interval,
+        );
+        check_fraction(
+            &mut issues,
+            "agents.risk_tolerance",
+            Some(agents.risk_tolerance),
+        );
+        check_model(
+            &mut issues,
+            "agents.rebalancer_model",
+            agents.rebalancer_model.as_deref(),
+        );
+        check_invariants(&mut issues, "agents.invariants", &agents.invariants);
+        
+        for (id, section) in &agents.instances {
+            let key = |field: &str| format!("agents.instances.{}.{}", id, field);
+            
+            check(
+                &mut issues,
+                section.initial_capital != Some(0),
+                &key("initial_capital"),
+                "must be non-zero",
+            );
+            check_fraction(&mut issues, &key("risk_tolerance"), section.risk_tolerance);
+            check(
+                &mut issues,
+                section.max_drawdown.is_none_or(|d| d > 0.0 && d <= 1.0),
+                &key("max_drawdown"),
+                "must be within (0, 1]",
+            );
+            check_fraction(&mut issues, &key("min_confidence"), section.min_confidence);
+            check(
+                &mut issues,
+                section.volatility_threshold.is_none_or(|v| v >= 0.0),
+                &key("volatility_threshold"),
+                "must not be negative",
+            );
+            if let Some(invariants) = &section.invariants {
+                check_invariants(&mut issues, &key("invariants"), invariants);
+            }
+            
+            if section.kind == AgentKind::Rebalancer {
+                check_model(&mut issues, &key("model"), section.model.as_deref());
+            } else {
+                check(
+                    &mut issues,
+                    section.model.is_none(),
+                    &key("model"),
+                    "only rebalancers load models",
+                );
+                check(
+                    &mut issues,
+                    section.min_confidence.is_none() && section.volatility_threshold.is_none(),
+                    &key("type"),
+                    "only rebalancers take min_confidence and volatility_threshold",
+                );
+            }
+        }
+        
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(issues))
+        }
+    }
+}
+
+/// Record an issue with `key` unless `ok`
+fn check(issues: &mut Vec<ConfigIssue>, ok: bool, key: &str, message: impl Into<String>) {
+    if !ok {
+        issues.push(ConfigIssue {
+            key: key.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+fn check_interval(issues: &mut Vec<ConfigIssue>, key: &str, seconds: u64) {
+    check(issues, seconds > 0, key, "interval must be non-zero");
+}
+
+fn check_fraction(issues: &mut Vec<ConfigIssue>, key: &str, value: Option<f64>) {
+    check(
+        issues,
+        value.is_none_or(|v| (0.0..=1.0).contains(&v)),
+        key,
+        "must be within [0, 1]",
+    );
+}
+
+fn check_model(issues: &mut Vec<ConfigIssue>, key: &str, path: Option<&str>) {
+    if let Some(path) = path {
+        check(
+            issues,
+            Path::new(path).is_file(),
+            key,
+            format!("model artifact {} not found", path),
+        );
+    }
+}
+
+fn check_invariants(issues: &mut Vec<ConfigIssue>, key: &str, invariants: &InvariantSet) {
+    check_fraction(
+        issues,
+        &format!("{}.max_drawdown", key),
+        invariants.max_drawdown,
+    );
+    check_fraction(
+        issues,
+        &format!("{}.max_exposure", key),
+        invariants.max_exposure,
+    );
+    check_fraction(
+        issues,
+        &format!("{}.min_cash_buffer", key),
+        invariants.min_cash_buffer,
+    );
+    check(
+        issues,
+        invariants.max_leverage.is_none_or(|l| l > 0.0),
+        &format!("{}.max_leverage", key),
+        "must be positive",
+    );
+}
+
+/// Whether `url` parses with one of `schemes`
+fn has_scheme(url: &str, schemes: &[&str]) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|url| schemes.contains(&url.scheme()))
+}
+
+/// A single problem found while validating configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Dotted path of the offending key, e.g. `agents.risk_tolerance`
+    pub key: String,
+    
+    /// What is wrong with its value
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Configuration loading errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// A source could not be read or deserialized
+    #[error("Failed to load configuration: {0}")]
+    Load(#[from] config::ConfigError),
+    
+    /// The loaded configuration failed validation
+    #[error("Invalid configuration:{}", .0.iter().map(|issue| format!("\n  {}", issue)).collect::<String>())]
+    Invalid(Vec<ConfigIssue>),
+}
+
+/// Layers configuration sources over [`Config::default`]
+///
+/// Later sources take precedence: the config file, then `MANUS_*`
+/// environment variables, then explicit overrides.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    file: Option<PathBuf>,
+    env: bool,
+    overrides: Vec<(String, String)>,
+}
+
+impl ConfigLoader {
+    /// Create a loader producing the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Read a YAML or TOML file, picking the format from its extension
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+    
+    /// Read `MANUS_*` environment variables, including those in `.env`
+    pub fn with_env(mut self) -> Self {
+        self.env = true;
+        self
+    }
+    
+    /// Set the dotted `key` to `value`, e.g. `agents.risk_tolerance` to `0.3`
+    pub fn with_override(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+    
+    /// Merge the sources without validating the result
+    pub fn build(&self) -> Result<Config, ConfigError> {
+        let mut builder =
+            config::Config::builder().add_source(config::Config::try_from(&Config::default())?);
         
-        let config = config::Config::builder()
-            .add_source(config::Environment::with_prefix("MANUS"))
-            .build()?;
+        if let Some(path) = &self.file {
+            builder = builder.add_source(config::File::from(path.as_path()));
+        }
+        
+        if self.env {
+            dotenv::dotenv().ok();
+            builder = builder.add_source(
+                config::Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
+                    .separator("__")
+                    .try_parsing(true),
+            );
+        }
+        
+        for (key, value) in &self.overrides {
+            builder = builder.set_override(key.as_str(), value.as_str())?;
+        }
         
-        Ok(config.try_deserialize()?)
+        Ok(builder.build()?.try_deserialize()?)
+    }
+    
+    /// Merge the sources and validate the result
+    pub fn load(&self) -> Result<Config, ConfigError> {
+        let config = self.build()?;
+        config.validate()?;
+        Ok(config)
     }
 }
 
+/// Configuration options accepted by the service binaries
+///
+/// ```text
+/// [--config <file.yaml|file.toml>] [--set <key>=<value>]... [config check]
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    /// Config file, defaulting to `MANUS_CONFIG`
+    pub config_file: Option<PathBuf>,
+    
+    /// Overrides applied over every other source
+    pub overrides: Vec<(String, String)>,
+    
+    /// Report configuration problems instead of starting
+    pub check: bool,
+}
+
+impl CommandLine {
+    /// Parse arguments, excluding the program name
+    pub fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut command_line = CommandLine::default();
+        
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow::anyhow!("{} requires a value", arg))
+            };
+            match arg.as_str() {
+                "--config" => command_line.config_file = Some(value()?.into()),
+                "--set" => {
+                    let setting = value()?;
+                    let (key, value) = setting
+                        .split_once('=')
+                        .ok_or_else(|| anyhow::anyhow!("Expected key=value, got {}", setting))?;
+                    command_line
+                        .overrides
+                        .push((key.trim().to_string(), value.trim().to_string()));
+                }
+                "config" => match args.next().as_deref() {
+                    Some("check") => command_line.check = true,
+                    _ => anyhow::bail!("Expected `config check`"),
+                },
+                other => anyhow::bail!("Unknown argument {}", other),
+            }
+        }
+        
+        Ok(command_line)
+    }
+    
+    /// Loader for the file, environment and overrides given
+    pub fn loader(&self) -> ConfigLoader {
+        let file = self
+            .config_file
+            .clone()
+            .or_else(|| std::env::var_os(CONFIG_FILE_VAR).map(PathBuf::from));
+        
+        let mut loader = ConfigLoader::new().with_env();
+        if let Some(file) = file {
+            loader = loader.with_file(file);
+        }
+        for (key, value) in &self.overrides {
+            loader = loader.with_override(key.as_str(), value.as_str());
+        }
+        loader
+    }
+    
+    /// Run `config check` if it was given
+    ///
+    /// Returns `None` when no check was requested, and otherwise whether the
+    /// configuration loads and validates. Reporting the outcome is left to
+    /// the caller.
+    pub fn run_check(&self) -> Option<Result<(), ConfigError>> {
+        self.check.then(|| self.loader().load().map(|_| ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::registry::configured_specs;
+    
+    fn write(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("manus-config-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+    
+    #[test]
+    fn test_layers_file_and_overrides() {
+        let path = write(
+            "layers.yaml",
+            "server:\n  port: 9000\nagents:\n  risk_tolerance: 0.2\n  rebalance_interval: 60\n",
+        );
+        
+        let config = ConfigLoader::new()
+            .with_file(&path)
+            .with_override("agents.risk_tolerance", "0.7")
+            .load()
+            .unwrap();
+        
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(config.agents.rebalance_interval, 60);
+        assert_eq!(config.agents.risk_tolerance, 0.7);
+        // Keys absent from the file keep their defaults
+        assert_eq!(config.database.max_connections, 10);
+        assert_eq!(config.agents.invariants, InvariantSet::default());
+        
+        std::fs::remove_file(path).ok();
+    }
+    
+    #[test]
+    fn test_reads_toml_sections_into_specs() {
+        let path = write(
+            "agents.toml",
+            r#"
+[agents]
+risk_tolerance = 0.4
+
+[agents.instances.cautious]
+type = "rebalancer"
+initial_capital = 5000
+min_confidence = 0.9
+
+[agents.instances.guard]
+type = "risk_manager"
+max_drawdown = 0.1
+"#,
+        );
+        
+        let config = ConfigLoader::new().with_file(&path).load().unwrap();
+        let specs = configured_specs(&config.agents);
+        
+        assert_eq!(specs.len(), 2);
+        let cautious = specs.iter().find(|s| s.id == "cautious").unwrap();
+        assert_eq!(cautious.kind, AgentKind::Rebalancer);
+        assert_eq!(cautious.initial_capital, 5000);
+        assert_eq!(cautious.risk_tolerance, 0.4);
+        assert_eq!(cautious.min_confidence, Some(0.9));
+        let guard = specs.iter().find(|s| s.id == "guard").unwrap();
+        assert_eq!(guard.max_drawdown, Some(0.1));
+        assert!(specs.iter().all(|spec| spec.build().is_ok()));
+        
+        std::fs::remove_file(path).ok();
+    }
+    
+    #[test]
+    fn test_reports_every_issue() {
+        let err = ConfigLoader::new()
+            .with_override("server.port", "0")
+            .with_override("sui.network_url", "fullnode")
+            .with_override("database.url", "mysql://localhost/manus")
+            .with_override("agents.rebalance_interval", "0")
+            .with_override("agents.risk_tolerance", "1.5")
+            .with_override("agents.instances.guard.type", "risk_manager")
+            .with_override("agents.instances.guard.model", "model.json")
+            .load()
+            .unwrap_err();
+        
+        let ConfigError::Invalid(issues) = err else {
+            panic!("expected validation issues, got {}", err);
+        };
+        let keys: Vec<_> = issues.iter().map(|issue| issue.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "server.port",
+                "sui.network_url",
+                "database.url",
+                "agents.rebalance_interval",
+                "agents.risk_tolerance",
+                "agents.instances.guard.model",
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_runs_check_when_requested() {
+        let args = |args: &[&str]| CommandLine::parse(args.iter().map(|arg| arg.to_string()));
+        assert!(args(&[]).unwrap().run_check().is_none());
+        assert!(args(&["config", "check"]).unwrap().run_check().unwrap().is_ok());
+        
+        let invalid = args(&["--set", "server.port=0", "config", "check"]).unwrap();
+        assert!(matches!(
+            invalid.run_check(),
+            Some(Err(ConfigError::Invalid(issues))) if issues[0].key == "server.port"
+        ));
+        assert!(args(&["config"]).is_err());
+    }
+    
+    #[test]
+    fn test_defaults_are_valid() {
+        Config::default().validate().unwrap();
+        assert!(ConfigLoader::new()
+            .with_file("missing.yaml")
+            .build()
+            .is_err());
+    }
+}
@@ -9,6 +9,27 @@ pub enum ManusError {
     #[error("Sui error: {0}")]
     Sui(String),
 
+    /// A Move call aborted, on chain or when checked before submitting
+    #[error("Move abort in {module} with code {code}")]
+    MoveAbort {
+        /// Module that aborted
+        module: String,
+        /// Abort code
+        code: u64,
+    },
+
+    /// Move arithmetic overflowed, on chain or when checked before submitting
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(String),
+
+    /// No object exists with the given ID
+    #[error("Object {0} not found")]
+    ObjectNotFound(String),
+
+    /// A transaction needs signing but no signing key is configured
+    #[error("No signing key configured")]
+    MissingSigner,
+
     /// Cryptography error
     #[error("Cryptography error: {0}")]
     Crypto(String),
@@ -17,6 +38,10 @@ pub enum ManusError {
     #[error("Agent error: {0}")]
     Agent(String),
 
+    /// An agent with the given ID is already registered
+    #[error("Agent {0} already registered")]
+    AgentExists(String),
+
     /// Database error
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
@@ -12,6 +12,15 @@ pub mod api;
 /// Autonomous AI agents for capital management
 pub mod agents;
 
+/// Backtesting agents against historical market data
+pub mod backtest;
+
+/// Performance attribution of vault PnL
+pub mod attribution;
+
+/// Persistent storage of agents, decisions and vault activity
+pub mod storage;
+
 /// Post-quantum cryptography primitives
 pub mod crypto;
 
@@ -1,6 +1,6 @@
 //! System monitoring and metrics
 
-use prometheus::{Counter, Gauge, Histogram, Registry};
+use prometheus::{Counter, Gauge, Histogram, HistogramOpts, Registry};
 use std::sync::Arc;
 
 /// Metrics collector
@@ -35,8 +35,11 @@ impl Metrics {
         let gas_efficiency = Gauge::new("gas_efficiency", "Gas efficiency ratio")
             .expect("Failed to create gauge");
         
-        let transaction_latency = Histogram::new("transaction_latency", "Transaction latency in seconds")
-            .expect("Failed to create histogram");
+        let transaction_latency = Histogram::with_opts(HistogramOpts::new(
+            "transaction_latency",
+            "Transaction latency in seconds",
+        ))
+        .expect("Failed to create histogram");
         
         registry.register(Box::new(transactions_total.clone())).unwrap();
         registry.register(Box::new(active_users.clone())).unwrap();
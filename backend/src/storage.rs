@@ -0,0 +1,725 @@
+//! Persistent storage of agents, their history and vault activity
+//!
+//! A [`Repository`] stores agent specs, [`AgentState`] snapshots, agent
+//! [`Checkpoint`]s, [`AgentAction`] history, [`MLDecision`] records, vault
+//! balance snapshots, vault transactions and the position snapshots PnL is
+//! attributed from in Postgres, or in SQLite when the database URL
+//! starts with `sqlite:`. The schema is created by the migrations embedded
+//! from `migrations/postgres` and `migrations/sqlite` when connecting.
+//!
+//! Records are append-only apart from agent specs and checkpoints, of which
+//! only the latest per agent is kept. Structured values are stored as
+//! JSON alongside the columns they are queried by.
+
+use crate::agents::checkpoint::Checkpoint;
+use crate::agents::ml_agent::MLDecision;
+use crate::agents::registry::{AgentKind, AgentSpec};
+use crate::agents::{AgentAction, AgentState};
+use crate::attribution::{timestamp_ms, LedgerEntry};
+use crate::config::DatabaseConfig;
+use crate::error::{ManusError, Result};
+use crate::sui::{VaultInfo, VaultReceipt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::any::{AnyPoolOptions, AnyRow};
+use sqlx::migrate::Migrator;
+use sqlx::{AnyPool, Row, TypeInfo, ValueRef};
+
+static POSTGRES_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/postgres");
+
+static SQLITE_MIGRATIONS: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// Value recorded at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recorded<T> {
+    /// Milliseconds since the Unix epoch
+    pub recorded_at_ms: u64,
+    /// Recorded value
+    #[serde(flatten)]
+    pub value: T,
+}
+
+/// Action an agent proposed or executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRecord {
+    /// Agent ID
+    pub agent_id: String,
+    /// Action
+    pub action: AgentAction,
+    /// Whether the action was executed rather than only proposed
+    pub executed: bool,
+    /// Why the action was not executed, or failed, if it did
+    pub error: Option<String>,
+}
+
+/// Balances of a vault after a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultSnapshot {
+    /// Vault ID
+    pub vault_id: String,
+    /// Balance held by the vault
+    pub balance: u64,
+    /// Total underlying value
+    pub total_value: u64,
+    /// Total shares issued
+    pub total_shares: u64,
+    /// Digest of the transaction that led to the snapshot
+    pub transaction_digest: Option<String>,
+}
+
+impl VaultSnapshot {
+    /// Snapshot of `vault` after `transaction_digest`
+    pub fn new(vault: &VaultInfo, transaction_digest: Option<&str>) -> Self {
+        Self {
+            vault_id: vault.id.clone(),
+            balance: vault.balance,
+            total_value: vault.total_value,
+            total_shares: vault.total_shares,
+            transaction_digest: transaction_digest.map(str::to_string),
+        }
+    }
+}
+
+/// Kind of vault transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    /// Coins deposited for shares
+    Deposit,
+    /// Shares burned for coins
+    Withdrawal,
+}
+
+impl TransactionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TransactionKind::Deposit => "deposit",
+            TransactionKind::Withdrawal => "withdrawal",
+        }
+    }
+
+    fn parse(kind: &str) -> Result<Self> {
+        match kind {
+            "deposit" => Ok(TransactionKind::Deposit),
+            "withdrawal" => Ok(TransactionKind::Withdrawal),
+            _ => Err(ManusError::Internal(format!(
+                "Unknown vault transaction kind {}",
+                kind
+            ))),
+        }
+    }
+}
+
+/// Deposit or withdrawal executed against a vault
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    /// Transaction digest
+    pub digest: String,
+    /// Vault ID
+    pub vault_id: String,
+    /// Deposit or withdrawal
+    pub kind: TransactionKind,
+    /// Amount deposited or withdrawn
+    pub amount: u64,
+    /// Shares minted or burned
+    pub shares: u64,
+    /// Net gas charged
+    pub gas_used: u64,
+}
+
+impl TransactionRecord {
+    /// Record of `receipt` for a transaction of `kind` against `vault_id`
+    pub fn new(vault_id: &str, kind: TransactionKind, receipt: &VaultReceipt) -> Self {
+        Self {
+            digest: receipt.digest.clone(),
+            vault_id: vault_id.to_string(),
+            kind,
+            amount: receipt.amount,
+            shares: receipt.shares,
+            gas_used: receipt.gas_used,
+        }
+    }
+}
+
+/// Repository over a Postgres or SQLite database
+#[derive(Debug, Clone)]
+pub struct Repository {
+    pool: AnyPool,
+}
+
+impl Repository {
+    /// Connect to the database configured in `config` and run pending migrations
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        Self::connect_url(&config.url, config.max_connections).await
+    }
+
+    /// Connect to `url` with up to `max_connections` and run pending migrations
+    ///
+    /// In-memory SQLite databases exist per connection, so they are kept on
+    /// a single connection that is never recycled.
+    pub async fn connect_url(url: &str, max_connections: u32) -> Result<Self> {
+        sqlx::any::install_default_drivers();
+        let mut options = AnyPoolOptions::new().max_connections(max_connections.max(1));
+        if is_sqlite(url) && url.contains(":memory:") {
+            options = options
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None);
+        }
+        let pool = options.connect(url).await?;
+
+        let migrator = if is_sqlite(url) {
+            &SQLITE_MIGRATIONS
+        } else {
+            &POSTGRES_MIGRATIONS
+        };
+        migrator
+            .run(&pool)
+            .await
+            .map_err(|e| ManusError::Database(e.into()))?;
+        Ok(Self { pool })
+    }
+
+    /// Save `spec`, replacing any agent with the same ID
+    pub async fn save_agent(&self, spec: &AgentSpec) -> Result<()> {
+        let now = timestamp_ms() as i64;
+        sqlx::query(
+            "INSERT INTO agents (id, kind, vault_id, spec, created_at_ms, updated_at_ms) \
+             VALUES ($1, $2, $3, $4, $5, $5) \
+             ON CONFLICT (id) DO UPDATE SET kind = excluded.kind, vault_id = excluded.vault_id, \
+             spec = excluded.spec, updated_at_ms = excluded.updated_at_ms",
+        )
+        .bind(&spec.id)
+        .bind(kind_name(spec.kind))
+        .bind(spec.vault_id.clone())
+        .bind(to_json(spec)?)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Saved agent specs, in ID order
+    pub async fn load_agents(&self) -> Result<Vec<AgentSpec>> {
+        sqlx::query("SELECT spec FROM agents ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| from_json(&row.try_get::<String, _>("spec")?))
+            .collect()
+    }
+
+    /// Delete a saved agent, returning whether it was saved
+    ///
+    /// Its history is kept.
+    pub async fn delete_agent(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM agents WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a snapshot of an agent's state
+    pub async fn record_state(&self, state: &AgentState) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO agent_states (agent_id, recorded_at_ms, capital, risk_tolerance, state) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&state.id)
+        .bind(timestamp_ms() as i64)
+        .bind(state.capital as i64)
+        .bind(state.risk_tolerance)
+        .bind(to_json(state)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Most recent state recorded for `agent_id`
+    pub async fn latest_state(&self, agent_id: &str) -> Result<Option<Recorded<AgentState>>> {
+        Ok(self.states(agent_id, 1).await?.pop())
+    }
+
+    /// Up to `limit` of the most recent states recorded for `agent_id`, newest first
+    pub async fn states(&self, agent_id: &str, limit: u32) -> Result<Vec<Recorded<AgentState>>> {
+        sqlx::query(
+            "SELECT recorded_at_ms, state FROM agent_states WHERE agent_id = $1 \
+             ORDER BY id DESC LIMIT $2",
+        )
+        .bind(agent_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| recorded(row, from_json(&row.try_get::<String, _>("state")?)?))
+        .collect()
+    }
+
+    /// Save `checkpoint`, replacing the agent's previous checkpoint
+    pub async fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO agent_checkpoints (agent_id, recorded_at_ms, checkpoint) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (agent_id) DO UPDATE SET recorded_at_ms = excluded.recorded_at_ms, \
+             checkpoint = excluded.checkpoint",
+        )
+        .bind(checkpoint.agent_id())
+        .bind(timestamp_ms() as i64)
+        .bind(to_json(checkpoint)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Latest checkpoint of `agent_id`
+    pub async fn latest_checkpoint(&self, agent_id: &str) -> Result<Option<Recorded<Checkpoint>>> {
+        sqlx::query("SELECT recorded_at_ms, checkpoint FROM agent_checkpoints WHERE agent_id = $1")
+            .bind(agent_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| recorded(&row, from_json(&row.try_get::<String, _>("checkpoint")?)?))
+            .transpose()
+    }
+
+    /// Delete the checkpoint of `agent_id`, so it starts afresh on the next restart
+    pub async fn clear_checkpoint(&self, agent_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM agent_checkpoints WHERE agent_id = $1")
+            .bind(agent_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record an action `agent_id` proposed or executed
+    pub async fn record_action(&self, record: &ActionRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO agent_actions (agent_id, recorded_at_ms, action, executed, error) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&record.agent_id)
+        .bind(timestamp_ms() as i64)
+        .bind(to_json(&record.action)?)
+        .bind(record.executed)
+        .bind(record.error.clone())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the most recent actions of `agent_id`, newest first
+    pub async fn actions(&self, agent_id: &str, limit: u32) -> Result<Vec<Recorded<ActionRecord>>> {
+        // The Any driver cannot decode SQLite booleans, so read them as integers
+        sqlx::query(
+            "SELECT agent_id, recorded_at_ms, action, \
+             CASE WHEN executed THEN 1 ELSE 0 END AS executed, error \
+             FROM agent_actions WHERE agent_id = $1 ORDER BY id DESC LIMIT $2",
+        )
+        .bind(agent_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| {
+            let record = ActionRecord {
+                agent_id: row.try_get("agent_id")?,
+                action: from_json(&row.try_get::<String, _>("action")?)?,
+                executed: row.try_get::<i64, _>("executed")? != 0,
+                error: optional(row, "error")?,
+            };
+            recorded(row, record)
+        })
+        .collect()
+    }
+
+    /// Record the scores behind a decision of `agent_id`
+    pub async fn record_decision(&self, agent_id: &str, decision: &MLDecision) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ml_decisions (agent_id, recorded_at_ms, confidence, risk_score, decision) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(agent_id)
+        .bind(timestamp_ms() as i64)
+        .bind(decision.confidence)
+        .bind(decision.risk_score)
+        .bind(to_json(decision)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the most recent decisions of `agent_id`, newest first
+    pub async fn decisions(&self, agent_id: &str, limit: u32) -> Result<Vec<Recorded<MLDecision>>> {
+        sqlx::query(
+            "SELECT recorded_at_ms, decision FROM ml_decisions WHERE agent_id = $1 \
+             ORDER BY id DESC LIMIT $2",
+        )
+        .bind(agent_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| recorded(row, from_json(&row.try_get::<String, _>("decision")?)?))
+        .collect()
+    }
+
+    /// Record a vault's balances
+    pub async fn record_vault_snapshot(&self, snapshot: &VaultSnapshot) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO vault_snapshots \
+             (vault_id, recorded_at_ms, balance, total_value, total_shares, transaction_digest) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&snapshot.vault_id)
+        .bind(timestamp_ms() as i64)
+        .bind(snapshot.balance as i64)
+        .bind(snapshot.total_value as i64)
+        .bind(snapshot.total_shares as i64)
+        .bind(snapshot.transaction_digest.clone())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the most recent snapshots of `vault_id`, newest first
+    pub async fn vault_snapshots(
+        &self,
+        vault_id: &str,
+        limit: u32,
+    ) -> Result<Vec<Recorded<VaultSnapshot>>> {
+        sqlx::query(
+            "SELECT vault_id, recorded_at_ms, balance, total_value, total_shares, \
+             transaction_digest FROM vault_snapshots WHERE vault_id = $1 \
+             ORDER BY id DESC LIMIT $2",
+        )
+        .bind(vault_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| {
+            let snapshot = VaultSnapshot {
+                vault_id: row.try_get("vault_id")?,
+                balance: row.try_get::<i64, _>("balance")? as u64,
+                total_value: row.try_get::<i64, _>("total_value")? as u64,
+                total_shares: row.try_get::<i64, _>("total_shares")? as u64,
+                transaction_digest: optional(row, "transaction_digest")?,
+            };
+            recorded(row, snapshot)
+        })
+        .collect()
+    }
+
+    /// Record a vault transaction, ignoring digests already recorded
+    pub async fn record_transaction(&self, record: &TransactionRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO vault_transactions \
+             (digest, vault_id, kind, amount, shares, gas_used, recorded_at_ms) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (digest) DO NOTHING",
+        )
+        .bind(&record.digest)
+        .bind(&record.vault_id)
+        .bind(record.kind.as_str())
+        .bind(record.amount as i64)
+        .bind(record.shares as i64)
+        .bind(record.gas_used as i64)
+        .bind(timestamp_ms() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the most recent transactions against `vault_id`, newest first
+    pub async fn transactions(
+        &self,
+        vault_id: &str,
+        limit: u32,
+    ) -> Result<Vec<Recorded<TransactionRecord>>> {
+        sqlx::query(
+            "SELECT digest, vault_id, kind, amount, shares, gas_used, recorded_at_ms \
+             FROM vault_transactions WHERE vault_id = $1 \
+             ORDER BY recorded_at_ms DESC, digest LIMIT $2",
+        )
+        .bind(vault_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| {
+            let record = TransactionRecord {
+                digest: row.try_get("digest")?,
+                vault_id: row.try_get("vault_id")?,
+                kind: TransactionKind::parse(&row.try_get::<String, _>("kind")?)?,
+                amount: row.try_get::<i64, _>("amount")? as u64,
+                shares: row.try_get::<i64, _>("shares")? as u64,
+                gas_used: row.try_get::<i64, _>("gas_used")? as u64,
+            };
+            recorded(row, record)
+        })
+        .collect()
+    }
+
+    /// Record holdings of the agent managing `vault_id`, at the snapshot's time
+    pub async fn record_position(&self, vault_id: &str, entry: &LedgerEntry) -> Result<()> {
+        let trade = entry.trade.as_ref().map(to_json).transpose()?;
+        sqlx::query(
+            "INSERT INTO position_snapshots (vault_id, recorded_at_ms, snapshot, trade) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(vault_id)
+        .bind(entry.snapshot.timestamp_ms.min(i64::MAX as u64) as i64)
+        .bind(to_json(&entry.snapshot)?)
+        .bind(trade)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the most recent positions of `vault_id` between
+    /// `from_ms` and `to_ms`, oldest first
+    pub async fn positions(
+        &self,
+        vault_id: &str,
+        from_ms: u64,
+        to_ms: u64,
+        limit: u32,
+    ) -> Result<Vec<LedgerEntry>> {
+        let mut entries = sqlx::query(
+            "SELECT snapshot, trade FROM position_snapshots \
+             WHERE vault_id = $1 AND recorded_at_ms >= $2 AND recorded_at_ms <= $3 \
+             ORDER BY recorded_at_ms DESC, id DESC LIMIT $4",
+        )
+        .bind(vault_id)
+        .bind(from_ms.min(i64::MAX as u64) as i64)
+        .bind(to_ms.min(i64::MAX as u64) as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(LedgerEntry {
+                snapshot: from_json(&row.try_get::<String, _>("snapshot")?)?,
+                trade: optional(row, "trade")?
+                    .map(|trade| from_json(&trade))
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+fn is_sqlite(url: &str) -> bool {
+    url.starts_with("sqlite:")
+}
+
+fn kind_name(kind: AgentKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn recorded<T>(row: &AnyRow, value: T) -> Result<Recorded<T>> {
+    Ok(Recorded {
+        recorded_at_ms: row.try_get::<i64, _>("recorded_at_ms")? as u64,
+        value,
+    })
+}
+
+/// Nullable text column
+///
+/// The Any driver reports no value as NULL and type checks SQLite columns
+/// against the first row fetched, so NULLs are detected by the value's type
+/// and values are read unchecked.
+fn optional(row: &AnyRow, column: &str) -> Result<Option<String>> {
+    if row.try_get_raw(column)?.type_info().name() == "NULL" {
+        return Ok(None);
+    }
+    Ok(Some(row.try_get_unchecked(column)?))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| ManusError::Internal(format!("Failed to encode record: {}", e)))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json)
+        .map_err(|e| ManusError::Internal(format!("Failed to decode record: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::valuation::InvariantSet;
+    use crate::agents::Position;
+    use crate::attribution::PositionSnapshot;
+
+    async fn repository() -> Repository {
+        Repository::connect_url("sqlite::memory:", 4).await.unwrap()
+    }
+
+    fn spec(id: &str, vault_id: Option<&str>) -> AgentSpec {
+        AgentSpec {
+            id: id.to_string(),
+            kind: AgentKind::RangeProvider,
+            initial_capital: 1_000,
+            risk_tolerance: 0.4,
+            max_drawdown: None,
+            model: None,
+            min_confidence: None,
+            volatility_threshold: None,
+            invariants: InvariantSet::default(),
+            vault_id: vault_id.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agents_states_and_history() {
+        let repository = repository().await;
+        repository.save_agent(&spec("b", None)).await.unwrap();
+        repository.save_agent(&spec("a", None)).await.unwrap();
+        repository
+            .save_agent(&spec("a", Some("0x1")))
+            .await
+            .unwrap();
+        let agents = repository.load_agents().await.unwrap();
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].id, "a");
+        assert_eq!(agents[0].vault_id.as_deref(), Some("0x1"));
+        assert_eq!(agents[1].kind, AgentKind::RangeProvider);
+        assert!(repository.delete_agent("b").await.unwrap());
+        assert!(!repository.delete_agent("b").await.unwrap());
+
+        let mut state = AgentState {
+            id: "a".to_string(),
+            capital: 1_000,
+            initial_capital: 1_000,
+            positions: vec![],
+            risk_tolerance: 0.4,
+        };
+        assert!(repository.latest_state("a").await.unwrap().is_none());
+        repository.record_state(&state).await.unwrap();
+        state.capital = 400;
+        state.positions.push(Position {
+            asset: "SUI".to_string(),
+            amount: 600,
+            entry_price: 1.0,
+        });
+        repository.record_state(&state).await.unwrap();
+        let latest = repository.latest_state("a").await.unwrap().unwrap();
+        assert_eq!(latest.value.capital, 400);
+        assert_eq!(latest.value.positions[0].amount, 600);
+        assert_eq!(repository.states("a", 10).await.unwrap().len(), 2);
+
+        let decision = MLDecision {
+            action: AgentAction::Hold,
+            confidence: 0.8,
+            predicted_return: 0.01,
+            risk_score: 0.2,
+        };
+        repository.record_decision("a", &decision).await.unwrap();
+        repository
+            .record_action(&ActionRecord {
+                agent_id: "a".to_string(),
+                action: AgentAction::AdjustRisk { new_tolerance: 0.3 },
+                executed: false,
+                error: Some("vetoed".to_string()),
+            })
+            .await
+            .unwrap();
+        repository
+            .record_action(&ActionRecord {
+                agent_id: "a".to_string(),
+                action: AgentAction::Hold,
+                executed: true,
+                error: None,
+            })
+            .await
+            .unwrap();
+
+        let actions = repository.actions("a", 10).await.unwrap();
+        assert_eq!(actions.len(), 2);
+        assert!(actions[0].value.executed);
+        assert_eq!(actions[1].value.error.as_deref(), Some("vetoed"));
+        assert!(matches!(
+            actions[1].value.action,
+            AgentAction::AdjustRisk { .. }
+        ));
+        assert_eq!(repository.actions("a", 1).await.unwrap().len(), 1);
+        let decisions = repository.decisions("a", 10).await.unwrap();
+        assert_eq!(decisions[0].value.confidence, 0.8);
+        assert!(repository.decisions("b", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_vault_snapshots_and_transactions() {
+        let repository = repository().await;
+        let receipt = VaultReceipt {
+            digest: "digest".to_string(),
+            shares: 500,
+            amount: 500,
+            gas_used: 12,
+        };
+        let record = TransactionRecord::new("0x1", TransactionKind::Deposit, &receipt);
+        repository.record_transaction(&record).await.unwrap();
+        // Recording the same digest again is a no-op
+        repository.record_transaction(&record).await.unwrap();
+        let transactions = repository.transactions("0x1", 10).await.unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].value, record);
+
+        let snapshot = VaultSnapshot {
+            vault_id: "0x1".to_string(),
+            balance: 1_500,
+            total_value: 1_500,
+            total_shares: 1_500,
+            transaction_digest: Some("digest".to_string()),
+        };
+        repository.record_vault_snapshot(&snapshot).await.unwrap();
+        let snapshots = repository.vault_snapshots("0x1", 10).await.unwrap();
+        assert_eq!(snapshots[0].value, snapshot);
+        assert!(snapshots[0].recorded_at_ms > 0);
+        assert!(repository
+            .vault_snapshots("0x2", 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_positions() {
+        let repository = repository().await;
+        let snapshot = |timestamp_ms, base| PositionSnapshot {
+            timestamp_ms,
+            price: 1.0,
+            base,
+            quote: 1_000.0,
+            fees_earned: 0.0,
+        };
+        for entry in LedgerEntry::execution(snapshot(10, 0.0), snapshot(20, 100.0)) {
+            repository.record_position("0x1", &entry).await.unwrap();
+        }
+        repository
+            .record_position("0x1", &LedgerEntry::snapshot(snapshot(30, 100.0)))
+            .await
+            .unwrap();
+
+        let positions = repository.positions("0x1", 0, u64::MAX, 10).await.unwrap();
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0], LedgerEntry::snapshot(snapshot(10, 0.0)));
+        assert_eq!(positions[1].trade.unwrap().base, 100.0);
+        assert!(positions[2].trade.is_none());
+        // Bounded reads keep the most recent positions in the window
+        let positions = repository.positions("0x1", 0, 25, 1).await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].snapshot.timestamp_ms, 20);
+        assert!(repository
+            .positions("0x2", 0, u64::MAX, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
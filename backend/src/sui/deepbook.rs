@@ -0,0 +1,701 @@
+//! DeepBook pool reads
+//!
+//! Level 2 order book snapshots are read by dev-inspecting calls to
+//! `pool::get_level2_ticks_from_mid` and `pool::pool_book_params`, and kept
+//! in DeepBook's native integer units so they can be recorded as JSON
+//! fixtures and replayed exactly. Orders are placed, modified and cancelled
+//! through a `BalanceManager` with an [`OrderTransactionBuilder`]; resting
+//! orders are read back with `pool::get_account_order_details` and their
+//! lifecycle followed through [`OrderEvent`]s.
+
+use crate::error::{ManusError, Result};
+use crate::sui::normalize_id;
+use crate::sui::objects::Address;
+use crate::sui::ptb::{
+    Argument, ObjectArg, ProgrammableTransaction, ProgrammableTransactionBuilder, TypeTag,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Fixed-point scaling of DeepBook prices
+pub const FLOAT_SCALING: u64 = 1_000_000_000;
+
+/// ID of the shared `sui::clock::Clock` object
+pub const CLOCK_OBJECT_ID: &str = "0x6";
+
+/// Order type that aborts instead of taking liquidity
+pub const POST_ONLY: u8 = 3;
+
+/// Self-matching option cancelling the incoming order
+pub const CANCEL_TAKER: u8 = 1;
+
+/// Expiry of orders resting until cancelled
+pub const MAX_TIMESTAMP: u64 = u64::MAX;
+
+/// Level 2 snapshot of a DeepBook pool in native units
+///
+/// Prices are quote units per base unit scaled by [`FLOAT_SCALING`];
+/// quantities are base units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct L2Snapshot {
+    /// Pool object ID
+    pub pool_id: String,
+    /// When the snapshot was taken (ms since the Unix epoch)
+    #[serde(default)]
+    pub timestamp_ms: u64,
+    /// Decimals of the base coin
+    pub base_decimals: u8,
+    /// Decimals of the quote coin
+    pub quote_decimals: u8,
+    /// Minimum price increment
+    pub tick_size: u64,
+    /// Minimum quantity increment
+    pub lot_size: u64,
+    /// Minimum order quantity
+    #[serde(default)]
+    pub min_size: u64,
+    /// Bid prices, best first
+    pub bid_prices: Vec<u64>,
+    /// Quantity resting at each bid price
+    pub bid_quantities: Vec<u64>,
+    /// Ask prices, best first
+    pub ask_prices: Vec<u64>,
+    /// Quantity resting at each ask price
+    pub ask_quantities: Vec<u64>,
+}
+
+impl L2Snapshot {
+    /// Load a recorded JSON array of snapshots
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ManusError::Internal(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&contents)
+    }
+
+    /// Parse a JSON array of snapshots
+    pub fn parse(contents: &str) -> Result<Vec<Self>> {
+        serde_json::from_str(contents)
+            .map_err(|e| ManusError::Internal(format!("Invalid L2 snapshot JSON: {}", e)))
+    }
+
+    /// Decimal price of a native price
+    pub fn price(&self, raw: u64) -> f64 {
+        let decimals = self.base_decimals as i32 - self.quote_decimals as i32;
+        raw as f64 / FLOAT_SCALING as f64 * 10f64.powi(decimals)
+    }
+
+    /// Decimal quantity of a native quantity
+    pub fn quantity(&self, raw: u64) -> f64 {
+        raw as f64 / 10f64.powi(self.base_decimals as i32)
+    }
+}
+
+/// Split a `<package>::pool::Pool<Base, Quote>` type into its package and coin types
+pub fn parse_pool_type(object_type: &str) -> Result<(Address, TypeTag, TypeTag)> {
+    let invalid = || ManusError::Sui(format!("Not a DeepBook pool: {}", object_type));
+    let tag: TypeTag = object_type.parse().map_err(|_| invalid())?;
+    match tag {
+        TypeTag::Struct(pool) if pool.module == "pool" && pool.name == "Pool" => {
+            match <[TypeTag; 2]>::try_from(pool.type_params) {
+                Ok([base, quote]) => Ok((pool.address, base, quote)),
+                Err(_) => Err(invalid()),
+            }
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Read-only calls reading a pool's book parameters and `ticks` levels each side of the mid
+pub fn level2_transaction(
+    package: Address,
+    base: TypeTag,
+    quote: TypeTag,
+    pool: ObjectArg,
+    ticks: u64,
+) -> Result<ProgrammableTransaction> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let pool = ptb.object(pool);
+    let ticks = ptb.pure(&ticks)?;
+    let clock = ptb.object(clock_object());
+    let type_arguments = vec![base, quote];
+
+    ptb.move_call(
+        package,
+        "pool",
+        "pool_book_params",
+        type_arguments.clone(),
+        vec![pool],
+    );
+    ptb.move_call(
+        package,
+        "pool",
+        "get_level2_ticks_from_mid",
+        type_arguments,
+        vec![pool, ticks, clock],
+    );
+    Ok(ptb.finish())
+}
+
+/// A DeepBook pool traded through a `BalanceManager`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolAccount {
+    /// DeepBook package
+    pub package: Address,
+    /// Base coin type
+    pub base: TypeTag,
+    /// Quote coin type
+    pub quote: TypeTag,
+    /// Pool object input
+    pub pool: ObjectArg,
+    /// Balance manager object input
+    pub balance_manager: ObjectArg,
+    /// Decimals of the base coin
+    pub base_decimals: u8,
+    /// Decimals of the quote coin
+    pub quote_decimals: u8,
+}
+
+impl PoolAccount {
+    /// Native price of a decimal price, rounded to the nearest unit
+    pub fn native_price(&self, price: f64) -> u64 {
+        let decimals = self.base_decimals as i32 - self.quote_decimals as i32;
+        (price * FLOAT_SCALING as f64 / 10f64.powi(decimals)).round() as u64
+    }
+
+    /// Native quantity of a decimal quantity, rounded to the nearest unit
+    pub fn native_quantity(&self, quantity: f64) -> u64 {
+        (quantity * 10f64.powi(self.base_decimals as i32)).round() as u64
+    }
+
+    /// Decimal price of a native price
+    pub fn price(&self, raw: u64) -> f64 {
+        let decimals = self.base_decimals as i32 - self.quote_decimals as i32;
+        raw as f64 / FLOAT_SCALING as f64 * 10f64.powi(decimals)
+    }
+
+    /// Decimal quantity of a native quantity
+    pub fn quantity(&self, raw: u64) -> f64 {
+        raw as f64 / 10f64.powi(self.base_decimals as i32)
+    }
+
+    /// Whether `event` concerns an order of this account's balance manager on its pool
+    pub fn owns(&self, event: &OrderEvent) -> bool {
+        event.pool_id == self.pool.id().to_string()
+            && event.balance_manager_id == self.balance_manager.id().to_string()
+    }
+
+    /// Start a transaction trading on the pool as the balance manager's owner
+    pub fn orders(&self) -> OrderTransactionBuilder {
+        OrderTransactionBuilder::new(
+            self.package,
+            self.base.clone(),
+            self.quote.clone(),
+            self.pool.clone(),
+            self.balance_manager.clone(),
+        )
+    }
+}
+
+/// Typed builder for DeepBook `pool` order calls through one `BalanceManager`
+///
+/// The trade proof is generated once, as the balance manager's owner, and
+/// shared by every call. Limit orders are post-only, never cross the book and
+/// pay fees in DEEP.
+pub struct OrderTransactionBuilder {
+    ptb: ProgrammableTransactionBuilder,
+    package: Address,
+    type_arguments: Vec<TypeTag>,
+    pool: Argument,
+    balance_manager: Argument,
+    proof: Argument,
+    clock: Argument,
+}
+
+impl OrderTransactionBuilder {
+    /// Start a transaction against `pool`, both objects being shared
+    pub fn new(
+        package: Address,
+        base: TypeTag,
+        quote: TypeTag,
+        pool: ObjectArg,
+        balance_manager: ObjectArg,
+    ) -> Self {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let pool = ptb.object(pool);
+        let balance_manager = ptb.object(balance_manager);
+        let clock = ptb.object(clock_object());
+        let proof = ptb.move_call(
+            package,
+            "balance_manager",
+            "generate_proof_as_owner",
+            vec![],
+            vec![balance_manager],
+        );
+
+        Self {
+            ptb,
+            package,
+            type_arguments: vec![base, quote],
+            pool,
+            balance_manager,
+            proof,
+            clock,
+        }
+    }
+
+    /// Place a post-only limit order at a native price and quantity
+    pub fn place_limit_order(
+        &mut self,
+        client_order_id: u64,
+        price: u64,
+        quantity: u64,
+        is_bid: bool,
+        expire_timestamp: u64,
+    ) -> Result<()> {
+        let mut arguments = vec![self.pool, self.balance_manager, self.proof];
+        arguments.push(self.ptb.pure(&client_order_id)?);
+        arguments.push(self.ptb.pure(&POST_ONLY)?);
+        arguments.push(self.ptb.pure(&CANCEL_TAKER)?);
+        arguments.push(self.ptb.pure(&price)?);
+        arguments.push(self.ptb.pure(&quantity)?);
+        arguments.push(self.ptb.pure(&is_bid)?);
+        arguments.push(self.ptb.pure(&true)?);
+        arguments.push(self.ptb.pure(&expire_timestamp)?);
+        arguments.push(self.clock);
+        self.call("place_limit_order", arguments);
+        Ok(())
+    }
+
+    /// Reduce the total quantity of an order, including anything filled, to `new_quantity`
+    pub fn modify_order(&mut self, order_id: u128, new_quantity: u64) -> Result<()> {
+        let order_id = self.ptb.pure(&order_id)?;
+        let new_quantity = self.ptb.pure(&new_quantity)?;
+        self.call(
+            "modify_order",
+            vec![
+                self.pool,
+                self.balance_manager,
+                self.proof,
+                order_id,
+                new_quantity,
+                self.clock,
+            ],
+        );
+        Ok(())
+    }
+
+    /// Cancel an order
+    pub fn cancel_order(&mut self, order_id: u128) -> Result<()> {
+        let order_id = self.ptb.pure(&order_id)?;
+        self.call(
+            "cancel_order",
+            vec![
+                self.pool,
+                self.balance_manager,
+                self.proof,
+                order_id,
+                self.clock,
+            ],
+        );
+        Ok(())
+    }
+
+    /// Finish building
+    pub fn finish(self) -> ProgrammableTransaction {
+        self.ptb.finish()
+    }
+
+    fn call(&mut self, function: &str, arguments: Vec<Argument>) {
+        self.ptb.move_call(
+            self.package,
+            "pool",
+            function,
+            self.type_arguments.clone(),
+            arguments,
+        );
+    }
+}
+
+/// Read-only call listing the orders `account` has resting on its pool
+pub fn account_orders_transaction(account: &PoolAccount) -> ProgrammableTransaction {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let pool = ptb.object(account.pool.clone());
+    let balance_manager = ptb.object(account.balance_manager.clone());
+    ptb.move_call(
+        account.package,
+        "pool",
+        "get_account_order_details",
+        vec![account.base.clone(), account.quote.clone()],
+        vec![pool, balance_manager],
+    );
+    ptb.finish()
+}
+
+/// Side and native price packed into a DeepBook order ID
+///
+/// Asks have the top bit set, the price takes the next 63 bits and a
+/// sequence number the low 64.
+pub fn decode_order_id(order_id: u128) -> (bool, u64) {
+    let is_bid = order_id >> 127 == 0;
+    let price = (order_id >> 64) as u64 & (u64::MAX >> 1);
+    (is_bid, price)
+}
+
+/// Pack a side, native price and sequence number into a DeepBook order ID
+pub fn encode_order_id(is_bid: bool, price: u64, sequence: u64) -> u128 {
+    ((!is_bid as u128) << 127) | ((price as u128) << 64) | sequence as u128
+}
+
+/// DEEP fee rate locked in when an order was placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderDeepPrice {
+    /// Whether `deep_per_asset` is quoted per base unit
+    pub asset_is_base: bool,
+    /// DEEP charged per unit of the asset
+    pub deep_per_asset: u64,
+}
+
+/// Mirror of `deepbook::order::Order`, decoded from `get_account_order_details`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Order {
+    /// Balance manager the order trades for
+    pub balance_manager_id: Address,
+    /// On-chain order ID, packing side and price
+    pub order_id: u128,
+    /// ID chosen by the client placing the order
+    pub client_order_id: u64,
+    /// Original quantity (native base units)
+    pub quantity: u64,
+    /// Quantity filled so far
+    pub filled_quantity: u64,
+    /// Whether fees are paid in DEEP
+    pub fee_is_deep: bool,
+    /// DEEP fee rate
+    pub order_deep_price: OrderDeepPrice,
+    /// Epoch the order was placed in
+    pub epoch: u64,
+    /// Live (0), partially filled (1), filled (2), cancelled (3) or expired (4)
+    pub status: u8,
+    /// Expiry (ms since the Unix epoch)
+    pub expire_timestamp: u64,
+}
+
+impl Order {
+    /// Whether the order is a bid
+    pub fn is_bid(&self) -> bool {
+        decode_order_id(self.order_id).0
+    }
+
+    /// Native limit price
+    pub fn price(&self) -> u64 {
+        decode_order_id(self.order_id).1
+    }
+}
+
+/// Change to an order reported by a DeepBook event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEventKind {
+    /// `order_info::OrderPlaced`
+    Placed {
+        /// Whether the order is a bid
+        is_bid: bool,
+        /// Native limit price
+        price: u64,
+        /// Quantity left resting after any immediate match
+        quantity: u64,
+    },
+    /// `order_info::OrderFilled`, from the resting (maker) order's side
+    Filled {
+        /// Native execution price
+        price: u64,
+        /// Base quantity filled
+        base_quantity: u64,
+    },
+    /// `order::OrderModified`
+    Modified {
+        /// New total quantity, including anything already filled
+        quantity: u64,
+    },
+    /// `order::OrderCanceled`
+    Canceled,
+}
+
+/// DeepBook event about one order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    /// Pool the order rests on
+    pub pool_id: String,
+    /// Balance manager owning the order
+    pub balance_manager_id: String,
+    /// On-chain order ID
+    pub order_id: u128,
+    /// ID chosen by the client placing the order
+    pub client_order_id: u64,
+    /// When the event was emitted (ms since the Unix epoch)
+    pub timestamp_ms: u64,
+    /// What happened
+    pub kind: OrderEventKind,
+}
+
+impl OrderEvent {
+    /// Parse an event from a transaction response or `suix_queryEvents`
+    ///
+    /// Returns `None` for events that are not about orders.
+    pub fn from_json(event: &Value) -> Option<Self> {
+        let event_type = event["type"].as_str()?;
+        let fields = &event["parsedJson"];
+        let (prefix, kind) = if event_type.ends_with("::order_info::OrderPlaced") {
+            let kind = OrderEventKind::Placed {
+                is_bid: fields["is_bid"].as_bool()?,
+                price: number(&fields["price"])?,
+                quantity: number(&fields["placed_quantity"])?,
+            };
+            ("", kind)
+        } else if event_type.ends_with("::order_info::OrderFilled") {
+            let kind = OrderEventKind::Filled {
+                price: number(&fields["price"])?,
+                base_quantity: number(&fields["base_quantity"])?,
+            };
+            ("maker_", kind)
+        } else if event_type.ends_with("::order::OrderModified") {
+            let kind = OrderEventKind::Modified {
+                quantity: number(&fields["new_quantity"])?,
+            };
+            ("", kind)
+        } else if event_type.ends_with("::order::OrderCanceled") {
+            ("", OrderEventKind::Canceled)
+        } else {
+            return None;
+        };
+
+        let field = |name: &str| &fields[format!("{}{}", prefix, name)];
+        Some(Self {
+            pool_id: normalize_id(fields["pool_id"].as_str()?),
+            balance_manager_id: normalize_id(field("balance_manager_id").as_str()?),
+            order_id: number(field("order_id"))?,
+            client_order_id: number(field("client_order_id"))?,
+            timestamp_ms: number(&fields["timestamp"]).unwrap_or_default(),
+            kind,
+        })
+    }
+}
+
+/// Position in an event stream, as returned by `suix_queryEvents`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCursor {
+    /// Transaction that emitted the event
+    pub tx_digest: String,
+    /// Index of the event within the transaction
+    pub event_seq: String,
+}
+
+/// One page of order events, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderEventPage {
+    /// Order events on the page; other events are skipped
+    pub events: Vec<OrderEvent>,
+    /// Cursor to resume after the last event on the page
+    pub next_cursor: Option<EventCursor>,
+    /// Whether more events follow
+    pub has_next_page: bool,
+}
+
+/// Integer from a JSON number or decimal string, as Move integers are rendered
+fn number<T: FromStr>(value: &Value) -> Option<T> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Read-only input for the shared `sui::clock::Clock`
+fn clock_object() -> ObjectArg {
+    ObjectArg::SharedObject {
+        id: Address::from_hex(CLOCK_OBJECT_ID).expect("valid clock object ID"),
+        initial_shared_version: 1,
+        mutable: false,
+    }
+}
+
+/// Decode return value `index` of command `command` from a dev-inspect response
+pub fn return_value<T: serde::de::DeserializeOwned>(
+    response: &Value,
+    command: usize,
+    index: usize,
+) -> Result<T> {
+    let value = &response["results"][command]["returnValues"][index][0];
+    let bytes: Vec<u8> = serde_json::from_value(value.clone()).map_err(|_| {
+        ManusError::Sui(format!(
+            "Missing return value {} of command {}",
+            index, command
+        ))
+    })?;
+    bcs::from_bytes(&bytes).map_err(|e| {
+        ManusError::Sui(format!(
+            "Invalid return value {} of command {}: {}",
+            index, command, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sui::ptb::{CallArg, Command};
+
+    #[test]
+    fn test_parse_pool_type() {
+        let (package, base, quote) =
+            parse_pool_type("0xdee9::pool::Pool<0x2::sui::SUI, 0xdba3::usdc::USDC>").unwrap();
+        assert_eq!(package, Address::from_hex("0xdee9").unwrap());
+        assert!(base.is_sui());
+        assert_eq!(quote.to_string(), format!("0x{:0>64}::usdc::USDC", "dba3"));
+        assert!(parse_pool_type("0x2::coin::Coin<0x2::sui::SUI>").is_err());
+    }
+
+    #[test]
+    fn test_native_units() {
+        let snapshot = L2Snapshot {
+            pool_id: "0x1".to_string(),
+            timestamp_ms: 0,
+            base_decimals: 9,
+            quote_decimals: 6,
+            tick_size: 1_000,
+            lot_size: 100_000_000,
+            min_size: 0,
+            bid_prices: vec![],
+            bid_quantities: vec![],
+            ask_prices: vec![],
+            ask_quantities: vec![],
+        };
+        assert!((snapshot.price(3_512_000) - 3.512).abs() < 1e-12);
+        assert!((snapshot.quantity(2_500_000_000) - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_order_ids_and_events() {
+        let order_id = encode_order_id(false, 3_514_000, 7);
+        assert_eq!(decode_order_id(order_id), (false, 3_514_000));
+        assert_eq!(
+            decode_order_id(encode_order_id(true, 1, u64::MAX)),
+            (true, 1)
+        );
+
+        let placed = serde_json::json!({
+            "type": "0xdee9::order_info::OrderPlaced",
+            "parsedJson": {
+                "balance_manager_id": "0x2",
+                "pool_id": "0x1",
+                "order_id": order_id.to_string(),
+                "client_order_id": "4",
+                "price": "3514000",
+                "is_bid": false,
+                "placed_quantity": "2500000000",
+                "timestamp": "1760000000000",
+            },
+        });
+        let event = OrderEvent::from_json(&placed).unwrap();
+        assert_eq!(event.balance_manager_id, normalize_id("0x2"));
+        assert_eq!((event.order_id, event.client_order_id), (order_id, 4));
+        assert_eq!(
+            event.kind,
+            OrderEventKind::Placed {
+                is_bid: false,
+                price: 3_514_000,
+                quantity: 2_500_000_000
+            }
+        );
+
+        let filled = serde_json::json!({
+            "type": "0xdee9::order_info::OrderFilled",
+            "parsedJson": {
+                "pool_id": "0x1",
+                "maker_order_id": order_id.to_string(),
+                "maker_client_order_id": "4",
+                "maker_balance_manager_id": "0x2",
+                "taker_balance_manager_id": "0x3",
+                "price": "3514000",
+                "base_quantity": "1000000000",
+                "timestamp": "1760000001000",
+            },
+        });
+        let event = OrderEvent::from_json(&filled).unwrap();
+        assert_eq!(event.balance_manager_id, normalize_id("0x2"));
+        assert_eq!(
+            event.kind,
+            OrderEventKind::Filled {
+                price: 3_514_000,
+                base_quantity: 1_000_000_000
+            }
+        );
+
+        let deposit = serde_json::json!({"type": "0x1::vault::DepositEvent", "parsedJson": {}});
+        assert!(OrderEvent::from_json(&deposit).is_none());
+    }
+
+    #[test]
+    fn test_order_calls() {
+        let account = PoolAccount {
+            package: Address([7; 32]),
+            base: "0x2::sui::SUI".parse().unwrap(),
+            quote: "0xdba3::usdc::USDC".parse().unwrap(),
+            pool: ObjectArg::SharedObject {
+                id: Address([1; 32]),
+                initial_shared_version: 5,
+                mutable: true,
+            },
+            balance_manager: ObjectArg::SharedObject {
+                id: Address([2; 32]),
+                initial_shared_version: 6,
+                mutable: true,
+            },
+            base_decimals: 9,
+            quote_decimals: 6,
+        };
+        assert_eq!(account.native_price(3.512), 3_512_000);
+        assert_eq!(account.native_quantity(2.5), 2_500_000_000);
+        assert!((account.price(3_512_000) - 3.512).abs() < 1e-12);
+        assert!((account.quantity(2_500_000_000) - 2.5).abs() < 1e-12);
+
+        let mut builder = account.orders();
+        builder
+            .place_limit_order(1, 3_512_000, 2_500_000_000, true, MAX_TIMESTAMP)
+            .unwrap();
+        builder.cancel_order(42).unwrap();
+        let ptb = builder.finish();
+
+        assert_eq!(ptb.commands.len(), 3);
+        let Command::MoveCall(proof) = &ptb.commands[0] else {
+            panic!("expected move call");
+        };
+        assert_eq!(proof.module, "balance_manager");
+        assert_eq!(proof.arguments, vec![Argument::Input(1)]);
+
+        let Command::MoveCall(place) = &ptb.commands[1] else {
+            panic!("expected move call");
+        };
+        assert_eq!(place.function, "place_limit_order");
+        assert_eq!(place.type_arguments.len(), 2);
+        assert_eq!(place.arguments.len(), 12);
+        assert_eq!(place.arguments[2], Argument::Result(0));
+        assert_eq!(place.arguments[11], Argument::Input(2));
+
+        let Command::MoveCall(cancel) = &ptb.commands[2] else {
+            panic!("expected move call");
+        };
+        assert_eq!(cancel.function, "cancel_order");
+        let Argument::Input(order_id) = cancel.arguments[3] else {
+            panic!("expected input");
+        };
+        assert_eq!(
+            ptb.inputs[order_id as usize],
+            CallArg::Pure(42u128.to_le_bytes().to_vec())
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Ed25519 key management and Sui transaction signing
+
+use crate::error::{ManusError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Signature scheme flag for Ed25519 keys
+pub const ED25519_FLAG: u8 = 0x00;
+
+/// Intent prefix for transaction data (scope, version, app id)
+const TRANSACTION_INTENT: [u8; 3] = [0, 0, 0];
+
+/// Ed25519 keypair used to sign Sui transactions
+#[derive(Clone)]
+pub struct SuiKeypair {
+    signing_key: SigningKey,
+}
+
+impl SuiKeypair {
+    /// Create a keypair from a 32-byte secret seed
+    pub fn from_bytes(secret: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// Generate a new random keypair
+    pub fn generate() -> Self {
+        Self::from_bytes(&rand::random::<[u8; 32]>())
+    }
+
+    /// Load a keypair from the Sui keystore encoding (base64 of flag || secret)
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .map_err(|e| ManusError::Crypto(format!("Invalid keystore entry: {}", e)))?;
+
+        match bytes.split_first() {
+            Some((&ED25519_FLAG, secret)) if secret.len() == 32 => {
+                let mut seed = [0u8; 32];
+                seed.copy_from_slice(secret);
+                Ok(Self::from_bytes(&seed))
+            }
+            _ => Err(ManusError::Crypto(
+                "Keystore entry is not an Ed25519 key".to_string(),
+            )),
+        }
+    }
+
+    /// Sui address derived from the public key
+    pub fn address(&self) -> String {
+        address_from_public_key(&self.signing_key.verifying_key())
+    }
+
+    /// Sign transaction bytes and return the serialized Sui signature (base64)
+    pub fn sign_transaction(&self, tx_bytes: &[u8]) -> String {
+        let digest = transaction_digest(tx_bytes);
+        let signature = self.signing_key.sign(&digest);
+
+        let mut serialized = Vec::with_capacity(1 + 64 + 32);
+        serialized.push(ED25519_FLAG);
+        serialized.extend_from_slice(&signature.to_bytes());
+        serialized.extend_from_slice(self.signing_key.verifying_key().as_bytes());
+
+        BASE64.encode(serialized)
+    }
+}
+
+/// Verify a serialized Sui signature over transaction bytes
+///
+/// Returns the address of the signer on success.
+pub fn verify_transaction_signature(tx_bytes: &[u8], signature: &str) -> Result<String> {
+    let bytes = BASE64
+        .decode(signature)
+        .map_err(|e| ManusError::Crypto(format!("Invalid signature encoding: {}", e)))?;
+
+    if bytes.len() != 1 + 64 + 32 || bytes[0] != ED25519_FLAG {
+        return Err(ManusError::Crypto(
+            "Unsupported signature scheme".to_string(),
+        ));
+    }
+
+    let mut public_key = [0u8; 32];
+    public_key.copy_from_slice(&bytes[65..]);
+    let verifying_key = VerifyingKey::from_bytes(&public_key)
+        .map_err(|e| ManusError::Crypto(format!("Invalid public key: {}", e)))?;
+
+    let mut raw_signature = [0u8; 64];
+    raw_signature.copy_from_slice(&bytes[1..65]);
+    let signature = ed25519_dalek::Signature::from_bytes(&raw_signature);
+
+    verifying_key
+        .verify(&transaction_digest(tx_bytes), &signature)
+        .map_err(|e| ManusError::Crypto(format!("Signature verification failed: {}", e)))?;
+
+    Ok(address_from_public_key(&verifying_key))
+}
+
+/// Blake2b-256 digest of the intent message for transaction bytes
+fn transaction_digest(tx_bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake2b256::new();
+    hasher.update(TRANSACTION_INTENT);
+    hasher.update(tx_bytes);
+    hasher.finalize().into()
+}
+
+fn address_from_public_key(public_key: &VerifyingKey) -> String {
+    let mut hasher = Blake2b256::new();
+    hasher.update([ED25519_FLAG]);
+    hasher.update(public_key.as_bytes());
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_transaction() {
+        let keypair = SuiKeypair::from_bytes(&[7u8; 32]);
+        let signature = keypair.sign_transaction(b"tx bytes");
+
+        let signer = verify_transaction_signature(b"tx bytes", &signature).unwrap();
+        assert_eq!(signer, keypair.address());
+        assert!(verify_transaction_signature(b"other bytes", &signature).is_err());
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let mut encoded = vec![ED25519_FLAG];
+        encoded.extend_from_slice(&[9u8; 32]);
+        let keypair = SuiKeypair::from_base64(&BASE64.encode(encoded)).unwrap();
+
+        assert_eq!(
+            keypair.address(),
+            SuiKeypair::from_bytes(&[9u8; 32]).address()
+        );
+        assert_eq!(keypair.address().len(), 66);
+    }
+}
@@ -0,0 +1,1841 @@
+//! In-process mock Sui node
+//!
+//! Implements the subset of the Sui JSON-RPC surface used by [`SuiClient`](super::SuiClient)
+//! against an in-memory object store that mirrors the `manus_liquidity::vault` and
+//! `deepbook_lp_risk_controls::risk_control` layouts, so deposits, withdrawals and
+//! circuit breaker changes can be exercised without a network. DeepBook pools
+//! answer the read-only level 2 and account order calls made through
+//! `sui_devInspectTransactionBlock`, and accept post-only limit orders placed,
+//! modified and cancelled through a `BalanceManager`. Balance manager funds are
+//! not tracked. Order events are kept in a node-wide log served by
+//! `suix_queryEvents`, and fills are simulated with [`MockSuiNode::fill_order`].
+
+use crate::error::{ManusError, Result};
+use crate::sui::deepbook::{
+    self, decode_order_id, encode_order_id, OrderDeepPrice, CLOCK_OBJECT_ID, FLOAT_SCALING,
+    POST_ONLY,
+};
+use crate::sui::keys::verify_transaction_signature;
+use crate::sui::objects::{self, MoveObject};
+use crate::sui::ptb::{
+    Argument, CallArg, Command, GasCostSummary, ObjectArg, ObjectRef, ProgrammableMoveCall,
+    ProgrammableTransaction, TransactionData, TransactionDataV1, TransactionKind, TypeTag,
+};
+use crate::sui::transport::SuiTransport;
+use crate::sui::vault_math::{
+    self, ARITHMETIC_ERROR, E_INSUFFICIENT_BALANCE, E_INSUFFICIENT_SHARES, E_INVALID_AMOUNT,
+    E_MAX_CAPACITY_EXCEEDED, E_VAULT_PAUSED,
+};
+use crate::sui::{normalize_id, SUI_COIN_TYPE};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Reference gas price reported by the mock node
+const REFERENCE_GAS_PRICE: u64 = 1_000;
+
+/// Computation units charged per PTB command
+const COMPUTATION_UNITS_PER_COMMAND: u64 = 1_000;
+
+/// Storage cost charged for each created or mutated object
+const STORAGE_COST_PER_OBJECT: u64 = 1_000_000;
+
+/// Storage rebate returned for each mutated or deleted object
+const STORAGE_REBATE_PER_OBJECT: u64 = 990_000;
+
+/// Abort code of a DeepBook call with a price off the tick grid
+const E_INVALID_PRICE: u64 = 1;
+
+/// Abort code of a DeepBook call with a quantity off the lot grid or below the minimum size
+const E_INVALID_QUANTITY: u64 = 2;
+
+/// Abort code of a post-only order that would take liquidity
+const E_POST_ONLY_CROSSES: u64 = 3;
+
+/// Abort code of a call on an order the balance manager does not have
+const E_ORDER_NOT_FOUND: u64 = 4;
+
+/// Abort code of a trade proof for another balance manager or sender
+const E_INVALID_PROOF: u64 = 5;
+
+/// Owner of a mock object
+#[derive(Debug, Clone, PartialEq)]
+enum Owner {
+    Address(String),
+    Shared(u64),
+}
+
+/// Typed contents of a mock object
+#[derive(Debug, Clone)]
+enum ObjectData {
+    Vault {
+        coin_type: String,
+        balance: u64,
+        total_shares: u64,
+        total_underlying: u64,
+        max_capacity: u64,
+        strategy: Vec<u8>,
+        paused: bool,
+    },
+    VaultCap {
+        vault_id: String,
+    },
+    Share {
+        vault_id: String,
+        amount: u64,
+    },
+    Coin {
+        coin_type: String,
+        balance: u64,
+    },
+    RiskControl {
+        drawdown_limit: u64,
+        circuit_breaker_active: bool,
+        timelock_duration: u64,
+    },
+    Pool {
+        base_type: String,
+        quote_type: String,
+        tick_size: u64,
+        lot_size: u64,
+        min_size: u64,
+        bids: Vec<(u64, u64)>,
+        asks: Vec<(u64, u64)>,
+        orders: Vec<MockOrder>,
+        next_order_seq: u64,
+    },
+    BalanceManager {
+        owner: String,
+    },
+    Clock,
+}
+
+/// Order resting in a mock pool
+#[derive(Debug, Clone)]
+struct MockOrder {
+    balance_manager_id: String,
+    order_id: u128,
+    client_order_id: u64,
+    quantity: u64,
+    filled_quantity: u64,
+    expire_timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MockObject {
+    version: u64,
+    owner: Owner,
+    data: ObjectData,
+}
+
+/// Value flowing between PTB commands
+#[derive(Debug, Clone)]
+enum RuntimeValue {
+    Object(String),
+    Pure(Vec<u8>),
+}
+
+#[derive(Default)]
+struct TransactionEffects {
+    created: Vec<String>,
+    mutated: Vec<String>,
+    deleted: Vec<String>,
+    events: Vec<Value>,
+    gas: GasCostSummary,
+}
+
+#[derive(Clone)]
+struct NodeState {
+    package_id: String,
+    objects: BTreeMap<String, MockObject>,
+    transactions: BTreeMap<String, Value>,
+    coin_decimals: BTreeMap<String, u8>,
+    events: Vec<Value>,
+    next_id: u64,
+    lamport: u64,
+}
+
+/// In-memory Sui node implementing [`SuiTransport`]
+pub struct MockSuiNode {
+    state: Mutex<NodeState>,
+}
+
+impl MockSuiNode {
+    /// Create an empty node with a freshly "published" `manus_liquidity` package
+    pub fn new() -> Self {
+        let mut state = NodeState {
+            package_id: String::new(),
+            objects: BTreeMap::new(),
+            transactions: BTreeMap::new(),
+            coin_decimals: BTreeMap::from([(SUI_COIN_TYPE.to_string(), 9)]),
+            events: Vec::new(),
+            next_id: 0,
+            lamport: 1,
+        };
+        state.objects.insert(
+            normalize_id(CLOCK_OBJECT_ID),
+            MockObject {
+                version: 1,
+                owner: Owner::Shared(1),
+                data: ObjectData::Clock,
+            },
+        );
+        state.package_id = state.fresh_id();
+
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// ID of the mock `manus_liquidity` package
+    pub fn package_id(&self) -> String {
+        self.state.lock().unwrap().package_id.clone()
+    }
+
+    /// Create a shared `Vault<T>` and give its `VaultCap` to `admin`
+    ///
+    /// Returns `(vault_id, cap_id)`.
+    pub fn create_vault(
+        &self,
+        admin: &str,
+        coin_type: &str,
+        max_capacity: u64,
+        strategy: &str,
+    ) -> (String, String) {
+        let mut state = self.state.lock().unwrap();
+        let version = state.lamport;
+
+        let vault_id = state.insert(
+            Owner::Shared(version),
+            ObjectData::Vault {
+                coin_type: coin_type.to_string(),
+                balance: 0,
+                total_shares: 0,
+                total_underlying: 0,
+                max_capacity,
+                strategy: strategy.as_bytes().to_vec(),
+                paused: false,
+            },
+        );
+        let cap_id = state.insert(
+            Owner::Address(normalize_id(admin)),
+            ObjectData::VaultCap {
+                vault_id: vault_id.clone(),
+            },
+        );
+
+        (vault_id, cap_id)
+    }
+
+    /// Mint a `Coin<T>` owned by `owner`
+    pub fn mint_coin(&self, owner: &str, coin_type: &str, balance: u64) -> String {
+        self.state.lock().unwrap().insert(
+            Owner::Address(normalize_id(owner)),
+            ObjectData::Coin {
+                coin_type: coin_type.to_string(),
+                balance,
+            },
+        )
+    }
+
+    /// Create a `RiskControl` owned by `admin`
+    pub fn create_risk_control(
+        &self,
+        admin: &str,
+        drawdown_limit: u64,
+        timelock_duration: u64,
+    ) -> String {
+        self.state.lock().unwrap().insert(
+            Owner::Address(normalize_id(admin)),
+            ObjectData::RiskControl {
+                drawdown_limit,
+                circuit_breaker_active: false,
+                timelock_duration,
+            },
+        )
+    }
+
+    /// Set the circuit breaker flag of a risk control directly
+    pub fn set_circuit_breaker(&self, risk_control_id: &str, value: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(MockObject {
+            data:
+                ObjectData::RiskControl {
+                    circuit_breaker_active,
+                    ..
+                },
+            ..
+        }) = state.objects.get_mut(&normalize_id(risk_control_id))
+        {
+            *circuit_breaker_active = value;
+        }
+    }
+
+    /// Create a shared DeepBook `Pool<Base, Quote>` with an empty book
+    pub fn create_pool(
+        &self,
+        base_type: &str,
+        quote_type: &str,
+        tick_size: u64,
+        lot_size: u64,
+        min_size: u64,
+    ) -> String {
+        let mut state = self.state.lock().unwrap();
+        let version = state.lamport;
+        state.insert(
+            Owner::Shared(version),
+            ObjectData::Pool {
+                base_type: base_type.to_string(),
+                quote_type: quote_type.to_string(),
+                tick_size,
+                lot_size,
+                min_size,
+                bids: vec![],
+                asks: vec![],
+                orders: vec![],
+                next_order_seq: 0,
+            },
+        )
+    }
+
+    /// Create a shared DeepBook `BalanceManager` owned by `owner`
+    pub fn create_balance_manager(&self, owner: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let version = state.lamport;
+        state.insert(
+            Owner::Shared(version),
+            ObjectData::BalanceManager {
+                owner: normalize_id(owner),
+            },
+        )
+    }
+
+    /// Replace the `(price, quantity)` levels resting in a pool
+    pub fn set_pool_levels(
+        &self,
+        pool_id: &str,
+        bid_levels: Vec<(u64, u64)>,
+        ask_levels: Vec<(u64, u64)>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(MockObject {
+            data: ObjectData::Pool { bids, asks, .. },
+            ..
+        }) = state.objects.get_mut(&normalize_id(pool_id))
+        {
+            *bids = bid_levels;
+            *asks = ask_levels;
+        }
+    }
+
+    /// Fill up to `quantity` of a resting order against a taker, as an
+    /// `OrderFilled` event in its own transaction
+    ///
+    /// Fully filled orders leave the book. Returns the quantity filled.
+    pub fn fill_order(&self, pool_id: &str, order_id: u128, quantity: u64) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let pool_id = normalize_id(pool_id);
+        let package_id = state.package_id.clone();
+        let Some(MockObject {
+            data: ObjectData::Pool { orders, .. },
+            ..
+        }) = state.objects.get_mut(&pool_id)
+        else {
+            return 0;
+        };
+        let Some(index) = orders.iter().position(|order| order.order_id == order_id) else {
+            return 0;
+        };
+
+        let order = &mut orders[index];
+        let quantity = quantity.min(order.quantity - order.filled_quantity);
+        order.filled_quantity += quantity;
+        let (is_bid, price) = decode_order_id(order_id);
+        let quote_quantity = quantity as u128 * price as u128 / FLOAT_SCALING as u128;
+        let event = json!({
+            "type": format!("{}::order_info::OrderFilled", package_id),
+            "packageId": package_id,
+            "transactionModule": "pool",
+            "parsedJson": {
+                "pool_id": pool_id,
+                "maker_order_id": order_id.to_string(),
+                "taker_order_id": "0",
+                "maker_client_order_id": order.client_order_id.to_string(),
+                "taker_client_order_id": "0",
+                "price": price.to_string(),
+                "taker_is_bid": !is_bid,
+                "base_quantity": quantity.to_string(),
+                "quote_quantity": quote_quantity.to_string(),
+                "maker_balance_manager_id": order.balance_manager_id,
+                "taker_balance_manager_id": normalize_id("0x0"),
+                "timestamp": timestamp_ms().to_string(),
+            },
+        });
+        if order.filled_quantity == order.quantity {
+            orders.remove(index);
+        }
+
+        state.lamport += 1;
+        let digest = bs58::encode(blake2b(&state.lamport.to_le_bytes())).into_string();
+        state.record_events(&digest, &mut [event]);
+        quantity
+    }
+
+    /// Remove a resting order without emitting any event, as if the
+    /// events reporting it had been missed
+    pub fn drop_order(&self, pool_id: &str, order_id: u128) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(MockObject {
+            data: ObjectData::Pool { orders, .. },
+            ..
+        }) = state.objects.get_mut(&normalize_id(pool_id))
+        {
+            orders.retain(|order| order.order_id != order_id);
+        }
+    }
+
+    /// Register the decimals reported in a coin type's metadata
+    pub fn set_coin_decimals(&self, coin_type: &str, decimals: u8) {
+        self.state
+            .lock()
+            .unwrap()
+            .coin_decimals
+            .insert(coin_type.to_string(), decimals);
+    }
+
+    /// Set the paused flag of a vault directly
+    pub fn set_paused(&self, vault_id: &str, value: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(MockObject {
+            data: ObjectData::Vault { paused, .. },
+            ..
+        }) = state.objects.get_mut(&normalize_id(vault_id))
+        {
+            *paused = value;
+        }
+    }
+
+    /// Total balance of `Coin<T>` objects owned by `owner`
+    pub fn coin_balance(&self, owner: &str, coin_type: &str) -> u64 {
+        let owner = Owner::Address(normalize_id(owner));
+        self.state
+            .lock()
+            .unwrap()
+            .objects
+            .values()
+            .filter(|object| object.owner == owner)
+            .map(|object| match &object.data {
+                ObjectData::Coin {
+                    coin_type: t,
+                    balance,
+                } if same_type(t, coin_type) => *balance,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn handle(&self, method: &str, params: &Value) -> Result<Value> {
+        let mut state = self.state.lock().unwrap();
+
+        match method {
+            "sui_getObject" => {
+                let id = normalize_id(param_str(params, 0)?);
+                Ok(match state.objects.get(&id) {
+                    Some(object) => json!({"data": state.object_json(&id, object)}),
+                    None => json!({"error": {"code": "notExists", "object_id": id}}),
+                })
+            }
+            "suix_getCoins" => {
+                let owner = Owner::Address(normalize_id(param_str(params, 0)?));
+                let coin_type = params[1].as_str().unwrap_or("0x2::sui::SUI");
+                let data: Vec<Value> = state
+                    .objects
+                    .iter()
+                    .filter(|(_, object)| object.owner == owner)
+                    .filter_map(|(id, object)| match &object.data {
+                        ObjectData::Coin {
+                            coin_type: t,
+                            balance,
+                        } if same_type(t, coin_type) => Some(json!({
+                            "coinType": t,
+                            "coinObjectId": id,
+                            "version": object.version.to_string(),
+                            "digest": object_digest(id, object.version),
+                            "balance": balance.to_string(),
+                        })),
+                        _ => None,
+                    })
+                    .collect();
+                Ok(json!({"data": data, "nextCursor": null, "hasNextPage": false}))
+            }
+            "suix_getOwnedObjects" => {
+                let owner = Owner::Address(normalize_id(param_str(params, 0)?));
+                let struct_type = params[1]["filter"]["StructType"].as_str();
+                let data: Vec<Value> = state
+                    .objects
+                    .iter()
+                    .filter(|(_, object)| object.owner == owner)
+                    .filter(|(_, object)| {
+                        struct_type.is_none_or(|t| state.type_of(&object.data) == t)
+                    })
+                    .map(|(id, object)| json!({"data": state.object_json(id, object)}))
+                    .collect();
+                Ok(json!({"data": data, "nextCursor": null, "hasNextPage": false}))
+            }
+            "suix_getReferenceGasPrice" => Ok(json!(REFERENCE_GAS_PRICE.to_string())),
+            "suix_getCoinMetadata" => {
+                let coin_type = param_str(params, 0)?;
+                Ok(state
+                    .coin_decimals
+                    .iter()
+                    .find(|(t, _)| same_type(t, coin_type))
+                    .map_or(Value::Null, |(_, decimals)| json!({"decimals": decimals})))
+            }
+            "sui_devInspectTransactionBlock" => {
+                let bytes = BASE64
+                    .decode(param_str(params, 1)?)
+                    .map_err(|e| ManusError::Sui(format!("Invalid transaction bytes: {}", e)))?;
+                let TransactionKind::ProgrammableTransaction(ptb) = bcs::from_bytes(&bytes)
+                    .map_err(|e| ManusError::Sui(format!("Invalid transaction kind: {}", e)))?;
+                Ok(match state.inspect(&ptb) {
+                    Ok(results) => json!({
+                        "effects": {"status": {"status": "success"}},
+                        "results": results,
+                    }),
+                    Err(error) => json!({
+                        "effects": {"status": {"status": "failure", "error": error}},
+                        "error": error,
+                    }),
+                })
+            }
+            "suix_queryEvents" => {
+                let filter = &params[0]["MoveModule"];
+                let (Some(package), Some(module)) =
+                    (filter["package"].as_str(), filter["module"].as_str())
+                else {
+                    return Err(ManusError::Sui(
+                        "Only MoveModule event filters are supported".to_string(),
+                    ));
+                };
+                let package = normalize_id(package);
+                let cursor = &params[1];
+                let limit = params[2].as_u64().unwrap_or(50) as usize;
+
+                let mut events: Vec<&Value> = state
+                    .events
+                    .iter()
+                    .filter(|event| {
+                        event["packageId"] == package.as_str()
+                            && event["transactionModule"] == module
+                    })
+                    .collect();
+                if params[3].as_bool().unwrap_or(false) {
+                    events.reverse();
+                }
+                if !cursor.is_null() {
+                    let position = events
+                        .iter()
+                        .position(|event| event["id"] == *cursor)
+                        .ok_or_else(|| {
+                            ManusError::Sui(format!("Unknown event cursor {}", cursor))
+                        })?;
+                    events.drain(..=position);
+                }
+
+                let has_next_page = events.len() > limit;
+                events.truncate(limit);
+                let next_cursor = events
+                    .last()
+                    .map_or_else(|| cursor.clone(), |event| event["id"].clone());
+                Ok(json!({
+                    "data": events,
+                    "nextCursor": next_cursor,
+                    "hasNextPage": has_next_page,
+                }))
+            }
+            "sui_dryRunTransactionBlock" => {
+                let (_, data) = decode_transaction(param_str(params, 0)?)?;
+                let mut scratch = state.clone();
+                scratch.lamport += 1;
+                let (status, effects) = match scratch.run(data.inner()) {
+                    Ok(effects) => (json!({"status": "success"}), effects),
+                    Err(error) => (
+                        json!({"status": "failure", "error": error}),
+                        TransactionEffects::default(),
+                    ),
+                };
+                Ok(json!({
+                    "effects": scratch.effects_json(&status, &effects, ""),
+                    "events": effects.events,
+                }))
+            }
+            "sui_executeTransactionBlock" => {
+                let signature = params[1][0]
+                    .as_str()
+                    .ok_or_else(|| ManusError::Sui("Missing signature".to_string()))?;
+                state.execute(param_str(params, 0)?, signature)
+            }
+            "sui_getTransactionBlock" => {
+                let digest = param_str(params, 0)?;
+                state
+                    .transactions
+                    .get(digest)
+                    .cloned()
+                    .ok_or_else(|| ManusError::Sui(format!("Transaction {} not found", digest)))
+            }
+            _ => Err(ManusError::Sui(format!("Method not found: {}", method))),
+        }
+    }
+}
+
+impl Default for MockSuiNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SuiTransport for MockSuiNode {
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.handle(method, &params)
+    }
+}
+
+impl NodeState {
+    fn fresh_id(&mut self) -> String {
+        loop {
+            self.next_id += 1;
+            let id = format!("0x{:064x}", self.next_id);
+            if !self.objects.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    fn insert(&mut self, owner: Owner, data: ObjectData) -> String {
+        let id = self.fresh_id();
+        self.objects.insert(
+            id.clone(),
+            MockObject {
+                version: self.lamport,
+                owner,
+                data,
+            },
+        );
+        id
+    }
+
+    fn type_of(&self, data: &ObjectData) -> String {
+        match data {
+            ObjectData::Vault { coin_type, .. } => {
+                format!("{}::vault::Vault<{}>", self.package_id, coin_type)
+            }
+            ObjectData::VaultCap { .. } => format!("{}::vault::VaultCap", self.package_id),
+            ObjectData::Share { .. } => format!("{}::vault::Share", self.package_id),
+            ObjectData::Coin { coin_type, .. } => format!("0x2::coin::Coin<{}>", coin_type),
+            ObjectData::RiskControl { .. } => {
+                format!("{}::risk_control::RiskControl", self.package_id)
+            }
+            ObjectData::Pool {
+                base_type,
+                quote_type,
+                ..
+            } => format!(
+                "{}::pool::Pool<{}, {}>",
+                self.package_id, base_type, quote_type
+            ),
+            ObjectData::BalanceManager { .. } => {
+                format!("{}::balance_manager::BalanceManager", self.package_id)
+            }
+            ObjectData::Clock => "0x2::clock::Clock".to_string(),
+        }
+    }
+
+    fn object_json(&self, id: &str, object: &MockObject) -> Value {
+        let object_type = self.type_of(&object.data);
+        let fields = match &object.data {
+            ObjectData::Vault {
+                balance,
+                total_shares,
+                total_underlying,
+                max_capacity,
+                strategy,
+                paused,
+                ..
+            } => json!({
+                "id": {"id": id},
+                "balance": balance.to_string(),
+                "total_shares": total_shares.to_string(),
+                "total_underlying": total_underlying.to_string(),
+                "max_capacity": max_capacity.to_string(),
+                "strategy": strategy,
+                "paused": paused,
+            }),
+            ObjectData::VaultCap { vault_id } => json!({"id": {"id": id}, "vault_id": vault_id}),
+            ObjectData::Share { vault_id, amount } => json!({
+                "id": {"id": id},
+                "vault_id": vault_id,
+                "amount": amount.to_string(),
+            }),
+            ObjectData::Coin { balance, .. } => {
+                json!({"id": {"id": id}, "balance": balance.to_string()})
+            }
+            ObjectData::RiskControl {
+                drawdown_limit,
+                circuit_breaker_active,
+                timelock_duration,
+            } => json!({
+                "id": {"id": id},
+                "drawdown_limit": drawdown_limit.to_string(),
+                "circuit_breaker_active": circuit_breaker_active,
+                "timelock_duration": timelock_duration.to_string(),
+            }),
+            ObjectData::Pool {
+                tick_size,
+                lot_size,
+                min_size,
+                ..
+            } => json!({
+                "id": {"id": id},
+                "tick_size": tick_size.to_string(),
+                "lot_size": lot_size.to_string(),
+                "min_size": min_size.to_string(),
+            }),
+            ObjectData::BalanceManager { owner } => json!({"id": {"id": id}, "owner": owner}),
+            ObjectData::Clock => json!({
+                "id": {"id": id},
+                "timestamp_ms": timestamp_ms().to_string(),
+            }),
+        };
+
+        let has_public_transfer =
+            !matches!(object.data, ObjectData::VaultCap { .. } | ObjectData::Clock);
+
+        json!({
+            "objectId": id,
+            "version": object.version.to_string(),
+            "digest": object_digest(id, object.version),
+            "type": object_type,
+            "owner": owner_json(&object.owner),
+            "content": {
+                "dataType": "moveObject",
+                "type": object_type,
+                "hasPublicTransfer": has_public_transfer,
+                "fields": fields,
+            },
+            "bcs": {
+                "dataType": "moveObject",
+                "type": object_type,
+                "hasPublicTransfer": has_public_transfer,
+                "version": object.version,
+                "bcsBytes": BASE64.encode(bcs_bytes(id, &object.data)),
+            },
+        })
+    }
+
+    fn execute(&mut self, tx_bytes: &str, signature: &str) -> Result<Value> {
+        let (raw, data) = decode_transaction(tx_bytes)?;
+        let data = data.inner();
+
+        let signer = verify_transaction_signature(&raw, signature)
+            .map_err(|e| ManusError::Sui(format!("Invalid signature: {}", e)))?;
+        if signer != data.sender.to_string() {
+            return Err(ManusError::Sui(
+                "Signer does not match transaction sender".to_string(),
+            ));
+        }
+
+        let digest = bs58::encode(blake2b(&raw)).into_string();
+        if self.transactions.contains_key(&digest) {
+            return Err(ManusError::Sui(format!(
+                "Transaction {} already executed",
+                digest
+            )));
+        }
+
+        // Apply against a scratch copy so aborted transactions leave no trace;
+        // unlike a real node, failed transactions are not charged gas
+        let mut scratch = self.clone();
+        scratch.lamport += 1;
+        let (status, effects) = match scratch.run(data) {
+            Ok(mut effects) => {
+                *self = scratch;
+                self.record_events(&digest, &mut effects.events);
+                (json!({"status": "success"}), effects)
+            }
+            Err(error) => {
+                self.lamport += 1;
+                (
+                    json!({"status": "failure", "error": error}),
+                    TransactionEffects::default(),
+                )
+            }
+        };
+
+        let response = json!({
+            "digest": digest,
+            "effects": self.effects_json(&status, &effects, &digest),
+            "events": effects.events,
+        });
+        self.transactions.insert(digest, response.clone());
+
+        Ok(response)
+    }
+
+    /// Number the events of transaction `digest` and append them to the event log
+    fn record_events(&mut self, digest: &str, events: &mut [Value]) {
+        let timestamp = timestamp_ms().to_string();
+        for (seq, event) in events.iter_mut().enumerate() {
+            event["id"] = json!({"txDigest": digest, "eventSeq": seq.to_string()});
+            event["timestampMs"] = json!(timestamp);
+            self.events.push(event.clone());
+        }
+    }
+
+    fn effects_json(&self, status: &Value, effects: &TransactionEffects, digest: &str) -> Value {
+        let refs = |ids: &[String]| -> Vec<Value> {
+            ids.iter()
+                .filter_map(|id| {
+                    self.objects.get(id).map(|object| {
+                        json!({
+                            "owner": owner_json(&object.owner),
+                            "reference": {
+                                "objectId": id,
+                                "version": object.version.to_string(),
+                                "digest": object_digest(id, object.version),
+                            },
+                        })
+                    })
+                })
+                .collect()
+        };
+
+        json!({
+            "status": status,
+            "transactionDigest": digest,
+            "gasUsed": {
+                "computationCost": effects.gas.computation_cost.to_string(),
+                "storageCost": effects.gas.storage_cost.to_string(),
+                "storageRebate": effects.gas.storage_rebate.to_string(),
+                "nonRefundableStorageFee": "0",
+            },
+            "created": refs(&effects.created),
+            "mutated": refs(&effects.mutated),
+            "deleted": effects.deleted.iter().map(|id| json!({"objectId": id})).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Evaluate read-only DeepBook pool calls, returning each command's return values
+    fn inspect(&self, ptb: &ProgrammableTransaction) -> std::result::Result<Vec<Value>, String> {
+        let input = |argument: &Argument| match argument {
+            Argument::Input(i) => ptb
+                .inputs
+                .get(*i as usize)
+                .ok_or("Invalid input".to_string()),
+            other => Err(format!("Unsupported argument {:?}", other)),
+        };
+
+        let mut results = Vec::new();
+        for command in &ptb.commands {
+            let Command::MoveCall(call) = command else {
+                return Err("Only Move calls can be inspected".to_string());
+            };
+            if call.package.to_string() != self.package_id || call.module != "pool" {
+                return Err(format!(
+                    "FunctionNotFound: {}::{}::{}",
+                    call.package, call.module, call.function
+                ));
+            }
+            let pool_id = match input(call.arguments.first().ok_or("Missing pool argument")?)? {
+                CallArg::Object(object) => object.id().to_string(),
+                CallArg::Pure(_) => return Err("Expected a pool object".to_string()),
+            };
+            let Some(MockObject {
+                data:
+                    ObjectData::Pool {
+                        tick_size,
+                        lot_size,
+                        min_size,
+                        bids,
+                        asks,
+                        orders,
+                        ..
+                    },
+                ..
+            }) = self.objects.get(&pool_id)
+            else {
+                return Err(format!("Object {} is not a Pool", pool_id));
+            };
+
+            let values = match call.function.as_str() {
+                "pool_book_params" => vec![
+                    return_value(tick_size, "u64"),
+                    return_value(lot_size, "u64"),
+                    return_value(min_size, "u64"),
+                ],
+                "get_level2_ticks_from_mid" => {
+                    let ticks: u64 =
+                        match input(call.arguments.get(1).ok_or("Missing ticks argument")?)? {
+                            CallArg::Pure(bytes) => bcs::from_bytes(bytes)
+                                .map_err(|e| format!("Invalid pure argument: {}", e))?,
+                            CallArg::Object(_) => return Err("Expected a tick count".to_string()),
+                        };
+                    let mut bids = book_side(bids, orders, true);
+                    let mut asks = book_side(asks, orders, false);
+                    bids.truncate(ticks as usize);
+                    asks.truncate(ticks as usize);
+                    let (bid_prices, bid_quantities): (Vec<u64>, Vec<u64>) =
+                        bids.into_iter().unzip();
+                    let (ask_prices, ask_quantities): (Vec<u64>, Vec<u64>) =
+                        asks.into_iter().unzip();
+                    vec![
+                        return_value(&bid_prices, "vector<u64>"),
+                        return_value(&bid_quantities, "vector<u64>"),
+                        return_value(&ask_prices, "vector<u64>"),
+                        return_value(&ask_quantities, "vector<u64>"),
+                    ]
+                }
+                "get_account_order_details" => {
+                    let balance_manager_id = match input(
+                        call.arguments
+                            .get(1)
+                            .ok_or("Missing balance manager argument")?,
+                    )? {
+                        CallArg::Object(object) => object.id().to_string(),
+                        CallArg::Pure(_) => return Err("Expected a balance manager".to_string()),
+                    };
+                    let account_orders: Vec<deepbook::Order> = orders
+                        .iter()
+                        .filter(|order| order.balance_manager_id == balance_manager_id)
+                        .map(|order| deepbook::Order {
+                            balance_manager_id: address(&order.balance_manager_id),
+                            order_id: order.order_id,
+                            client_order_id: order.client_order_id,
+                            quantity: order.quantity,
+                            filled_quantity: order.filled_quantity,
+                            fee_is_deep: true,
+                            order_deep_price: OrderDeepPrice {
+                                asset_is_base: true,
+                                deep_per_asset: 0,
+                            },
+                            epoch: 0,
+                            status: u8::from(order.filled_quantity > 0),
+                            expire_timestamp: order.expire_timestamp,
+                        })
+                        .collect();
+                    vec![return_value(
+                        &account_orders,
+                        &format!("vector<{}::order::Order>", self.package_id),
+                    )]
+                }
+                other => return Err(format!("FunctionNotFound: pool::{}", other)),
+            };
+            results.push(json!({"returnValues": values}));
+        }
+
+        Ok(results)
+    }
+
+    /// Execute a programmable transaction, including gas smashing and charging
+    fn run(&mut self, data: &TransactionDataV1) -> std::result::Result<TransactionEffects, String> {
+        let sender = data.sender.to_string();
+        let gas = &data.gas_data;
+        let mut effects = TransactionEffects::default();
+
+        if gas.price < REFERENCE_GAS_PRICE {
+            return Err(format!(
+                "Gas price {} below reference price {}",
+                gas.price, REFERENCE_GAS_PRICE
+            ));
+        }
+
+        // Smash all gas payment coins into the first one
+        let mut gas_ids = Vec::new();
+        for payment in &gas.payment {
+            gas_ids.push(self.owned_input(&sender, payment)?);
+        }
+        let gas_coin = gas_ids.first().cloned().ok_or("No gas payment")?;
+        for id in &gas_ids[1..] {
+            let balance = *self.sui_balance(&sender, id)?;
+            *self.sui_balance(&sender, &gas_coin)? += balance;
+            self.objects.remove(id);
+            effects.deleted.push(id.clone());
+        }
+        if *self.sui_balance(&sender, &gas_coin)? < gas.budget {
+            return Err(format!("Gas balance below budget {}", gas.budget));
+        }
+
+        let TransactionKind::ProgrammableTransaction(ptb) = &data.kind;
+        let mut inputs = Vec::new();
+        for input in &ptb.inputs {
+            inputs.push(match input {
+                CallArg::Pure(bytes) => RuntimeValue::Pure(bytes.clone()),
+                CallArg::Object(ObjectArg::ImmOrOwnedObject(object)) => {
+                    RuntimeValue::Object(self.owned_input(&sender, object)?)
+                }
+                CallArg::Object(ObjectArg::SharedObject { id, .. }) => {
+                    let id = id.to_string();
+                    match self.objects.get(&id) {
+                        Some(MockObject {
+                            owner: Owner::Shared(_),
+                            ..
+                        }) => RuntimeValue::Object(id),
+                        _ => return Err(format!("Object {} is not shared", id)),
+                    }
+                }
+                CallArg::Object(ObjectArg::Receiving(_)) => {
+                    return Err("Receiving is not supported".to_string())
+                }
+            });
+        }
+
+        let mut results: Vec<Vec<RuntimeValue>> = Vec::new();
+        let mut unconsumed = Vec::new();
+        for command in &ptb.commands {
+            let resolve = |argument: &Argument| -> std::result::Result<RuntimeValue, String> {
+                match argument {
+                    Argument::GasCoin => Some(RuntimeValue::Object(gas_coin.clone())),
+                    Argument::Input(i) => inputs.get(*i as usize).cloned(),
+                    Argument::Result(i) => {
+                        results.get(*i as usize).and_then(|r| r.first()).cloned()
+                    }
+                    Argument::NestedResult(i, j) => results
+                        .get(*i as usize)
+                        .and_then(|r| r.get(*j as usize))
+                        .cloned(),
+                }
+                .ok_or_else(|| format!("Invalid argument {:?}", argument))
+            };
+            let mut consume = |value: &RuntimeValue| {
+                if let RuntimeValue::Object(id) = value {
+                    unconsumed.retain(|pending| pending != id);
+                }
+            };
+
+            let result = match command {
+                Command::SplitCoins(coin, amounts) => {
+                    let coin = object_id(&resolve(coin)?)?;
+                    let mut created = Vec::new();
+                    for amount in amounts {
+                        let amount: u64 = pure(&resolve(amount)?)?;
+                        let (coin_type, balance) = match self.owned(&sender, &coin)? {
+                            ObjectData::Coin { coin_type, balance } => (coin_type.clone(), balance),
+                            _ => return Err(format!("Object {} is not a coin", coin)),
+                        };
+                        *balance = balance
+                            .checked_sub(amount)
+                            .ok_or("InsufficientCoinBalance")?;
+                        let id = self.insert(
+                            Owner::Address(sender.clone()),
+                            ObjectData::Coin {
+                                coin_type,
+                                balance: amount,
+                            },
+                        );
+                        effects.created.push(id.clone());
+                        unconsumed.push(id.clone());
+                        created.push(RuntimeValue::Object(id));
+                    }
+                    self.touch(&coin, &mut effects);
+                    created
+                }
+                Command::MergeCoins(target, sources) => {
+                    let target = object_id(&resolve(target)?)?;
+                    for source in sources {
+                        let source_value = resolve(source)?;
+                        consume(&source_value);
+                        let source = object_id(&source_value)?;
+                        let (source_type, amount) = match self.owned(&sender, &source)? {
+                            ObjectData::Coin { coin_type, balance } => {
+                                (coin_type.clone(), *balance)
+                            }
+                            _ => return Err(format!("Object {} is not a coin", source)),
+                        };
+                        match self.owned(&sender, &target)? {
+                            ObjectData::Coin { coin_type, balance }
+                                if same_type(coin_type, &source_type) =>
+                            {
+                                *balance += amount
+                            }
+                            _ => return Err(format!("Cannot merge {} into {}", source, target)),
+                        }
+                        self.objects.remove(&source);
+                        effects.deleted.push(source);
+                    }
+                    self.touch(&target, &mut effects);
+                    vec![]
+                }
+                Command::TransferObjects(objects, recipient) => {
+                    let recipient: objects::Address = pure(&resolve(recipient)?)?;
+                    for object in objects {
+                        let value = resolve(object)?;
+                        consume(&value);
+                        let id = object_id(&value)?;
+                        self.owned(&sender, &id)?;
+                        if let Some(object) = self.objects.get_mut(&id) {
+                            object.owner = Owner::Address(recipient.to_string());
+                        }
+                        self.touch(&id, &mut effects);
+                    }
+                    vec![]
+                }
+                Command::MoveCall(call) => {
+                    if call.package.to_string() != self.package_id {
+                        return Err(format!(
+                            "FunctionNotFound: {}::{}::{}",
+                            call.package, call.module, call.function
+                        ));
+                    }
+                    let arguments = call
+                        .arguments
+                        .iter()
+                        .map(&resolve)
+                        .collect::<std::result::Result<Vec<_>, _>>()?;
+                    arguments.iter().for_each(&mut consume);
+
+                    let first_event = effects.events.len();
+                    let returned = match call.module.as_str() {
+                        "vault" => {
+                            self.vault_call(&sender, call, &arguments, &mut effects)?;
+                            vec![]
+                        }
+                        "risk_control" => {
+                            self.risk_control_call(
+                                &sender,
+                                &call.function,
+                                &arguments,
+                                &mut effects,
+                            )?;
+                            vec![]
+                        }
+                        "balance_manager" => {
+                            self.balance_manager_call(&sender, &call.function, &arguments)?
+                        }
+                        "pool" => {
+                            self.pool_call(&sender, &call.function, &arguments, &mut effects)?;
+                            vec![]
+                        }
+                        other => {
+                            return Err(format!(
+                                "FunctionNotFound: {}::{}::{}",
+                                call.package, other, call.function
+                            ))
+                        }
+                    };
+                    for event in &mut effects.events[first_event..] {
+                        event["packageId"] = json!(self.package_id);
+                        event["transactionModule"] = json!(call.module);
+                        event["sender"] = json!(sender);
+                    }
+                    returned
+                }
+            };
+            results.push(result);
+        }
+
+        if !unconsumed.is_empty() {
+            return Err("UnusedValueWithoutDrop".to_string());
+        }
+
+        // Owned inputs are bumped to the new version even when only borrowed
+        for input in &inputs {
+            if let RuntimeValue::Object(id) = input {
+                self.touch(id, &mut effects);
+            }
+        }
+
+        // Objects created and destroyed within the transaction never existed on chain
+        let transient: Vec<String> = effects
+            .created
+            .iter()
+            .filter(|id| effects.deleted.contains(id))
+            .cloned()
+            .collect();
+        effects.created.retain(|id| !transient.contains(id));
+        effects.deleted.retain(|id| !transient.contains(id));
+        effects
+            .mutated
+            .retain(|id| !effects.deleted.contains(id) && !effects.created.contains(id));
+
+        self.touch(&gas_coin, &mut effects);
+        let changed = (effects.created.len() + effects.mutated.len()) as u64;
+        let released = (effects.mutated.len() + effects.deleted.len()) as u64;
+        effects.gas = GasCostSummary {
+            computation_cost: COMPUTATION_UNITS_PER_COMMAND * ptb.commands.len() as u64 * gas.price,
+            storage_cost: STORAGE_COST_PER_OBJECT * changed,
+            storage_rebate: STORAGE_REBATE_PER_OBJECT * released,
+        };
+        let charge = effects.gas.net_gas_usage();
+        if effects.gas.computation_cost.max(charge) > gas.budget {
+            return Err("InsufficientGas".to_string());
+        }
+
+        let balance = self.sui_balance(&sender, &gas_coin)?;
+        *balance = balance.checked_sub(charge).ok_or("InsufficientGas")?;
+
+        Ok(effects)
+    }
+
+    fn vault_call(
+        &mut self,
+        sender: &str,
+        call: &ProgrammableMoveCall,
+        arguments: &[RuntimeValue],
+        effects: &mut TransactionEffects,
+    ) -> std::result::Result<(), String> {
+        let coin_type = call
+            .type_arguments
+            .first()
+            .ok_or("Missing type argument")?
+            .to_string();
+        let vault_id = object_id(arguments.first().ok_or("Missing vault argument")?)?;
+        let object_arg = object_id(arguments.get(1).ok_or("Missing object argument")?)?;
+
+        match call.function.as_str() {
+            "deposit" => self.deposit(sender, &vault_id, &coin_type, &object_arg, effects),
+            "withdraw" => self.withdraw(sender, &vault_id, &coin_type, &object_arg, effects),
+            "pause" | "resume" => {
+                if !matches!(
+                    self.owned(sender, &object_arg)?,
+                    ObjectData::VaultCap { .. }
+                ) {
+                    return Err(format!("Object {} is not a VaultCap", object_arg));
+                }
+                *self.vault(&vault_id, &coin_type)?.paused = call.function == "pause";
+                self.touch(&vault_id, effects);
+                Ok(())
+            }
+            other => Err(format!("FunctionNotFound: vault::{}", other)),
+        }
+    }
+
+    fn risk_control_call(
+        &mut self,
+        sender: &str,
+        function: &str,
+        arguments: &[RuntimeValue],
+        effects: &mut TransactionEffects,
+    ) -> std::result::Result<(), String> {
+        let id = object_id(arguments.first().ok_or("Missing risk control argument")?)?;
+        let new_limit = match function {
+            "update_drawdown_limit" => Some(pure::<u64>(
+                arguments.get(1).ok_or("Missing limit argument")?,
+            )?),
+            _ => None,
+        };
+        let (drawdown_limit, circuit_breaker_active) = match self.owned(sender, &id)? {
+            ObjectData::RiskControl {
+                drawdown_limit,
+                circuit_breaker_active,
+                ..
+            } => (drawdown_limit, circuit_breaker_active),
+            _ => return Err(format!("Object {} is not a RiskControl", id)),
+        };
+
+        match (function, new_limit) {
+            ("activate_circuit_breaker", _) => *circuit_breaker_active = true,
+            ("deactivate_circuit_breaker", _) => *circuit_breaker_active = false,
+            (_, Some(limit)) => *drawdown_limit = limit,
+            (other, None) => return Err(format!("FunctionNotFound: risk_control::{}", other)),
+        }
+        self.touch(&id, effects);
+        Ok(())
+    }
+
+    fn balance_manager_call(
+        &mut self,
+        sender: &str,
+        function: &str,
+        arguments: &[RuntimeValue],
+    ) -> std::result::Result<Vec<RuntimeValue>, String> {
+        let id = object_id(
+            arguments
+                .first()
+                .ok_or("Missing balance manager argument")?,
+        )?;
+        let owner = match self.objects.get(&id) {
+            Some(MockObject {
+                data: ObjectData::BalanceManager { owner },
+                ..
+            }) => owner,
+            _ => return Err(format!("Object {} is not a BalanceManager", id)),
+        };
+
+        match function {
+            "generate_proof_as_owner" if owner == sender => {
+                // The proof names the balance manager it was generated for
+                let proof = bcs::to_bytes(&address(&id)).map_err(|e| e.to_string())?;
+                Ok(vec![RuntimeValue::Pure(proof)])
+            }
+            "generate_proof_as_owner" => Err(pool_abort(E_INVALID_PROOF)),
+            other => Err(format!("FunctionNotFound: balance_manager::{}", other)),
+        }
+    }
+
+    fn pool_call(
+        &mut self,
+        sender: &str,
+        function: &str,
+        arguments: &[RuntimeValue],
+        effects: &mut TransactionEffects,
+    ) -> std::result::Result<(), String> {
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .ok_or_else(|| format!("Missing argument {} of pool::{}", index, function))
+        };
+        let pool_id = object_id(argument(0)?)?;
+        let balance_manager_id = object_id(argument(1)?)?;
+        let proof: objects::Address = pure(argument(2)?)?;
+        if proof.to_string() != balance_manager_id {
+            return Err(pool_abort(E_INVALID_PROOF));
+        }
+
+        let package_id = self.package_id.clone();
+        let Some(MockObject {
+            data:
+                ObjectData::Pool {
+                    tick_size,
+                    lot_size,
+                    min_size,
+                    bids,
+                    asks,
+                    orders,
+                    next_order_seq,
+                    ..
+                },
+            ..
+        }) = self.objects.get_mut(&pool_id)
+        else {
+            return Err(format!("Object {} is not a Pool", pool_id));
+        };
+        let find = |orders: &[MockOrder], order_id: u128| {
+            orders
+                .iter()
+                .position(|order| {
+                    order.order_id == order_id && order.balance_manager_id == balance_manager_id
+                })
+                .ok_or_else(|| pool_abort(E_ORDER_NOT_FOUND))
+        };
+
+        let event = match function {
+            "place_limit_order" => {
+                let client_order_id: u64 = pure(argument(3)?)?;
+                let order_type: u8 = pure(argument(4)?)?;
+                let price: u64 = pure(argument(6)?)?;
+                let quantity: u64 = pure(argument(7)?)?;
+                let is_bid: bool = pure(argument(8)?)?;
+                let expire_timestamp: u64 = pure(argument(10)?)?;
+                if order_type != POST_ONLY {
+                    return Err("Only post-only orders are supported".to_string());
+                }
+                if price == 0 || !price.is_multiple_of(*tick_size) {
+                    return Err(pool_abort(E_INVALID_PRICE));
+                }
+                if quantity < *min_size || !quantity.is_multiple_of(*lot_size) {
+                    return Err(pool_abort(E_INVALID_QUANTITY));
+                }
+                let crosses = if is_bid {
+                    book_side(asks, orders, false)
+                        .first()
+                        .is_some_and(|&(ask, _)| ask <= price)
+                } else {
+                    book_side(bids, orders, true)
+                        .first()
+                        .is_some_and(|&(bid, _)| bid >= price)
+                };
+                if crosses {
+                    return Err(pool_abort(E_POST_ONLY_CROSSES));
+                }
+
+                *next_order_seq += 1;
+                let order = MockOrder {
+                    balance_manager_id: balance_manager_id.clone(),
+                    order_id: encode_order_id(is_bid, price, *next_order_seq),
+                    client_order_id,
+                    quantity,
+                    filled_quantity: 0,
+                    expire_timestamp,
+                };
+                let event = order_event(
+                    &package_id,
+                    "order_info::OrderPlaced",
+                    &pool_id,
+                    &order,
+                    sender,
+                    json!({
+                        "placed_quantity": quantity.to_string(),
+                        "expire_timestamp": expire_timestamp.to_string(),
+                    }),
+                );
+                orders.push(order);
+                event
+            }
+            "modify_order" => {
+                let order_id: u128 = pure(argument(3)?)?;
+                let new_quantity: u64 = pure(argument(4)?)?;
+                let index = find(orders, order_id)?;
+                let order = &mut orders[index];
+                if new_quantity >= order.quantity
+                    || new_quantity <= order.filled_quantity
+                    || !new_quantity.is_multiple_of(*lot_size)
+                {
+                    return Err(pool_abort(E_INVALID_QUANTITY));
+                }
+                let previous_quantity = order.quantity;
+                order.quantity = new_quantity;
+                order_event(
+                    &package_id,
+                    "order::OrderModified",
+                    &pool_id,
+                    order,
+                    sender,
+                    json!({
+                        "previous_quantity": previous_quantity.to_string(),
+                        "filled_quantity": order.filled_quantity.to_string(),
+                        "new_quantity": new_quantity.to_string(),
+                    }),
+                )
+            }
+            "cancel_order" => {
+                let order_id: u128 = pure(argument(3)?)?;
+                let order = orders.remove(find(orders, order_id)?);
+                order_event(
+                    &package_id,
+                    "order::OrderCanceled",
+                    &pool_id,
+                    &order,
+                    sender,
+                    json!({
+                        "original_quantity": order.quantity.to_string(),
+                        "base_asset_quantity_canceled":
+                            (order.quantity - order.filled_quantity).to_string(),
+                    }),
+                )
+            }
+            other => return Err(format!("FunctionNotFound: pool::{}", other)),
+        };
+
+        effects.events.push(event);
+        self.touch(&pool_id, effects);
+        Ok(())
+    }
+
+    fn deposit(
+        &mut self,
+        sender: &str,
+        vault_id: &str,
+        coin_type: &str,
+        coin_id: &str,
+        effects: &mut TransactionEffects,
+    ) -> std::result::Result<(), String> {
+        let deposit_amount = match self.owned(sender, coin_id)? {
+            ObjectData::Coin {
+                coin_type: t,
+                balance,
+            } if same_type(t, coin_type) => *balance,
+            _ => return Err(format!("Object {} is not a Coin<{}>", coin_id, coin_type)),
+        };
+
+        let vault = self.vault(vault_id, coin_type)?;
+        if *vault.paused {
+            return Err(abort(E_VAULT_PAUSED));
+        }
+        if deposit_amount == 0 {
+            return Err(abort(E_INVALID_AMOUNT));
+        }
+        if *vault.balance + deposit_amount > *vault.max_capacity {
+            return Err(abort(E_MAX_CAPACITY_EXCEEDED));
+        }
+
+        let shares_minted = vault_math::calculate_shares_for_deposit(
+            deposit_amount,
+            *vault.total_shares,
+            *vault.total_underlying,
+        )
+        .ok_or(ARITHMETIC_ERROR)?;
+
+        *vault.balance += deposit_amount;
+        *vault.total_shares += shares_minted;
+        *vault.total_underlying += deposit_amount;
+
+        self.objects.remove(coin_id);
+        effects.deleted.push(coin_id.to_string());
+        self.touch(vault_id, effects);
+
+        let share_id = self.insert(
+            Owner::Address(sender.to_string()),
+            ObjectData::Share {
+                vault_id: vault_id.to_string(),
+                amount: shares_minted,
+            },
+        );
+        effects.created.push(share_id);
+        effects.events.push(json!({
+            "type": format!("{}::vault::DepositEvent", self.package_id),
+            "parsedJson": {
+                "vault_id": vault_id,
+                "depositor": sender,
+                "amount": deposit_amount.to_string(),
+                "shares_minted": shares_minted.to_string(),
+            },
+        }));
+
+        Ok(())
+    }
+
+    fn withdraw(
+        &mut self,
+        sender: &str,
+        vault_id: &str,
+        coin_type: &str,
+        share_id: &str,
+        effects: &mut TransactionEffects,
+    ) -> std::result::Result<(), String> {
+        let (share_vault, shares_to_burn) = match self.owned(sender, share_id)? {
+            ObjectData::Share { vault_id, amount } => (vault_id.clone(), *amount),
+            _ => return Err(format!("Object {} is not a Share", share_id)),
+        };
+
+        let vault = self.vault(vault_id, coin_type)?;
+        if *vault.paused {
+            return Err(abort(E_VAULT_PAUSED));
+        }
+        if share_vault != vault_id || shares_to_burn == 0 {
+            return Err(abort(E_INVALID_AMOUNT));
+        }
+        if *vault.total_shares < shares_to_burn {
+            return Err(abort(E_INSUFFICIENT_SHARES));
+        }
+
+        let amount_withdrawn = vault_math::calculate_amount_for_withdrawal(
+            shares_to_burn,
+            *vault.total_shares,
+            *vault.total_underlying,
+        )
+        .ok_or(ARITHMETIC_ERROR)?;
+        if *vault.balance < amount_withdrawn {
+            return Err(abort(E_INSUFFICIENT_BALANCE));
+        }
+
+        *vault.total_shares -= shares_to_burn;
+        *vault.total_underlying -= amount_withdrawn;
+        *vault.balance -= amount_withdrawn;
+
+        self.objects.remove(share_id);
+        effects.deleted.push(share_id.to_string());
+        self.touch(vault_id, effects);
+
+        let coin_id = self.insert(
+            Owner::Address(sender.to_string()),
+            ObjectData::Coin {
+                coin_type: coin_type.to_string(),
+                balance: amount_withdrawn,
+            },
+        );
+        effects.created.push(coin_id);
+        effects.events.push(json!({
+            "type": format!("{}::vault::WithdrawEvent", self.package_id),
+            "parsedJson": {
+                "vault_id": vault_id,
+                "withdrawer": sender,
+                "shares_burned": shares_to_burn.to_string(),
+                "amount_withdrawn": amount_withdrawn.to_string(),
+            },
+        }));
+
+        Ok(())
+    }
+
+    /// Resolve an owned object input, checking ownership and version
+    fn owned_input(
+        &mut self,
+        sender: &str,
+        object: &ObjectRef,
+    ) -> std::result::Result<String, String> {
+        let id = object.id().to_string();
+        match self.objects.get(&id) {
+            Some(existing) if existing.version != object.1 => Err(format!(
+                "Object {} is at version {}, not {}",
+                id, existing.version, object.1
+            )),
+            Some(_) => {
+                self.owned(sender, &id)?;
+                Ok(id)
+            }
+            None => Err(format!("Object {} does not exist", id)),
+        }
+    }
+
+    /// Borrow the balance of a SUI coin owned by `sender`
+    fn sui_balance(&mut self, sender: &str, id: &str) -> std::result::Result<&mut u64, String> {
+        match self.owned(sender, id)? {
+            ObjectData::Coin { coin_type, balance } if same_type(coin_type, SUI_COIN_TYPE) => {
+                Ok(balance)
+            }
+            _ => Err(format!("Object {} is not a SUI coin", id)),
+        }
+    }
+
+    /// Borrow an object owned by `sender`
+    fn owned(&mut self, sender: &str, id: &str) -> std::result::Result<&mut ObjectData, String> {
+        match self.objects.get_mut(id) {
+            Some(object) if object.owner == Owner::Address(sender.to_string()) => {
+                Ok(&mut object.data)
+            }
+            Some(_) => Err(format!("Object {} is not owned by {}", id, sender)),
+            None => Err(format!("Object {} does not exist", id)),
+        }
+    }
+
+    /// Borrow the mutable fields of a shared `Vault<T>`
+    fn vault(&mut self, id: &str, coin_type: &str) -> std::result::Result<VaultFields<'_>, String> {
+        match self.objects.get_mut(id).map(|object| &mut object.data) {
+            Some(ObjectData::Vault {
+                coin_type: t,
+                balance,
+                total_shares,
+                total_underlying,
+                max_capacity,
+                paused,
+                ..
+            }) if same_type(t, coin_type) => Ok(VaultFields {
+                balance,
+                total_shares,
+                total_underlying,
+                max_capacity,
+                paused,
+            }),
+            _ => Err(format!("Object {} is not a Vault<{}>", id, coin_type)),
+        }
+    }
+
+    fn touch(&mut self, id: &str, effects: &mut TransactionEffects) {
+        if let Some(object) = self.objects.get_mut(id) {
+            object.version = self.lamport;
+            if !effects.mutated.iter().any(|mutated| mutated == id) {
+                effects.mutated.push(id.to_string());
+            }
+        }
+    }
+}
+
+struct VaultFields<'a> {
+    balance: &'a mut u64,
+    total_shares: &'a mut u64,
+    total_underlying: &'a mut u64,
+    max_capacity: &'a mut u64,
+    paused: &'a mut bool,
+}
+
+/// BCS encoding of an object using the Move struct layouts in [`objects`]
+fn bcs_bytes(id: &str, data: &ObjectData) -> Vec<u8> {
+    let id = address(id);
+    let encoded = match data {
+        ObjectData::Vault {
+            balance,
+            total_shares,
+            total_underlying,
+            max_capacity,
+            strategy,
+            paused,
+            ..
+        } => objects::Vault {
+            id,
+            balance: *balance,
+            total_shares: *total_shares,
+            total_underlying: *total_underlying,
+            max_capacity: *max_capacity,
+            strategy: strategy.clone(),
+            paused: *paused,
+        }
+        .to_bcs(),
+        ObjectData::VaultCap { vault_id } => objects::VaultCap {
+            id,
+            vault_id: address(vault_id),
+        }
+        .to_bcs(),
+        ObjectData::Share { vault_id, amount } => objects::Share {
+            id,
+            vault_id: address(vault_id),
+            amount: *amount,
+        }
+        .to_bcs(),
+        ObjectData::Coin { balance, .. } => objects::Coin {
+            id,
+            balance: *balance,
+        }
+        .to_bcs(),
+        // Pool and balance state lives in dynamic fields on chain; only the UID is encoded
+        ObjectData::Pool { .. } | ObjectData::BalanceManager { .. } => {
+            bcs::to_bytes(&id).map_err(|e| ManusError::Sui(format!("Failed to encode UID: {}", e)))
+        }
+        ObjectData::Clock => bcs::to_bytes(&(id, timestamp_ms()))
+            .map_err(|e| ManusError::Sui(format!("Failed to encode clock: {}", e))),
+        ObjectData::RiskControl {
+            drawdown_limit,
+            circuit_breaker_active,
+            timelock_duration,
+        } => objects::RiskControl {
+            id,
+            drawdown_limit: *drawdown_limit,
+            circuit_breaker_active: *circuit_breaker_active,
+            timelock_duration: *timelock_duration,
+        }
+        .to_bcs(),
+    };
+
+    encoded.expect("mock objects always encode")
+}
+
+fn address(id: &str) -> objects::Address {
+    objects::Address::from_hex(id).expect("mock IDs are valid addresses")
+}
+
+/// Dev-inspect return value: BCS bytes and Move type
+fn return_value<T: serde::Serialize + ?Sized>(value: &T, move_type: &str) -> Value {
+    json!([
+        bcs::to_bytes(value).expect("return values always encode"),
+        move_type
+    ])
+}
+
+fn abort(code: u64) -> String {
+    super::move_abort_status("vault", code)
+}
+
+fn pool_abort(code: u64) -> String {
+    super::move_abort_status("pool", code)
+}
+
+/// Aggregate a pool's resting levels and open orders on one side, best price first
+fn book_side(levels: &[(u64, u64)], orders: &[MockOrder], is_bid: bool) -> Vec<(u64, u64)> {
+    let mut side = BTreeMap::new();
+    for &(price, quantity) in levels {
+        *side.entry(price).or_insert(0) += quantity;
+    }
+    for order in orders {
+        let (order_is_bid, price) = decode_order_id(order.order_id);
+        if order_is_bid == is_bid {
+            *side.entry(price).or_insert(0) += order.quantity - order.filled_quantity;
+        }
+    }
+
+    let mut side: Vec<(u64, u64)> = side.into_iter().collect();
+    if is_bid {
+        side.reverse();
+    }
+    side
+}
+
+/// `order_info` or `order` event about `order`, with event-specific fields in `extra`
+fn order_event(
+    package_id: &str,
+    event_type: &str,
+    pool_id: &str,
+    order: &MockOrder,
+    trader: &str,
+    extra: Value,
+) -> Value {
+    let (is_bid, price) = decode_order_id(order.order_id);
+    let mut fields = json!({
+        "balance_manager_id": order.balance_manager_id,
+        "pool_id": pool_id,
+        "order_id": order.order_id.to_string(),
+        "client_order_id": order.client_order_id.to_string(),
+        "trader": trader,
+        "price": price.to_string(),
+        "is_bid": is_bid,
+        "timestamp": timestamp_ms().to_string(),
+    });
+    if let (Some(fields), Value::Object(extra)) = (fields.as_object_mut(), extra) {
+        fields.extend(extra);
+    }
+    json!({"type": format!("{}::{}", package_id, event_type), "parsedJson": fields})
+}
+
+fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+fn owner_json(owner: &Owner) -> Value {
+    match owner {
+        Owner::Address(address) => json!({"AddressOwner": address}),
+        Owner::Shared(version) => json!({"Shared": {"initial_shared_version": version}}),
+    }
+}
+
+fn object_digest(id: &str, version: u64) -> String {
+    let mut preimage = id.as_bytes().to_vec();
+    preimage.extend_from_slice(&version.to_le_bytes());
+    bs58::encode(blake2b(&preimage)).into_string()
+}
+
+fn blake2b(data: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(data).into()
+}
+
+fn decode_transaction(tx_bytes: &str) -> Result<(Vec<u8>, TransactionData)> {
+    let raw = BASE64
+        .decode(tx_bytes)
+        .map_err(|e| ManusError::Sui(format!("Invalid transaction bytes: {}", e)))?;
+    let data = TransactionData::from_bytes(&raw)?;
+    Ok((raw, data))
+}
+
+fn object_id(value: &RuntimeValue) -> std::result::Result<String, String> {
+    match value {
+        RuntimeValue::Object(id) => Ok(id.clone()),
+        RuntimeValue::Pure(_) => Err("Expected an object argument".to_string()),
+    }
+}
+
+fn pure<T: serde::de::DeserializeOwned>(value: &RuntimeValue) -> std::result::Result<T, String> {
+    match value {
+        RuntimeValue::Pure(bytes) => {
+            bcs::from_bytes(bytes).map_err(|e| format!("Invalid pure argument: {}", e))
+        }
+        RuntimeValue::Object(id) => Err(format!("Expected a pure argument, got object {}", id)),
+    }
+}
+
+/// Compare Move types irrespective of address formatting
+fn same_type(a: &str, b: &str) -> bool {
+    match (a.parse::<TypeTag>(), b.parse::<TypeTag>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn param_str(params: &Value, index: usize) -> Result<&str> {
+    params[index]
+        .as_str()
+        .ok_or_else(|| ManusError::Sui(format!("Missing string parameter {}", index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_object_reports_missing() {
+        let node = MockSuiNode::new();
+        let response = node
+            .request("sui_getObject", json!(["0x99", {}]))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], "notExists");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let node = MockSuiNode::new();
+        assert!(node.request("sui_unknown", json!([])).await.is_err());
+    }
+}
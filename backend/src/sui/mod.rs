@@ -1,50 +1,1369 @@
 //! Sui blockchain integration
 
 use crate::error::{ManusError, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use deepbook::{EventCursor, L2Snapshot, Order, OrderEvent, OrderEventPage, PoolAccount};
+use objects::{Address, RiskControl, Share, Vault, VaultCap};
+use ptb::{
+    GasCostSummary, ObjectArg, ObjectRef, ProgrammableTransaction, RiskControlTransactionBuilder,
+    TransactionData, TransactionKind, TypeTag, VaultTransactionBuilder,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub mod deepbook;
+pub mod keys;
+pub mod mock;
+pub mod objects;
+pub mod ptb;
+pub mod transport;
+pub mod vault_math;
+
+pub use keys::SuiKeypair;
+pub use mock::MockSuiNode;
+pub use objects::MoveObject;
+pub use transport::{HttpTransport, SuiTransport};
+
+/// Default gas budget ceiling for vault transactions (in MIST)
+pub const DEFAULT_GAS_BUDGET: u64 = 50_000_000;
+
+/// Move type of the native SUI coin
+pub const SUI_COIN_TYPE: &str = "0x2::sui::SUI";
 
 /// Sui client wrapper
 pub struct SuiClient {
-    // TODO: Add sui-sdk client
+    transport: Arc<dyn SuiTransport>,
+    keypair: Option<SuiKeypair>,
+    gas_budget: u64,
 }
 
 impl SuiClient {
     /// Create a new Sui client
     pub async fn new(network_url: &str) -> Result<Self> {
-        // TODO: Initialize sui-sdk client
-        Ok(Self {})
+        Ok(Self::with_transport(Arc::new(HttpTransport::new(
+            network_url,
+        ))))
+    }
+
+    /// Create a client on top of an arbitrary transport
+    pub fn with_transport(transport: Arc<dyn SuiTransport>) -> Self {
+        Self {
+            transport,
+            keypair: None,
+            gas_budget: DEFAULT_GAS_BUDGET,
+        }
+    }
+
+    /// Attach the keypair used to sign transactions
+    pub fn with_keypair(mut self, keypair: SuiKeypair) -> Self {
+        self.keypair = Some(keypair);
+        self
     }
-    
+
+    /// Override the gas budget used for transactions
+    pub fn with_gas_budget(mut self, gas_budget: u64) -> Self {
+        self.gas_budget = gas_budget;
+        self
+    }
+
+    /// Address of the configured signer, if any
+    pub fn address(&self) -> Option<String> {
+        self.keypair.as_ref().map(SuiKeypair::address)
+    }
+
+    /// Fetch an object with its type, owner and Move content
+    pub async fn get_object(&self, object_id: &str) -> Result<Value> {
+        let mut response = self
+            .transport
+            .request(
+                "sui_getObject",
+                json!([
+                    object_id,
+                    {"showType": true, "showOwner": true, "showContent": true, "showBcs": true},
+                ]),
+            )
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            if error["code"] == "notExists" {
+                return Err(ManusError::ObjectNotFound(object_id.to_string()));
+            }
+            return Err(ManusError::Sui(format!(
+                "Object {} unavailable: {}",
+                object_id, error
+            )));
+        }
+
+        match response.get_mut("data") {
+            Some(data) => Ok(data.take()),
+            None => Err(ManusError::ObjectNotFound(object_id.to_string())),
+        }
+    }
+
+    /// Fetch an object and decode its BCS contents as `T`
+    pub async fn get_move_object<T: MoveObject>(&self, object_id: &str) -> Result<T> {
+        decode_bcs(&self.get_object(object_id).await?)
+    }
+
     /// Get vault information
     pub async fn get_vault(&self, vault_id: &str) -> Result<VaultInfo> {
-        // TODO: Implement vault retrieval
-        Err(ManusError::Sui("Not implemented".to_string()))
+        let object = self.get_object(vault_id).await?;
+        let (_, coin_type) = parse_vault_type(object["type"].as_str().unwrap_or_default())?;
+        let vault: Vault = decode_bcs(&object)?;
+
+        Ok(VaultInfo {
+            id: vault.id.to_string(),
+            total_value: vault.total_underlying,
+            total_shares: vault.total_shares,
+            strategy: String::from_utf8_lossy(&vault.strategy).into_owned(),
+            balance: vault.balance,
+            max_capacity: vault.max_capacity,
+            paused: vault.paused,
+            coin_type,
+        })
+    }
+
+    /// Quote the shares a deposit of `amount` would mint at the vault's current state
+    pub async fn quote_deposit(&self, vault_id: &str, amount: u64) -> Result<vault_math::Quote> {
+        let vault: Vault = self.get_move_object(vault_id).await?;
+        vault_math::quote_deposit(&vault, amount)
     }
-    
+
+    /// Quote the amount burning `shares` would withdraw at the vault's current state
+    pub async fn quote_withdrawal(&self, vault_id: &str, shares: u64) -> Result<vault_math::Quote> {
+        let vault: Vault = self.get_move_object(vault_id).await?;
+        vault_math::quote_withdrawal(&vault, shares)
+    }
+
     /// Execute deposit transaction
-    pub async fn deposit(&self, vault_id: &str, amount: u64) -> Result<String> {
-        // TODO: Implement deposit transaction
-        Err(ManusError::Sui("Not implemented".to_string()))
+    pub async fn deposit(&self, vault_id: &str, amount: u64) -> Result<VaultReceipt> {
+        let sender = self.sender()?;
+        let vault = self.vault_handle(vault_id).await?;
+        let mut builder = vault.builder();
+
+        // SUI deposits are split off the gas coin, so gas payment must cover them too
+        let gas_coin_spend = if vault.coin_type.is_sui() {
+            builder.deposit_from_gas(amount)?;
+            amount
+        } else {
+            let coins = self
+                .select_coins(&sender, &vault.coin_type.to_string(), amount)
+                .await?;
+            builder.deposit_from_coins(coins, amount)?;
+            0
+        };
+
+        self.submit(sender, builder.finish(), gas_coin_spend).await
     }
-    
+
     /// Execute withdrawal transaction
-    pub async fn withdraw(&self, vault_id: &str, shares: u64) -> Result<String> {
-        // TODO: Implement withdrawal transaction
-        Err(ManusError::Sui("Not implemented".to_string()))
+    ///
+    /// The vault burns whole `Share` objects, so this burns a set of them whose
+    /// amounts add up to exactly `shares`, and fails if none does.
+    pub async fn withdraw(&self, vault_id: &str, shares: u64) -> Result<VaultReceipt> {
+        if shares == 0 {
+            return Err(ManusError::Sui("Cannot withdraw zero shares".to_string()));
+        }
+
+        let sender = self.sender()?;
+        let vault = self.vault_handle(vault_id).await?;
+        let owned: Vec<(ObjectRef, Share)> = self
+            .owned_objects::<Share>(&sender, &format!("{}::vault::Share", vault.package))
+            .await?
+            .into_iter()
+            .filter(|(_, share)| share.vault_id == vault.id && share.amount > 0)
+            .collect();
+
+        let amounts: Vec<u64> = owned.iter().map(|(_, share)| share.amount).collect();
+        let selected = exact_subset(&amounts, shares).ok_or_else(|| {
+            ManusError::Sui(format!(
+                "Shares owned by {} in vault {} cannot add up to {}",
+                sender, vault_id, shares
+            ))
+        })?;
+
+        let mut builder = vault.builder();
+        for index in selected {
+            builder.withdraw(owned[index].0.clone());
+        }
+
+        self.submit(sender, builder.finish(), 0).await
+    }
+
+    /// Pause or resume a vault using the signer's `VaultCap`
+    pub async fn set_paused(&self, vault_id: &str, paused: bool) -> Result<VaultReceipt> {
+        let sender = self.sender()?;
+        let vault = self.vault_handle(vault_id).await?;
+        let (cap, _) = self
+            .owned_objects::<VaultCap>(&sender, &format!("{}::vault::VaultCap", vault.package))
+            .await?
+            .into_iter()
+            .find(|(_, cap)| cap.vault_id == vault.id)
+            .ok_or_else(|| {
+                ManusError::Sui(format!("{} holds no VaultCap for {}", sender, vault_id))
+            })?;
+
+        let mut builder = vault.builder();
+        if paused {
+            builder.pause(cap);
+        } else {
+            builder.resume(cap);
+        }
+
+        self.submit(sender, builder.finish(), 0).await
+    }
+
+    /// Read a `RiskControl` object
+    pub async fn get_risk_control(&self, risk_control_id: &str) -> Result<RiskControl> {
+        self.get_move_object(risk_control_id).await
+    }
+
+    /// Activate or deactivate the circuit breaker of a `RiskControl`
+    ///
+    /// The signer must own the object unless it is shared.
+    pub async fn set_circuit_breaker(
+        &self,
+        risk_control_id: &str,
+        active: bool,
+    ) -> Result<VaultReceipt> {
+        let sender = self.sender()?;
+        let mut builder = self.risk_control_builder(risk_control_id).await?;
+        if active {
+            builder.activate_circuit_breaker();
+        } else {
+            builder.deactivate_circuit_breaker();
+        }
+
+        self.submit(sender, builder.finish(), 0).await
+    }
+
+    /// Set the drawdown limit of a `RiskControl`, in basis points (1000 = 10%)
+    pub async fn update_drawdown_limit(
+        &self,
+        risk_control_id: &str,
+        limit: u64,
+    ) -> Result<VaultReceipt> {
+        let sender = self.sender()?;
+        let mut builder = self.risk_control_builder(risk_control_id).await?;
+        builder.update_drawdown_limit(limit)?;
+
+        self.submit(sender, builder.finish(), 0).await
+    }
+
+    /// Decimals of a coin type, from its `CoinMetadata`
+    pub async fn coin_decimals(&self, coin_type: &str) -> Result<u8> {
+        let metadata = self
+            .transport
+            .request("suix_getCoinMetadata", json!([coin_type]))
+            .await?;
+        metadata["decimals"]
+            .as_u64()
+            .and_then(|decimals| u8::try_from(decimals).ok())
+            .ok_or_else(|| ManusError::Sui(format!("No metadata for coin {}", coin_type)))
+    }
+
+    /// Read `ticks` price levels each side of the mid of a DeepBook pool
+    pub async fn get_level2_snapshot(&self, pool_id: &str, ticks: u64) -> Result<L2Snapshot> {
+        let object = self.get_object(pool_id).await?;
+        let (package, base, quote) =
+            deepbook::parse_pool_type(object["type"].as_str().unwrap_or_default())?;
+        let initial_shared_version = json_u64(&object["owner"]["Shared"]["initial_shared_version"])
+            .map_err(|_| ManusError::Sui(format!("Pool {} is not a shared object", pool_id)))?;
+        let base_decimals = self.coin_decimals(&base.to_string()).await?;
+        let quote_decimals = self.coin_decimals(&quote.to_string()).await?;
+
+        let pool = ObjectArg::SharedObject {
+            id: Address::from_hex(pool_id)?,
+            initial_shared_version,
+            mutable: false,
+        };
+        let transaction = deepbook::level2_transaction(package, base, quote, pool, ticks)?;
+        let response = self
+            .dev_inspect(transaction, &format!("Level 2 read of pool {}", pool_id))
+            .await?;
+
+        Ok(L2Snapshot {
+            pool_id: normalize_id(pool_id),
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+            base_decimals,
+            quote_decimals,
+            tick_size: deepbook::return_value(&response, 0, 0)?,
+            lot_size: deepbook::return_value(&response, 0, 1)?,
+            min_size: deepbook::return_value(&response, 0, 2)?,
+            bid_prices: deepbook::return_value(&response, 1, 0)?,
+            bid_quantities: deepbook::return_value(&response, 1, 1)?,
+            ask_prices: deepbook::return_value(&response, 1, 2)?,
+            ask_quantities: deepbook::return_value(&response, 1, 3)?,
+        })
+    }
+
+    /// Resolve a DeepBook pool and a `BalanceManager` to trade it through
+    pub async fn pool_account(
+        &self,
+        pool_id: &str,
+        balance_manager_id: &str,
+    ) -> Result<PoolAccount> {
+        let pool = self.get_object(pool_id).await?;
+        let (package, base, quote) =
+            deepbook::parse_pool_type(pool["type"].as_str().unwrap_or_default())?;
+        let pool_version = json_u64(&pool["owner"]["Shared"]["initial_shared_version"])
+            .map_err(|_| ManusError::Sui(format!("Pool {} is not a shared object", pool_id)))?;
+
+        let balance_manager = self.get_object(balance_manager_id).await?;
+        let expected: TypeTag = format!("{}::balance_manager::BalanceManager", package).parse()?;
+        let actual = balance_manager["type"]
+            .as_str()
+            .unwrap_or_default()
+            .parse()
+            .ok();
+        if actual != Some(expected) {
+            return Err(ManusError::Sui(format!(
+                "{} is not a BalanceManager of package {}",
+                balance_manager_id, package
+            )));
+        }
+        let balance_manager_version = json_u64(
+            &balance_manager["owner"]["Shared"]["initial_shared_version"],
+        )
+        .map_err(|_| {
+            ManusError::Sui(format!(
+                "Balance manager {} is not a shared object",
+                balance_manager_id
+            ))
+        })?;
+
+        Ok(PoolAccount {
+            package,
+            base_decimals: self.coin_decimals(&base.to_string()).await?,
+            quote_decimals: self.coin_decimals(&quote.to_string()).await?,
+            base,
+            quote,
+            pool: ObjectArg::SharedObject {
+                id: Address::from_hex(pool_id)?,
+                initial_shared_version: pool_version,
+                mutable: true,
+            },
+            balance_manager: ObjectArg::SharedObject {
+                id: Address::from_hex(balance_manager_id)?,
+                initial_shared_version: balance_manager_version,
+                mutable: true,
+            },
+        })
+    }
+
+    /// Sign and execute a DeepBook order transaction built from a [`PoolAccount`]
+    pub async fn execute_orders(
+        &self,
+        transaction: ProgrammableTransaction,
+    ) -> Result<OrderReceipt> {
+        let sender = self.sender()?;
+        let (digest, response) = self.submit_response(sender, transaction, 0).await?;
+        Ok(OrderReceipt::from_response(digest, &response))
+    }
+
+    /// Orders `account` has resting on its pool
+    pub async fn account_orders(&self, account: &PoolAccount) -> Result<Vec<Order>> {
+        let response = self
+            .dev_inspect(
+                deepbook::account_orders_transaction(account),
+                &format!("Order read of pool {}", account.pool.id()),
+            )
+            .await?;
+        deepbook::return_value(&response, 0, 0)
+    }
+
+    /// Page of order events emitted through a DeepBook package's `pool`
+    /// module, oldest first, resuming after `cursor`
+    pub async fn query_order_events(
+        &self,
+        package: &Address,
+        cursor: Option<&EventCursor>,
+        limit: usize,
+    ) -> Result<OrderEventPage> {
+        let response = self
+            .query_pool_events(package, cursor, limit, false)
+            .await?;
+
+        Ok(OrderEventPage {
+            events: response["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(OrderEvent::from_json)
+                .collect(),
+            next_cursor: serde_json::from_value(response["nextCursor"].clone()).ok(),
+            has_next_page: response["hasNextPage"].as_bool().unwrap_or(false),
+        })
+    }
+
+    /// Cursor of the latest event emitted through a DeepBook package's `pool` module
+    pub async fn latest_order_event_cursor(
+        &self,
+        package: &Address,
+    ) -> Result<Option<EventCursor>> {
+        let response = self.query_pool_events(package, None, 1, true).await?;
+        Ok(serde_json::from_value(response["data"][0]["id"].clone()).ok())
+    }
+
+    async fn query_pool_events(
+        &self,
+        package: &Address,
+        cursor: Option<&EventCursor>,
+        limit: usize,
+        descending: bool,
+    ) -> Result<Value> {
+        self.transport
+            .request(
+                "suix_queryEvents",
+                json!([
+                    {"MoveModule": {"package": package.to_string(), "module": "pool"}},
+                    cursor,
+                    limit,
+                    descending,
+                ]),
+            )
+            .await
+    }
+
+    /// Current reference gas price
+    pub async fn reference_gas_price(&self) -> Result<u64> {
+        let price = self
+            .transport
+            .request("suix_getReferenceGasPrice", json!([]))
+            .await?;
+        json_u64(&price)
+    }
+
+    /// Dry-run a transaction and return its gas cost
+    pub async fn dry_run(&self, data: &TransactionData) -> Result<GasCostSummary> {
+        let tx_bytes = BASE64.encode(data.to_bytes()?);
+        let response = self
+            .transport
+            .request("sui_dryRunTransactionBlock", json!([tx_bytes]))
+            .await?;
+
+        check_status(&response["effects"], "Dry run")?;
+        Ok(GasCostSummary::from_json(&response["effects"]["gasUsed"]))
+    }
+
+    /// Sign and execute a transaction, returning its vault receipt
+    pub async fn execute(&self, data: &TransactionData) -> Result<VaultReceipt> {
+        let (digest, response) = self.execute_response(data).await?;
+        Ok(VaultReceipt::from_response(digest, &response))
+    }
+
+    /// Sign and execute a transaction, returning its digest and full response
+    async fn execute_response(&self, data: &TransactionData) -> Result<(String, Value)> {
+        let raw = data.to_bytes()?;
+        let signature = self.signer()?.sign_transaction(&raw);
+
+        let response = self
+            .transport
+            .request(
+                "sui_executeTransactionBlock",
+                json!([
+                    BASE64.encode(&raw),
+                    [signature],
+                    {"showEffects": true, "showEvents": true},
+                    "WaitForLocalExecution",
+                ]),
+            )
+            .await?;
+
+        let digest = response["digest"].as_str().unwrap_or_default().to_string();
+        check_status(&response["effects"], &format!("Transaction {}", digest))?;
+
+        Ok((digest, response))
+    }
+
+    /// Build gas payment, budget the transaction from a dry run and execute it
+    async fn submit(
+        &self,
+        sender: Address,
+        transaction: ProgrammableTransaction,
+        gas_coin_spend: u64,
+    ) -> Result<VaultReceipt> {
+        let (digest, response) = self
+            .submit_response(sender, transaction, gas_coin_spend)
+            .await?;
+        Ok(VaultReceipt::from_response(digest, &response))
+    }
+
+    /// [`SuiClient::submit`], returning the digest and full response
+    async fn submit_response(
+        &self,
+        sender: Address,
+        transaction: ProgrammableTransaction,
+        gas_coin_spend: u64,
+    ) -> Result<(String, Value)> {
+        let price = self.reference_gas_price().await?;
+        let gas_needed = gas_coin_spend.checked_add(self.gas_budget).ok_or_else(|| {
+            ManusError::Arithmetic(format!(
+                "Spending {} from the gas coin overflows with a gas budget of {}",
+                gas_coin_spend, self.gas_budget
+            ))
+        })?;
+        let payment = self
+            .select_coins(&sender, SUI_COIN_TYPE, gas_needed)
+            .await?;
+        let data =
+            TransactionData::new_programmable(sender, payment, transaction, self.gas_budget, price);
+
+        let budget = self.dry_run(&data).await?.recommended_budget(price);
+        if budget > self.gas_budget {
+            return Err(ManusError::Sui(format!(
+                "Transaction needs a gas budget of {}, above the configured {}",
+                budget, self.gas_budget
+            )));
+        }
+
+        self.execute_response(&data.with_budget(budget)).await
+    }
+
+    /// Dev-inspect a read-only transaction, failing if it aborts
+    async fn dev_inspect(
+        &self,
+        transaction: ProgrammableTransaction,
+        context: &str,
+    ) -> Result<Value> {
+        let tx_bytes = bcs::to_bytes(&TransactionKind::ProgrammableTransaction(transaction))
+            .map_err(|e| ManusError::Sui(format!("Failed to encode transaction: {}", e)))?;
+        let sender = self
+            .address()
+            .unwrap_or_else(|| Address::default().to_string());
+        let response = self
+            .transport
+            .request(
+                "sui_devInspectTransactionBlock",
+                json!([sender, BASE64.encode(tx_bytes), null, null]),
+            )
+            .await?;
+        check_status(&response["effects"], context)?;
+        Ok(response)
+    }
+
+    /// Select coins of `coin_type`, largest first, until they cover `target`
+    async fn select_coins(
+        &self,
+        owner: &Address,
+        coin_type: &str,
+        target: u64,
+    ) -> Result<Vec<ObjectRef>> {
+        let response = self
+            .transport
+            .request(
+                "suix_getCoins",
+                json!([owner.to_string(), coin_type, null, null]),
+            )
+            .await?;
+
+        let mut coins = Vec::new();
+        for coin in response["data"].as_array().into_iter().flatten() {
+            coins.push((json_u64(&coin["balance"])?, ObjectRef::from_json(coin)?));
+        }
+        coins.sort_by_key(|(balance, _)| std::cmp::Reverse(*balance));
+
+        let mut total = 0u64;
+        let mut selected = Vec::new();
+        for (balance, coin) in coins {
+            if total >= target {
+                break;
+            }
+            total += balance;
+            selected.push(coin);
+        }
+
+        if total < target || selected.is_empty() {
+            return Err(ManusError::Sui(format!(
+                "Insufficient {} balance: need {}, have {}",
+                coin_type, target, total
+            )));
+        }
+
+        Ok(selected)
+    }
+
+    /// Owned objects of a struct type, decoded from BCS
+    async fn owned_objects<T: MoveObject>(
+        &self,
+        owner: &Address,
+        struct_type: &str,
+    ) -> Result<Vec<(ObjectRef, T)>> {
+        let response = self
+            .transport
+            .request(
+                "suix_getOwnedObjects",
+                json!([
+                    owner.to_string(),
+                    {
+                        "filter": {"StructType": struct_type},
+                        "options": {"showType": true, "showBcs": true},
+                    },
+                    null,
+                    null,
+                ]),
+            )
+            .await?;
+
+        response["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|entry| {
+                Ok((
+                    ObjectRef::from_json(&entry["data"])?,
+                    decode_bcs(&entry["data"])?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Resolve the package, coin type and shared version of a vault
+    async fn vault_handle(&self, vault_id: &str) -> Result<VaultHandle> {
+        let object = self.get_object(vault_id).await?;
+        let (package, coin_type) = parse_vault_type(object["type"].as_str().unwrap_or_default())?;
+        let initial_shared_version = json_u64(&object["owner"]["Shared"]["initial_shared_version"])
+            .map_err(|_| ManusError::Sui(format!("Vault {} is not a shared object", vault_id)))?;
+
+        Ok(VaultHandle {
+            id: Address::from_hex(vault_id)?,
+            package: Address::from_hex(&package)?,
+            coin_type: coin_type.parse()?,
+            initial_shared_version,
+        })
+    }
+
+    /// Resolve the package and object input of a `RiskControl`
+    async fn risk_control_builder(
+        &self,
+        risk_control_id: &str,
+    ) -> Result<RiskControlTransactionBuilder> {
+        let object = self.get_object(risk_control_id).await?;
+        let object_type = object["type"].as_str().unwrap_or_default();
+        let package = object_type
+            .strip_suffix("::risk_control::RiskControl")
+            .ok_or_else(|| {
+                ManusError::Sui(format!("Not a risk control object: {}", object_type))
+            })?;
+        let risk_control = match json_u64(&object["owner"]["Shared"]["initial_shared_version"]) {
+            Ok(initial_shared_version) => ObjectArg::SharedObject {
+                id: Address::from_hex(risk_control_id)?,
+                initial_shared_version,
+                mutable: true,
+            },
+            Err(_) => ObjectArg::ImmOrOwnedObject(ObjectRef::from_json(&object)?),
+        };
+
+        Ok(RiskControlTransactionBuilder::new(
+            Address::from_hex(package)?,
+            risk_control,
+        ))
+    }
+
+    fn sender(&self) -> Result<Address> {
+        Address::from_hex(&self.signer()?.address())
+    }
+
+    fn signer(&self) -> Result<&SuiKeypair> {
+        self.keypair.as_ref().ok_or(ManusError::MissingSigner)
     }
 }
 
 /// Vault information from Sui blockchain
+#[derive(Debug, Clone, Serialize)]
 pub struct VaultInfo {
     /// Vault ID
     pub id: String,
-    
+
     /// Total value locked
     pub total_value: u64,
-    
+
     /// Total shares
     pub total_shares: u64,
-    
+
     /// Strategy name
     pub strategy: String,
+
+    /// Balance of underlying coins held by the vault
+    pub balance: u64,
+
+    /// Maximum capacity
+    pub max_capacity: u64,
+
+    /// Whether deposits and withdrawals are paused
+    pub paused: bool,
+
+    /// Move type of the underlying coin
+    pub coin_type: String,
+}
+
+/// Outcome of a DeepBook order transaction
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrderReceipt {
+    /// Transaction digest
+    pub digest: String,
+
+    /// Order events emitted by the transaction
+    pub events: Vec<OrderEvent>,
+
+    /// Net gas charged
+    pub gas_used: u64,
+}
+
+impl OrderReceipt {
+    fn from_response(digest: String, response: &Value) -> Self {
+        Self {
+            digest,
+            events: response["events"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(OrderEvent::from_json)
+                .collect(),
+            gas_used: GasCostSummary::from_json(&response["effects"]["gasUsed"]).net_gas_usage(),
+        }
+    }
+}
+
+/// Outcome of a vault transaction, aggregated from its events
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VaultReceipt {
+    /// Transaction digest
+    pub digest: String,
+
+    /// Shares minted (deposit) or burned (withdrawal)
+    pub shares: u64,
+
+    /// Amount deposited or withdrawn
+    pub amount: u64,
+
+    /// Net gas charged
+    pub gas_used: u64,
+}
+
+impl VaultReceipt {
+    fn from_response(digest: String, response: &Value) -> Self {
+        let mut receipt = Self {
+            digest,
+            gas_used: GasCostSummary::from_json(&response["effects"]["gasUsed"]).net_gas_usage(),
+            ..Self::default()
+        };
+
+        for event in response["events"].as_array().into_iter().flatten() {
+            let event_type = event["type"].as_str().unwrap_or_default();
+            let fields = &event["parsedJson"];
+            let (shares, amount) = if event_type.ends_with("::vault::DepositEvent") {
+                (&fields["shares_minted"], &fields["amount"])
+            } else if event_type.ends_with("::vault::WithdrawEvent") {
+                (&fields["shares_burned"], &fields["amount_withdrawn"])
+            } else {
+                continue;
+            };
+            receipt.shares += json_u64(shares).unwrap_or(0);
+            receipt.amount += json_u64(amount).unwrap_or(0);
+        }
+
+        receipt
+    }
+}
+
+/// A shared vault resolved for transaction building
+struct VaultHandle {
+    id: Address,
+    package: Address,
+    coin_type: TypeTag,
+    initial_shared_version: u64,
+}
+
+impl VaultHandle {
+    fn builder(&self) -> VaultTransactionBuilder {
+        VaultTransactionBuilder::new(
+            self.package,
+            self.coin_type.clone(),
+            self.id,
+            self.initial_shared_version,
+        )
+    }
+}
+
+/// Fail if transaction effects report a non-success status
+///
+/// Move aborts and arithmetic errors are reported as their own variants.
+fn check_status(effects: &Value, context: &str) -> Result<()> {
+    let status = &effects["status"];
+    if status["status"] == "success" {
+        return Ok(());
+    }
+
+    let error = status["error"].as_str().unwrap_or("unknown error");
+    if let Some((module, code)) = parse_move_abort(error) {
+        return Err(ManusError::MoveAbort { module, code });
+    }
+    if error.contains("ArithmeticError") {
+        return Err(ManusError::Arithmetic(error.to_string()));
+    }
+    Err(ManusError::Sui(format!("{} failed: {}", context, error)))
+}
+
+/// Status a node reports when `module` aborts with `code`
+pub(crate) fn move_abort_status(module: &str, code: u64) -> String {
+    format!(
+        "MoveAbort(MoveLocation {{ module: ModuleId {{ address: 0x0, name: Identifier(\"{}\") }}, \
+         function: 0, instruction: 0, function_name: None }}, {}) in command 0",
+        module, code
+    )
+}
+
+/// Module and code of a `MoveAbort` execution status
+fn parse_move_abort(error: &str) -> Option<(String, u64)> {
+    let abort = &error[error.find("MoveAbort(")?..];
+    let module = abort
+        .split_once("name: Identifier(\"")?
+        .1
+        .split_once('"')?
+        .0;
+    let code = abort.rsplit_once("}, ")?.1.split_once(')')?.0;
+    Some((module.to_string(), code.trim().parse().ok()?))
 }
 
+/// Split a `<package>::vault::Vault<T>` type into its package ID and coin type
+fn parse_vault_type(object_type: &str) -> Result<(String, String)> {
+    object_type
+        .split_once("::vault::Vault<")
+        .and_then(|(package, rest)| {
+            rest.strip_suffix('>')
+                .map(|coin| (package.to_string(), coin.to_string()))
+        })
+        .ok_or_else(|| ManusError::Sui(format!("Not a vault object: {}", object_type)))
+}
+
+/// Decode the `bcs` section of an object returned with `showBcs`
+fn decode_bcs<T: MoveObject>(object: &Value) -> Result<T> {
+    let object_type = object["type"].as_str().unwrap_or_default();
+    if !T::matches_type(object_type) {
+        return Err(ManusError::Sui(format!(
+            "Expected {}::{}, got {}",
+            T::MODULE,
+            T::NAME,
+            object_type
+        )));
+    }
+
+    let encoded = object["bcs"]["bcsBytes"].as_str().ok_or_else(|| {
+        ManusError::Sui(format!("Object {} has no BCS contents", object["objectId"]))
+    })?;
+    let bytes = BASE64
+        .decode(encoded)
+        .map_err(|e| ManusError::Sui(format!("Invalid BCS encoding: {}", e)))?;
+
+    T::from_bcs(&bytes)
+}
+
+/// Read a u64 that the RPC may encode as either a string or a number
+fn json_u64(value: &Value) -> Result<u64> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+    .ok_or_else(|| ManusError::Sui(format!("Expected u64, got {}", value)))
+}
+
+/// Canonical form of an address or object ID
+pub(crate) fn normalize_id(id: &str) -> String {
+    let hex = id.trim_start_matches("0x").to_lowercase();
+    format!("0x{:0>64}", hex)
+}
+
+/// Indices of `amounts` adding up to exactly `target`, if any do
+///
+/// Searches largest amounts first and prunes branches that cannot reach the
+/// target, so the handful of shares an owner typically holds resolve quickly.
+fn exact_subset(amounts: &[u64], target: u64) -> Option<Vec<usize>> {
+    fn search(
+        order: &[usize],
+        amounts: &[u64],
+        remaining: &[u128],
+        target: u64,
+        selected: &mut Vec<usize>,
+    ) -> bool {
+        if target == 0 {
+            return true;
+        }
+        let Some((&index, rest)) = order.split_first() else {
+            return false;
+        };
+        if remaining[0] < u128::from(target) {
+            return false;
+        }
+
+        let amount = amounts[index];
+        if amount <= target {
+            selected.push(index);
+            if search(rest, amounts, &remaining[1..], target - amount, selected) {
+                return true;
+            }
+            selected.pop();
+        }
+        search(rest, amounts, &remaining[1..], target, selected)
+    }
+
+    let mut order: Vec<usize> = (0..amounts.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(amounts[index]));
+    // Sum of each suffix of `order`, for pruning
+    let mut remaining = vec![0u128; order.len() + 1];
+    for position in (0..order.len()).rev() {
+        remaining[position] = remaining[position + 1] + u128::from(amounts[order[position]]);
+    }
+
+    let mut selected = Vec::new();
+    search(&order, amounts, &remaining, target, &mut selected).then_some(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deepbook::{OrderEventKind, MAX_TIMESTAMP};
+
+    const SUI: &str = "0x2::sui::SUI";
+    const USDC: &str = "0xdba3::usdc::USDC";
+    const FUNDS: u64 = 1_000_000_000;
+
+    fn client_for(node: &Arc<MockSuiNode>, keypair: &SuiKeypair) -> SuiClient {
+        SuiClient::with_transport(node.clone()).with_keypair(keypair.clone())
+    }
+
+    #[test]
+    fn test_failed_status_is_typed() {
+        let failure = |error: &str| json!({"status": {"status": "failure", "error": error}});
+        let abort = "MoveAbort(MoveLocation { module: ModuleId { address: \
+                     0000000000000000000000000000000000000000000000000000000000000abc, \
+                     name: Identifier(\"vault\") }, function: 3, instruction: 18, \
+                     function_name: Some(\"deposit\") }, 4) in command 1";
+        assert!(matches!(
+            check_status(&failure(abort), "Dry run"),
+            Err(ManusError::MoveAbort { module, code: 4 }) if module == "vault"
+        ));
+        assert!(matches!(
+            check_status(&failure(&move_abort_status("pool", 7)), "Dry run"),
+            Err(ManusError::MoveAbort { code: 7, .. })
+        ));
+        assert!(matches!(
+            check_status(&failure(vault_math::ARITHMETIC_ERROR), "Dry run"),
+            Err(ManusError::Arithmetic(_))
+        ));
+        assert!(matches!(
+            check_status(&failure("InsufficientGas"), "Dry run"),
+            Err(ManusError::Sui(_))
+        ));
+        assert!(check_status(&json!({"status": {"status": "success"}}), "Dry run").is_ok());
+    }
+
+    #[test]
+    fn test_parse_vault_type() {
+        let (package, coin) = parse_vault_type("0xabc::vault::Vault<0x2::sui::SUI>").unwrap();
+        assert_eq!(package, "0xabc");
+        assert_eq!(coin, SUI);
+        assert!(parse_vault_type("0x2::coin::Coin<0x2::sui::SUI>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deposit_and_withdraw_against_mock_node() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[1u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 10_000, "market_making");
+        node.mint_coin(&keypair.address(), SUI, FUNDS);
+
+        let client = client_for(&node, &keypair);
+        let receipt = client.deposit(&vault_id, 1_200).await.unwrap();
+        assert_eq!(receipt.shares, 1_200);
+        assert_eq!(receipt.amount, 1_200);
+        assert!(receipt.gas_used > 0);
+
+        let vault = client.get_vault(&vault_id).await.unwrap();
+        assert_eq!(vault.total_value, 1_200);
+        assert_eq!(vault.total_shares, 1_200);
+        assert_eq!(vault.strategy, "market_making");
+        assert_eq!(vault.balance, 1_200);
+        assert_eq!(vault.coin_type, SUI);
+        assert!(!vault.paused);
+        assert_eq!(
+            node.coin_balance(&keypair.address(), SUI),
+            FUNDS - 1_200 - receipt.gas_used
+        );
+
+        let withdrawal = client.withdraw(&vault_id, 1_200).await.unwrap();
+        assert_eq!(withdrawal.amount, 1_200);
+        let vault = client.get_vault(&vault_id).await.unwrap();
+        assert_eq!(vault.total_shares, 0);
+        assert_eq!(
+            node.coin_balance(&keypair.address(), SUI),
+            FUNDS - receipt.gas_used - withdrawal.gas_used
+        );
+
+        // Gas payment must cover the deposit and the budget without overflowing
+        assert!(matches!(
+            client.deposit(&vault_id, u64::MAX).await,
+            Err(ManusError::Arithmetic(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_quotes_match_executed_transactions() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[9u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 1_000_000, "grid");
+        node.mint_coin(&keypair.address(), SUI, FUNDS);
+        let client = client_for(&node, &keypair);
+
+        for amount in [1_000, 333, 77_777] {
+            let quote = client.quote_deposit(&vault_id, amount).await.unwrap();
+            let receipt = client.deposit(&vault_id, amount).await.unwrap();
+            assert_eq!(
+                (quote.shares, quote.amount),
+                (receipt.shares, receipt.amount)
+            );
+        }
+
+        let quote = client.quote_withdrawal(&vault_id, 333).await.unwrap();
+        let receipt = client.withdraw(&vault_id, 333).await.unwrap();
+        assert_eq!(
+            (quote.shares, quote.amount),
+            (receipt.shares, receipt.amount)
+        );
+
+        assert!(client.quote_deposit(&vault_id, 2_000_000).await.is_err());
+        assert!(client.quote_withdrawal(&vault_id, 0).await.is_err());
+    }
+
+    #[test]
+    fn test_exact_share_subset() {
+        // Taking the largest share first would leave 1 that no share matches
+        let mut selected = exact_subset(&[5, 3, 3], 6).unwrap();
+        selected.sort();
+        assert_eq!(selected, [1, 2]);
+        assert_eq!(exact_subset(&[5, 3, 3], 11).unwrap().len(), 3);
+        assert!(exact_subset(&[5, 3, 3], 7).is_none());
+        assert!(exact_subset(&[10], 4).is_none());
+        assert!(exact_subset(&[u64::MAX, u64::MAX], u64::MAX).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_combines_share_objects() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[4u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 10_000, "grid");
+        node.mint_coin(&keypair.address(), SUI, FUNDS);
+
+        let client = client_for(&node, &keypair);
+        for amount in [500, 300, 300] {
+            client.deposit(&vault_id, amount).await.unwrap();
+        }
+
+        assert!(client.withdraw(&vault_id, 400).await.is_err());
+        let receipt = client.withdraw(&vault_id, 600).await.unwrap();
+        assert_eq!(receipt.shares, 600);
+        assert_eq!(client.get_vault(&vault_id).await.unwrap().total_shares, 500);
+
+        // Shares are burned whole, so part of the one left cannot be withdrawn
+        assert!(matches!(
+            client.withdraw(&vault_id, 200).await,
+            Err(ManusError::Sui(_))
+        ));
+        assert_eq!(client.get_vault(&vault_id).await.unwrap().total_shares, 500);
+        assert_eq!(client.withdraw(&vault_id, 500).await.unwrap().shares, 500);
+    }
+
+    #[tokio::test]
+    async fn test_deposit_from_non_sui_coins() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[5u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), USDC, 10_000, "grid");
+        node.mint_coin(&keypair.address(), SUI, FUNDS);
+        node.mint_coin(&keypair.address(), USDC, 400);
+        node.mint_coin(&keypair.address(), USDC, 700);
+
+        let client = client_for(&node, &keypair);
+        let receipt = client.deposit(&vault_id, 1_000).await.unwrap();
+        assert_eq!(receipt.shares, 1_000);
+        assert_eq!(node.coin_balance(&keypair.address(), USDC), 100);
+        assert_eq!(
+            node.coin_balance(&keypair.address(), SUI),
+            FUNDS - receipt.gas_used
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_paused_uses_vault_cap() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[6u8; 32]);
+        let (vault_id, _) = node.create_vault(&admin.address(), SUI, 10_000, "grid");
+        node.mint_coin(&admin.address(), SUI, FUNDS);
+
+        let client = client_for(&node, &admin);
+        client.set_paused(&vault_id, true).await.unwrap();
+        assert!(client.get_vault(&vault_id).await.unwrap().paused);
+        client.set_paused(&vault_id, false).await.unwrap();
+        assert!(!client.get_vault(&vault_id).await.unwrap().paused);
+
+        let other = SuiKeypair::from_bytes(&[7u8; 32]);
+        node.mint_coin(&other.address(), SUI, FUNDS);
+        assert!(client_for(&node, &other)
+            .set_paused(&vault_id, true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_transactions() {
+        let node = Arc::new(MockSuiNode::new());
+        let admin = SuiKeypair::from_bytes(&[10u8; 32]);
+        let risk_control_id = node.create_risk_control(&admin.address(), 1_000, 86_400);
+        node.mint_coin(&admin.address(), SUI, FUNDS);
+
+        let client = client_for(&node, &admin);
+        let risk = client.get_risk_control(&risk_control_id).await.unwrap();
+        assert_eq!(risk.drawdown_limit, 1_000);
+        assert!(!risk.circuit_breaker_active);
+
+        client
+            .set_circuit_breaker(&risk_control_id, true)
+            .await
+            .unwrap();
+        client
+            .update_drawdown_limit(&risk_control_id, 500)
+            .await
+            .unwrap();
+        let risk = client.get_risk_control(&risk_control_id).await.unwrap();
+        assert!(risk.circuit_breaker_active);
+        assert_eq!(risk.drawdown_limit, 500);
+
+        client
+            .set_circuit_breaker(&risk_control_id, false)
+            .await
+            .unwrap();
+        assert!(
+            !client
+                .get_risk_control(&risk_control_id)
+                .await
+                .unwrap()
+                .circuit_breaker_active
+        );
+
+        // Only the owner can use the object
+        let other = SuiKeypair::from_bytes(&[11u8; 32]);
+        node.mint_coin(&other.address(), SUI, FUNDS);
+        assert!(client_for(&node, &other)
+            .set_circuit_breaker(&risk_control_id, true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_level2_snapshot_from_pool() {
+        let node = Arc::new(MockSuiNode::new());
+        node.set_coin_decimals(USDC, 6);
+        let pool_id = node.create_pool(SUI, USDC, 1_000, 100_000_000, 1_000_000_000);
+        node.set_pool_levels(
+            &pool_id,
+            vec![
+                (999_000, 5_000_000_000),
+                (1_000_000, 2_000_000_000),
+                (998_000, 1_000_000_000),
+            ],
+            vec![(1_002_000, 3_000_000_000), (1_001_000, 4_000_000_000)],
+        );
+
+        // Reads need no signer
+        let client = SuiClient::with_transport(node.clone());
+        let snapshot = client.get_level2_snapshot(&pool_id, 2).await.unwrap();
+        assert_eq!((snapshot.base_decimals, snapshot.quote_decimals), (9, 6));
+        assert_eq!(
+            (snapshot.tick_size, snapshot.lot_size, snapshot.min_size),
+            (1_000, 100_000_000, 1_000_000_000)
+        );
+        assert_eq!(snapshot.bid_prices, vec![1_000_000, 999_000]);
+        assert_eq!(snapshot.bid_quantities, vec![2_000_000_000, 5_000_000_000]);
+        assert_eq!(snapshot.ask_prices, vec![1_001_000, 1_002_000]);
+        assert!(snapshot.timestamp_ms > 0);
+
+        let (vault_id, _) = node.create_vault(
+            &SuiKeypair::from_bytes(&[1u8; 32]).address(),
+            SUI,
+            1,
+            "grid",
+        );
+        assert!(client.get_level2_snapshot(&vault_id, 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pool_account_resolves_shared_inputs() {
+        let node = Arc::new(MockSuiNode::new());
+        node.set_coin_decimals(USDC, 6);
+        let owner = SuiKeypair::from_bytes(&[13u8; 32]);
+        let pool_id = node.create_pool(SUI, USDC, 1_000, 100_000_000, 1_000_000_000);
+        let manager_id = node.create_balance_manager(&owner.address());
+        let client = client_for(&node, &owner);
+
+        let account = client.pool_account(&pool_id, &manager_id).await.unwrap();
+        assert!(account.base.is_sui());
+        assert_eq!((account.base_decimals, account.quote_decimals), (9, 6));
+        assert_eq!(account.pool.id(), Address::from_hex(&pool_id).unwrap());
+        assert!(matches!(
+            account.balance_manager,
+            ObjectArg::SharedObject { mutable: true, .. }
+        ));
+
+        // The pool is not a balance manager
+        assert!(client.pool_account(&pool_id, &pool_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_order_lifecycle_events() {
+        let node = Arc::new(MockSuiNode::new());
+        node.set_coin_decimals(USDC, 6);
+        let owner = SuiKeypair::from_bytes(&[14u8; 32]);
+        let pool_id = node.create_pool(SUI, USDC, 1_000, 100_000_000, 1_000_000_000);
+        node.set_pool_levels(
+            &pool_id,
+            vec![(1_990_000, 5_000_000_000)],
+            vec![(2_010_000, 5_000_000_000)],
+        );
+        let manager_id = node.create_balance_manager(&owner.address());
+        node.mint_coin(&owner.address(), SUI, FUNDS);
+        let client = client_for(&node, &owner);
+        let account = client.pool_account(&pool_id, &manager_id).await.unwrap();
+        let package = account.package;
+
+        let mut orders = account.orders();
+        orders
+            .place_limit_order(1, 2_000_000, 10_000_000_000, true, MAX_TIMESTAMP)
+            .unwrap();
+        orders
+            .place_limit_order(2, 2_005_000, 10_000_000_000, false, MAX_TIMESTAMP)
+            .unwrap();
+        let receipt = client.execute_orders(orders.finish()).await.unwrap();
+        assert_eq!(receipt.events.len(), 2);
+        assert!(receipt.events.iter().all(|event| account.owns(event)));
+
+        let resting = client.account_orders(&account).await.unwrap();
+        assert_eq!(resting.len(), 2);
+        let bid = resting.iter().find(|o| o.client_order_id == 1).unwrap();
+        assert!(bid.is_bid());
+        assert_eq!(bid.price(), 2_000_000);
+        let ask_id = resting
+            .iter()
+            .find(|o| o.client_order_id == 2)
+            .unwrap()
+            .order_id;
+
+        // Post-only orders may not cross the book
+        let mut crossing = account.orders();
+        crossing
+            .place_limit_order(3, 2_000_000, 1_000_000_000, false, MAX_TIMESTAMP)
+            .unwrap();
+        assert!(client.execute_orders(crossing.finish()).await.is_err());
+
+        assert_eq!(
+            node.fill_order(&pool_id, bid.order_id, 4_000_000_000),
+            4_000_000_000
+        );
+        let mut cancel = account.orders();
+        cancel.cancel_order(ask_id).unwrap();
+        client.execute_orders(cancel.finish()).await.unwrap();
+
+        let first = client.query_order_events(&package, None, 2).await.unwrap();
+        assert_eq!(first.events.len(), 2);
+        assert!(first.has_next_page);
+        let rest = client
+            .query_order_events(&package, first.next_cursor.as_ref(), 10)
+            .await
+            .unwrap();
+        assert!(!rest.has_next_page);
+        let kinds: Vec<OrderEventKind> = rest.events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                OrderEventKind::Filled {
+                    price: 2_000_000,
+                    base_quantity: 4_000_000_000
+                },
+                OrderEventKind::Canceled,
+            ]
+        );
+        assert_eq!(
+            client.latest_order_event_cursor(&package).await.unwrap(),
+            rest.next_cursor
+        );
+
+        let resting = client.account_orders(&account).await.unwrap();
+        assert_eq!(resting.len(), 1);
+        assert_eq!(resting[0].filled_quantity, 4_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_gas_without_committing() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[8u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 10_000, "grid");
+        let gas_coin = node.mint_coin(&keypair.address(), SUI, FUNDS);
+        let client = client_for(&node, &keypair);
+
+        let gas_object = client.get_object(&gas_coin).await.unwrap();
+        let vault_object = client.get_object(&vault_id).await.unwrap();
+        let mut builder = VaultTransactionBuilder::new(
+            Address::from_hex(&node.package_id()).unwrap(),
+            SUI.parse().unwrap(),
+            Address::from_hex(&vault_id).unwrap(),
+            json_u64(&vault_object["owner"]["Shared"]["initial_shared_version"]).unwrap(),
+        );
+        builder.deposit_from_gas(500).unwrap();
+        let data = TransactionData::new_programmable(
+            Address::from_hex(&keypair.address()).unwrap(),
+            vec![ObjectRef::from_json(&gas_object).unwrap()],
+            builder.finish(),
+            DEFAULT_GAS_BUDGET,
+            1_000,
+        );
+
+        let summary = client.dry_run(&data).await.unwrap();
+        assert!(summary.computation_cost > 0);
+        assert!(summary.recommended_budget(1_000) >= summary.net_gas_usage());
+        assert_eq!(client.get_vault(&vault_id).await.unwrap().total_shares, 0);
+        assert_eq!(node.coin_balance(&keypair.address(), SUI), FUNDS);
+
+        assert!(client.dry_run(&data.with_budget(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_move_object_checks_type() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[3u8; 32]);
+        let (vault_id, cap_id) = node.create_vault(&keypair.address(), SUI, 100, "grid");
+        let client = client_for(&node, &keypair);
+
+        let cap: objects::VaultCap = client.get_move_object(&cap_id).await.unwrap();
+        assert_eq!(cap.vault_id.to_string(), vault_id);
+        assert!(client
+            .get_move_object::<objects::VaultCap>(&vault_id)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deposit_rejected_when_paused() {
+        let node = Arc::new(MockSuiNode::new());
+        let keypair = SuiKeypair::from_bytes(&[2u8; 32]);
+        let (vault_id, _) = node.create_vault(&keypair.address(), SUI, 10_000, "hodl");
+        node.mint_coin(&keypair.address(), SUI, FUNDS);
+        node.set_paused(&vault_id, true);
+
+        let client = client_for(&node, &keypair);
+        assert!(client.deposit(&vault_id, 1_000).await.is_err());
+        assert_eq!(node.coin_balance(&keypair.address(), SUI), FUNDS);
+    }
+}
@@ -0,0 +1,336 @@
+//! Typed BCS decoders for the Move objects deployed by this repository
+//!
+//! Each struct mirrors the field order of its Move definition so that the
+//! `bcsBytes` returned by `sui_getObject` decode directly into it.
+
+use crate::error::{ManusError, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+
+/// 32-byte Sui address or object ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Address(pub [u8; 32]);
+
+impl Address {
+    /// Parse a `0x`-prefixed hex address, left-padding short forms
+    pub fn from_hex(value: &str) -> Result<Self> {
+        let hex_digits = value.trim_start_matches("0x");
+        if hex_digits.len() > 64 {
+            return Err(ManusError::Sui(format!("Address too long: {}", value)));
+        }
+
+        let bytes = hex::decode(format!("{:0>64}", hex_digits))
+            .map_err(|e| ManusError::Sui(format!("Invalid address {}: {}", value, e)))?;
+        let mut address = [0u8; 32];
+        address.copy_from_slice(&bytes);
+        Ok(Self(address))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+/// A Move object that can be decoded from its BCS representation
+pub trait MoveObject: Serialize + DeserializeOwned {
+    /// Module declaring the struct
+    const MODULE: &'static str;
+
+    /// Struct name
+    const NAME: &'static str;
+
+    /// Whether a fully qualified Move type refers to this struct
+    ///
+    /// Only the module and struct name are compared; package IDs differ per deployment.
+    fn matches_type(object_type: &str) -> bool {
+        let base = object_type.split('<').next().unwrap_or_default();
+        base.ends_with(&format!("::{}::{}", Self::MODULE, Self::NAME))
+    }
+
+    /// Decode from BCS bytes
+    fn from_bcs(bytes: &[u8]) -> Result<Self> {
+        bcs::from_bytes(bytes).map_err(|e| {
+            ManusError::Sui(format!(
+                "Failed to decode {}::{}: {}",
+                Self::MODULE,
+                Self::NAME,
+                e
+            ))
+        })
+    }
+
+    /// Encode to BCS bytes
+    fn to_bcs(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(|e| {
+            ManusError::Sui(format!(
+                "Failed to encode {}::{}: {}",
+                Self::MODULE,
+                Self::NAME,
+                e
+            ))
+        })
+    }
+}
+
+/// `manus_liquidity::vault::Vault<T>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vault {
+    /// Object ID
+    pub id: Address,
+    /// Value of the `Balance<T>` held by the vault
+    pub balance: u64,
+    /// Total shares issued
+    pub total_shares: u64,
+    /// Total underlying value
+    pub total_underlying: u64,
+    /// Maximum capacity
+    pub max_capacity: u64,
+    /// Strategy identifier
+    pub strategy: Vec<u8>,
+    /// Paused state
+    pub paused: bool,
+}
+
+impl MoveObject for Vault {
+    const MODULE: &'static str = "vault";
+    const NAME: &'static str = "Vault";
+}
+
+/// `manus_liquidity::vault::VaultCap`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultCap {
+    /// Object ID
+    pub id: Address,
+    /// Vault administered by this capability
+    pub vault_id: Address,
+}
+
+impl MoveObject for VaultCap {
+    const MODULE: &'static str = "vault";
+    const NAME: &'static str = "VaultCap";
+}
+
+/// `manus_liquidity::vault::Share`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Share {
+    /// Object ID
+    pub id: Address,
+    /// Vault the shares belong to
+    pub vault_id: Address,
+    /// Number of shares
+    pub amount: u64,
+}
+
+impl MoveObject for Share {
+    const MODULE: &'static str = "vault";
+    const NAME: &'static str = "Share";
+}
+
+/// `deepbook_lp_vaults::vault::VaultCapability`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultCapability {
+    /// Object ID
+    pub id: Address,
+    /// Vault administered by this capability
+    pub vault_id: Address,
+}
+
+impl MoveObject for VaultCapability {
+    const MODULE: &'static str = "vault";
+    const NAME: &'static str = "VaultCapability";
+}
+
+/// `deepbook_lp_vaults::vault::Vault` (SUI-only LP vault)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LpVault {
+    /// Object ID
+    pub id: Address,
+    /// Asset type tag (0 for SUI, 1 for USDC)
+    pub asset_type: u8,
+    /// Value of the `Balance<SUI>` held by the vault
+    pub balance: u64,
+    /// Total shares issued
+    pub total_shares: u64,
+    /// Total underlying value
+    pub total_underlying: u64,
+}
+
+impl MoveObject for LpVault {
+    const MODULE: &'static str = "vault";
+    const NAME: &'static str = "Vault";
+}
+
+/// `deepbook_lp_strategies::strategy::Strategy`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Strategy {
+    /// Object ID
+    pub id: Address,
+    /// Strategy name (UTF-8 bytes)
+    pub name: Vec<u8>,
+    /// Strategy description (UTF-8 bytes)
+    pub description: Vec<u8>,
+    /// First strategy parameter
+    pub param_a: u64,
+    /// Second strategy parameter
+    pub param_b: u64,
+}
+
+impl MoveObject for Strategy {
+    const MODULE: &'static str = "strategy";
+    const NAME: &'static str = "Strategy";
+}
+
+/// `deepbook_lp_risk_controls::risk_control::RiskControl`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskControl {
+    /// Object ID
+    pub id: Address,
+    /// Drawdown limit in basis points (1000 = 10%)
+    pub drawdown_limit: u64,
+    /// Whether the circuit breaker is active
+    pub circuit_breaker_active: bool,
+    /// Timelock duration in seconds
+    pub timelock_duration: u64,
+}
+
+impl MoveObject for RiskControl {
+    const MODULE: &'static str = "risk_control";
+    const NAME: &'static str = "RiskControl";
+}
+
+/// `deepbook_lp_accounting::accounting::Position`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    /// Object ID
+    pub id: Address,
+    /// Owner of the position
+    pub user_address: Address,
+    /// Vault the position is allocated to
+    pub vault_id: Address,
+    /// Strategy the position is allocated to
+    pub strategy_id: Address,
+    /// Amount deposited
+    pub amount_deposited: u64,
+    /// Entry timestamp (ms)
+    pub entry_timestamp: u64,
+    /// P&L snapshot at entry or last rebalance
+    pub pnl_snapshot: u64,
+}
+
+impl MoveObject for Position {
+    const MODULE: &'static str = "accounting";
+    const NAME: &'static str = "Position";
+}
+
+/// `sui::coin::Coin<T>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coin {
+    /// Object ID
+    pub id: Address,
+    /// Value of the coin's `Balance<T>`
+    pub balance: u64,
+}
+
+impl MoveObject for Coin {
+    const MODULE: &'static str = "coin";
+    const NAME: &'static str = "Coin";
+}
+
+/// `sui::table::Table` handle as embedded in other objects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    /// Table object ID
+    pub id: Address,
+    /// Number of entries
+    pub size: u64,
+}
+
+/// `deepbook_lp_registry::registry::Registry`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Registry {
+    /// Object ID
+    pub id: Address,
+    /// Registered vaults
+    pub vaults: Table,
+    /// Registered strategies
+    pub strategies: Table,
+    /// Registered risk controls
+    pub risk_controls: Table,
+}
+
+impl MoveObject for Registry {
+    const MODULE: &'static str = "registry";
+    const NAME: &'static str = "Registry";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> Address {
+        Address([byte; 32])
+    }
+
+    #[test]
+    fn test_vault_fixture_roundtrip() {
+        let mut fixture = vec![0x11; 32];
+        fixture.extend_from_slice(&1_500u64.to_le_bytes());
+        fixture.extend_from_slice(&1_000u64.to_le_bytes());
+        fixture.extend_from_slice(&1_500u64.to_le_bytes());
+        fixture.extend_from_slice(&1_000_000u64.to_le_bytes());
+        fixture.push(4);
+        fixture.extend_from_slice(b"grid");
+        fixture.push(1);
+
+        let vault = Vault::from_bcs(&fixture).unwrap();
+        assert_eq!(vault.id, address(0x11));
+        assert_eq!(vault.balance, 1_500);
+        assert_eq!(vault.total_shares, 1_000);
+        assert_eq!(vault.max_capacity, 1_000_000);
+        assert_eq!(vault.strategy, b"grid");
+        assert!(vault.paused);
+        assert_eq!(vault.to_bcs().unwrap(), fixture);
+    }
+
+    #[test]
+    fn test_risk_control_and_position_fixtures() {
+        let mut fixture = vec![0x22; 32];
+        fixture.extend_from_slice(&1_000u64.to_le_bytes());
+        fixture.push(0);
+        fixture.extend_from_slice(&86_400u64.to_le_bytes());
+
+        let risk = RiskControl::from_bcs(&fixture).unwrap();
+        assert_eq!(risk.drawdown_limit, 1_000);
+        assert!(!risk.circuit_breaker_active);
+        assert_eq!(risk.to_bcs().unwrap(), fixture);
+
+        let position = Position {
+            id: address(1),
+            user_address: address(2),
+            vault_id: address(3),
+            strategy_id: address(4),
+            amount_deposited: 42,
+            entry_timestamp: 1_700_000_000_000,
+            pnl_snapshot: 0,
+        };
+        let bytes = position.to_bcs().unwrap();
+        assert_eq!(bytes.len(), 4 * 32 + 3 * 8);
+        assert_eq!(Position::from_bcs(&bytes).unwrap(), position);
+        assert!(Position::from_bcs(&bytes[..100]).is_err());
+    }
+
+    #[test]
+    fn test_matches_type() {
+        assert!(Vault::matches_type("0xabc::vault::Vault<0x2::sui::SUI>"));
+        assert!(!VaultCap::matches_type(
+            "0xabc::vault::Vault<0x2::sui::SUI>"
+        ));
+        assert!(Strategy::matches_type("0x53::strategy::Strategy"));
+        assert_eq!(
+            Address::from_hex("0x2").unwrap().to_string(),
+            format!("0x{:0>64}", "2")
+        );
+    }
+}
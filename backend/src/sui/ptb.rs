@@ -0,0 +1,835 @@
+//! Programmable transaction block (PTB) builder
+//!
+//! Mirrors the BCS layout of Sui's `TransactionData` so transactions can be
+//! built, dry-run and signed locally, and provides typed helpers for the
+//! `manus_liquidity::vault` and `deepbook_lp_risk_controls::risk_control`
+//! entry functions.
+
+use crate::error::{ManusError, Result};
+use crate::sui::objects::Address;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Safety overhead (in gas units) added on top of the dry-run computation cost
+pub const GAS_SAFE_OVERHEAD: u64 = 1_000;
+
+/// 32-byte digest, BCS-encoded as a length-prefixed byte vector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Digest(Vec<u8>);
+
+impl Digest {
+    /// Parse a base58-encoded digest as returned by the RPC
+    pub fn from_base58(value: &str) -> Result<Self> {
+        let bytes = bs58::decode(value)
+            .into_vec()
+            .map_err(|e| ManusError::Sui(format!("Invalid digest {}: {}", value, e)))?;
+        if bytes.len() != 32 {
+            return Err(ManusError::Sui(format!("Digest {} is not 32 bytes", value)));
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", bs58::encode(&self.0).into_string())
+    }
+}
+
+/// Reference to a specific version of an owned object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectRef(pub Address, pub u64, pub Digest);
+
+impl ObjectRef {
+    /// Build a reference from RPC JSON (`objectId`/`coinObjectId`, `version`, `digest`)
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let id = value["objectId"]
+            .as_str()
+            .or_else(|| value["coinObjectId"].as_str())
+            .ok_or_else(|| ManusError::Sui("Object reference has no ID".to_string()))?;
+        let version = match &value["version"] {
+            Value::String(v) => v.parse().ok(),
+            v => v.as_u64(),
+        }
+        .ok_or_else(|| ManusError::Sui(format!("Object {} has no version", id)))?;
+        let digest = value["digest"]
+            .as_str()
+            .ok_or_else(|| ManusError::Sui(format!("Object {} has no digest", id)))?;
+
+        Ok(Self(
+            Address::from_hex(id)?,
+            version,
+            Digest::from_base58(digest)?,
+        ))
+    }
+
+    /// Object ID
+    pub fn id(&self) -> Address {
+        self.0
+    }
+}
+
+/// Move type tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypeTag {
+    /// `bool`
+    Bool,
+    /// `u8`
+    U8,
+    /// `u64`
+    U64,
+    /// `u128`
+    U128,
+    /// `address`
+    Address,
+    /// `signer`
+    Signer,
+    /// `vector<T>`
+    Vector(Box<TypeTag>),
+    /// Struct type
+    Struct(Box<StructTag>),
+    /// `u16`
+    U16,
+    /// `u32`
+    U32,
+    /// `u256`
+    U256,
+}
+
+/// Fully qualified Move struct type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructTag {
+    /// Package address
+    pub address: Address,
+    /// Module name
+    pub module: String,
+    /// Struct name
+    pub name: String,
+    /// Type parameters
+    pub type_params: Vec<TypeTag>,
+}
+
+impl TypeTag {
+    /// Whether this is `0x2::sui::SUI`
+    pub fn is_sui(&self) -> bool {
+        matches!(self, TypeTag::Struct(tag)
+            if tag.address == Address::from_hex("0x2").unwrap_or_default()
+                && tag.module == "sui"
+                && tag.name == "SUI"
+                && tag.type_params.is_empty())
+    }
+
+    fn parse(input: &str) -> Result<(Self, &str)> {
+        let input = input.trim_start();
+        for (name, tag) in [
+            ("bool", TypeTag::Bool),
+            ("u8", TypeTag::U8),
+            ("u16", TypeTag::U16),
+            ("u32", TypeTag::U32),
+            ("u64", TypeTag::U64),
+            ("u128", TypeTag::U128),
+            ("u256", TypeTag::U256),
+            ("address", TypeTag::Address),
+            ("signer", TypeTag::Signer),
+        ] {
+            if let Some(rest) = input.strip_prefix(name) {
+                if !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    return Ok((tag, rest));
+                }
+            }
+        }
+
+        if let Some(rest) = input.strip_prefix("vector<") {
+            let (inner, rest) = Self::parse(rest)?;
+            let rest = rest
+                .trim_start()
+                .strip_prefix('>')
+                .ok_or_else(|| ManusError::Sui(format!("Unterminated vector type: {}", input)))?;
+            return Ok((TypeTag::Vector(Box::new(inner)), rest));
+        }
+
+        let end = input.find(['<', '>', ',']).unwrap_or(input.len());
+        let mut parts = input[..end].trim().split("::");
+        let (address, module, name) = match (parts.next(), parts.next(), parts.next(), parts.next())
+        {
+            (Some(a), Some(m), Some(n), None) => {
+                (Address::from_hex(a)?, m.to_string(), n.to_string())
+            }
+            _ => return Err(ManusError::Sui(format!("Invalid type tag: {}", input))),
+        };
+
+        let mut rest = &input[end..];
+        let mut type_params = Vec::new();
+        if let Some(mut params) = rest.strip_prefix('<') {
+            loop {
+                let (param, after) = Self::parse(params)?;
+                type_params.push(param);
+                let after = after.trim_start();
+                if let Some(after) = after.strip_prefix(',') {
+                    params = after;
+                } else if let Some(after) = after.strip_prefix('>') {
+                    rest = after;
+                    break;
+                } else {
+                    return Err(ManusError::Sui(format!(
+                        "Unterminated type parameters: {}",
+                        input
+                    )));
+                }
+            }
+        }
+
+        Ok((
+            TypeTag::Struct(Box::new(StructTag {
+                address,
+                module,
+                name,
+                type_params,
+            })),
+            rest,
+        ))
+    }
+}
+
+impl FromStr for TypeTag {
+    type Err = ManusError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::parse(s)? {
+            (tag, rest) if rest.trim().is_empty() => Ok(tag),
+            _ => Err(ManusError::Sui(format!(
+                "Trailing characters in type tag: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeTag::Bool => write!(f, "bool"),
+            TypeTag::U8 => write!(f, "u8"),
+            TypeTag::U16 => write!(f, "u16"),
+            TypeTag::U32 => write!(f, "u32"),
+            TypeTag::U64 => write!(f, "u64"),
+            TypeTag::U128 => write!(f, "u128"),
+            TypeTag::U256 => write!(f, "u256"),
+            TypeTag::Address => write!(f, "address"),
+            TypeTag::Signer => write!(f, "signer"),
+            TypeTag::Vector(inner) => write!(f, "vector<{}>", inner),
+            TypeTag::Struct(tag) => {
+                write!(f, "{}::{}::{}", tag.address, tag.module, tag.name)?;
+                if !tag.type_params.is_empty() {
+                    let params: Vec<String> =
+                        tag.type_params.iter().map(ToString::to_string).collect();
+                    write!(f, "<{}>", params.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Transaction input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CallArg {
+    /// BCS-encoded pure value
+    Pure(Vec<u8>),
+    /// Object input
+    Object(ObjectArg),
+}
+
+/// Object input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ObjectArg {
+    /// Owned or immutable object
+    ImmOrOwnedObject(ObjectRef),
+    /// Shared object
+    SharedObject {
+        /// Object ID
+        id: Address,
+        /// Version at which the object became shared
+        initial_shared_version: u64,
+        /// Whether the transaction mutates the object
+        mutable: bool,
+    },
+    /// Object sent to another object, to be received
+    Receiving(ObjectRef),
+}
+
+impl ObjectArg {
+    /// Object ID of the input
+    pub fn id(&self) -> Address {
+        match self {
+            ObjectArg::ImmOrOwnedObject(object) | ObjectArg::Receiving(object) => object.id(),
+            ObjectArg::SharedObject { id, .. } => *id,
+        }
+    }
+}
+
+/// Reference to a value inside a PTB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Argument {
+    /// The gas coin
+    GasCoin,
+    /// A transaction input
+    Input(u16),
+    /// The result of a command
+    Result(u16),
+    /// One value of a command returning multiple results
+    NestedResult(u16, u16),
+}
+
+/// Move call command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgrammableMoveCall {
+    /// Package ID
+    pub package: Address,
+    /// Module name
+    pub module: String,
+    /// Function name
+    pub function: String,
+    /// Type arguments
+    pub type_arguments: Vec<TypeTag>,
+    /// Arguments
+    pub arguments: Vec<Argument>,
+}
+
+/// PTB command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    /// Call a Move function
+    MoveCall(Box<ProgrammableMoveCall>),
+    /// Transfer objects to an address
+    TransferObjects(Vec<Argument>, Argument),
+    /// Split amounts off a coin
+    SplitCoins(Argument, Vec<Argument>),
+    /// Merge coins into a target coin
+    MergeCoins(Argument, Vec<Argument>),
+}
+
+/// A programmable transaction: inputs plus the commands consuming them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgrammableTransaction {
+    /// Inputs
+    pub inputs: Vec<CallArg>,
+    /// Commands
+    pub commands: Vec<Command>,
+}
+
+/// Transaction kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    /// Programmable transaction block
+    ProgrammableTransaction(ProgrammableTransaction),
+}
+
+/// Gas payment configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasData {
+    /// Coins paying for gas
+    pub payment: Vec<ObjectRef>,
+    /// Owner of the gas coins
+    pub owner: Address,
+    /// Gas price
+    pub price: u64,
+    /// Gas budget
+    pub budget: u64,
+}
+
+/// Transaction expiration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionExpiration {
+    /// Never expires
+    None,
+    /// Expires after the given epoch
+    Epoch(u64),
+}
+
+/// Versioned transaction data, the payload that gets signed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionData {
+    /// Version 1
+    V1(TransactionDataV1),
+}
+
+/// Version 1 transaction data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionDataV1 {
+    /// Transaction kind
+    pub kind: TransactionKind,
+    /// Sender address
+    pub sender: Address,
+    /// Gas configuration
+    pub gas_data: GasData,
+    /// Expiration
+    pub expiration: TransactionExpiration,
+}
+
+impl TransactionData {
+    /// Create programmable transaction data
+    pub fn new_programmable(
+        sender: Address,
+        payment: Vec<ObjectRef>,
+        transaction: ProgrammableTransaction,
+        budget: u64,
+        price: u64,
+    ) -> Self {
+        TransactionData::V1(TransactionDataV1 {
+            kind: TransactionKind::ProgrammableTransaction(transaction),
+            sender,
+            gas_data: GasData {
+                payment,
+                owner: sender,
+                price,
+                budget,
+            },
+            expiration: TransactionExpiration::None,
+        })
+    }
+
+    /// Transaction payload
+    pub fn inner(&self) -> &TransactionDataV1 {
+        match self {
+            TransactionData::V1(data) => data,
+        }
+    }
+
+    /// Copy of this transaction with a different gas budget
+    pub fn with_budget(&self, budget: u64) -> Self {
+        let mut data = self.clone();
+        match &mut data {
+            TransactionData::V1(inner) => inner.gas_data.budget = budget,
+        }
+        data
+    }
+
+    /// BCS encoding, as submitted in `txBytes`
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self)
+            .map_err(|e| ManusError::Sui(format!("Failed to encode transaction: {}", e)))
+    }
+
+    /// Decode BCS transaction bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bcs::from_bytes(bytes)
+            .map_err(|e| ManusError::Sui(format!("Failed to decode transaction: {}", e)))
+    }
+}
+
+/// Gas cost summary reported by a dry run or execution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasCostSummary {
+    /// Computation cost
+    pub computation_cost: u64,
+    /// Storage cost
+    pub storage_cost: u64,
+    /// Storage rebate
+    pub storage_rebate: u64,
+}
+
+impl GasCostSummary {
+    /// Parse the `gasUsed` section of transaction effects
+    pub fn from_json(gas_used: &Value) -> Self {
+        let field = |name: &str| match &gas_used[name] {
+            Value::String(v) => v.parse().unwrap_or(0),
+            v => v.as_u64().unwrap_or(0),
+        };
+
+        Self {
+            computation_cost: field("computationCost"),
+            storage_cost: field("storageCost"),
+            storage_rebate: field("storageRebate"),
+        }
+    }
+
+    /// Net gas charged
+    pub fn net_gas_usage(&self) -> u64 {
+        (self.computation_cost + self.storage_cost).saturating_sub(self.storage_rebate)
+    }
+
+    /// Budget sufficient to execute the dry-run transaction again at `gas_price`
+    pub fn recommended_budget(&self, gas_price: u64) -> u64 {
+        let computation = self.computation_cost + GAS_SAFE_OVERHEAD * gas_price;
+        (computation + self.storage_cost)
+            .saturating_sub(self.storage_rebate)
+            .max(computation)
+    }
+}
+
+/// Low-level PTB builder
+#[derive(Debug, Default)]
+pub struct ProgrammableTransactionBuilder {
+    inputs: Vec<CallArg>,
+    commands: Vec<Command>,
+}
+
+impl ProgrammableTransactionBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a BCS-encodable pure input
+    pub fn pure<T: Serialize>(&mut self, value: &T) -> Result<Argument> {
+        let bytes = bcs::to_bytes(value)
+            .map_err(|e| ManusError::Sui(format!("Failed to encode input: {}", e)))?;
+        Ok(self.input(CallArg::Pure(bytes)))
+    }
+
+    /// Add an object input, reusing an existing input for the same object
+    pub fn object(&mut self, object: ObjectArg) -> Argument {
+        let id = object.id();
+        let existing = self
+            .inputs
+            .iter()
+            .position(|input| matches!(input, CallArg::Object(o) if o.id() == id));
+
+        match existing {
+            Some(index) => Argument::Input(index as u16),
+            None => self.input(CallArg::Object(object)),
+        }
+    }
+
+    /// Split `amounts` off `coin`, returning one argument per new coin
+    pub fn split_coins(&mut self, coin: Argument, amounts: &[u64]) -> Result<Vec<Argument>> {
+        let amounts = amounts
+            .iter()
+            .map(|amount| self.pure(amount))
+            .collect::<Result<Vec<_>>>()?;
+        let count = amounts.len();
+        let result = self.command(Command::SplitCoins(coin, amounts));
+
+        Ok(nested_results(result, count))
+    }
+
+    /// Merge `sources` into `target`
+    pub fn merge_coins(&mut self, target: Argument, sources: Vec<Argument>) {
+        if !sources.is_empty() {
+            self.command(Command::MergeCoins(target, sources));
+        }
+    }
+
+    /// Call a Move function
+    pub fn move_call(
+        &mut self,
+        package: Address,
+        module: &str,
+        function: &str,
+        type_arguments: Vec<TypeTag>,
+        arguments: Vec<Argument>,
+    ) -> Argument {
+        self.command(Command::MoveCall(Box::new(ProgrammableMoveCall {
+            package,
+            module: module.to_string(),
+            function: function.to_string(),
+            type_arguments,
+            arguments,
+        })))
+    }
+
+    /// Transfer objects to `recipient`
+    pub fn transfer_objects(&mut self, objects: Vec<Argument>, recipient: Address) -> Result<()> {
+        let recipient = self.pure(&recipient)?;
+        self.command(Command::TransferObjects(objects, recipient));
+        Ok(())
+    }
+
+    /// Finish building
+    pub fn finish(self) -> ProgrammableTransaction {
+        ProgrammableTransaction {
+            inputs: self.inputs,
+            commands: self.commands,
+        }
+    }
+
+    fn input(&mut self, arg: CallArg) -> Argument {
+        self.inputs.push(arg);
+        Argument::Input((self.inputs.len() - 1) as u16)
+    }
+
+    fn command(&mut self, command: Command) -> Argument {
+        self.commands.push(command);
+        Argument::Result((self.commands.len() - 1) as u16)
+    }
+}
+
+fn nested_results(result: Argument, count: usize) -> Vec<Argument> {
+    match result {
+        Argument::Result(index) => (0..count as u16)
+            .map(|i| Argument::NestedResult(index, i))
+            .collect(),
+        other => vec![other],
+    }
+}
+
+/// Typed builder for `manus_liquidity::vault` entry functions on one vault
+pub struct VaultTransactionBuilder {
+    ptb: ProgrammableTransactionBuilder,
+    package: Address,
+    coin_type: TypeTag,
+    vault: Argument,
+}
+
+impl VaultTransactionBuilder {
+    /// Start a transaction against the shared vault `vault_id`
+    pub fn new(
+        package: Address,
+        coin_type: TypeTag,
+        vault_id: Address,
+        initial_shared_version: u64,
+    ) -> Self {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let vault = ptb.object(ObjectArg::SharedObject {
+            id: vault_id,
+            initial_shared_version,
+            mutable: true,
+        });
+
+        Self {
+            ptb,
+            package,
+            coin_type,
+            vault,
+        }
+    }
+
+    /// Deposit `amount` split off the gas coin (SUI vaults only)
+    pub fn deposit_from_gas(&mut self, amount: u64) -> Result<()> {
+        if !self.coin_type.is_sui() {
+            return Err(ManusError::Sui(format!(
+                "Cannot pay a {} deposit from the gas coin",
+                self.coin_type
+            )));
+        }
+
+        let coin = self.ptb.split_coins(Argument::GasCoin, &[amount])?[0];
+        self.call("deposit", vec![self.vault, coin]);
+        Ok(())
+    }
+
+    /// Merge `coins` and deposit `amount` split off the result
+    pub fn deposit_from_coins(&mut self, coins: Vec<ObjectRef>, amount: u64) -> Result<()> {
+        let mut coins = coins
+            .into_iter()
+            .map(|coin| self.ptb.object(ObjectArg::ImmOrOwnedObject(coin)));
+        let primary = coins
+            .next()
+            .ok_or_else(|| ManusError::Sui("No coins supplied for deposit".to_string()))?;
+        let rest: Vec<Argument> = coins.collect();
+        self.ptb.merge_coins(primary, rest);
+
+        let coin = self.ptb.split_coins(primary, &[amount])?[0];
+        self.call("deposit", vec![self.vault, coin]);
+        Ok(())
+    }
+
+    /// Burn a `Share` and withdraw the underlying assets to the sender
+    pub fn withdraw(&mut self, share: ObjectRef) {
+        let share = self.ptb.object(ObjectArg::ImmOrOwnedObject(share));
+        self.call("withdraw", vec![self.vault, share]);
+    }
+
+    /// Pause the vault using its `VaultCap`
+    pub fn pause(&mut self, cap: ObjectRef) {
+        let cap = self.ptb.object(ObjectArg::ImmOrOwnedObject(cap));
+        self.call("pause", vec![self.vault, cap]);
+    }
+
+    /// Resume the vault using its `VaultCap`
+    pub fn resume(&mut self, cap: ObjectRef) {
+        let cap = self.ptb.object(ObjectArg::ImmOrOwnedObject(cap));
+        self.call("resume", vec![self.vault, cap]);
+    }
+
+    /// Transfer `Share` objects to another address
+    pub fn transfer_shares(&mut self, shares: Vec<ObjectRef>, recipient: Address) -> Result<()> {
+        let shares = shares
+            .into_iter()
+            .map(|share| self.ptb.object(ObjectArg::ImmOrOwnedObject(share)))
+            .collect();
+        self.ptb.transfer_objects(shares, recipient)
+    }
+
+    /// Finish building
+    pub fn finish(self) -> ProgrammableTransaction {
+        self.ptb.finish()
+    }
+
+    fn call(&mut self, function: &str, arguments: Vec<Argument>) {
+        self.ptb.move_call(
+            self.package,
+            "vault",
+            function,
+            vec![self.coin_type.clone()],
+            arguments,
+        );
+    }
+}
+
+/// Typed builder for `deepbook_lp_risk_controls::risk_control` calls on one `RiskControl`
+pub struct RiskControlTransactionBuilder {
+    ptb: ProgrammableTransactionBuilder,
+    package: Address,
+    risk_control: Argument,
+}
+
+impl RiskControlTransactionBuilder {
+    /// Start a transaction against `risk_control`, either owned or shared
+    pub fn new(package: Address, risk_control: ObjectArg) -> Self {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let risk_control = ptb.object(risk_control);
+
+        Self {
+            ptb,
+            package,
+            risk_control,
+        }
+    }
+
+    /// Activate the circuit breaker, pausing strategy execution
+    pub fn activate_circuit_breaker(&mut self) {
+        self.call("activate_circuit_breaker", vec![self.risk_control]);
+    }
+
+    /// Deactivate the circuit breaker, allowing strategy execution to resume
+    pub fn deactivate_circuit_breaker(&mut self) {
+        self.call("deactivate_circuit_breaker", vec![self.risk_control]);
+    }
+
+    /// Set the drawdown limit, in basis points (1000 = 10%)
+    pub fn update_drawdown_limit(&mut self, limit: u64) -> Result<()> {
+        let limit = self.ptb.pure(&limit)?;
+        self.call("update_drawdown_limit", vec![self.risk_control, limit]);
+        Ok(())
+    }
+
+    /// Finish building
+    pub fn finish(self) -> ProgrammableTransaction {
+        self.ptb.finish()
+    }
+
+    fn call(&mut self, function: &str, arguments: Vec<Argument>) {
+        self.ptb
+            .move_call(self.package, "risk_control", function, vec![], arguments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_ref(byte: u8) -> ObjectRef {
+        ObjectRef(Address([byte; 32]), 3, Digest(vec![byte; 32]))
+    }
+
+    #[test]
+    fn test_type_tag_parse_and_display() {
+        let tag: TypeTag = "0x2::coin::Coin<0x2::sui::SUI>".parse().unwrap();
+        let TypeTag::Struct(outer) = &tag else {
+            panic!("expected struct tag");
+        };
+        assert_eq!(outer.name, "Coin");
+        assert!(outer.type_params[0].is_sui());
+        assert_eq!(tag.to_string().parse::<TypeTag>().unwrap(), tag);
+        assert_eq!(
+            "vector<u8>".parse::<TypeTag>().unwrap(),
+            TypeTag::Vector(Box::new(TypeTag::U8))
+        );
+        assert!("0x2::sui".parse::<TypeTag>().is_err());
+    }
+
+    #[test]
+    fn test_argument_bcs_layout() {
+        assert_eq!(bcs::to_bytes(&Argument::GasCoin).unwrap(), vec![0]);
+        assert_eq!(bcs::to_bytes(&Argument::Input(1)).unwrap(), vec![1, 1, 0]);
+        assert_eq!(
+            bcs::to_bytes(&Argument::NestedResult(2, 0)).unwrap(),
+            vec![3, 2, 0, 0, 0]
+        );
+        assert_eq!(
+            bcs::to_bytes(&object_ref(1)).unwrap().len(),
+            32 + 8 + 1 + 32
+        );
+    }
+
+    #[test]
+    fn test_vault_deposit_from_gas_composes_split_and_call() {
+        let sui: TypeTag = "0x2::sui::SUI".parse().unwrap();
+        let mut builder = VaultTransactionBuilder::new(Address([9; 32]), sui, Address([1; 32]), 4);
+        builder.deposit_from_gas(500).unwrap();
+        let ptb = builder.finish();
+
+        assert_eq!(ptb.inputs.len(), 2);
+        assert_eq!(
+            ptb.commands[0],
+            Command::SplitCoins(Argument::GasCoin, vec![Argument::Input(1)])
+        );
+        let Command::MoveCall(call) = &ptb.commands[1] else {
+            panic!("expected move call");
+        };
+        assert_eq!(call.function, "deposit");
+        assert_eq!(
+            call.arguments,
+            vec![Argument::Input(0), Argument::NestedResult(0, 0)]
+        );
+
+        let data =
+            TransactionData::new_programmable(Address([5; 32]), vec![object_ref(7)], ptb, 1_000, 1);
+        let bytes = data.to_bytes().unwrap();
+        assert_eq!(TransactionData::from_bytes(&bytes).unwrap(), data);
+    }
+
+    #[test]
+    fn test_non_sui_deposit_rejects_gas_coin() {
+        let usdc: TypeTag = "0xa::usdc::USDC".parse().unwrap();
+        let mut builder = VaultTransactionBuilder::new(Address([9; 32]), usdc, Address([1; 32]), 4);
+        assert!(builder.deposit_from_gas(500).is_err());
+
+        builder
+            .deposit_from_coins(vec![object_ref(2), object_ref(3)], 500)
+            .unwrap();
+        builder.withdraw(object_ref(4));
+        let ptb = builder.finish();
+        assert!(matches!(
+            ptb.commands[0],
+            Command::MergeCoins(Argument::Input(1), _)
+        ));
+        assert_eq!(ptb.commands.len(), 4);
+    }
+
+    #[test]
+    fn test_risk_control_calls() {
+        let mut builder = RiskControlTransactionBuilder::new(
+            Address([9; 32]),
+            ObjectArg::ImmOrOwnedObject(object_ref(1)),
+        );
+        builder.update_drawdown_limit(500).unwrap();
+        builder.activate_circuit_breaker();
+        let ptb = builder.finish();
+
+        assert_eq!(ptb.inputs.len(), 2);
+        assert_eq!(ptb.inputs[1], CallArg::Pure(500u64.to_le_bytes().to_vec()));
+        let Command::MoveCall(call) = &ptb.commands[1] else {
+            panic!("expected move call");
+        };
+        assert_eq!(call.module, "risk_control");
+        assert_eq!(call.function, "activate_circuit_breaker");
+        assert_eq!(call.arguments, vec![Argument::Input(0)]);
+        assert!(call.type_arguments.is_empty());
+    }
+
+    #[test]
+    fn test_recommended_budget() {
+        let summary = GasCostSummary {
+            computation_cost: 1_000_000,
+            storage_cost: 2_000_000,
+            storage_rebate: 500_000,
+        };
+        assert_eq!(summary.net_gas_usage(), 2_500_000);
+        assert_eq!(summary.recommended_budget(1_000), 3_500_000);
+    }
+}
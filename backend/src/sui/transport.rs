@@ -0,0 +1,92 @@
+//! JSON-RPC transport for Sui full nodes
+
+use crate::error::{ManusError, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Transport capable of issuing Sui JSON-RPC requests
+///
+/// Implemented by [`HttpTransport`] for real networks and by
+/// [`MockSuiNode`](super::mock::MockSuiNode) for local testing.
+#[async_trait]
+pub trait SuiTransport: Send + Sync {
+    /// Send a request and return the `result` payload
+    async fn request(&self, method: &str, params: Value) -> Result<Value>;
+}
+
+/// HTTP JSON-RPC transport
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    next_id: AtomicU64,
+}
+
+impl HttpTransport {
+    /// Create a transport for the given full node URL
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+#[async_trait]
+impl SuiTransport for HttpTransport {
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ManusError::Sui(format!("{} request failed: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| ManusError::Sui(format!("{} returned invalid JSON: {}", method, e)))?;
+
+        parse_response(method, response)
+    }
+}
+
+/// Extract the `result` of a JSON-RPC response, surfacing RPC errors
+pub(crate) fn parse_response(method: &str, mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(ManusError::Sui(format!("{} failed: {}", method, message)));
+    }
+
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(ManusError::Sui(format!("{} returned no result", method))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let ok = json!({"jsonrpc": "2.0", "id": 1, "result": {"value": 5}});
+        assert_eq!(parse_response("m", ok).unwrap()["value"], 5);
+
+        let err =
+            json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "bad params"}});
+        let message = parse_response("m", err).unwrap_err().to_string();
+        assert!(message.contains("bad params"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Share accounting for `manus_liquidity::vault`
+//!
+//! Port of `vault_math.move` with identical u64/u128 rounding, plus the
+//! precondition checks `vault::deposit` and `vault::withdraw` run around it,
+//! so deposits and withdrawals can be quoted before they are submitted.
+//!
+//! Both conversions round down, which always favours the vault: depositors
+//! never receive more shares than their deposit is worth and withdrawers
+//! never receive more than their shares are worth.
+
+use crate::error::{ManusError, Result};
+use crate::sui::objects::Vault;
+use serde::Serialize;
+
+/// Move abort codes from `manus_liquidity::vault`
+pub const E_VAULT_PAUSED: u64 = 1;
+/// Zero amount, or a share belonging to another vault
+pub const E_INVALID_AMOUNT: u64 = 2;
+/// Burning more shares than the vault has issued
+pub const E_INSUFFICIENT_SHARES: u64 = 3;
+/// Deposit would take the vault balance above `max_capacity`
+pub const E_MAX_CAPACITY_EXCEEDED: u64 = 4;
+/// Vault balance cannot cover the withdrawal
+pub const E_INSUFFICIENT_BALANCE: u64 = 5;
+
+/// Status reported when the Move implementation aborts on overflow
+pub const ARITHMETIC_ERROR: &str = "ArithmeticError in vault_math";
+
+/// Shares to mint for `deposit_amount`
+///
+/// Mirrors `vault_math::calculate_shares_for_deposit`. Returns `None` where the
+/// Move `as u64` cast would abort on overflow.
+pub fn calculate_shares_for_deposit(
+    deposit_amount: u64,
+    total_shares: u64,
+    total_underlying: u64,
+) -> Option<u64> {
+    if total_shares == 0 || total_underlying == 0 {
+        Some(deposit_amount)
+    } else {
+        u64::try_from(deposit_amount as u128 * total_shares as u128 / total_underlying as u128).ok()
+    }
+}
+
+/// Underlying amount released by burning `shares_to_burn`
+///
+/// Mirrors `vault_math::calculate_amount_for_withdrawal`. Returns `None` where the
+/// Move `as u64` cast would abort on overflow.
+pub fn calculate_amount_for_withdrawal(
+    shares_to_burn: u64,
+    total_shares: u64,
+    total_underlying: u64,
+) -> Option<u64> {
+    if total_shares == 0 {
+        Some(0)
+    } else {
+        u64::try_from(shares_to_burn as u128 * total_underlying as u128 / total_shares as u128).ok()
+    }
+}
+
+/// Quoted outcome of a deposit or withdrawal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Quote {
+    /// Shares minted (deposit) or burned (withdrawal)
+    pub shares: u64,
+
+    /// Amount deposited or withdrawn
+    pub amount: u64,
+}
+
+/// Quote a deposit of `amount` into `vault`, applying the checks of `vault::deposit`
+pub fn quote_deposit(vault: &Vault, amount: u64) -> Result<Quote> {
+    if vault.paused {
+        return Err(abort(E_VAULT_PAUSED));
+    }
+    if amount == 0 {
+        return Err(abort(E_INVALID_AMOUNT));
+    }
+    let balance = vault
+        .balance
+        .checked_add(amount)
+        .ok_or_else(arithmetic_error)?;
+    if balance > vault.max_capacity {
+        return Err(abort(E_MAX_CAPACITY_EXCEEDED));
+    }
+
+    let shares = calculate_shares_for_deposit(amount, vault.total_shares, vault.total_underlying)
+        .ok_or_else(arithmetic_error)?;
+    // `vault::deposit` adds both to the vault's totals, aborting on overflow
+    vault
+        .total_shares
+        .checked_add(shares)
+        .ok_or_else(arithmetic_error)?;
+    vault
+        .total_underlying
+        .checked_add(amount)
+        .ok_or_else(arithmetic_error)?;
+    Ok(Quote { shares, amount })
+}
+
+/// Quote burning `shares` of `vault`, applying the checks of `vault::withdraw`
+pub fn quote_withdrawal(vault: &Vault, shares: u64) -> Result<Quote> {
+    if vault.paused {
+        return Err(abort(E_VAULT_PAUSED));
+    }
+    if shares == 0 {
+        return Err(abort(E_INVALID_AMOUNT));
+    }
+    if vault.total_shares < shares {
+        return Err(abort(E_INSUFFICIENT_SHARES));
+    }
+
+    let amount =
+        calculate_amount_for_withdrawal(shares, vault.total_shares, vault.total_underlying)
+            .ok_or_else(arithmetic_error)?;
+    if vault.balance < amount {
+        return Err(abort(E_INSUFFICIENT_BALANCE));
+    }
+    Ok(Quote { shares, amount })
+}
+
+fn abort(code: u64) -> ManusError {
+    ManusError::MoveAbort {
+        module: "vault".to_string(),
+        code,
+    }
+}
+
+fn arithmetic_error() -> ManusError {
+    ManusError::Arithmetic(ARITHMETIC_ERROR.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sui::objects::Address;
+    use proptest::prelude::*;
+
+    fn vault(balance: u64, total_shares: u64, max_capacity: u64) -> Vault {
+        Vault {
+            id: Address([7; 32]),
+            balance,
+            total_shares,
+            total_underlying: balance,
+            max_capacity,
+            strategy: b"grid".to_vec(),
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_matches_move_semantics() {
+        assert_eq!(calculate_shares_for_deposit(500, 0, 0), Some(500));
+        assert_eq!(calculate_shares_for_deposit(500, 100, 0), Some(500));
+        assert_eq!(calculate_shares_for_deposit(100, 1_000, 1_500), Some(66));
+        assert_eq!(calculate_shares_for_deposit(u64::MAX, u64::MAX, 1), None);
+
+        assert_eq!(calculate_amount_for_withdrawal(10, 0, 1_000), Some(0));
+        assert_eq!(calculate_amount_for_withdrawal(66, 1_000, 1_500), Some(99));
+        assert_eq!(calculate_amount_for_withdrawal(u64::MAX, 1, u64::MAX), None);
+
+        // Deposits overflowing the vault's balance or totals abort
+        let arithmetic = |quote: Result<Quote>| matches!(quote, Err(ManusError::Arithmetic(_)));
+        assert!(arithmetic(quote_deposit(&vault(u64::MAX, 1, u64::MAX), 1)));
+        assert!(arithmetic(quote_deposit(&vault(1, u64::MAX, u64::MAX), 1)));
+        let underlying = Vault {
+            total_underlying: u64::MAX,
+            ..vault(0, 1, u64::MAX)
+        };
+        assert!(arithmetic(quote_deposit(&underlying, 1)));
+        assert!(quote_deposit(&vault(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1).is_ok());
+    }
+
+    #[test]
+    fn test_quote_checks_vault_preconditions() {
+        let open = vault(1_000, 1_000, 2_000);
+        assert_eq!(
+            quote_deposit(&open, 500).unwrap(),
+            Quote {
+                shares: 500,
+                amount: 500
+            }
+        );
+        assert!(quote_deposit(&open, 0).is_err());
+        assert!(quote_deposit(&open, 1_001).is_err());
+        assert!(quote_withdrawal(&open, 1_001).is_err());
+        assert_eq!(quote_withdrawal(&open, 250).unwrap().amount, 250);
+
+        let paused = Vault {
+            paused: true,
+            ..open
+        };
+        assert!(quote_deposit(&paused, 1).is_err());
+        assert!(quote_withdrawal(&paused, 1).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_deposit_never_dilutes_existing_holders(
+            total_underlying in 1u64..=u64::MAX / 4,
+            total_shares in 1u64..=u64::MAX / 4,
+            deposit in 1u64..=u64::MAX / 4,
+        ) {
+            let minted = calculate_shares_for_deposit(deposit, total_shares, total_underlying);
+            prop_assume!(minted.is_some());
+            let minted = minted.unwrap();
+
+            // Value per share after the deposit is at least the value per share before:
+            // (U + d) / (S + m) >= U / S  <=>  (U + d) * S >= U * (S + m)
+            let before = total_underlying as u128 * (total_shares as u128 + minted as u128);
+            let after = (total_underlying as u128 + deposit as u128) * total_shares as u128;
+            prop_assert!(after >= before);
+        }
+
+        #[test]
+        fn prop_withdrawal_never_exceeds_share_value(
+            total_underlying in 0u64..=u64::MAX,
+            total_shares in 1u64..=u64::MAX,
+            burn_fraction in 0.0f64..=1.0,
+        ) {
+            let burn = (total_shares as f64 * burn_fraction) as u64;
+            let burn = burn.min(total_shares);
+            let amount = calculate_amount_for_withdrawal(burn, total_shares, total_underlying).unwrap();
+
+            // amount / U <= burn / S, so remaining holders are never worse off
+            prop_assert!(amount as u128 * total_shares as u128 <= burn as u128 * total_underlying as u128);
+            prop_assert!(amount <= total_underlying);
+        }
+
+        #[test]
+        fn prop_round_trip_never_profits(
+            balance in 1u64..=1_000_000_000_000,
+            total_shares in 1u64..=1_000_000_000_000,
+            deposit in 1u64..=1_000_000_000_000,
+        ) {
+            let mut state = vault(balance, total_shares, u64::MAX);
+            let minted = quote_deposit(&state, deposit).unwrap().shares;
+            state.balance += deposit;
+            state.total_underlying += deposit;
+            state.total_shares += minted;
+
+            let withdrawn = if minted == 0 { 0 } else { quote_withdrawal(&state, minted).unwrap().amount };
+            prop_assert!(withdrawn <= deposit);
+        }
+    }
+}